@@ -0,0 +1,425 @@
+//! Read geomedea into Arrow [`RecordBatch`]es, e.g. for loading into Polars or DataFusion.
+//!
+//! Each batch has a `geometry` column of WKB, tagged with the `geoarrow.wkb` extension type, so
+//! GeoArrow aware readers will recognize it, followed by a column for each property.
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use std::io::BufReader;
+//! let input = BufReader::new(File::open("places.geomedea").unwrap());
+//! let features = geomedea::Reader::new(input).unwrap().select_all().unwrap();
+//! let batches = geomedea_arrow::RecordBatchIter::new(features).unwrap();
+//! println!("{:?}", batches.schema());
+//! for batch in batches {
+//!     let batch = batch.unwrap();
+//!     println!("{} rows", batch.num_rows());
+//! }
+//! ```
+//!
+//! # Schema inference
+//!
+//! geomedea stores each feature's properties with the feature, whereas Arrow needs a schema up
+//! front, so the schema is inferred from the first features, see
+//! [`RecordBatchIter::with_options`]:
+//!
+//! - There's a column for every property seen, in the order they were first seen. Features
+//!   without the property have a null value.
+//! - Integers of any width become `Int64`, except `UInt64` values too large for an `Int64`, which
+//!   are treated as floats.
+//! - Floats, or a mix of integers and floats, become `Float64`.
+//! - Bools become `Boolean`, and bytes become `Binary`.
+//! - Anything else, i.e. strings, lists, maps, or a mix of types, becomes `Utf8`. Values other
+//!   than strings are written as JSON, e.g. `[1,2]`.
+//!
+//! Properties of later features which weren't seen while inferring the schema are dropped, and
+//! values which can't be converted to their column's type are null. A property named `geometry`
+//! is dropped, since it would collide with the geometry column.
+
+use arrow_array::builder::{
+    BinaryBuilder, BooleanBuilder, Float64Builder, Int64Builder, StringBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use geomedea::geojson::property_to_json;
+use geomedea::{Feature, FeatureIter, PropertyValue};
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+use std::sync::Arc;
+
+/// The name of the WKB geometry column.
+pub const GEOMETRY_COLUMN: &str = "geometry";
+/// How many features the schema is inferred from, unless configured otherwise.
+pub const DEFAULT_INFER_SCHEMA_LENGTH: usize = 1000;
+/// The most rows in each batch, unless configured otherwise.
+pub const DEFAULT_BATCH_SIZE: usize = 8192;
+
+/// Yields the selected features as Arrow [`RecordBatch`]es, see the [crate docs](crate).
+pub struct RecordBatchIter<'r, R: Read> {
+    features: FeatureIter<'r, R>,
+    /// Features read while inferring the schema, which haven't been yielded yet.
+    buffered: VecDeque<Feature>,
+    columns: Vec<(String, ColumnType)>,
+    schema: SchemaRef,
+    batch_size: usize,
+}
+
+impl<'r, R: Read> RecordBatchIter<'r, R> {
+    pub fn new(features: FeatureIter<'r, R>) -> geomedea::Result<Self> {
+        Self::with_options(features, DEFAULT_INFER_SCHEMA_LENGTH, DEFAULT_BATCH_SIZE)
+    }
+
+    /// Infer the schema from the first `infer_schema_length` features, and yield batches of at
+    /// most `batch_size` rows.
+    ///
+    /// The features used to infer the schema are buffered in memory until they're yielded.
+    pub fn with_options(
+        mut features: FeatureIter<'r, R>,
+        infer_schema_length: usize,
+        batch_size: usize,
+    ) -> geomedea::Result<Self> {
+        assert!(batch_size > 0, "batch_size must be positive");
+        let mut buffered = VecDeque::new();
+        while buffered.len() < infer_schema_length {
+            let Some(feature) = features.try_next()? else {
+                break;
+            };
+            buffered.push_back(feature);
+        }
+        let columns = infer_columns(buffered.iter());
+
+        let geometry_field =
+            Field::new(GEOMETRY_COLUMN, DataType::Binary, false).with_metadata(HashMap::from([(
+                "ARROW:extension:name".to_string(),
+                "geoarrow.wkb".to_string(),
+            )]));
+        let fields = std::iter::once(geometry_field).chain(
+            columns
+                .iter()
+                .map(|(name, column_type)| Field::new(name, column_type.data_type(), true)),
+        );
+        let schema = Arc::new(Schema::new(fields.collect::<Vec<_>>()));
+
+        Ok(Self {
+            features,
+            buffered,
+            columns,
+            schema,
+            batch_size,
+        })
+    }
+
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn next_feature(&mut self) -> geomedea::Result<Option<Feature>> {
+        if let Some(feature) = self.buffered.pop_front() {
+            return Ok(Some(feature));
+        }
+        self.features.try_next()
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
+        let mut geometries = BinaryBuilder::new();
+        let mut columns: Vec<ColumnBuilder> = self
+            .columns
+            .iter()
+            .map(|(_name, column_type)| ColumnBuilder::new(*column_type))
+            .collect();
+        let mut row_count = 0;
+        while row_count < self.batch_size {
+            let Some(feature) = self.next_feature().map_err(external_error)? else {
+                break;
+            };
+            geometries.append_value(feature.geometry().to_ewkb(None));
+            for ((name, _column_type), column) in self.columns.iter().zip(&mut columns) {
+                column.append(feature.properties().get(name));
+            }
+            row_count += 1;
+        }
+        if row_count == 0 {
+            return Ok(None);
+        }
+
+        let arrays: Vec<ArrayRef> = std::iter::once(Arc::new(geometries.finish()) as ArrayRef)
+            .chain(columns.into_iter().map(ColumnBuilder::finish))
+            .collect();
+        RecordBatch::try_new(self.schema.clone(), arrays).map(Some)
+    }
+}
+
+impl<R: Read> Iterator for RecordBatchIter<'_, R> {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch().transpose()
+    }
+}
+
+impl<R: Read> arrow_array::RecordBatchReader for RecordBatchIter<'_, R> {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+fn external_error(error: geomedea::Error) -> ArrowError {
+    ArrowError::ExternalError(error.to_string().into())
+}
+
+/// The type of a property column, see the [crate docs](crate) for how it's inferred.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnType {
+    Boolean,
+    Int64,
+    Float64,
+    Binary,
+    Utf8,
+}
+
+impl ColumnType {
+    fn of(value: &PropertyValue) -> Self {
+        match value {
+            PropertyValue::Bool(_) => ColumnType::Boolean,
+            PropertyValue::Int8(_)
+            | PropertyValue::UInt8(_)
+            | PropertyValue::Int16(_)
+            | PropertyValue::UInt16(_)
+            | PropertyValue::Int32(_)
+            | PropertyValue::UInt32(_)
+            | PropertyValue::Int64(_) => ColumnType::Int64,
+            PropertyValue::UInt64(value) => {
+                if i64::try_from(*value).is_ok() {
+                    ColumnType::Int64
+                } else {
+                    ColumnType::Float64
+                }
+            }
+            PropertyValue::Float32(_) | PropertyValue::Float64(_) => ColumnType::Float64,
+            PropertyValue::Bytes(_) => ColumnType::Binary,
+            PropertyValue::String(_) | PropertyValue::Vec(_) | PropertyValue::Map(_) => {
+                ColumnType::Utf8
+            }
+        }
+    }
+
+    /// The narrowest type which can hold the values of both.
+    fn widen(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (ColumnType::Int64, ColumnType::Float64) | (ColumnType::Float64, ColumnType::Int64) => {
+                ColumnType::Float64
+            }
+            _ => ColumnType::Utf8,
+        }
+    }
+
+    fn data_type(self) -> DataType {
+        match self {
+            ColumnType::Boolean => DataType::Boolean,
+            ColumnType::Int64 => DataType::Int64,
+            ColumnType::Float64 => DataType::Float64,
+            ColumnType::Binary => DataType::Binary,
+            ColumnType::Utf8 => DataType::Utf8,
+        }
+    }
+}
+
+fn infer_columns<'a>(features: impl Iterator<Item = &'a Feature>) -> Vec<(String, ColumnType)> {
+    let mut columns: Vec<(String, ColumnType)> = vec![];
+    for feature in features {
+        for (name, value) in feature.properties().iter() {
+            if name == GEOMETRY_COLUMN {
+                continue;
+            }
+            let value_type = ColumnType::of(value);
+            match columns.iter_mut().find(|(column, _)| column == name) {
+                Some((_, column_type)) => *column_type = column_type.widen(value_type),
+                None => columns.push((name.to_string(), value_type)),
+            }
+        }
+    }
+    columns
+}
+
+enum ColumnBuilder {
+    Boolean(BooleanBuilder),
+    Int64(Int64Builder),
+    Float64(Float64Builder),
+    Binary(BinaryBuilder),
+    Utf8(StringBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(column_type: ColumnType) -> Self {
+        match column_type {
+            ColumnType::Boolean => ColumnBuilder::Boolean(BooleanBuilder::new()),
+            ColumnType::Int64 => ColumnBuilder::Int64(Int64Builder::new()),
+            ColumnType::Float64 => ColumnBuilder::Float64(Float64Builder::new()),
+            ColumnType::Binary => ColumnBuilder::Binary(BinaryBuilder::new()),
+            ColumnType::Utf8 => ColumnBuilder::Utf8(StringBuilder::new()),
+        }
+    }
+
+    /// Appends `value` converted to the column's type, or null if it's missing or can't be.
+    fn append(&mut self, value: Option<&PropertyValue>) {
+        match self {
+            ColumnBuilder::Boolean(builder) => builder.append_option(match value {
+                Some(PropertyValue::Bool(value)) => Some(*value),
+                _ => None,
+            }),
+            ColumnBuilder::Int64(builder) => builder.append_option(value.and_then(as_i64)),
+            ColumnBuilder::Float64(builder) => builder.append_option(value.and_then(as_f64)),
+            ColumnBuilder::Binary(builder) => builder.append_option(match value {
+                Some(PropertyValue::Bytes(value)) => Some(value),
+                _ => None,
+            }),
+            ColumnBuilder::Utf8(builder) => match value {
+                None => builder.append_null(),
+                Some(PropertyValue::String(value)) => builder.append_value(value),
+                Some(value) => builder.append_value(property_to_json(value).to_string()),
+            },
+        }
+    }
+
+    fn finish(self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Boolean(mut builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Int64(mut builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Float64(mut builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Binary(mut builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Utf8(mut builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+fn as_i64(value: &PropertyValue) -> Option<i64> {
+    match *value {
+        PropertyValue::Int8(value) => Some(value.into()),
+        PropertyValue::UInt8(value) => Some(value.into()),
+        PropertyValue::Int16(value) => Some(value.into()),
+        PropertyValue::UInt16(value) => Some(value.into()),
+        PropertyValue::Int32(value) => Some(value.into()),
+        PropertyValue::UInt32(value) => Some(value.into()),
+        PropertyValue::Int64(value) => Some(value),
+        PropertyValue::UInt64(value) => value.try_into().ok(),
+        _ => None,
+    }
+}
+
+fn as_f64(value: &PropertyValue) -> Option<f64> {
+    match *value {
+        PropertyValue::Float32(value) => Some(value.into()),
+        PropertyValue::Float64(value) => Some(value),
+        PropertyValue::UInt64(value) => Some(value as f64),
+        _ => as_i64(value).map(|value| value as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float64Type, Int64Type};
+    use geomedea::{Geometry, LngLat, Properties, Reader, Writer};
+
+    /// Every feature is at the same point, so they're read back in the order they're written.
+    fn write(all_properties: Vec<Properties>) -> Vec<u8> {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        for properties in all_properties {
+            let feature = Feature::new(LngLat::degrees(1.0, 2.0).into(), properties);
+            writer.add_feature(&feature).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn properties(entries: Vec<(&str, PropertyValue)>) -> Properties {
+        let mut properties = Properties::empty();
+        for (name, value) in entries {
+            properties.insert(name.to_string(), value);
+        }
+        properties
+    }
+
+    #[test]
+    fn infers_schema() {
+        let input = write(vec![
+            properties(vec![
+                ("name", "a".into()),
+                ("rank", PropertyValue::UInt8(1)),
+                ("score", PropertyValue::Int32(2)),
+                ("misc", "x".into()),
+            ]),
+            properties(vec![
+                ("name", "b".into()),
+                ("rank", PropertyValue::Int64(-2)),
+                ("score", PropertyValue::Float64(2.5)),
+                ("misc", PropertyValue::Vec(vec![PropertyValue::Int8(1)])),
+                ("flag", PropertyValue::Bool(true)),
+            ]),
+        ]);
+        let features = Reader::new(input.as_slice()).unwrap().select_all().unwrap();
+        let mut batches = RecordBatchIter::new(features).unwrap();
+
+        let schema = batches.schema();
+        let columns: Vec<(&str, &DataType)> = schema
+            .fields()
+            .iter()
+            .map(|field| (field.name().as_str(), field.data_type()))
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                ("geometry", &DataType::Binary),
+                ("name", &DataType::Utf8),
+                ("rank", &DataType::Int64),
+                ("score", &DataType::Float64),
+                ("misc", &DataType::Utf8),
+                ("flag", &DataType::Boolean),
+            ]
+        );
+
+        let batch = batches.next().unwrap().unwrap();
+        assert!(batches.next().is_none());
+        assert_eq!(batch.num_rows(), 2);
+
+        let geometry = batch.column(0).as_binary::<i32>().value(0);
+        assert_eq!(
+            Geometry::from_ewkb(geometry).unwrap(),
+            (LngLat::degrees(1.0, 2.0).into(), None)
+        );
+        let rank = batch.column(2).as_primitive::<Int64Type>();
+        assert_eq!((rank.value(0), rank.value(1)), (1, -2));
+        let score = batch.column(3).as_primitive::<Float64Type>();
+        assert_eq!((score.value(0), score.value(1)), (2.0, 2.5));
+        let misc = batch.column(4).as_string::<i32>();
+        assert_eq!((misc.value(0), misc.value(1)), ("x", "[1]"));
+        let flag = batch.column(5).as_boolean();
+        assert!(flag.is_null(0));
+        assert!(flag.value(1));
+    }
+
+    #[test]
+    fn batches() {
+        let input = write(vec![
+            properties(vec![("rank", PropertyValue::Int32(1))]),
+            properties(vec![("rank", "two".into()), ("other", "dropped".into())]),
+            properties(vec![("rank", PropertyValue::Int32(3))]),
+        ]);
+        let features = Reader::new(input.as_slice()).unwrap().select_all().unwrap();
+        // Only the first feature is used to infer the schema.
+        let batches = RecordBatchIter::with_options(features, 1, 2).unwrap();
+        assert_eq!(batches.schema().fields().len(), 2);
+
+        let batches: Vec<RecordBatch> = batches.map(Result::unwrap).collect();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(batches[1].num_rows(), 1);
+
+        let rank = batches[0].column(1).as_primitive::<Int64Type>();
+        assert_eq!(rank.value(0), 1);
+        // "two" doesn't fit the inferred Int64 column
+        assert!(rank.is_null(1));
+        let rank = batches[1].column(1).as_primitive::<Int64Type>();
+        assert_eq!(rank.value(0), 3);
+    }
+}
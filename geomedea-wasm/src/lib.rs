@@ -1,11 +1,8 @@
-mod to_geo_type;
 mod utils;
 
-use crate::to_geo_type::ToGeoType;
-
 use futures_util::stream::StreamExt;
-use geojson::{JsonObject, JsonValue};
-use geomedea::{Bounds, FeatureStream, LngLat, Properties, PropertyValue};
+use geomedea::geojson::{feature_to_geojson, GeoJsonWriter};
+use geomedea::{Bounds, FeatureStream, LngLat};
 use wasm_bindgen::prelude::*;
 
 #[cfg(feature = "log")]
@@ -62,6 +59,11 @@ impl HttpReader {
             .map(JsValue::from)?)
     }
 
+    /// Selects the features within the box bounded by the given edges, in degrees.
+    ///
+    /// Latitudes must be within [-90, 90], with `bottom <= top`, and longitudes within
+    /// [-180, 180], otherwise an error is returned. If `left` is east of `right`, the box spans
+    /// the antimeridian.
     pub async fn select_bbox(
         &mut self,
         top: f64,
@@ -70,96 +72,166 @@ impl HttpReader {
         left: f64,
     ) -> Result<JsValue, JsError> {
         debug!("selecting bbox");
+        let bounds = bounds_from_edges(top, right, bottom, left)?;
         let mut http_reader = self.open().await?;
         debug!("http_reader: {:?}", http_reader);
 
-        let top_right = LngLat::degrees(right, top);
-        let bottom_left = LngLat::degrees(left, bottom);
-        let bounds = Bounds::from_corners(&top_right, &bottom_left);
         let feature_stream = http_reader.select_bbox(&bounds).await?;
         debug!("opened iter");
         Ok(FeatureCollection::new(feature_stream)
             .await
             .map(JsValue::from)?)
     }
+
+    /// Like `select_all`, but yields features one at a time as they arrive, rather than
+    /// buffering them all into a FeatureCollection.
+    pub async fn stream_all(&mut self) -> Result<FeatureIter, JsError> {
+        debug!("streaming all");
+        let mut http_reader = self.open().await?;
+        let feature_stream = http_reader.select_all().await?;
+        Ok(FeatureIter(feature_stream))
+    }
+
+    /// Like `select_bbox`, but yields features one at a time as they arrive, rather than
+    /// buffering them all into a FeatureCollection.
+    ///
+    /// The edges are validated the same way as for `select_bbox`.
+    pub async fn stream_bbox(
+        &mut self,
+        top: f64,
+        right: f64,
+        bottom: f64,
+        left: f64,
+    ) -> Result<FeatureIter, JsError> {
+        debug!("streaming bbox");
+        let bounds = bounds_from_edges(top, right, bottom, left)?;
+        let mut http_reader = self.open().await?;
+        let feature_stream = http_reader.select_bbox(&bounds).await?;
+        Ok(FeatureIter(feature_stream))
+    }
 }
 
-struct FeatureCollection(geojson::FeatureCollection);
-impl FeatureCollection {
-    async fn new(mut feature_stream: FeatureStream) -> geomedea::Result<Self> {
-        let mut geojson_feature_collection = geojson::FeatureCollection {
-            bbox: None,
-            features: vec![],
-            foreign_members: None,
-        };
+fn bounds_from_edges(top: f64, right: f64, bottom: f64, left: f64) -> Result<Bounds, JsError> {
+    checked_bounds(top, right, bottom, left).map_err(|message| JsError::new(&message))
+}
 
-        while let Some(feature) = feature_stream.next().await {
-            let feature = feature?;
-            geojson_feature_collection
-                .features
-                .push(geojson::Feature::from(GeoJsonFeature(feature)));
+/// Like [`bounds_from_edges`], but with a plain error message, since a `JsError` can only be
+/// created on wasm.
+fn checked_bounds(top: f64, right: f64, bottom: f64, left: f64) -> Result<Bounds, String> {
+    // NaN isn't within any range, so it's rejected here too.
+    for (name, value, limit) in [
+        ("top", top, 90.0),
+        ("right", right, 180.0),
+        ("bottom", bottom, 90.0),
+        ("left", left, 180.0),
+    ] {
+        if !(-limit..=limit).contains(&value) {
+            return Err(format!(
+                "invalid bbox: {name} must be a number of degrees within [-{limit}, {limit}], but was {value}"
+            ));
         }
+    }
+    if bottom > top {
+        return Err(format!(
+            "invalid bbox: bottom ({bottom}) is above top ({top}), expected arguments in the order top, right, bottom, left"
+        ));
+    }
 
-        Ok(Self(geojson_feature_collection))
+    // A left edge east of the right edge spans the antimeridian.
+    Ok(Bounds::new(
+        LngLat::degrees(left, bottom),
+        LngLat::degrees(right, top),
+    ))
+}
+
+/// Features from a selection, read incrementally so they can be rendered as they arrive.
+///
+/// ```js
+/// const features = await httpReader.stream_bbox(top, right, bottom, left);
+/// let feature;
+/// while ((feature = await features.next_feature()) !== undefined) {
+///     render(JSON.parse(feature));
+/// }
+/// ```
+#[wasm_bindgen]
+pub struct FeatureIter(FeatureStream);
+
+#[wasm_bindgen]
+impl FeatureIter {
+    /// The next feature as a GeoJSON Feature string, or `undefined` once all features have been
+    /// read.
+    pub async fn next_feature(&mut self) -> Result<JsValue, JsError> {
+        let Some(feature) = self.0.next().await else {
+            return Ok(JsValue::UNDEFINED);
+        };
+        Ok(JsValue::from(feature_to_geojson(&feature?).to_string()))
+    }
+
+    /// The number of features read so far, e.g. for showing progress.
+    pub fn features_read(&self) -> f64 {
+        self.0.handle().features_read() as f64
+    }
+
+    /// The number of bytes of feature data read so far.
+    pub fn bytes_read(&self) -> f64 {
+        self.0.handle().bytes_read() as f64
+    }
+
+    /// Stop reading: no more features will be returned, and no further requests made.
+    pub fn abort(&self) {
+        self.0.handle().abort()
+    }
+}
+
+struct FeatureCollection(String);
+impl FeatureCollection {
+    async fn new(feature_stream: FeatureStream) -> geomedea::Result<Self> {
+        let mut writer = GeoJsonWriter::new(vec![])?;
+        writer.write_feature_stream(feature_stream).await?;
+        let output = writer.finish()?;
+        Ok(Self(
+            String::from_utf8(output).expect("serde_json only writes valid UTF-8"),
+        ))
     }
 }
 
 impl From<FeatureCollection> for JsValue {
     fn from(value: FeatureCollection) -> Self {
-        JsValue::from(value.0.to_string())
+        JsValue::from(value.0)
     }
 }
 
-struct GeoJsonFeature(geomedea::Feature);
-impl From<GeoJsonFeature> for geojson::Feature {
-    fn from(value: GeoJsonFeature) -> Self {
-        let (geometry, properties) = value.0.into_inner();
-
-        fn geomedea_properties_to_json(properties: Properties) -> JsonObject {
-            fn property_to_json(property: PropertyValue) -> JsonValue {
-                match property {
-                    PropertyValue::Bool(value) => JsonValue::from(value),
-                    PropertyValue::Int8(value) => JsonValue::from(value),
-                    PropertyValue::UInt8(value) => JsonValue::from(value),
-                    PropertyValue::Int16(value) => JsonValue::from(value),
-                    PropertyValue::UInt16(value) => JsonValue::from(value),
-                    PropertyValue::Int32(value) => JsonValue::from(value),
-                    PropertyValue::UInt32(value) => JsonValue::from(value),
-                    PropertyValue::Int64(value) => JsonValue::from(value),
-                    PropertyValue::UInt64(value) => JsonValue::from(value),
-                    PropertyValue::Float32(value) => JsonValue::from(value),
-                    PropertyValue::Float64(value) => JsonValue::from(value),
-                    PropertyValue::Bytes(value) => JsonValue::from(value),
-                    PropertyValue::String(value) => JsonValue::from(value),
-                    PropertyValue::Vec(value) => {
-                        JsonValue::Array(value.into_iter().map(property_to_json).collect())
-                    }
-                    PropertyValue::Map(value) => {
-                        JsonValue::from(geomedea_properties_to_json(value))
-                    }
-                }
-            }
-            let mut json_map = JsonObject::new();
-            for (key, value) in properties.into_iter() {
-                json_map.insert(key, property_to_json(value));
-            }
-            json_map
-        }
-
-        let geo_geometry: geo_types::Geometry = geometry.to_geo_type();
-        let geojson_geometry: geojson::Geometry = (&geo_geometry).into();
-        let geojson_properties = if properties.is_empty() {
-            None
-        } else {
-            Some(geomedea_properties_to_json(properties))
-        };
-
-        geojson::Feature {
-            bbox: None,
-            geometry: Some(geojson_geometry),
-            id: None,
-            properties: geojson_properties,
-            foreign_members: None,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_bounds_from_edges() {
+        let bounds = checked_bounds(2.0, 3.0, 1.0, -4.0).unwrap();
+        assert_eq!(bounds.min(), &LngLat::degrees(-4.0, 1.0));
+        assert_eq!(bounds.max(), &LngLat::degrees(3.0, 2.0));
+        assert!(!bounds.spans_antimeridian());
+
+        // left is east of right
+        let bounds = checked_bounds(2.0, -170.0, 1.0, 170.0).unwrap();
+        assert_eq!(bounds.min(), &LngLat::degrees(170.0, 1.0));
+        assert_eq!(bounds.max(), &LngLat::degrees(-170.0, 2.0));
+        assert!(bounds.spans_antimeridian());
+
+        for (top, right, bottom, left) in [
+            (f64::NAN, 3.0, 1.0, -4.0),
+            (2.0, f64::NAN, 1.0, -4.0),
+            (2.0, 3.0, f64::NAN, -4.0),
+            (2.0, 3.0, 1.0, f64::NAN),
+            (91.0, 3.0, 1.0, -4.0),
+            (2.0, 3.0, 1.0, -181.0),
+            // bottom is above top
+            (1.0, 3.0, 2.0, -4.0),
+        ] {
+            assert!(
+                checked_bounds(top, right, bottom, left).is_err(),
+                "{top}, {right}, {bottom}, {left}"
+            );
         }
     }
 }
@@ -15,8 +15,9 @@ async fn select_all(url: &str) {
     assert_eq!(count, 3221);
 }
 
-async fn select_bbox(url: &str) {
+async fn select_bbox(url: &str, concurrent_requests: usize) {
     let mut reader = HttpReader::open(url).await.unwrap();
+    reader.set_concurrent_requests(concurrent_requests);
     let mut features = reader
         .select_bbox(&Bounds::from_corners(
             &LngLat::degrees(-86.0, 10.0),
@@ -64,13 +65,31 @@ fn benchmark(c: &mut Criterion) {
 
     c.bench_function("HTTP select_bbox (compressed)", |b| {
         let runtime = tokio::runtime::Runtime::new().unwrap();
-        b.to_async(runtime).iter(|| select_bbox(&compressed_url))
+        b.to_async(runtime).iter(|| select_bbox(&compressed_url, 1))
     });
 
+    c.bench_function(
+        "HTTP select_bbox (compressed, 4 concurrent requests)",
+        |b| {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            b.to_async(runtime).iter(|| select_bbox(&compressed_url, 4))
+        },
+    );
+
     c.bench_function("HTTP select_bbox (uncompressed)", |b| {
         let runtime = tokio::runtime::Runtime::new().unwrap();
-        b.to_async(runtime).iter(|| select_bbox(&uncompressed_url))
+        b.to_async(runtime)
+            .iter(|| select_bbox(&uncompressed_url, 1))
     });
+
+    c.bench_function(
+        "HTTP select_bbox (uncompressed, 4 concurrent requests)",
+        |b| {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            b.to_async(runtime)
+                .iter(|| select_bbox(&uncompressed_url, 4))
+        },
+    );
 }
 
 criterion_group!(name=benches; config=Criterion::default().sample_size(10); targets=benchmark);
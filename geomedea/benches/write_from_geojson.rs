@@ -4,9 +4,14 @@ use geomedea_geozero::GeomedeaWriter as GeozeroWriter;
 use std::fs::File;
 use std::io::BufReader;
 
-fn write(mut geojson: GeoJsonReader<BufReader<File>>, is_compressed: bool) -> Vec<u8> {
+fn write(
+    mut geojson: GeoJsonReader<BufReader<File>>,
+    is_compressed: bool,
+    intern_property_keys: bool,
+) -> Vec<u8> {
     let mut output = vec![];
     let mut writer = GeozeroWriter::new(&mut output, is_compressed).unwrap();
+    writer.set_intern_property_keys(intern_property_keys);
     // Artificially small page size to make sure we're exercising paging code paths
     writer.set_page_size_goal(8 * 1024);
     geojson.process(&mut writer).unwrap();
@@ -21,7 +26,7 @@ fn benchmark(c: &mut Criterion) {
                 let input = BufReader::new(File::open("../test_fixtures/places.json").unwrap());
                 GeoJsonReader(input)
             },
-            |geojson| write(black_box(geojson), true),
+            |geojson| write(black_box(geojson), true, true),
             BatchSize::LargeInput,
         );
     });
@@ -31,7 +36,17 @@ fn benchmark(c: &mut Criterion) {
                 let input = BufReader::new(File::open("../test_fixtures/places.json").unwrap());
                 GeoJsonReader(input)
             },
-            |geojson| write(black_box(geojson), false),
+            |geojson| write(black_box(geojson), false, true),
+            BatchSize::LargeInput,
+        );
+    });
+    c.bench_function("write (compressed, without interning property keys)", |b| {
+        b.iter_batched(
+            || {
+                let input = BufReader::new(File::open("../test_fixtures/places.json").unwrap());
+                GeoJsonReader(input)
+            },
+            |geojson| write(black_box(geojson), true, false),
             BatchSize::LargeInput,
         );
     });
@@ -0,0 +1,339 @@
+//! Reading from any async source which can seek, see [`AsyncReader`].
+
+use crate::asyncio::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+use crate::format::verify_page_checksum;
+use crate::http_reader::FeatureBuffer;
+use crate::io::{decode_page, read_untrusted_len};
+use crate::packed_r_tree::{select_bboxes_in_slice, PackedRTree};
+use crate::{
+    serialized_size, Bounds, Error, FeatureLocation, FeatureStream, Header, MaybeSend, PageHeader,
+    Result,
+};
+use bytes::Bytes;
+use futures_util::Stream;
+use std::io::SeekFrom;
+use std::sync::Arc;
+
+/// Reads features from any async source which can seek, e.g. a `tokio::fs::File`, or an object
+/// store's client adapted to `AsyncRead` and `AsyncSeek`.
+///
+/// Unlike [`HttpReader`](crate::HttpReader), which makes (concurrent) range requests, this reads
+/// sequentially, seeking between pages as needed. Each page is read and decoded whole, and
+/// [`AsyncReader::select_bbox`] reads the entire index into memory.
+#[derive(Debug)]
+pub struct AsyncReader<R> {
+    reader: R,
+    header: Header,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin + MaybeSend + 'static> AsyncReader<R> {
+    /// Reads the header from the start of `reader`.
+    pub async fn new(mut reader: R) -> Result<Self> {
+        reader.seek(SeekFrom::Start(0)).await?;
+        // The header might be smaller than this, and so might a file with no features.
+        let mut header_bytes = vec![];
+        (&mut reader)
+            .take(Header::max_serialized_size())
+            .read_to_end(&mut header_bytes)
+            .await?;
+        let (mut header, dictionary_range) = Header::read_without_dictionary(&*header_bytes)?;
+        if let Some(dictionary_range) = dictionary_range {
+            reader.seek(SeekFrom::Start(dictionary_range.start)).await?;
+            let dictionary =
+                read_untrusted_len(&mut reader, dictionary_range.end - dictionary_range.start)
+                    .await?;
+            header.dictionary = Some(Arc::from(dictionary));
+        }
+        Ok(Self { reader, header })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    fn tree(&self) -> PackedRTree {
+        PackedRTree::new(self.header.feature_count, self.header.branching_factor)
+    }
+
+    /// Yields every feature, in the order they're stored.
+    pub async fn select_all(mut self) -> Result<FeatureStream> {
+        let feature_start = serialized_size(&self.header)? + self.tree().index_size();
+        self.reader.seek(SeekFrom::Start(feature_start)).await?;
+        let coord_precision_nanos = self.header.coord_precision_nanos;
+        Ok(FeatureStream::new(
+            select_all(self.reader, self.header),
+            coord_precision_nanos,
+        ))
+    }
+
+    /// Yields every feature whose bounds intersect `bounds`, in the order they're stored.
+    pub async fn select_bbox(self, bounds: &Bounds) -> Result<FeatureStream> {
+        self.select_bboxes(std::slice::from_ref(bounds)).await
+    }
+
+    /// Yields every feature whose bounds intersect any of `bounds`, in the order they're stored,
+    /// each only once.
+    pub async fn select_bboxes(mut self, bounds: &[Bounds]) -> Result<FeatureStream> {
+        let tree = self.tree();
+        let index_start = serialized_size(&self.header)?;
+        self.reader.seek(SeekFrom::Start(index_start)).await?;
+        let index = read_untrusted_len(&mut self.reader, tree.index_size()).await?;
+        let locations = select_bboxes_in_slice(&tree, &index, bounds)?;
+        debug!("select_bboxes locations: {locations:?}");
+
+        let feature_start = index_start + tree.index_size();
+        let coord_precision_nanos = self.header.coord_precision_nanos;
+        Ok(FeatureStream::new(
+            select_locations(self.reader, self.header, feature_start, locations),
+            coord_precision_nanos,
+        ))
+    }
+}
+
+/// Yields each feature buffer, starting from the first page, which `reader` is positioned at.
+fn select_all<R: AsyncRead + Unpin>(
+    mut reader: R,
+    header: Header,
+) -> impl Stream<Item = Result<FeatureBuffer>> {
+    async_stream::try_stream! {
+        let mut features_left = header.feature_count;
+        let mut page_starting_offset = 0;
+        let mut page_index = 0;
+        while features_left > 0 {
+            let (page, page_len) = read_page(&mut reader, &header).await?;
+            let mut offset = 0;
+            while offset < page.len() && features_left > 0 {
+                let location = FeatureLocation {
+                    page_starting_offset,
+                    feature_offset: offset as u32,
+                };
+                let bytes = feature_at(&page, offset)?;
+                offset += 8 + bytes.len();
+                features_left -= 1;
+                yield FeatureBuffer {
+                    bytes,
+                    location,
+                    page_index: Some(page_index),
+                };
+            }
+            page_starting_offset += page_len;
+            page_index += 1;
+        }
+    }
+}
+
+/// Yields the feature buffer at each of `locations`, which must be sorted, so that each page is
+/// only read once.
+fn select_locations<R: AsyncRead + AsyncSeek + Unpin>(
+    mut reader: R,
+    header: Header,
+    feature_start: u64,
+    locations: Vec<FeatureLocation>,
+) -> impl Stream<Item = Result<FeatureBuffer>> {
+    async_stream::try_stream! {
+        // The starting offset of the page most recently read, and its decoded contents.
+        let mut current_page: Option<(u64, Bytes)> = None;
+        for location in locations {
+            let page = match &current_page {
+                Some((page_starting_offset, page))
+                    if *page_starting_offset == location.page_starting_offset =>
+                {
+                    page.clone()
+                }
+                _ => {
+                    let page_start = feature_start + location.page_starting_offset;
+                    reader.seek(SeekFrom::Start(page_start)).await?;
+                    let (page, _page_len) = read_page(&mut reader, &header).await?;
+                    current_page = Some((location.page_starting_offset, page.clone()));
+                    page
+                }
+            };
+            yield FeatureBuffer {
+                bytes: feature_at(&page, location.feature_offset as usize)?,
+                location,
+                // Pages are jumped to, so which of them this is isn't known.
+                page_index: None,
+            };
+        }
+    }
+}
+
+/// Reads the page header at the current position of `reader`, and returns the decoded page
+/// which follows it, along with how many bytes the page took up, including its header.
+async fn read_page<R: AsyncRead + Unpin>(reader: &mut R, header: &Header) -> Result<(Bytes, u64)> {
+    let mut page_header_bytes = vec![0u8; PageHeader::serialized_size(header.has_page_checksums)];
+    reader.read_exact(&mut page_header_bytes).await?;
+    let (page_header, _page_bounds) =
+        PageHeader::read_from(&*page_header_bytes, header.has_page_checksums)?;
+    trace!("read page header: {page_header:?}");
+
+    let encoded = read_untrusted_len(reader, page_header.encoded_page_length() as u64).await?;
+    verify_page_checksum(&encoded, page_header.checksum())?;
+    let decoded = decode_page(
+        &encoded,
+        header.compression,
+        page_header.decoded_page_length(),
+        header.dictionary.as_deref(),
+    )?;
+    let page_len = (page_header_size + encoded.len()) as u64;
+    Ok((Bytes::from(decoded), page_len))
+}
+
+/// The serialized feature whose length prefix starts at `offset` within `page`.
+fn feature_at(page: &Bytes, offset: usize) -> Result<Bytes> {
+    let truncated = || Error::from(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+    let len_bytes = page.get(offset..offset + 8).ok_or_else(truncated)?;
+    let feature_len = u64::from_le_bytes(len_bytes.try_into().expect("8 bytes")) as usize;
+    let start = offset + 8;
+    let end = start
+        .checked_add(feature_len)
+        .filter(|end| *end <= page.len())
+        .ok_or_else(truncated)?;
+    Ok(page.slice(start..end))
+}
+
+#[cfg(test)]
+#[cfg(feature = "writer")]
+mod tests {
+    use super::*;
+    use crate::geometry::Bounded;
+    use crate::{test_data, wkt, Compression, Feature, Geometry, LngLat, Properties, Writer};
+    use futures_util::TryStreamExt;
+    use std::io::Cursor;
+
+    async fn geometries(stream: FeatureStream) -> Vec<Geometry> {
+        stream
+            .map_ok(|feature| feature.geometry().clone())
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn select_all() {
+        for is_compressed in [false, true] {
+            let output = test_data::small_pages(4, is_compressed);
+            let reader = AsyncReader::new(Cursor::new(output)).await.unwrap();
+            assert_eq!(reader.header().feature_count, 4);
+            let stream = reader.select_all().await.unwrap();
+            // slightly re-ordered vs. input because of hilbert
+            assert_eq!(
+                geometries(stream).await,
+                vec![
+                    Geometry::Point(wkt!(POINT(3 3))),
+                    Geometry::Point(wkt!(POINT(2 2))),
+                    Geometry::Point(wkt!(POINT(1 1))),
+                    Geometry::Point(wkt!(POINT(0 0))),
+                ]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn select_bbox() {
+        for compression in [
+            Compression::None,
+            Compression::Zstd,
+            Compression::Lz4,
+            Compression::Brotli,
+        ] {
+            let mut writer = Writer::new(vec![], false).unwrap();
+            writer.set_compression(compression);
+            writer.set_page_size_goal(100);
+            for lng in 0..20 {
+                let point = LngLat::degrees(lng as f64, 0.0);
+                writer
+                    .add_feature(&Feature::new(point.into(), Properties::empty()))
+                    .unwrap();
+            }
+            let output = writer.finish().unwrap();
+
+            let reader = AsyncReader::new(Cursor::new(output)).await.unwrap();
+            let stream = reader
+                .select_bbox(&wkt!(RECT(4.5 -1, 7.5 1)))
+                .await
+                .unwrap();
+            let mut selected = geometries(stream).await;
+            // Features are stored in hilbert order
+            selected.sort_by_key(|geometry| geometry.bounds().min().lng_unscaled());
+            assert_eq!(
+                selected,
+                vec![
+                    Geometry::Point(wkt!(POINT(5 0))),
+                    Geometry::Point(wkt!(POINT(6 0))),
+                    Geometry::Point(wkt!(POINT(7 0))),
+                ],
+                "compression: {compression:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn select_bboxes() {
+        for is_compressed in [false, true] {
+            let output = test_data::small_pages(100, is_compressed);
+            let bounds = [
+                wkt!(RECT(10 10,12 12)),
+                wkt!(RECT(80.5 80.5,81.5 81.5)),
+                // overlaps the first
+                wkt!(RECT(11 11,13 13)),
+            ];
+            let reader = AsyncReader::new(Cursor::new(output)).await.unwrap();
+            let stream = reader.select_bboxes(&bounds).await.unwrap();
+            // Each feature is only selected once, in the order they're stored.
+            let mut selected = geometries(stream).await;
+            selected.sort_by_key(|geometry| geometry.bounds().min().lng_unscaled());
+            assert_eq!(
+                selected,
+                vec![
+                    Geometry::Point(wkt!(POINT(10 10))),
+                    Geometry::Point(wkt!(POINT(11 11))),
+                    Geometry::Point(wkt!(POINT(12 12))),
+                    Geometry::Point(wkt!(POINT(13 13))),
+                    Geometry::Point(wkt!(POINT(81 81))),
+                ]
+            );
+
+            let reader = AsyncReader::new(Cursor::new(test_data::small_pages(100, is_compressed)))
+                .await
+                .unwrap();
+            let stream = reader.select_bboxes(&[]).await.unwrap();
+            assert!(geometries(stream).await.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn corrupt_feature() {
+        let mut output = test_data::small_pages(4, false);
+        test_data::corrupt_last_feature(&mut output);
+
+        let reader = AsyncReader::new(Cursor::new(output.clone())).await.unwrap();
+        let stream = reader.select_all().await.unwrap();
+        let error = stream.try_collect::<Vec<_>>().await.unwrap_err();
+        let (page_index, page_starting_offset, feature_offset) =
+            test_data::corrupt_feature_location(error);
+        assert!(page_index.unwrap() > 0);
+        assert!(page_starting_offset > 0);
+
+        // Pages are jumped to, so which page it's on isn't known.
+        let reader = AsyncReader::new(Cursor::new(output)).await.unwrap();
+        let stream = reader.select_bbox(&wkt!(RECT(-1 -1, 4 4))).await.unwrap();
+        let error = stream.try_collect::<Vec<_>>().await.unwrap_err();
+        assert_eq!(
+            test_data::corrupt_feature_location(error),
+            (None, page_starting_offset, feature_offset)
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_file() {
+        let output = Writer::new(vec![], true).unwrap().finish().unwrap();
+        let reader = AsyncReader::new(Cursor::new(output.clone())).await.unwrap();
+        assert!(geometries(reader.select_all().await.unwrap())
+            .await
+            .is_empty());
+        let reader = AsyncReader::new(Cursor::new(output)).await.unwrap();
+        let stream = reader.select_bbox(&wkt!(RECT(0 0, 1 1))).await.unwrap();
+        assert!(geometries(stream).await.is_empty());
+    }
+}
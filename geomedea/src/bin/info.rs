@@ -1,20 +1,51 @@
-use geomedea::Reader;
+use geomedea::{Bounds, LngLat, Reader};
 use std::fs;
 use std::io::BufReader;
 
 type Error = Box<dyn std::error::Error>;
 type Result<T> = std::result::Result<T, Error>;
 
+/// How to write the features to stdout, if at all.
+#[derive(Clone, Copy, PartialEq)]
+enum Dump {
+    None,
+    /// Newline delimited GeoJSON, one Feature per line.
+    Features,
+    /// A single GeoJSON FeatureCollection.
+    FeatureCollection,
+}
+
 fn main() -> Result<()> {
     let mut args = std::env::args();
     let bin_name = args.next().expect("Missing bin name");
-    let input_path = args.next().unwrap_or_else(|| {
-        panic!("Missing input path.\nUsage:\n\t`{bin_name} <input-path.geomedea>`")
-    });
+    let usage = format!(
+        "Usage:\n\t`{bin_name} <input-path.geomedea> [--features | --dump-geojson] [--bbox minx,miny,maxx,maxy]`"
+    );
+    let input_path = args
+        .next()
+        .unwrap_or_else(|| panic!("Missing input path.\n{usage}"));
+    let mut dump = Dump::None;
+    let mut bbox = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--features" => dump = Dump::Features,
+            "--dump-geojson" => dump = Dump::FeatureCollection,
+            "--bbox" => {
+                let value = args
+                    .next()
+                    .unwrap_or_else(|| panic!("Missing value for --bbox.\n{usage}"));
+                bbox = Some(parse_bbox(&value)?);
+            }
+            other => panic!("Unknown argument: {other}\n{usage}"),
+        }
+    }
+    if bbox.is_some() && dump == Dump::None {
+        panic!("--bbox requires --features or --dump-geojson.\n{usage}");
+    }
 
     let file_size = fs::metadata(&input_path)?.len();
     let input = BufReader::new(std::fs::File::open(&input_path)?);
-    let reader = Reader::new(input)?;
+    let mut reader = Reader::new(input)?;
     let info = reader.info();
     eprintln!("info: {info:?}");
 
@@ -22,11 +53,77 @@ fn main() -> Result<()> {
     eprintln!("        file_size: {:?}", file_size);
     eprintln!("      header_size: {header_size}");
     eprintln!("       index_size: {}", info.index_size());
+    eprintln!("    feature_count: {}", info.feature_count());
+    eprintln!("       page_count: {}", info.page_count());
+    eprintln!("           extent: {:?}", info.extent());
     eprintln!(
         "feature_data_size: {}",
-        file_size - header_size - info.index_size()
+        // Saturating in case the file is truncated
+        file_size.saturating_sub(header_size + info.index_size())
     );
+    match reader.validate() {
+        Ok(()) => eprintln!("            valid: true"),
+        Err(e) => eprintln!("            valid: false, {e}"),
+    }
+
+    if dump != Dump::None {
+        write_features(&input_path, bbox.as_ref(), dump)?;
+    }
 
     eprintln!("done");
     Ok(())
 }
+
+/// Parses `minx,miny,maxx,maxy`, in degrees.
+fn parse_bbox(value: &str) -> Result<Bounds> {
+    let edges = value
+        .split(',')
+        .map(|edge| edge.trim().parse::<f64>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid --bbox {value:?}: {e}"))?;
+    let [min_x, min_y, max_x, max_y] = edges[..] else {
+        return Err(format!("Invalid --bbox {value:?}: expected minx,miny,maxx,maxy").into());
+    };
+    let corner = |lng, lat| {
+        LngLat::try_degrees(lng, lat).map_err(|e| format!("Invalid --bbox {value:?}: {e}"))
+    };
+    Ok(Bounds::from_corners(
+        &corner(min_x, min_y)?,
+        &corner(max_x, max_y)?,
+    ))
+}
+
+/// Writes the features within `bbox`, or every feature, to stdout.
+#[cfg(feature = "geojson")]
+fn write_features(input_path: &str, bbox: Option<&Bounds>, dump: Dump) -> Result<()> {
+    use geomedea::geojson::{feature_to_geojson, GeoJsonWriter};
+    use std::io::Write;
+
+    let input = BufReader::new(std::fs::File::open(input_path)?);
+    let reader = Reader::new(input)?;
+    let mut features = match bbox {
+        Some(bbox) => reader.select_bbox(bbox)?,
+        None => reader.select_all()?,
+    };
+    let mut output = std::io::BufWriter::new(std::io::stdout().lock());
+    match dump {
+        Dump::None => {}
+        Dump::Features => {
+            while let Some(feature) = features.try_next()? {
+                writeln!(output, "{}", feature_to_geojson(&feature))?;
+            }
+            output.flush()?;
+        }
+        Dump::FeatureCollection => {
+            let mut writer = GeoJsonWriter::new(output)?;
+            writer.write_feature_iter(features)?;
+            writer.finish()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "geojson"))]
+fn write_features(_input_path: &str, _bbox: Option<&Bounds>, _dump: Dump) -> Result<()> {
+    Err("Writing features requires building with the `geojson` feature".into())
+}
@@ -1,8 +1,15 @@
+use crate::geometry::COORD_SCALE_FACTOR;
 use crate::LngLat;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Formatter};
 
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// 180°, in unscaled units.
+const HALF_TURN_UNSCALED: i64 = 180 * COORD_SCALE_FACTOR as i64;
+const FULL_TURN_UNSCALED: i64 = 2 * HALF_TURN_UNSCALED;
+
+/// Bounds are ordered lexicographically by their min corner, then their max corner, each
+/// ordered as a [`LngLat`].
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Bounds {
     min: LngLat,
     max: LngLat,
@@ -31,7 +38,12 @@ impl Bounds {
     }
 
     pub fn unscaled_lng_width(&self) -> u32 {
-        (self.max.lng_unscaled() as i64 - self.min.lng_unscaled() as i64) as u32
+        let width = self.max.lng_unscaled() as i64 - self.min.lng_unscaled() as i64;
+        if self.spans_antimeridian() {
+            (width + FULL_TURN_UNSCALED) as u32
+        } else {
+            width as u32
+        }
     }
 
     pub fn unscaled_lat_height(&self) -> u32 {
@@ -45,8 +57,28 @@ impl Bounds {
         }
     }
 
+    fn is_empty(&self) -> bool {
+        self.min.lat_unscaled() > self.max.lat_unscaled()
+    }
+
+    /// Bounds with a lng/lat of at least `min` and at most `max`.
+    ///
+    /// If `min`'s lng is greater than `max`'s, the bounds span the antimeridian, extending east
+    /// from `min` to 180° and then on from -180° to `max`.
+    ///
+    /// See also [`Bounds::from_corners`], which never spans the antimeridian.
+    pub fn new(min: LngLat, max: LngLat) -> Self {
+        debug_assert!(
+            min.lat_unscaled() <= max.lat_unscaled(),
+            "min lat must not be greater than max lat"
+        );
+        Bounds { min, max }
+    }
+
+    /// The smallest bounds containing both corners, which are in no particular order.
+    ///
+    /// Use [`Bounds::new`] for bounds which span the antimeridian.
     pub fn from_corners(a: &LngLat, b: &LngLat) -> Self {
-        // TODO: should I not do this sorting in order to allow bounds to span IDL?
         let min_lng = a.lng_unscaled().min(b.lng_unscaled());
         let min_lat = a.lat_unscaled().min(b.lat_unscaled());
         let max_lng = a.lng_unscaled().max(b.lng_unscaled());
@@ -57,7 +89,48 @@ impl Bounds {
         }
     }
 
+    /// Do these bounds cross from 180° to -180° lng? If so, `min`'s lng is greater than `max`'s.
+    pub fn spans_antimeridian(&self) -> bool {
+        self.min.lng_unscaled() > self.max.lng_unscaled() && !self.is_empty()
+    }
+
+    /// The (inclusive) ranges of lng covered by these bounds: two ranges if the bounds span the
+    /// antimeridian, otherwise one.
+    fn lng_ranges(&self) -> impl Iterator<Item = (i32, i32)> {
+        let (min, max) = (self.min.lng_unscaled(), self.max.lng_unscaled());
+        let (west, east) = if self.spans_antimeridian() {
+            ((min, i32::MAX), Some((i32::MIN, max)))
+        } else {
+            ((min, max), None)
+        };
+        std::iter::once(west).chain(east)
+    }
+
+    /// These bounds, with any lng beyond ±180° or lat beyond ±90° moved to the nearest valid
+    /// value, e.g. after growing the bounds past the edge of the map.
+    pub fn clamp_to_valid(&self) -> Bounds {
+        if self.is_empty() {
+            return self.clone();
+        }
+        const HALF_TURN: i32 = HALF_TURN_UNSCALED as i32;
+        const QUARTER_TURN: i32 = HALF_TURN / 2;
+        let clamp = |point: &LngLat| {
+            LngLat::unscaled(
+                point.lng_unscaled().clamp(-HALF_TURN, HALF_TURN),
+                point.lat_unscaled().clamp(-QUARTER_TURN, QUARTER_TURN),
+            )
+        };
+        Bounds {
+            min: clamp(&self.min),
+            max: clamp(&self.max),
+        }
+    }
+
     pub fn extend(&mut self, other: &Bounds) {
+        if self.spans_antimeridian() || other.spans_antimeridian() {
+            self.extend_spanning_antimeridian(other);
+            return;
+        }
         if other.max.lng_unscaled() > self.max.lng_unscaled() {
             self.max.set_lng_unscaled(other.max.lng_unscaled());
         }
@@ -72,7 +145,65 @@ impl Bounds {
         }
     }
 
+    /// Extend to the smallest bounds covering both, going whichever way around the globe is
+    /// shorter.
+    fn extend_spanning_antimeridian(&mut self, other: &Bounds) {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            *self = other.clone();
+            return;
+        }
+
+        let eastward_distance = |from: i64, to: i64| (to - from).rem_euclid(FULL_TURN_UNSCALED);
+        let arcs = [
+            (
+                self.min.lng_unscaled() as i64,
+                self.unscaled_lng_width() as i64,
+            ),
+            (
+                other.min.lng_unscaled() as i64,
+                other.unscaled_lng_width() as i64,
+            ),
+        ];
+        // The union must start at the western edge of one of the two arcs. Pick whichever is
+        // shorter.
+        let (west, width) = arcs
+            .iter()
+            .map(|&(start, _)| {
+                let width = arcs
+                    .iter()
+                    .map(|&(arc_start, arc_width)| eastward_distance(start, arc_start) + arc_width)
+                    .max()
+                    .expect("non-empty");
+                (start, width)
+            })
+            .min_by_key(|&(_start, width)| width)
+            .expect("non-empty");
+
+        let (west, east) = if width >= FULL_TURN_UNSCALED {
+            (-HALF_TURN_UNSCALED, HALF_TURN_UNSCALED)
+        } else {
+            let mut east = west + width;
+            if east > HALF_TURN_UNSCALED {
+                east -= FULL_TURN_UNSCALED;
+            }
+            (west, east)
+        };
+        self.min.set_lng_unscaled(west as i32);
+        self.max.set_lng_unscaled(east as i32);
+        self.min
+            .set_lat_unscaled(self.min.lat_unscaled().min(other.min.lat_unscaled()));
+        self.max
+            .set_lat_unscaled(self.max.lat_unscaled().max(other.max.lat_unscaled()));
+    }
+
     pub fn extend_point(&mut self, point: &LngLat) {
+        if self.spans_antimeridian() {
+            self.extend_spanning_antimeridian(&Bounds::from_corners(point, point));
+            return;
+        }
         if point.lng_unscaled() > self.max.lng_unscaled() {
             self.max.set_lng_unscaled(point.lng_unscaled());
         }
@@ -89,29 +220,63 @@ impl Bounds {
 
     #[cfg(feature = "writer")]
     pub(crate) fn center(&self) -> LngLat {
-        let half_lng_width = self.unscaled_lng_width() / 2;
-        let half_lat_height = self.unscaled_lat_height() / 2;
+        // The width and height of large bounds can exceed i32::MAX, so compute in i64.
+        let half_lng_width = self.unscaled_lng_width() as i64 / 2;
+        let half_lat_height = self.unscaled_lat_height() as i64 / 2;
+
+        let mut mid_lng = self.min.lng_unscaled() as i64 + half_lng_width;
+        if self.spans_antimeridian() && mid_lng > HALF_TURN_UNSCALED {
+            mid_lng -= FULL_TURN_UNSCALED;
+        }
+        let mid_lat = self.min.lat_unscaled() as i64 + half_lat_height;
 
-        let mid_lng = self.min.lng_unscaled() + half_lng_width as i32;
-        let mid_lat = self.min.lat_unscaled() + half_lat_height as i32;
+        let clamp = |value: i64| value.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+        LngLat::unscaled(clamp(mid_lng), clamp(mid_lat))
+    }
 
-        LngLat::unscaled(mid_lng, mid_lat)
+    /// The squared planar distance, in unscaled units, from `point` to the nearest point within
+    /// these bounds. This is zero if the bounds contain `point`.
+    pub(crate) fn distance_squared(&self, point: &LngLat) -> u128 {
+        fn axis_distance(value: i32, min: i32, max: i32) -> u128 {
+            let value = value as i64;
+            let distance = (min as i64 - value).max(value - max as i64).max(0);
+            distance as u128
+        }
+        let lng_distance = self
+            .lng_ranges()
+            .map(|(min, max)| axis_distance(point.lng_unscaled(), min, max))
+            .min()
+            .expect("at least one lng range");
+        let lat_distance = axis_distance(
+            point.lat_unscaled(),
+            self.min.lat_unscaled(),
+            self.max.lat_unscaled(),
+        );
+        lng_distance * lng_distance + lat_distance * lat_distance
     }
 
     pub(crate) fn intersects(&self, other: &Bounds) -> bool {
-        if self.max.lng_unscaled() < other.min.lng_unscaled() {
+        if self.max.lat_unscaled() < other.min.lat_unscaled() {
             return false;
         }
 
-        if self.max.lat_unscaled() < other.min.lat_unscaled() {
+        if self.min.lat_unscaled() > other.max.lat_unscaled() {
             return false;
         }
 
-        if self.min.lng_unscaled() > other.max.lng_unscaled() {
+        if self.spans_antimeridian() || other.spans_antimeridian() {
+            return self.lng_ranges().any(|(min, max)| {
+                other
+                    .lng_ranges()
+                    .any(|(other_min, other_max)| min <= other_max && other_min <= max)
+            });
+        }
+
+        if self.max.lng_unscaled() < other.min.lng_unscaled() {
             return false;
         }
 
-        if self.min.lat_unscaled() > other.max.lat_unscaled() {
+        if self.min.lng_unscaled() > other.max.lng_unscaled() {
             return false;
         }
 
@@ -122,6 +287,7 @@ impl Bounds {
 #[cfg(feature = "writer")]
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::wkt;
 
     #[test]
@@ -133,9 +299,97 @@ mod tests {
         assert_eq!(wkt!(POINT(2.5 2.5)), node_2.center());
     }
 
+    /// Bounds from `west` to `east`, which span the antimeridian if `west` is greater.
+    fn lng_span(west: f64, south: f64, east: f64, north: f64) -> Bounds {
+        Bounds::new(LngLat::degrees(west, south), LngLat::degrees(east, north))
+    }
+
     #[test]
     fn negative() {
         let bounds = wkt!(RECT(1 2,-3 -6));
         assert_eq!(wkt!(POINT(-1.0 - 2.0)), bounds.center());
     }
+
+    #[test]
+    fn rect_never_spans_antimeridian() {
+        let bounds = wkt!(RECT(170 0,-160 10));
+        assert!(!bounds.spans_antimeridian());
+        assert_eq!(bounds, wkt!(RECT(-160 0,170 10)));
+    }
+
+    #[test]
+    fn wide_center() {
+        let globe = Bounds::from_corners(
+            &LngLat::degrees(-180.0, -90.0),
+            &LngLat::degrees(180.0, 90.0),
+        );
+        assert_eq!(LngLat::degrees(0.0, 0.0), globe.center());
+
+        let widest = Bounds::from_corners(
+            &LngLat::unscaled(i32::MIN, i32::MIN),
+            &LngLat::unscaled(i32::MAX, i32::MAX),
+        );
+        assert_eq!(LngLat::unscaled(-1, -1), widest.center());
+    }
+
+    #[test]
+    fn clamp_to_valid() {
+        let bounds = wkt!(RECT(150 -100,200 10));
+        assert_eq!(bounds.clamp_to_valid(), wkt!(RECT(150 -90,180 10)));
+
+        let valid = wkt!(RECT(-180 -90,180 90));
+        assert_eq!(valid.clamp_to_valid(), valid);
+
+        let spanning = lng_span(170.0, 0.0, -200.0, 95.0);
+        assert_eq!(
+            spanning.clamp_to_valid(),
+            lng_span(170.0, 0.0, -180.0, 90.0)
+        );
+    }
+
+    #[test]
+    fn spanning_antimeridian() {
+        let bounds = lng_span(170.0, 0.0, -160.0, 10.0);
+        assert!(bounds.spans_antimeridian());
+        assert_eq!(
+            bounds.unscaled_lng_width(),
+            wkt!(RECT(0 0,30 0)).unscaled_lng_width()
+        );
+        assert_eq!(wkt!(POINT(-175 5)), bounds.center());
+
+        assert!(bounds.intersects(&wkt!(RECT(175 5,176 6))));
+        assert!(bounds.intersects(&wkt!(RECT(-170 5,-165 6))));
+        assert!(!bounds.intersects(&wkt!(RECT(-10 5,10 6))));
+        assert!(bounds.intersects(&wkt!(RECT(-180 5,180 6))));
+        assert!(!bounds.intersects(&wkt!(RECT(175 20,176 21))));
+    }
+
+    #[test]
+    fn extend_across_antimeridian() {
+        let mut bounds = lng_span(170.0, 0.0, -160.0, 10.0);
+        bounds.extend_point(&wkt!(POINT(-150 5)));
+        assert_eq!(bounds, lng_span(170.0, 0.0, -150.0, 10.0));
+
+        // Closer to the western edge
+        bounds.extend_point(&wkt!(POINT(160 20)));
+        assert_eq!(bounds, lng_span(160.0, 0.0, -150.0, 20.0));
+
+        // Already covered
+        bounds.extend(&lng_span(175.0, 5.0, -175.0, 5.0));
+        assert_eq!(bounds, lng_span(160.0, 0.0, -150.0, 20.0));
+
+        let mut bounds = wkt!(RECT(10 0,20 10));
+        bounds.extend(&lng_span(170.0, 0.0, -170.0, 10.0));
+        assert_eq!(bounds, lng_span(10.0, 0.0, -170.0, 10.0));
+
+        let mut bounds = wkt!(RECT(-20 0,20 10));
+        bounds.extend(&lng_span(170.0, 0.0, -170.0, 10.0));
+        // Both ways around are equally short
+        assert_eq!(bounds, lng_span(-20.0, 0.0, -170.0, 10.0));
+
+        // Covers the whole globe
+        let mut bounds = wkt!(RECT(-100 0,100 10));
+        bounds.extend(&lng_span(90.0, 0.0, -90.0, 10.0));
+        assert_eq!(bounds, wkt!(RECT(-180 0,180 10)));
+    }
 }
@@ -4,10 +4,84 @@ use thiserror::Error;
 pub enum Error {
     #[error("Failure with bincode")]
     Bincode(#[from] bincode::Error),
+    #[error(
+        "Failed to decode the feature at offset {feature_offset} of the page starting at offset {page_starting_offset}{}, the file may be truncated or corrupt",
+        .page_index.map(|page_index| format!(" (page #{page_index})")).unwrap_or_default()
+    )]
+    CorruptFeature {
+        /// Only known when the pages were read in order, rather than jumped to.
+        page_index: Option<u64>,
+        page_starting_offset: u64,
+        feature_offset: u32,
+        source: bincode::Error,
+    },
+    #[error(
+        "Page checksum is {found:#018x}, but expected {expected:#018x}, the file may be corrupt"
+    )]
+    PageChecksumMismatch { expected: u64, found: u64 },
     #[error("I/O error")]
     IO(#[from] std::io::Error),
+    #[error(
+        "The file is truncated, {section} should end at byte {expected_len}, but the file is only {file_len} bytes"
+    )]
+    TruncatedFile {
+        section: crate::FileSection,
+        expected_len: u64,
+        file_len: u64,
+    },
     #[error("Only had {found} features, but expected {expected}")]
     FeatureCountMismatch { found: u64, expected: u64 },
+    #[error(
+        "The index implied by a feature count of {feature_count} doesn't match the file's contents"
+    )]
+    IndexSizeMismatch { feature_count: u64 },
+    #[error("Invalid compression level {level}, expected a value between {min} and {max}")]
+    InvalidCompressionLevel { level: i32, min: i32, max: i32 },
+    #[error("Invalid zstd dictionary, expected one in zstd's format, e.g. from `zstd --train`")]
+    InvalidDictionary,
+    /// zstd is a C library, which isn't built for wasm32, so zstd compressed output can't be
+    /// written there.
+    #[error("Writing {0:?} compressed output isn't supported on this target")]
+    UnsupportedCompression(crate::Compression),
+    #[error("Invalid branching factor {0}, must be at least 2")]
+    InvalidBranchingFactor(u16),
+    #[error("Invalid coordinate precision {0} nanodegrees, coordinates would not fit in an i32")]
+    InvalidCoordPrecision(i32),
+    #[error("The coordinate precision can't be changed after features have been added")]
+    CoordPrecisionChangedAfterFeatures,
+    #[error("Coordinate ({lng}, {lat}) is out of range, it would not fit in an i32 once scaled")]
+    CoordinateOutOfRange { lng: f64, lat: f64 },
+    #[error("Invalid WKB: {0}")]
+    InvalidWkb(String),
+    #[error(
+        "Unsupported file format {}, expected format version {expected}",
+        found.map_or("(not a geomedea file)".to_string(), |found| format!("version {found}"))
+    )]
+    UnsupportedFormat { found: Option<u16>, expected: u16 },
+    #[error(
+        "The file appears to be {0} compressed, e.g. by a server applying `Content-Encoding: {0}`, which breaks range requests"
+    )]
+    UnexpectedContentEncoding(String),
+    #[error("Feature is {size} bytes, but features can be at most {limit} bytes")]
+    FeatureExceedsPageLimit { size: u64, limit: u64 },
+    #[error("Property `{column}` is a {found:?}, but the schema declares {expected:?}")]
+    SchemaMismatch {
+        column: String,
+        expected: crate::PropertyType,
+        found: crate::PropertyType,
+    },
+    #[error("Invalid geometry: {0:?}")]
+    InvalidGeometry(Vec<crate::ValidationError>),
+    #[error("Property `{0}` was added more than once")]
+    DuplicateProperty(String),
+    #[error("A feature needs a geometry")]
+    MissingGeometry,
+    #[error("Invalid GeoJSON: {0}")]
+    InvalidGeoJson(String),
+    #[error("JSON null has no equivalent PropertyValue")]
+    JsonNull,
+    #[error("Writer is unusable after a previous error")]
+    Poisoned,
     #[error("HTTP error")]
     HTTP(#[from] streaming_http_range_client::Error),
 }
@@ -1,9 +1,10 @@
 use crate::Geometry;
 use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Formatter;
+use std::sync::Arc;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Feature {
     geometry: Geometry,
     properties: Properties,
@@ -40,15 +41,128 @@ impl Feature {
     pub fn into_inner(self) -> (Geometry, Properties) {
         (self.geometry, self.properties)
     }
+
+    /// Replace the property keys with the shared copies from `interner`.
+    pub(crate) fn intern_property_keys(&mut self, interner: &mut PropertyKeyInterner) {
+        self.properties.intern_keys(interner);
+    }
+
+    /// See [`FeatureBuilder`].
+    pub fn builder() -> FeatureBuilder {
+        FeatureBuilder::new()
+    }
+}
+
+/// Builds a [`Feature`] by chaining calls, e.g. to generate test fixtures.
+///
+/// ```
+/// # use geomedea::{Feature, LngLat};
+/// let feature = Feature::builder()
+///     .geometry(LngLat::degrees(1.0, 2.0))
+///     .property("name", "Seattle")
+///     .build()
+///     .unwrap();
+/// assert_eq!(feature.properties().get_str("name"), Some("Seattle"));
+/// ```
+#[derive(Debug)]
+pub struct FeatureBuilder {
+    geometry: Option<Geometry>,
+    properties: Properties,
+    /// The first key which was set more than once, if any.
+    duplicate_key: Option<String>,
 }
 
-type PropertyMap = HashMap<String, PropertyValue>;
+impl FeatureBuilder {
+    pub fn new() -> Self {
+        Self {
+            geometry: None,
+            properties: Properties::empty(),
+            duplicate_key: None,
+        }
+    }
+
+    /// Replaces any previously set geometry.
+    pub fn geometry(mut self, geometry: impl Into<Geometry>) -> Self {
+        self.geometry = Some(geometry.into());
+        self
+    }
+
+    /// Properties are kept in the order they're added. Adding a property with the same name as
+    /// an earlier one is an error, returned from [`FeatureBuilder::build`].
+    pub fn property(mut self, name: impl Into<String>, value: impl Into<PropertyValue>) -> Self {
+        let name = name.into();
+        if self.properties.get(&name).is_some() {
+            self.duplicate_key.get_or_insert(name);
+        } else {
+            self.properties.insert(name, value.into());
+        }
+        self
+    }
+
+    /// Returns [`Error::MissingGeometry`](crate::Error::MissingGeometry) if no geometry was set,
+    /// or [`Error::DuplicateProperty`](crate::Error::DuplicateProperty) if a property was added
+    /// more than once.
+    pub fn build(self) -> crate::Result<Feature> {
+        if let Some(name) = self.duplicate_key {
+            return Err(crate::Error::DuplicateProperty(name));
+        }
+        let geometry = self.geometry.ok_or(crate::Error::MissingGeometry)?;
+        Ok(Feature::new(geometry, self.properties))
+    }
+}
+
+impl Default for FeatureBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keys are reference counted, so that a single allocation can be shared by the ordered keys and
+/// the map, and by many features with the same keys, see [`Writer::set_intern_property_keys`].
+///
+/// [`Writer::set_intern_property_keys`]: crate::Writer::set_intern_property_keys
+type PropertyKey = Arc<str>;
+type PropertyMap = HashMap<PropertyKey, PropertyValue>;
+
+/// A feature's named properties.
+///
+/// Properties are kept in insertion order: iterating, serializing, and `Debug` output all follow
+/// the order in which each key was first inserted, and a file's features are read back with their
+/// properties in the same order. Replacing the value of an existing key keeps its position.
+///
+/// Equality compares the order too, so properties with the same entries inserted in a different
+/// order are not equal.
 #[derive(Clone, PartialEq)]
 pub struct Properties {
-    ordered_keys: Vec<String>,
+    ordered_keys: Vec<PropertyKey>,
     property_map: PropertyMap,
 }
 
+/// Deduplicates property keys, so that features with the same keys can share a single allocation
+/// for each key, rather than every feature allocating its own copies.
+///
+/// Every distinct key is retained for the life of the interner.
+#[derive(Debug, Default)]
+pub(crate) struct PropertyKeyInterner {
+    keys: HashSet<PropertyKey>,
+}
+
+impl PropertyKeyInterner {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of `key`, allocating it only the first time it's seen.
+    pub(crate) fn intern(&mut self, key: &str) -> Arc<str> {
+        if let Some(existing) = self.keys.get(key) {
+            return existing.clone();
+        }
+        let key: PropertyKey = Arc::from(key);
+        self.keys.insert(key.clone());
+        key
+    }
+}
+
 impl Serialize for Properties {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -69,16 +183,11 @@ impl<'de> Deserialize<'de> for Properties {
     {
         let ordered_values: Vec<(String, PropertyValue)> = Vec::deserialize(deserializer)?;
 
-        let mut property_map = PropertyMap::new();
-        let mut ordered_keys = vec![];
+        let mut properties = Properties::empty();
         for (key, value) in ordered_values.into_iter() {
-            ordered_keys.push(key.clone());
-            property_map.insert(key, value);
+            properties.insert(key, value);
         }
-        Ok(Self {
-            ordered_keys,
-            property_map,
-        })
+        Ok(properties)
     }
 }
 
@@ -108,25 +217,97 @@ impl Properties {
         self.ordered_keys.is_empty()
     }
 
+    /// If a property named `name` already exists, its value is replaced, keeping its original
+    /// position, and the previous value is returned.
     pub fn insert(&mut self, name: String, value: PropertyValue) -> Option<PropertyValue> {
-        assert!(
-            !self.ordered_keys.contains(&name),
-            "handle caller error of repeated property"
-        );
+        self.insert_interned(PropertyKey::from(name), value)
+    }
+
+    /// Like [`Properties::insert`], but shares the allocation of `name`.
+    pub(crate) fn insert_interned(
+        &mut self,
+        name: Arc<str>,
+        value: PropertyValue,
+    ) -> Option<PropertyValue> {
+        if let Some(existing) = self.property_map.get_mut(&name) {
+            return Some(std::mem::replace(existing, value));
+        }
         self.ordered_keys.push(name.clone());
         self.property_map.insert(name, value)
     }
 
+    /// Replace each key with the shared copy from `interner`, keeping the order.
+    fn intern_keys(&mut self, interner: &mut PropertyKeyInterner) {
+        for key in &mut self.ordered_keys {
+            let interned = interner.intern(key);
+            let value = self
+                .property_map
+                .remove(&**key)
+                .expect("value for each key");
+            self.property_map.insert(interned.clone(), value);
+            *key = interned;
+        }
+    }
+
     pub fn get(&self, name: &str) -> Option<&PropertyValue> {
         self.property_map.get(name)
     }
 
+    /// The value of a `String` property, or `None` if it's missing or not a string.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            PropertyValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value of an integer property of any width, or `None` if it's missing, not an integer,
+    /// or a `UInt64` too large for an `i64`.
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        match *self.get(name)? {
+            PropertyValue::Int8(value) => Some(value.into()),
+            PropertyValue::UInt8(value) => Some(value.into()),
+            PropertyValue::Int16(value) => Some(value.into()),
+            PropertyValue::UInt16(value) => Some(value.into()),
+            PropertyValue::Int32(value) => Some(value.into()),
+            PropertyValue::UInt32(value) => Some(value.into()),
+            PropertyValue::Int64(value) => Some(value),
+            PropertyValue::UInt64(value) => value.try_into().ok(),
+            _ => None,
+        }
+    }
+
+    /// The value of a `Float32` or `Float64` property, or `None` if it's missing or not a float.
+    pub fn get_f64(&self, name: &str) -> Option<f64> {
+        match *self.get(name)? {
+            PropertyValue::Float32(value) => Some(value.into()),
+            PropertyValue::Float64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value of a `Bool` property, or `None` if it's missing or not a bool.
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match *self.get(name)? {
+            PropertyValue::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&str, &PropertyValue)> {
         PropertyIter {
             keys_iter: self.ordered_keys.iter(),
             values: &self.property_map,
         }
     }
+
+    /// See [`PropertyValue::downcast_integer`].
+    #[cfg(feature = "writer")]
+    pub(crate) fn downcast_integers(&mut self) {
+        for value in self.property_map.values_mut() {
+            value.downcast_integer();
+        }
+    }
 }
 
 impl IntoIterator for Properties {
@@ -142,7 +323,7 @@ impl IntoIterator for Properties {
 }
 
 pub struct PropertiesIntoIter {
-    keys_iter: std::vec::IntoIter<String>,
+    keys_iter: std::vec::IntoIter<PropertyKey>,
     property_map: PropertyMap,
 }
 
@@ -155,12 +336,12 @@ impl Iterator for PropertiesIntoIter {
             .property_map
             .remove(&next_key)
             .expect("value for each key");
-        Some((next_key, next_value))
+        Some((next_key.to_string(), next_value))
     }
 }
 
 struct PropertyIter<'a> {
-    keys_iter: std::slice::Iter<'a, String>,
+    keys_iter: std::slice::Iter<'a, PropertyKey>,
     values: &'a PropertyMap,
 }
 
@@ -172,7 +353,7 @@ impl<'a> Iterator for PropertyIter<'a> {
         let Some(next_value) = self.values.get(next_key) else {
             todo!("handle missing value");
         };
-        Some((next_key, next_value))
+        Some((&**next_key, next_value))
     }
 }
 
@@ -195,6 +376,143 @@ pub enum PropertyValue {
     Map(Properties),
 }
 
+impl PropertyValue {
+    pub fn property_type(&self) -> PropertyType {
+        match self {
+            PropertyValue::Bool(_) => PropertyType::Bool,
+            PropertyValue::Int8(_) => PropertyType::Int8,
+            PropertyValue::UInt8(_) => PropertyType::UInt8,
+            PropertyValue::Int16(_) => PropertyType::Int16,
+            PropertyValue::UInt16(_) => PropertyType::UInt16,
+            PropertyValue::Int32(_) => PropertyType::Int32,
+            PropertyValue::UInt32(_) => PropertyType::UInt32,
+            PropertyValue::Int64(_) => PropertyType::Int64,
+            PropertyValue::UInt64(_) => PropertyType::UInt64,
+            PropertyValue::Float32(_) => PropertyType::Float32,
+            PropertyValue::Float64(_) => PropertyType::Float64,
+            PropertyValue::Bytes(_) => PropertyType::Bytes,
+            PropertyValue::String(_) => PropertyType::String,
+            PropertyValue::Vec(_) => PropertyType::Vec,
+            PropertyValue::Map(_) => PropertyType::Map,
+        }
+    }
+
+    /// Replace an integer with the smallest integer variant of the same signedness that can
+    /// hold its value, e.g. `Int64(5)` becomes `Int8(5)`.
+    ///
+    /// Integers nested in a `Vec` or `Map` are downcast too. Other values are left as is.
+    #[cfg(feature = "writer")]
+    pub(crate) fn downcast_integer(&mut self) {
+        let downcast = match *self {
+            PropertyValue::Int16(value) => Self::smallest_signed(value as i64),
+            PropertyValue::Int32(value) => Self::smallest_signed(value as i64),
+            PropertyValue::Int64(value) => Self::smallest_signed(value),
+            PropertyValue::UInt16(value) => Self::smallest_unsigned(value as u64),
+            PropertyValue::UInt32(value) => Self::smallest_unsigned(value as u64),
+            PropertyValue::UInt64(value) => Self::smallest_unsigned(value),
+            PropertyValue::Vec(ref mut values) => {
+                values.iter_mut().for_each(PropertyValue::downcast_integer);
+                return;
+            }
+            PropertyValue::Map(ref mut properties) => {
+                properties.downcast_integers();
+                return;
+            }
+            _ => return,
+        };
+        *self = downcast;
+    }
+
+    #[cfg(feature = "writer")]
+    fn smallest_signed(value: i64) -> Self {
+        if let Ok(value) = i8::try_from(value) {
+            PropertyValue::Int8(value)
+        } else if let Ok(value) = i16::try_from(value) {
+            PropertyValue::Int16(value)
+        } else if let Ok(value) = i32::try_from(value) {
+            PropertyValue::Int32(value)
+        } else {
+            PropertyValue::Int64(value)
+        }
+    }
+
+    #[cfg(feature = "writer")]
+    fn smallest_unsigned(value: u64) -> Self {
+        if let Ok(value) = u8::try_from(value) {
+            PropertyValue::UInt8(value)
+        } else if let Ok(value) = u16::try_from(value) {
+            PropertyValue::UInt16(value)
+        } else if let Ok(value) = u32::try_from(value) {
+            PropertyValue::UInt32(value)
+        } else {
+            PropertyValue::UInt64(value)
+        }
+    }
+}
+
+/// The variant of a [`PropertyValue`], without its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PropertyType {
+    Bool,
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float32,
+    Float64,
+    Bytes,
+    String,
+    Vec,
+    Map,
+}
+
+/// The declared type of each property, see [`Writer::set_schema`](crate::Writer::set_schema).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    columns: HashMap<String, PropertyType>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare that every `name` property is a `property_type`.
+    pub fn with_column(mut self, name: impl Into<String>, property_type: PropertyType) -> Self {
+        self.columns.insert(name.into(), property_type);
+        self
+    }
+
+    pub fn column(&self, name: &str) -> Option<PropertyType> {
+        self.columns.get(name).copied()
+    }
+
+    /// Returns [`Error::SchemaMismatch`](crate::Error::SchemaMismatch) for the first property
+    /// whose type differs from its declared type.
+    ///
+    /// Properties which aren't declared, and declared properties which are missing, are allowed.
+    pub fn validate(&self, properties: &Properties) -> crate::Result<()> {
+        for (name, value) in properties.iter() {
+            let Some(expected) = self.column(name) else {
+                continue;
+            };
+            let found = value.property_type();
+            if found != expected {
+                return Err(crate::Error::SchemaMismatch {
+                    column: name.to_string(),
+                    expected,
+                    found,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
 impl From<&str> for PropertyValue {
     fn from(value: &str) -> Self {
         PropertyValue::String(value.to_string())
@@ -206,3 +524,420 @@ impl From<String> for PropertyValue {
         PropertyValue::String(value)
     }
 }
+
+/// The JSON equivalent of the property, e.g. `Bytes` become an array of numbers.
+#[cfg(feature = "json")]
+impl From<&PropertyValue> for serde_json::Value {
+    fn from(property: &PropertyValue) -> Self {
+        use serde_json::Value;
+        match property {
+            PropertyValue::Bool(value) => Value::from(*value),
+            PropertyValue::Int8(value) => Value::from(*value),
+            PropertyValue::UInt8(value) => Value::from(*value),
+            PropertyValue::Int16(value) => Value::from(*value),
+            PropertyValue::UInt16(value) => Value::from(*value),
+            PropertyValue::Int32(value) => Value::from(*value),
+            PropertyValue::UInt32(value) => Value::from(*value),
+            PropertyValue::Int64(value) => Value::from(*value),
+            PropertyValue::UInt64(value) => Value::from(*value),
+            PropertyValue::Float32(value) => Value::from(*value),
+            PropertyValue::Float64(value) => Value::from(*value),
+            PropertyValue::Bytes(value) => Value::from(value.as_slice()),
+            PropertyValue::String(value) => Value::from(value.as_str()),
+            PropertyValue::Vec(values) => values.iter().map(Value::from).collect(),
+            PropertyValue::Map(properties) => Value::Object(properties.into()),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<&Properties> for serde_json::Map<String, serde_json::Value> {
+    fn from(properties: &Properties) -> Self {
+        properties
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.into()))
+            .collect()
+    }
+}
+
+/// Integers become `Int64`, or `UInt64` if they're too large, and other numbers become
+/// `Float64`. Arrays become a `Vec`, and objects a `Map`.
+///
+/// Object members which are `null` are skipped, as for [`Properties`], but otherwise returns
+/// [`Error::JsonNull`](crate::Error::JsonNull) if the value is, or contains, `null`.
+#[cfg(feature = "json")]
+impl TryFrom<serde_json::Value> for PropertyValue {
+    type Error = crate::Error;
+
+    fn try_from(value: serde_json::Value) -> crate::Result<Self> {
+        use serde_json::Value;
+        Ok(match value {
+            Value::Null => return Err(crate::Error::JsonNull),
+            Value::Bool(value) => PropertyValue::Bool(value),
+            Value::Number(number) => {
+                if let Some(value) = number.as_i64() {
+                    PropertyValue::Int64(value)
+                } else if let Some(value) = number.as_u64() {
+                    PropertyValue::UInt64(value)
+                } else {
+                    // Without serde_json's arbitrary_precision feature, every number is an i64, a
+                    // u64, or an f64.
+                    PropertyValue::Float64(number.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            Value::String(value) => PropertyValue::String(value),
+            Value::Array(values) => PropertyValue::Vec(
+                values
+                    .into_iter()
+                    .map(PropertyValue::try_from)
+                    .collect::<crate::Result<_>>()?,
+            ),
+            Value::Object(map) => PropertyValue::Map(map.try_into()?),
+        })
+    }
+}
+
+/// Each member of the object becomes a property, converted as by the
+/// `TryFrom<serde_json::Value>` impl of [`PropertyValue`].
+///
+/// Members which are `null` are skipped, since there's no `PropertyValue` for them, so e.g.
+/// `{"name": "Seattle", "nickname": null}` has only a `name` property.
+#[cfg(feature = "json")]
+impl TryFrom<serde_json::Map<String, serde_json::Value>> for Properties {
+    type Error = crate::Error;
+
+    fn try_from(map: serde_json::Map<String, serde_json::Value>) -> crate::Result<Self> {
+        let mut properties = Properties::empty();
+        for (key, value) in map {
+            if value.is_null() {
+                continue;
+            }
+            properties.insert(key, value.try_into()?);
+        }
+        Ok(properties)
+    }
+}
+
+#[cfg(feature = "json")]
+impl Feature {
+    /// A GeoJSON `Feature` object, with coordinates in degrees.
+    ///
+    /// Properties are `null` rather than an empty object if the feature has none.
+    pub fn to_geojson_value(&self) -> serde_json::Value {
+        let properties = if self.properties.is_empty() {
+            serde_json::Value::Null
+        } else {
+            serde_json::Value::Object((&self.properties).into())
+        };
+        serde_json::json!({
+            "type": "Feature",
+            "geometry": self.geometry.to_geojson_value(),
+            "properties": properties,
+        })
+    }
+
+    /// Parses a GeoJSON `Feature` object, with coordinates in degrees.
+    ///
+    /// Properties are converted as by the `TryFrom<serde_json::Value>` impl of [`PropertyValue`],
+    /// and may be `null` or missing if the feature has none. Any `id` or `bbox` is ignored.
+    pub fn from_geojson_value(value: serde_json::Value) -> crate::Result<Self> {
+        use crate::Error;
+        use serde_json::Value;
+        let Value::Object(mut object) = value else {
+            return Err(Error::InvalidGeoJson(
+                "expected a feature object".to_string(),
+            ));
+        };
+        if object.get("type").and_then(Value::as_str) != Some("Feature") {
+            return Err(Error::InvalidGeoJson(
+                "expected an object with `\"type\": \"Feature\"`".to_string(),
+            ));
+        }
+        let geometry = match object.remove("geometry") {
+            Some(Value::Null) | None => return Err(Error::MissingGeometry),
+            Some(geometry) => Geometry::from_geojson_value(geometry)?,
+        };
+        let properties = match object.remove("properties") {
+            Some(Value::Null) | None => Properties::empty(),
+            Some(Value::Object(map)) => map.try_into()?,
+            Some(other) => {
+                return Err(Error::InvalidGeoJson(format!(
+                    "expected properties to be an object, found {other}"
+                )))
+            }
+        };
+        Ok(Feature::new(geometry, properties))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json() {
+        use serde_json::json;
+
+        let value = json!({
+            "name": "Seattle",
+            "population": 750_000,
+            "area": 217.4,
+            "big": u64::MAX,
+            "tags": ["city", true],
+            "nested": { "a": -1 },
+        });
+        let serde_json::Value::Object(map) = value.clone() else {
+            unreachable!()
+        };
+        let properties = Properties::try_from(map).unwrap();
+        assert_eq!(properties.get_str("name"), Some("Seattle"));
+        assert_eq!(
+            properties.get("population"),
+            Some(&PropertyValue::Int64(750_000))
+        );
+        assert_eq!(properties.get("area"), Some(&PropertyValue::Float64(217.4)));
+        assert_eq!(
+            properties.get("big"),
+            Some(&PropertyValue::UInt64(u64::MAX))
+        );
+        assert_eq!(
+            properties.get("tags"),
+            Some(&PropertyValue::Vec(vec![
+                "city".into(),
+                PropertyValue::Bool(true)
+            ]))
+        );
+
+        // Round trips
+        let round_tripped = serde_json::Value::from(&PropertyValue::Map(properties));
+        assert_eq!(round_tripped, value);
+
+        assert!(matches!(
+            PropertyValue::try_from(json!([1, null])),
+            Err(crate::Error::JsonNull)
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_null_members() {
+        use serde_json::json;
+
+        let value = json!({
+            "name": "Seattle",
+            "nickname": null,
+            "nested": { "a": -1, "b": null },
+        });
+        let serde_json::Value::Object(map) = value else {
+            unreachable!()
+        };
+        let properties = Properties::try_from(map).unwrap();
+        assert_eq!(properties.get_str("name"), Some("Seattle"));
+        assert_eq!(properties.get("nickname"), None);
+
+        let mut nested = Properties::empty();
+        nested.insert("a".to_string(), PropertyValue::Int64(-1));
+        assert_eq!(properties.get("nested"), Some(&PropertyValue::Map(nested)));
+
+        assert!(matches!(
+            PropertyValue::try_from(json!(null)),
+            Err(crate::Error::JsonNull)
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn geojson_feature() {
+        use serde_json::json;
+
+        let value = json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [1.0, 2.0] },
+            "properties": { "name": "Seattle", "population": 750000 },
+        });
+        let feature = Feature::from_geojson_value(value.clone()).unwrap();
+        assert_eq!(feature.geometry(), &crate::wkt!(POINT(1 2)).into());
+        assert_eq!(
+            feature.properties().get("name"),
+            Some(&PropertyValue::String("Seattle".to_string()))
+        );
+        assert_eq!(
+            feature.properties().get("population"),
+            Some(&PropertyValue::Int64(750000))
+        );
+        assert_eq!(feature.to_geojson_value(), value);
+
+        let without_properties = json!({
+            "type": "Feature",
+            "geometry": { "type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]] },
+            "properties": null,
+        });
+        let feature = Feature::from_geojson_value(without_properties.clone()).unwrap();
+        assert!(feature.properties().is_empty());
+        assert_eq!(feature.to_geojson_value(), without_properties);
+
+        assert!(matches!(
+            Feature::from_geojson_value(json!({ "type": "Feature", "geometry": null })),
+            Err(crate::Error::MissingGeometry)
+        ));
+        assert!(matches!(
+            Feature::from_geojson_value(json!({ "type": "FeatureCollection", "features": [] })),
+            Err(crate::Error::InvalidGeoJson(_))
+        ));
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let mut properties = Properties::empty();
+        assert_eq!(
+            properties.insert("a".to_string(), PropertyValue::UInt8(1)),
+            None
+        );
+        assert_eq!(
+            properties.insert("b".to_string(), PropertyValue::UInt8(2)),
+            None
+        );
+        assert_eq!(
+            properties.insert("a".to_string(), PropertyValue::UInt8(3)),
+            Some(PropertyValue::UInt8(1))
+        );
+
+        // The replaced value keeps its original position.
+        let entries: Vec<_> = properties.iter().collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("a", &PropertyValue::UInt8(3)),
+                ("b", &PropertyValue::UInt8(2))
+            ]
+        );
+    }
+
+    #[test]
+    fn serialization_preserves_order() {
+        let mut properties = Properties::empty();
+        // Neither alphabetical nor likely to be a hash order.
+        for key in ["zebra", "apple", "mango", "banana", "kiwi", "cherry"] {
+            properties.insert(key.to_string(), key.into());
+        }
+        let mut serialized = vec![];
+        crate::serialize_into(&mut serialized, &properties).unwrap();
+        let deserialized: Properties = crate::deserialize_from(serialized.as_slice()).unwrap();
+
+        let keys: Vec<&str> = deserialized.iter().map(|(key, _value)| key).collect();
+        assert_eq!(
+            keys,
+            vec!["zebra", "apple", "mango", "banana", "kiwi", "cherry"]
+        );
+        assert_eq!(deserialized, properties);
+
+        let mut reordered = Properties::empty();
+        let entries: Vec<_> = properties.clone().into_iter().collect();
+        for (key, value) in entries.into_iter().rev() {
+            reordered.insert(key, value);
+        }
+        assert_ne!(reordered, properties);
+    }
+
+    #[test]
+    fn feature_builder() {
+        let feature = Feature::builder()
+            .geometry(crate::wkt!(POINT(1 2)))
+            .property("name", "Seattle")
+            .property("population", PropertyValue::UInt32(750_000))
+            .build()
+            .unwrap();
+        let mut properties = Properties::empty();
+        properties.insert("name".to_string(), "Seattle".into());
+        properties.insert("population".to_string(), PropertyValue::UInt32(750_000));
+        assert_eq!(
+            feature,
+            Feature::new(crate::wkt!(POINT(1 2)).into(), properties)
+        );
+
+        let duplicate = Feature::builder()
+            .geometry(crate::wkt!(POINT(1 2)))
+            .property("name", "Seattle")
+            .property("name", "Tacoma")
+            .build();
+        assert!(matches!(duplicate, Err(crate::Error::DuplicateProperty(name)) if name == "name"));
+
+        let missing_geometry = FeatureBuilder::new().property("name", "Seattle").build();
+        assert!(matches!(
+            missing_geometry,
+            Err(crate::Error::MissingGeometry)
+        ));
+    }
+
+    #[test]
+    fn clone_feature() {
+        let mut properties = Properties::empty();
+        properties.insert("name".to_string(), "Seattle".into());
+        let feature = Feature::new(crate::wkt!(POINT(1 2)).into(), properties);
+
+        let mut cloned = feature.clone();
+        assert_eq!(cloned, feature);
+
+        cloned.insert_property("name".to_string(), "Tacoma".into());
+        assert_ne!(cloned, feature);
+    }
+
+    #[test]
+    fn intern_keys() {
+        let properties = |idx: u32| {
+            let mut properties = Properties::empty();
+            properties.insert("rank".to_string(), PropertyValue::UInt32(idx));
+            properties.insert("name".to_string(), format!("name-{idx}").into());
+            properties
+        };
+        let mut interner = PropertyKeyInterner::new();
+        let mut first = properties(1);
+        let mut second = properties(2);
+        assert!(!Arc::ptr_eq(
+            &first.ordered_keys[0],
+            &second.ordered_keys[0]
+        ));
+
+        first.intern_keys(&mut interner);
+        second.intern_keys(&mut interner);
+        for (first_key, second_key) in first.ordered_keys.iter().zip(&second.ordered_keys) {
+            assert!(Arc::ptr_eq(first_key, second_key));
+            let (map_key, _value) = first.property_map.get_key_value(first_key).unwrap();
+            assert!(Arc::ptr_eq(first_key, map_key));
+        }
+        // The order and values are unchanged.
+        assert_eq!(first, properties(1));
+        assert_eq!(second, properties(2));
+    }
+
+    #[test]
+    fn typed_getters() {
+        let mut properties = Properties::empty();
+        properties.insert(
+            "name".to_string(),
+            PropertyValue::String("Seattle".to_string()),
+        );
+        properties.insert("small".to_string(), PropertyValue::Int8(-3));
+        properties.insert("unsigned".to_string(), PropertyValue::UInt32(70_000));
+        properties.insert("huge".to_string(), PropertyValue::UInt64(u64::MAX));
+        properties.insert("float".to_string(), PropertyValue::Float32(1.5));
+        properties.insert("flag".to_string(), PropertyValue::Bool(true));
+
+        assert_eq!(properties.get_str("name"), Some("Seattle"));
+        assert_eq!(properties.get_i64("small"), Some(-3));
+        assert_eq!(properties.get_i64("unsigned"), Some(70_000));
+        assert_eq!(properties.get_i64("huge"), None);
+        assert_eq!(properties.get_f64("float"), Some(1.5));
+        assert_eq!(properties.get_bool("flag"), Some(true));
+
+        // wrong type
+        assert_eq!(properties.get_str("small"), None);
+        assert_eq!(properties.get_i64("float"), None);
+        assert_eq!(properties.get_f64("small"), None);
+        assert_eq!(properties.get_bool("name"), None);
+
+        // missing
+        assert_eq!(properties.get_str("missing"), None);
+    }
+}
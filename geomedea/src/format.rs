@@ -1,7 +1,9 @@
-use crate::serialized_size;
+use crate::{deserialize_from, serialized_size, Bounds, Error, Result};
 use serde::{Deserialize, Serialize};
+use std::io::Read;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Ordered by where the feature is stored, i.e. by page, then by position within the page.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FeatureLocation {
     /// How far into the feature data does this feature's page start?
     pub(crate) page_starting_offset: u64,
@@ -9,6 +11,18 @@ pub struct FeatureLocation {
     pub(crate) feature_offset: u32,
 }
 
+impl FeatureLocation {
+    /// How far into the feature data does this feature's page start?
+    pub fn page_starting_offset(&self) -> u64 {
+        self.page_starting_offset
+    }
+
+    /// The byte offset of this feature within its (uncompressed) page
+    pub fn feature_offset(&self) -> u32 {
+        self.feature_offset
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub(crate) struct PageHeader {
     /// The number of bytes on disk. The actual content bytes might be more or less if
@@ -21,6 +35,11 @@ pub(crate) struct PageHeader {
 
     /// How many features are in this page.
     feature_count: u32,
+
+    /// A checksum of the page's encoded contents, in files with page checksums. It isn't part of
+    /// the serialized page header, but follows the page's bounds, see [`PageHeader::read_from`].
+    #[serde(skip)]
+    checksum: Option<u64>,
 }
 
 impl PageHeader {
@@ -30,15 +49,37 @@ impl PageHeader {
             encoded_page_length,
             decoded_page_length,
             feature_count,
+            checksum: None,
         }
     }
 
-    pub fn serialized_size() -> usize {
+    /// Each page header is immediately followed by the bounds of the page's features, and in
+    /// files with page checksums, then by the page's checksum, which are included in this size.
+    pub fn serialized_size(has_page_checksums: bool) -> usize {
         // Assumes the PageHeader serialization is fixed. We'll have to revisit if this every changes.
         let value = serialized_size(&Self::default()).expect("valid serialization size");
         debug_assert_eq!(value, 12, "If PageHeader fields are changed, this assertion can be updated, but it *must* remain a fixed size - e.g. no dynamically sized types like a Vec");
-        value as usize
+        let mut size = value + serialized_size(&Bounds::empty()).expect("valid serialization size");
+        if has_page_checksums {
+            size += serialized_size(&0u64).expect("valid serialization size");
+        }
+        size as usize
     }
+
+    /// Read a page header, followed by the page's bounds, and the page's checksum if the file has
+    /// page checksums.
+    pub fn read_from(
+        mut reader: impl Read,
+        has_page_checksums: bool,
+    ) -> Result<(PageHeader, Bounds)> {
+        let mut page_header: PageHeader = deserialize_from(&mut reader)?;
+        let page_bounds = deserialize_from(&mut reader)?;
+        if has_page_checksums {
+            page_header.checksum = Some(deserialize_from(&mut reader)?);
+        }
+        Ok((page_header, page_bounds))
+    }
+
     pub fn encoded_page_length(&self) -> u32 {
         self.encoded_page_length
     }
@@ -48,4 +89,28 @@ impl PageHeader {
     pub fn decoded_page_length(&self) -> u32 {
         self.decoded_page_length
     }
+    pub fn checksum(&self) -> Option<u64> {
+        self.checksum
+    }
+}
+
+/// The checksum of a page's encoded contents, stored after its header in files with page
+/// checksums: the 64 bit xxHash, with a seed of 0.
+pub(crate) fn page_checksum(encoded: &[u8]) -> u64 {
+    use std::hash::Hasher;
+    let mut hasher = twox_hash::XxHash64::with_seed(0);
+    hasher.write(encoded);
+    hasher.finish()
+}
+
+/// Checks a page's encoded contents against its checksum, if it has one.
+pub(crate) fn verify_page_checksum(encoded: &[u8], checksum: Option<u64>) -> Result<()> {
+    let Some(expected) = checksum else {
+        return Ok(());
+    };
+    let found = page_checksum(encoded);
+    if found != expected {
+        return Err(Error::PageChecksumMismatch { expected, found });
+    }
+    Ok(())
 }
@@ -0,0 +1,174 @@
+//! Writes features as GeoJSON, without depending on `geozero`, see [`GeoJsonWriter`].
+
+use crate::{Feature, FeatureIter, FeatureStream, Geometry, PropertyValue, Result};
+use futures_util::StreamExt;
+use serde_json::Value;
+use std::io::{Read, Write};
+
+/// Writes a GeoJSON `FeatureCollection`, one feature at a time, so a whole selection never needs
+/// to be held in memory.
+///
+/// ```
+/// # use geomedea::{geojson::GeoJsonWriter, Feature, LngLat, Properties};
+/// let mut writer = GeoJsonWriter::new(vec![]).unwrap();
+/// let feature = Feature::new(LngLat::degrees(1.0, 2.0).into(), Properties::empty());
+/// writer.write_feature(&feature).unwrap();
+/// let output = writer.finish().unwrap();
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     r#"{"type":"FeatureCollection","features":[
+/// {"geometry":{"coordinates":[1.0,2.0],"type":"Point"},"properties":null,"type":"Feature"}
+/// ]}"#
+/// );
+/// ```
+pub struct GeoJsonWriter<W: Write> {
+    output: W,
+    feature_count: u64,
+}
+
+impl<W: Write> GeoJsonWriter<W> {
+    pub fn new(mut output: W) -> Result<Self> {
+        output.write_all(br#"{"type":"FeatureCollection","features":["#)?;
+        Ok(Self {
+            output,
+            feature_count: 0,
+        })
+    }
+
+    /// Each feature is written on its own line.
+    pub fn write_feature(&mut self, feature: &Feature) -> Result<()> {
+        self.write_geojson_feature(&feature_to_geojson(feature))
+    }
+
+    /// Like [`GeoJsonWriter::write_feature`], but for a feature which is already GeoJSON, e.g. one
+    /// from [`feature_to_geojson`] with members added to it.
+    pub fn write_geojson_feature(&mut self, feature: &Value) -> Result<()> {
+        if self.feature_count > 0 {
+            self.output.write_all(b",")?;
+        }
+        self.output.write_all(b"\n")?;
+        self.output.write_all(feature.to_string().as_bytes())?;
+        self.feature_count += 1;
+        Ok(())
+    }
+
+    /// Writes every remaining feature of `features`.
+    pub fn write_feature_iter<R: Read>(&mut self, mut features: FeatureIter<'_, R>) -> Result<()> {
+        while let Some(feature) = features.try_next()? {
+            self.write_feature(&feature)?;
+        }
+        Ok(())
+    }
+
+    /// Writes every remaining feature of `features`.
+    pub async fn write_feature_stream(&mut self, mut features: FeatureStream) -> Result<()> {
+        while let Some(feature) = features.next().await {
+            self.write_feature(&feature?)?;
+        }
+        Ok(())
+    }
+
+    /// The number of features written so far.
+    pub fn feature_count(&self) -> u64 {
+        self.feature_count
+    }
+
+    /// Closes the `FeatureCollection`, returning the output.
+    pub fn finish(mut self) -> Result<W> {
+        if self.feature_count > 0 {
+            self.output.write_all(b"\n")?;
+        }
+        self.output.write_all(b"]}")?;
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
+
+/// A GeoJSON `Feature` object, with coordinates in degrees, see [`Feature::to_geojson_value`].
+pub fn feature_to_geojson(feature: &Feature) -> Value {
+    feature.to_geojson_value()
+}
+
+/// A GeoJSON geometry object, with coordinates in degrees, see [`Geometry::to_geojson_value`].
+pub fn geometry_to_geojson(geometry: &Geometry) -> Value {
+    geometry.to_geojson_value()
+}
+
+/// The JSON equivalent of `property`, e.g. `Bytes` become an array of numbers.
+pub fn property_to_json(property: &PropertyValue) -> Value {
+    Value::from(property)
+}
+
+#[cfg(test)]
+#[cfg(feature = "writer")]
+mod tests {
+    use super::*;
+    use crate::{test_data, wkt, LngLat, Properties, Reader};
+    use serde_json::json;
+
+    #[test]
+    fn geometries() {
+        let polygon: Geometry = wkt!(POLYGON((0 0,1 0,1 1,0 0))).into();
+        assert_eq!(
+            geometry_to_geojson(&polygon),
+            json!({
+                "type": "Polygon",
+                "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]],
+            })
+        );
+
+        let collection: Geometry = wkt!(GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(0 0,1 1))).into();
+        assert_eq!(
+            geometry_to_geojson(&collection),
+            json!({
+                "type": "GeometryCollection",
+                "geometries": [
+                    { "type": "Point", "coordinates": [1.0, 2.0] },
+                    { "type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]] },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn properties() {
+        let mut properties = Properties::empty();
+        properties.insert("name".to_string(), "Seattle".into());
+        properties.insert("population".to_string(), PropertyValue::UInt32(750_000));
+        properties.insert(
+            "tags".to_string(),
+            PropertyValue::Vec(vec![PropertyValue::Bool(true), PropertyValue::Float64(1.5)]),
+        );
+        let feature = Feature::new(LngLat::degrees(-122.3, 47.6).into(), properties);
+        assert_eq!(
+            feature_to_geojson(&feature)["properties"],
+            json!({
+                "name": "Seattle",
+                "population": 750_000,
+                "tags": [true, 1.5],
+            })
+        );
+    }
+
+    #[test]
+    fn feature_collection() {
+        let input = test_data::points(3, true);
+        let features = Reader::new(input.as_slice()).unwrap().select_all().unwrap();
+        let mut writer = GeoJsonWriter::new(vec![]).unwrap();
+        writer.write_feature_iter(features).unwrap();
+        assert_eq!(writer.feature_count(), 3);
+        let output = writer.finish().unwrap();
+
+        let feature_collection: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(feature_collection["type"], "FeatureCollection");
+        let features = feature_collection["features"].as_array().unwrap();
+        assert_eq!(features.len(), 3);
+        assert_eq!(features[0]["properties"]["name"], "prop-2");
+
+        let empty = GeoJsonWriter::new(vec![]).unwrap().finish().unwrap();
+        assert_eq!(
+            String::from_utf8(empty).unwrap(),
+            r#"{"type":"FeatureCollection","features":[]}"#
+        );
+    }
+}
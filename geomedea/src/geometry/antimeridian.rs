@@ -0,0 +1,288 @@
+use super::COORD_SCALE_FACTOR;
+use crate::{
+    Geometry, GeometryCollection, LineString, LngLat, MultiLineString, MultiPolygon, Polygon,
+};
+
+const HALF_TURN: f64 = 180.0 * COORD_SCALE_FACTOR;
+const FULL_TURN: f64 = 360.0 * COORD_SCALE_FACTOR;
+
+impl Geometry {
+    /// Returns a copy of the geometry, with any line or polygon which crosses the antimeridian
+    /// (±180° longitude) split into parts on either side of it, with vertices inserted on the
+    /// antimeridian.
+    ///
+    /// A segment crosses the antimeridian when its endpoints are more than 180° of longitude
+    /// apart, since the shorter way around is across the antimeridian. A crossing LineString
+    /// becomes a MultiLineString, and a crossing Polygon becomes a MultiPolygon. Geometries
+    /// which don't cross are unchanged.
+    ///
+    /// Polygons which enclose a pole, and so can't be split into two closed parts, are unchanged.
+    pub fn split_antimeridian(&self) -> Geometry {
+        match self {
+            Geometry::Point(_) | Geometry::MultiPoint(_) => self.clone(),
+            Geometry::LineString(line_string) => {
+                let mut parts = line_string.split_antimeridian();
+                if parts.len() == 1 {
+                    parts.pop().expect("one part").into()
+                } else {
+                    MultiLineString::new(parts).into()
+                }
+            }
+            Geometry::Polygon(polygon) => {
+                let mut parts = polygon.split_antimeridian();
+                if parts.len() == 1 {
+                    parts.pop().expect("one part").into()
+                } else {
+                    MultiPolygon::new(parts).into()
+                }
+            }
+            Geometry::MultiLineString(multi_line_string) => MultiLineString::new(
+                multi_line_string
+                    .line_strings()
+                    .iter()
+                    .flat_map(LineString::split_antimeridian)
+                    .collect(),
+            )
+            .into(),
+            Geometry::MultiPolygon(multi_polygon) => MultiPolygon::new(
+                multi_polygon
+                    .polygons()
+                    .iter()
+                    .flat_map(Polygon::split_antimeridian)
+                    .collect(),
+            )
+            .into(),
+            Geometry::GeometryCollection(geometry_collection) => GeometryCollection::new(
+                geometry_collection
+                    .geometries()
+                    .iter()
+                    .map(Geometry::split_antimeridian)
+                    .collect(),
+            )
+            .into(),
+        }
+    }
+}
+
+impl LineString {
+    fn split_antimeridian(&self) -> Vec<LineString> {
+        let Some(first) = self.points().first() else {
+            return vec![self.clone()];
+        };
+
+        let mut parts = vec![];
+        let mut current = vec![first.clone()];
+        for segment in self.points().windows(2) {
+            let (start, end) = (&segment[0], &segment[1]);
+            if let Some((start_side, end_side)) = antimeridian_crossing(start, end) {
+                push_point(&mut current, start_side);
+                parts.push(LineString::new(std::mem::take(&mut current)));
+                current.push(end_side);
+            }
+            push_point(&mut current, end.clone());
+        }
+        parts.push(LineString::new(current));
+        parts
+    }
+}
+
+impl Polygon {
+    fn split_antimeridian(&self) -> Vec<Polygon> {
+        let Some((exterior, interiors)) = self.rings().split_first() else {
+            return vec![self.clone()];
+        };
+        let Some(exterior) = unwrap_ring(exterior) else {
+            return vec![self.clone()];
+        };
+
+        let (min_lng, max_lng) = exterior
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(min, max), (lng, _lat)| {
+                (min.min(*lng), max.max(*lng))
+            });
+        // Which side of the antimeridian the ring extends past, and the shift back into range.
+        let (meridian, shift) = if max_lng > HALF_TURN {
+            (HALF_TURN, -FULL_TURN)
+        } else if min_lng < -HALF_TURN {
+            (-HALF_TURN, FULL_TURN)
+        } else {
+            return vec![self.clone()];
+        };
+
+        // Holes are unwrapped independently, so move each one next to the exterior.
+        let center_lng = (min_lng + max_lng) / 2.0;
+        let interiors: Vec<Vec<(f64, f64)>> = interiors
+            .iter()
+            .filter_map(unwrap_ring)
+            .map(|mut interior| {
+                let offset = ((center_lng - interior[0].0) / FULL_TURN).round() * FULL_TURN;
+                for (lng, _lat) in &mut interior {
+                    *lng += offset;
+                }
+                interior
+            })
+            .collect();
+
+        let mut parts = vec![];
+        for (keep_below, shift) in [(meridian > 0.0, 0.0), (meridian < 0.0, shift)] {
+            let clip = |ring: &[(f64, f64)]| {
+                let clipped = clip_ring(ring, meridian, keep_below);
+                (clipped.len() >= 4).then(|| to_line_string(&clipped, shift))
+            };
+            let Some(exterior) = clip(&exterior) else {
+                continue;
+            };
+            let mut rings = vec![exterior];
+            rings.extend(interiors.iter().filter_map(|interior| clip(interior)));
+            parts.push(Polygon::new(rings));
+        }
+        parts
+    }
+}
+
+/// If the shorter way from `start` to `end` crosses the antimeridian, returns where it crosses,
+/// both on the start's side and on the end's side.
+fn antimeridian_crossing(start: &LngLat, end: &LngLat) -> Option<(LngLat, LngLat)> {
+    let start_lng = start.lng_unscaled() as f64;
+    let end_lng = end.lng_unscaled() as f64;
+    let lng_delta = end_lng - start_lng;
+    if lng_delta.abs() <= HALF_TURN {
+        return None;
+    }
+    // Unwrap the end so the segment is continuous, e.g. 170° to -170° becomes 170° to 190°.
+    let (meridian, unwrapped_end_lng) = if lng_delta > 0.0 {
+        (-HALF_TURN, end_lng - FULL_TURN)
+    } else {
+        (HALF_TURN, end_lng + FULL_TURN)
+    };
+    let fraction = (meridian - start_lng) / (unwrapped_end_lng - start_lng);
+    let lat_delta = end.lat_unscaled() as f64 - start.lat_unscaled() as f64;
+    let lat = (start.lat_unscaled() as f64 + lat_delta * fraction).round() as i32;
+    Some((
+        LngLat::unscaled(meridian as i32, lat),
+        LngLat::unscaled(-meridian as i32, lat),
+    ))
+}
+
+fn push_point(points: &mut Vec<LngLat>, point: LngLat) {
+    if points.last() != Some(&point) {
+        points.push(point)
+    }
+}
+
+/// The unscaled `(lng, lat)` of each point, with longitudes adjusted by whole turns so that no
+/// segment crosses the antimeridian, e.g. 170° to -170° becomes 170° to 190°.
+///
+/// Returns `None` if the unwrapped ring doesn't close, i.e. it encloses a pole.
+fn unwrap_ring(ring: &LineString) -> Option<Vec<(f64, f64)>> {
+    let mut unwrapped: Vec<(f64, f64)> = Vec::with_capacity(ring.points_len());
+    for point in ring.points() {
+        let mut lng = point.lng_unscaled() as f64;
+        if let Some((prev_lng, _prev_lat)) = unwrapped.last() {
+            while lng - prev_lng > HALF_TURN {
+                lng -= FULL_TURN;
+            }
+            while prev_lng - lng > HALF_TURN {
+                lng += FULL_TURN;
+            }
+        }
+        unwrapped.push((lng, point.lat_unscaled() as f64));
+    }
+    if unwrapped.first() != unwrapped.last() {
+        return None;
+    }
+    Some(unwrapped)
+}
+
+/// Clips a closed ring to the side of `meridian` which is below it (`keep_below`) or above it.
+///
+/// Returns an empty ring if nothing is on that side.
+fn clip_ring(ring: &[(f64, f64)], meridian: f64, keep_below: bool) -> Vec<(f64, f64)> {
+    let is_inside = |lng: f64| {
+        if keep_below {
+            lng <= meridian
+        } else {
+            lng >= meridian
+        }
+    };
+    let crossing = |(a_lng, a_lat): (f64, f64), (b_lng, b_lat): (f64, f64)| {
+        let fraction = (meridian - a_lng) / (b_lng - a_lng);
+        (meridian, a_lat + (b_lat - a_lat) * fraction)
+    };
+
+    let mut clipped: Vec<(f64, f64)> = vec![];
+    let mut push = |point: (f64, f64)| {
+        if clipped.last() != Some(&point) {
+            clipped.push(point)
+        }
+    };
+    for edge in ring.windows(2) {
+        let (a, b) = (edge[0], edge[1]);
+        match (is_inside(a.0), is_inside(b.0)) {
+            (true, true) => push(b),
+            (true, false) => push(crossing(a, b)),
+            (false, true) => {
+                push(crossing(a, b));
+                push(b);
+            }
+            (false, false) => {}
+        }
+    }
+    if let Some(first) = clipped.first().copied() {
+        if clipped.last() != Some(&first) {
+            clipped.push(first);
+        }
+    }
+    clipped
+}
+
+fn to_line_string(points: &[(f64, f64)], lng_shift: f64) -> LineString {
+    LineString::new(
+        points
+            .iter()
+            .map(|(lng, lat)| {
+                LngLat::unscaled((lng + lng_shift).round() as i32, lat.round() as i32)
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wkt;
+
+    #[test]
+    fn split_line_string() {
+        let line_string: Geometry = wkt!(LINESTRING(160 0,170 0,-170 10,-160 10)).into();
+        let expected: Geometry =
+            wkt!(MULTILINESTRING((160 0,170 0,180 5),(-180 5,-170 10,-160 10))).into();
+        assert_eq!(line_string.split_antimeridian(), expected);
+
+        // in the other direction
+        let line_string: Geometry = wkt!(LINESTRING(-170 10,170 0)).into();
+        let expected: Geometry = wkt!(MULTILINESTRING((-170 10,-180 5),(180 5,170 0))).into();
+        assert_eq!(line_string.split_antimeridian(), expected);
+    }
+
+    #[test]
+    fn line_string_not_crossing() {
+        // Spans more than 180°, but no single segment crosses the antimeridian.
+        let line_string: Geometry = wkt!(LINESTRING(-170 0,0 0,170 0)).into();
+        assert_eq!(line_string.split_antimeridian(), line_string);
+    }
+
+    #[test]
+    fn split_polygon() {
+        let polygon: Geometry = wkt!(POLYGON((170 -10,-170 -10,-170 10,170 10,170 -10))).into();
+        let expected: Geometry = wkt!(MULTIPOLYGON(
+            ((180 -10,180 10,170 10,170 -10,180 -10)),
+            ((-180 -10,-170 -10,-170 10,-180 10,-180 -10))
+        ))
+        .into();
+        assert_eq!(polygon.split_antimeridian(), expected);
+
+        let not_crossing: Geometry = wkt!(POLYGON((0 0,10 0,10 10,0 0))).into();
+        assert_eq!(not_crossing.split_antimeridian(), not_crossing);
+    }
+}
@@ -0,0 +1,261 @@
+//! Centroids and representative points, e.g. for placing labels.
+
+use super::predicates::{for_each_path, for_each_polygon};
+use crate::{Geometry, LngLat, Polygon};
+
+impl Geometry {
+    /// The centroid of the geometry's highest dimension parts: the area weighted centroid of its
+    /// polygons, or if it has no area, the length weighted centroid of its lines, or otherwise the
+    /// mean of its points.
+    ///
+    /// The centroid is computed in planar lng/lat space, and may lie outside the geometry, e.g.
+    /// for a crescent. See [`Geometry::representative_point`] for a point which is within it.
+    ///
+    /// Returns None if the geometry is empty.
+    pub fn centroid(&self) -> Option<LngLat> {
+        let mut centroid = Centroid::default();
+        centroid.add_geometry(self);
+        centroid.finish()
+    }
+
+    /// A point which is within the geometry, e.g. for placing a label.
+    ///
+    /// For a geometry with area, the point is within its largest polygon, in the middle of the
+    /// widest span inside the polygon along a horizontal line near the middle of its bounds.
+    /// Otherwise, it's the vertex nearest the [`Geometry::centroid`].
+    ///
+    /// Returns None if the geometry is empty.
+    pub fn representative_point(&self) -> Option<LngLat> {
+        let mut largest: Option<(f64, &Polygon)> = None;
+        for_each_polygon(self, &mut |polygon| {
+            let mut centroid = Centroid::default();
+            centroid.add_polygon(polygon);
+            let area = centroid.area.abs();
+            if area > 0.0 && largest.map_or(true, |(largest_area, _)| area > largest_area) {
+                largest = Some((area, polygon));
+            }
+        });
+        if let Some(point) = largest.and_then(|(_area, polygon)| interior_point(polygon)) {
+            return Some(point);
+        }
+
+        let centroid = self.centroid()?;
+        let distance_squared = |point: &LngLat| {
+            let d_lng = point.lng_unscaled() as i64 - centroid.lng_unscaled() as i64;
+            let d_lat = point.lat_unscaled() as i64 - centroid.lat_unscaled() as i64;
+            d_lng as i128 * d_lng as i128 + d_lat as i128 * d_lat as i128
+        };
+        let mut nearest: Option<&LngLat> = None;
+        for_each_path(self, &mut |path| {
+            for point in path {
+                if nearest.map_or(true, |nearest| {
+                    distance_squared(point) < distance_squared(nearest)
+                }) {
+                    nearest = Some(point);
+                }
+            }
+        });
+        nearest.cloned()
+    }
+}
+
+/// Sums for computing the centroid of each dimension, in unscaled units relative to `origin`, to
+/// limit the magnitude of the products.
+#[derive(Debug, Default)]
+struct Centroid {
+    origin: Option<(f64, f64)>,
+    /// Twice the area, with holes subtracted, and the sums of each triangle's twice area times 3
+    /// times its centroid.
+    area: f64,
+    area_x: f64,
+    area_y: f64,
+    /// The total length, and the sums of each segment's length times its midpoint.
+    length: f64,
+    length_x: f64,
+    length_y: f64,
+    point_count: f64,
+    point_x: f64,
+    point_y: f64,
+}
+
+impl Centroid {
+    fn relative(&mut self, point: &LngLat) -> (f64, f64) {
+        let (x, y) = (point.lng_unscaled() as f64, point.lat_unscaled() as f64);
+        let (origin_x, origin_y) = *self.origin.get_or_insert((x, y));
+        (x - origin_x, y - origin_y)
+    }
+
+    /// Each polygon's rings are also added as lines, in case it has no area.
+    fn add_geometry(&mut self, geometry: &Geometry) {
+        for_each_polygon(geometry, &mut |polygon| self.add_polygon(polygon));
+        for_each_path(geometry, &mut |path| self.add_path(path));
+    }
+
+    fn add_polygon(&mut self, polygon: &Polygon) {
+        for (ring_idx, ring) in polygon.rings().iter().enumerate() {
+            let (mut area, mut area_x, mut area_y) = (0.0, 0.0, 0.0);
+            for edge in ring.points().windows(2) {
+                let (ax, ay) = self.relative(&edge[0]);
+                let (bx, by) = self.relative(&edge[1]);
+                let cross = ax * by - bx * ay;
+                area += cross;
+                area_x += (ax + bx) * cross;
+                area_y += (ay + by) * cross;
+            }
+            // Exterior rings add area, and holes subtract it, whichever way they wind.
+            let sign = if (ring_idx == 0) == (area >= 0.0) {
+                1.0
+            } else {
+                -1.0
+            };
+            self.area += sign * area;
+            self.area_x += sign * area_x;
+            self.area_y += sign * area_y;
+        }
+    }
+
+    /// The points are also added individually, in case the path has no length.
+    fn add_path(&mut self, path: &[LngLat]) {
+        for point in path {
+            let (x, y) = self.relative(point);
+            self.point_count += 1.0;
+            self.point_x += x;
+            self.point_y += y;
+        }
+        for segment in path.windows(2) {
+            let (ax, ay) = self.relative(&segment[0]);
+            let (bx, by) = self.relative(&segment[1]);
+            let length = (bx - ax).hypot(by - ay);
+            self.length += length;
+            self.length_x += length * (ax + bx) / 2.0;
+            self.length_y += length * (ay + by) / 2.0;
+        }
+    }
+
+    fn finish(&self) -> Option<LngLat> {
+        let (origin_x, origin_y) = self.origin?;
+        let (x, y) = if self.area != 0.0 {
+            (
+                self.area_x / (3.0 * self.area),
+                self.area_y / (3.0 * self.area),
+            )
+        } else if self.length > 0.0 {
+            (self.length_x / self.length, self.length_y / self.length)
+        } else {
+            (
+                self.point_x / self.point_count,
+                self.point_y / self.point_count,
+            )
+        };
+        Some(LngLat::unscaled(
+            (x + origin_x).round() as i32,
+            (y + origin_y).round() as i32,
+        ))
+    }
+}
+
+/// The middle of the widest span inside `polygon`, along a horizontal line between the two
+/// distinct vertex latitudes nearest the middle of its bounds, so the line doesn't pass through
+/// any vertex.
+fn interior_point(polygon: &Polygon) -> Option<LngLat> {
+    let mut lats: Vec<i32> = polygon
+        .rings()
+        .iter()
+        .flat_map(|ring| ring.points())
+        .map(LngLat::lat_unscaled)
+        .collect();
+    lats.sort_unstable();
+    lats.dedup();
+    let (min, max) = (*lats.first()? as i64, *lats.last()? as i64);
+    let middle = (min + max) / 2;
+    let below = lats.iter().rev().find(|lat| **lat as i64 <= middle)?;
+    let above = lats.iter().find(|lat| **lat as i64 > middle)?;
+    let scan_lat = (*below as f64 + *above as f64) / 2.0;
+
+    let mut crossings = vec![];
+    for ring in polygon.rings() {
+        for edge in ring.points().windows(2) {
+            let (a, b) = (&edge[0], &edge[1]);
+            let (a_lat, b_lat) = (a.lat_unscaled() as f64, b.lat_unscaled() as f64);
+            if (a_lat < scan_lat) != (b_lat < scan_lat) {
+                let (a_lng, b_lng) = (a.lng_unscaled() as f64, b.lng_unscaled() as f64);
+                crossings.push(a_lng + (scan_lat - a_lat) * (b_lng - a_lng) / (b_lat - a_lat));
+            }
+        }
+    }
+    crossings.sort_by(f64::total_cmp);
+    // Between each pair of crossings is inside the polygon.
+    let (start, end) = crossings
+        .chunks_exact(2)
+        .map(|span| (span[0], span[1]))
+        .max_by(|a, b| (a.1 - a.0).total_cmp(&(b.1 - b.0)))?;
+    Some(LngLat::unscaled(
+        ((start + end) / 2.0).round() as i32,
+        scan_lat.round() as i32,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::predicates::polygon_contains_point;
+    use crate::{wkt, GeometryCollection};
+
+    #[test]
+    fn polygons() {
+        let square = Geometry::from(wkt!(POLYGON((0 0,2 0,2 2,0 2,0 0))));
+        assert_eq!(square.centroid(), Some(wkt!(POINT(1 1))));
+        assert_eq!(square.representative_point(), Some(wkt!(POINT(1 1))));
+
+        // The centroid of a "C" is in its gap.
+        let c = wkt!(POLYGON((0 0,3 0,3 1,1 1,1 2,3 2,3 3,0 3,0 0)));
+        let centroid = Geometry::from(c.clone()).centroid().unwrap();
+        assert!(!polygon_contains_point(&c, &centroid));
+        let representative_point = Geometry::from(c.clone()).representative_point().unwrap();
+        assert!(polygon_contains_point(&c, &representative_point));
+        assert_eq!(representative_point, wkt!(POINT(0.5 1.5)));
+
+        // The centroid of a square with a hole is in the hole.
+        let with_hole = wkt!(POLYGON((0 0,4 0,4 4,0 4,0 0),(1 1,1 3,3 3,3 1,1 1)));
+        assert_eq!(
+            Geometry::from(with_hole.clone()).centroid(),
+            Some(wkt!(POINT(2 2)))
+        );
+        let representative_point = Geometry::from(with_hole.clone())
+            .representative_point()
+            .unwrap();
+        assert!(polygon_contains_point(&with_hole, &representative_point));
+
+        // The representative point is within the largest polygon.
+        let multi_polygon = Geometry::from(wkt!(MULTIPOLYGON(
+            ((0 0,1 0,1 1,0 1,0 0)),
+            ((10 10,14 10,14 14,10 14,10 10))
+        )));
+        assert_eq!(
+            multi_polygon.representative_point(),
+            Some(wkt!(POINT(12 12)))
+        );
+    }
+
+    #[test]
+    fn lines_and_points() {
+        let line_string = Geometry::from(wkt!(LINESTRING(0 0,2 0,2 2)));
+        assert_eq!(line_string.centroid(), Some(wkt!(POINT(1.5 0.5))));
+        assert_eq!(line_string.representative_point(), Some(wkt!(POINT(2 0))));
+
+        let multi_point = Geometry::from(wkt!(MULTIPOINT(0 0,2 0,4 3)));
+        assert_eq!(multi_point.centroid(), Some(wkt!(POINT(2 1))));
+        assert_eq!(multi_point.representative_point(), Some(wkt!(POINT(2 0))));
+
+        // Lines outweigh points
+        let collection = Geometry::from(GeometryCollection::new(vec![
+            wkt!(POINT(100 100)).into(),
+            wkt!(LINESTRING(0 0,2 0)).into(),
+        ]));
+        assert_eq!(collection.centroid(), Some(wkt!(POINT(1 0))));
+
+        let empty = Geometry::from(GeometryCollection::new(vec![]));
+        assert_eq!(empty.centroid(), None);
+        assert_eq!(empty.representative_point(), None);
+    }
+}
@@ -0,0 +1,133 @@
+use super::COORD_SCALE_FACTOR;
+use crate::{
+    Geometry, GeometryCollection, LineString, LngLat, MultiLineString, MultiPolygon, Polygon,
+};
+
+impl Geometry {
+    /// Returns a copy of the geometry with intermediate vertices inserted, so that no segment
+    /// is longer than `max_segment_degrees`.
+    ///
+    /// Segments are measured and split in planar lng/lat space, not along great circles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_segment_degrees` is not positive.
+    pub fn densify(&self, max_segment_degrees: f64) -> Geometry {
+        assert!(
+            max_segment_degrees > 0.0,
+            "max_segment_degrees must be positive, but was {max_segment_degrees}"
+        );
+        let max_segment_unscaled = max_segment_degrees * COORD_SCALE_FACTOR;
+        self.densify_unscaled(max_segment_unscaled)
+    }
+
+    fn densify_unscaled(&self, max_segment_unscaled: f64) -> Geometry {
+        match self {
+            Geometry::Point(_) | Geometry::MultiPoint(_) => self.clone(),
+            Geometry::LineString(line_string) => line_string.densify(max_segment_unscaled).into(),
+            Geometry::Polygon(polygon) => polygon.densify(max_segment_unscaled).into(),
+            Geometry::MultiLineString(multi_line_string) => MultiLineString::new(
+                multi_line_string
+                    .line_strings()
+                    .iter()
+                    .map(|line_string| line_string.densify(max_segment_unscaled))
+                    .collect(),
+            )
+            .into(),
+            Geometry::MultiPolygon(multi_polygon) => MultiPolygon::new(
+                multi_polygon
+                    .polygons()
+                    .iter()
+                    .map(|polygon| polygon.densify(max_segment_unscaled))
+                    .collect(),
+            )
+            .into(),
+            Geometry::GeometryCollection(geometry_collection) => GeometryCollection::new(
+                geometry_collection
+                    .geometries()
+                    .iter()
+                    .map(|geometry| geometry.densify_unscaled(max_segment_unscaled))
+                    .collect(),
+            )
+            .into(),
+        }
+    }
+}
+
+impl Polygon {
+    fn densify(&self, max_segment_unscaled: f64) -> Polygon {
+        Polygon::new(
+            self.rings()
+                .iter()
+                .map(|ring| ring.densify(max_segment_unscaled))
+                .collect(),
+        )
+    }
+}
+
+impl LineString {
+    fn densify(&self, max_segment_unscaled: f64) -> LineString {
+        let Some(first) = self.points().first() else {
+            return LineString::new(vec![]);
+        };
+
+        let mut points = vec![first.clone()];
+        for segment in self.points().windows(2) {
+            let (start, end) = (&segment[0], &segment[1]);
+            let lng_delta = end.lng_unscaled() as f64 - start.lng_unscaled() as f64;
+            let lat_delta = end.lat_unscaled() as f64 - start.lat_unscaled() as f64;
+            let segment_count = (lng_delta.hypot(lat_delta) / max_segment_unscaled)
+                .ceil()
+                .max(1.0) as u64;
+            for i in 1..segment_count {
+                let fraction = i as f64 / segment_count as f64;
+                points.push(LngLat::unscaled(
+                    (start.lng_unscaled() as f64 + lng_delta * fraction).round() as i32,
+                    (start.lat_unscaled() as f64 + lat_delta * fraction).round() as i32,
+                ));
+            }
+            points.push(end.clone());
+        }
+        LineString::new(points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wkt;
+
+    #[test]
+    fn densify_line_string() {
+        let line_string: Geometry = wkt!(LINESTRING(0 0,10 0,10 0.5)).into();
+        let densified = line_string.densify(1.0);
+        // The first segment is split into 10 sub-segments, the second is short enough already.
+        let expected: Geometry =
+            wkt!(LINESTRING(0 0,1 0,2 0,3 0,4 0,5 0,6 0,7 0,8 0,9 0,10 0,10 0.5)).into();
+        assert_eq!(densified, expected);
+
+        // uneven split
+        let densified = line_string.densify(3.0);
+        let Geometry::LineString(densified) = densified else {
+            panic!("expected LineString");
+        };
+        // ceil(10 / 3) = 4 sub-segments for the first segment
+        assert_eq!(densified.points_len(), 6);
+        assert_eq!(densified.points()[1], LngLat::degrees(2.5, 0.0));
+    }
+
+    #[test]
+    fn densify_polygon() {
+        let polygon: Geometry = wkt!(POLYGON((0 0,4 0,4 4,0 4,0 0))).into();
+        let Geometry::Polygon(densified) = polygon.densify(2.0) else {
+            panic!("expected Polygon");
+        };
+        assert_eq!(densified.rings()[0].points_len(), 9);
+    }
+
+    #[test]
+    fn densify_point() {
+        let point: Geometry = wkt!(POINT(1 2)).into();
+        assert_eq!(point.densify(0.1), point);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{
+    Error, Geometry, GeometryCollection, LineString, LngLat, MultiLineString, MultiPoint,
+    MultiPolygon, Polygon, Result,
+};
+use serde_json::{json, Value};
+
+impl Geometry {
+    /// A GeoJSON geometry object, with coordinates in degrees.
+    pub fn to_geojson_value(&self) -> Value {
+        let (geometry_type, coordinates) = match self {
+            Geometry::Point(point) => ("Point", point_coordinates(point)),
+            Geometry::LineString(line_string) => {
+                ("LineString", line_string_coordinates(line_string))
+            }
+            Geometry::Polygon(polygon) => ("Polygon", polygon_coordinates(polygon)),
+            Geometry::MultiPoint(multi_point) => (
+                "MultiPoint",
+                multi_point.points().iter().map(point_coordinates).collect(),
+            ),
+            Geometry::MultiLineString(multi_line_string) => (
+                "MultiLineString",
+                multi_line_string
+                    .line_strings()
+                    .iter()
+                    .map(line_string_coordinates)
+                    .collect(),
+            ),
+            Geometry::MultiPolygon(multi_polygon) => (
+                "MultiPolygon",
+                multi_polygon
+                    .polygons()
+                    .iter()
+                    .map(polygon_coordinates)
+                    .collect(),
+            ),
+            Geometry::GeometryCollection(geometry_collection) => {
+                let geometries: Vec<Value> = geometry_collection
+                    .geometries()
+                    .iter()
+                    .map(Geometry::to_geojson_value)
+                    .collect();
+                return json!({
+                    "type": "GeometryCollection",
+                    "geometries": geometries,
+                });
+            }
+        };
+        json!({
+            "type": geometry_type,
+            "coordinates": coordinates,
+        })
+    }
+
+    /// Parses a GeoJSON geometry object, with coordinates in degrees.
+    ///
+    /// Any altitude in a position is discarded.
+    pub fn from_geojson_value(value: Value) -> Result<Geometry> {
+        let Value::Object(mut object) = value else {
+            return Err(invalid_geojson("expected a geometry object"));
+        };
+        let geometry_type = match object.get("type") {
+            Some(Value::String(geometry_type)) => geometry_type.clone(),
+            _ => return Err(invalid_geojson("geometry is missing its `type`")),
+        };
+        if geometry_type == "GeometryCollection" {
+            let Some(Value::Array(geometries)) = object.remove("geometries") else {
+                return Err(invalid_geojson(
+                    "GeometryCollection is missing its `geometries`",
+                ));
+            };
+            let geometries = geometries
+                .into_iter()
+                .map(Geometry::from_geojson_value)
+                .collect::<Result<_>>()?;
+            return Ok(GeometryCollection::new(geometries).into());
+        }
+
+        let coordinates = object.get("coordinates").ok_or_else(|| {
+            invalid_geojson(format!("{geometry_type} is missing its `coordinates`"))
+        })?;
+        Ok(match geometry_type.as_str() {
+            "Point" => parse_point(coordinates)?.into(),
+            "LineString" => parse_line_string(coordinates)?.into(),
+            "Polygon" => parse_polygon(coordinates)?.into(),
+            "MultiPoint" => MultiPoint::new(parse_array(coordinates, parse_point)?).into(),
+            "MultiLineString" => {
+                MultiLineString::new(parse_array(coordinates, parse_line_string)?).into()
+            }
+            "MultiPolygon" => MultiPolygon::new(parse_array(coordinates, parse_polygon)?).into(),
+            other => {
+                return Err(invalid_geojson(format!(
+                    "unsupported geometry type `{other}`"
+                )))
+            }
+        })
+    }
+}
+
+fn invalid_geojson(message: impl Into<String>) -> Error {
+    Error::InvalidGeoJson(message.into())
+}
+
+fn point_coordinates(point: &LngLat) -> Value {
+    json!([point.lng_degrees(), point.lat_degrees()])
+}
+
+fn line_string_coordinates(line_string: &LineString) -> Value {
+    line_string.points().iter().map(point_coordinates).collect()
+}
+
+fn polygon_coordinates(polygon: &Polygon) -> Value {
+    polygon
+        .rings()
+        .iter()
+        .map(line_string_coordinates)
+        .collect()
+}
+
+fn parse_array<T>(value: &Value, parse_item: impl Fn(&Value) -> Result<T>) -> Result<Vec<T>> {
+    let Value::Array(items) = value else {
+        return Err(invalid_geojson(format!("expected an array, found {value}")));
+    };
+    items.iter().map(parse_item).collect()
+}
+
+fn parse_point(value: &Value) -> Result<LngLat> {
+    match value.as_array().map(Vec::as_slice) {
+        Some([lng, lat, ..]) => match (lng.as_f64(), lat.as_f64()) {
+            (Some(lng), Some(lat)) => LngLat::try_degrees(lng, lat),
+            _ => Err(invalid_geojson(format!("invalid position {value}"))),
+        },
+        _ => Err(invalid_geojson(format!("invalid position {value}"))),
+    }
+}
+
+fn parse_line_string(value: &Value) -> Result<LineString> {
+    Ok(LineString::new(parse_array(value, parse_point)?))
+}
+
+fn parse_polygon(value: &Value) -> Result<Polygon> {
+    Ok(Polygon::new(parse_array(value, parse_line_string)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wkt;
+
+    #[test]
+    fn round_trip() {
+        let geometries: Vec<Geometry> = vec![
+            wkt!(POINT(1 - 2.5)).into(),
+            wkt!(LINESTRING(0 0,1 1)).into(),
+            wkt!(POLYGON((0 0,1 0,1 1,0 0),(0.1 0.1,0.2 0.1,0.2 0.2,0.1 0.1))).into(),
+            wkt!(MULTIPOINT(1 2,3 4)).into(),
+            wkt!(MULTILINESTRING((0 0,1 1),(2 2,3 3))).into(),
+            wkt!(MULTIPOLYGON(((0 0,1 0,1 1,0 0)),((2 2,3 2,3 3,2 2)))).into(),
+            wkt!(GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(0 0,1 1))).into(),
+        ];
+        for geometry in geometries {
+            let value = geometry.to_geojson_value();
+            assert_eq!(Geometry::from_geojson_value(value).unwrap(), geometry);
+        }
+    }
+
+    #[test]
+    fn parse() {
+        // Altitude is discarded
+        let point = Geometry::from_geojson_value(json!({
+            "type": "Point",
+            "coordinates": [1, 2.5, 100],
+        }))
+        .unwrap();
+        assert_eq!(point, wkt!(POINT(1 2.5)).into());
+
+        for invalid in [
+            json!([1, 2]),
+            json!({ "coordinates": [1, 2] }),
+            json!({ "type": "Point" }),
+            json!({ "type": "Point", "coordinates": [1] }),
+            json!({ "type": "LineString", "coordinates": [1, 2] }),
+            json!({ "type": "Circle", "coordinates": [1, 2] }),
+        ] {
+            assert!(
+                matches!(
+                    Geometry::from_geojson_value(invalid.clone()),
+                    Err(Error::InvalidGeoJson(_))
+                ),
+                "{invalid}"
+            );
+        }
+
+        let out_of_range = json!({ "type": "Point", "coordinates": [1, 1000] });
+        assert!(matches!(
+            Geometry::from_geojson_value(out_of_range),
+            Err(Error::CoordinateOutOfRange { .. })
+        ));
+    }
+}
@@ -0,0 +1,85 @@
+//! Measuring geometries on the WGS84 ellipsoid, using the `geo` crate.
+
+use super::predicates::for_each_polygon;
+use super::ToGeoType;
+use crate::Geometry;
+use geo::{GeodesicArea, GeodesicLength};
+
+impl Geometry {
+    /// The total area, in square meters, of every polygon, measured along geodesics on the WGS84
+    /// ellipsoid. Holes are excluded, and geometries without polygons have no area.
+    pub fn geodesic_area(&self) -> f64 {
+        let mut area = 0.0;
+        for_each_polygon(self, &mut |polygon| {
+            area += polygon.to_geo_type().geodesic_area_unsigned()
+        });
+        area
+    }
+
+    /// The total length, in meters, of every line string, plus the perimeter (including any holes)
+    /// of every polygon, measured along geodesics on the WGS84 ellipsoid. Points have no length.
+    pub fn geodesic_length(&self) -> f64 {
+        match self {
+            Geometry::Point(_) | Geometry::MultiPoint(_) => 0.0,
+            Geometry::LineString(line_string) => line_string.to_geo_type().geodesic_length(),
+            Geometry::MultiLineString(multi_line_string) => multi_line_string
+                .line_strings()
+                .iter()
+                .map(|line_string| line_string.to_geo_type().geodesic_length())
+                .sum(),
+            Geometry::Polygon(polygon) => polygon.to_geo_type().geodesic_perimeter(),
+            Geometry::MultiPolygon(multi_polygon) => multi_polygon
+                .polygons()
+                .iter()
+                .map(|polygon| polygon.to_geo_type().geodesic_perimeter())
+                .sum(),
+            Geometry::GeometryCollection(geometry_collection) => geometry_collection
+                .geometries()
+                .iter()
+                .map(Geometry::geodesic_length)
+                .sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wkt;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn area() {
+        // 1 degree square at the equator
+        let square = Geometry::from(wkt!(POLYGON((0 0,1 0,1 1,0 1,0 0))));
+        assert_relative_eq!(square.geodesic_area(), 12_308_778_361.5, epsilon = 1.0);
+        // Winding doesn't matter
+        let clockwise = Geometry::from(wkt!(POLYGON((0 0,0 1,1 1,1 0,0 0))));
+        assert_relative_eq!(clockwise.geodesic_area(), 12_308_778_361.5, epsilon = 1.0);
+
+        let with_hole = Geometry::from(wkt!(POLYGON(
+            (0 0,1 0,1 1,0 1,0 0),
+            (0.25 0.25,0.25 0.75,0.75 0.75,0.75 0.25,0.25 0.25)
+        )));
+        assert!(with_hole.geodesic_area() < square.geodesic_area() * 0.8);
+
+        assert_eq!(
+            Geometry::from(wkt!(LINESTRING(0 0,1 1))).geodesic_area(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn length() {
+        // 1 degree of longitude at the equator
+        let line_string = Geometry::from(wkt!(LINESTRING(0 0,1 0)));
+        assert_relative_eq!(line_string.geodesic_length(), 111_319.5, epsilon = 0.1);
+
+        let multi = Geometry::from(wkt!(MULTILINESTRING((0 0,1 0),(0 0,1 0))));
+        assert_relative_eq!(multi.geodesic_length(), 2.0 * 111_319.5, epsilon = 0.2);
+
+        let square = Geometry::from(wkt!(POLYGON((0 0,1 0,1 1,0 1,0 0))));
+        assert!(square.geodesic_length() > 4.0 * 110_000.0);
+        assert_eq!(Geometry::from(wkt!(POINT(1 2))).geodesic_length(), 0.0);
+    }
+}
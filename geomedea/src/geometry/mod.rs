@@ -1,11 +1,29 @@
+mod antimeridian;
 mod bounded;
+mod centroid;
+mod densify;
+#[cfg(feature = "json")]
+mod geojson;
+#[cfg(feature = "geo")]
+mod measure;
+mod orient;
+pub(crate) mod predicates;
 #[cfg(test)]
 mod relative_eq;
+mod simplify;
+#[cfg(feature = "geo-types")]
+mod to_geo_type;
+mod validity;
+mod wkb;
 
 use crate::bounds::Bounds;
+use crate::{Error, Result};
 pub(crate) use bounded::Bounded;
 use serde::{Deserialize, Serialize};
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
+#[cfg(feature = "geo-types")]
+pub use to_geo_type::ToGeoType;
+pub use validity::{ValidationError, WindingOrder};
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Geometry {
@@ -18,21 +36,94 @@ pub enum Geometry {
     GeometryCollection(GeometryCollection),
 }
 
-impl Debug for Geometry {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+/// The variant of a [`Geometry`], without its coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GeometryType {
+    Point,
+    LineString,
+    Polygon,
+    MultiPoint,
+    MultiLineString,
+    MultiPolygon,
+    GeometryCollection,
+}
+
+impl GeometryType {
+    /// The type of a serialized geometry, from its leading enum variant index, which follows the
+    /// declaration order of [`Geometry`].
+    pub(crate) fn from_variant_index(variant_index: u32) -> Option<Self> {
+        Some(match variant_index {
+            0 => GeometryType::Point,
+            1 => GeometryType::LineString,
+            2 => GeometryType::Polygon,
+            3 => GeometryType::MultiPoint,
+            4 => GeometryType::MultiLineString,
+            5 => GeometryType::MultiPolygon,
+            6 => GeometryType::GeometryCollection,
+            _ => return None,
+        })
+    }
+}
+
+impl Geometry {
+    pub fn geometry_type(&self) -> GeometryType {
         match self {
-            Geometry::Point(g) => write!(f, "POINT({g:?})"),
-            Geometry::LineString(g) => g.fmt(f),
-            Geometry::Polygon(g) => g.fmt(f),
-            Geometry::MultiPoint(g) => g.fmt(f),
-            Geometry::MultiLineString(g) => g.fmt(f),
-            Geometry::MultiPolygon(g) => g.fmt(f),
-            Geometry::GeometryCollection(g) => g.fmt(f),
+            Geometry::Point(_) => GeometryType::Point,
+            Geometry::LineString(_) => GeometryType::LineString,
+            Geometry::Polygon(_) => GeometryType::Polygon,
+            Geometry::MultiPoint(_) => GeometryType::MultiPoint,
+            Geometry::MultiLineString(_) => GeometryType::MultiLineString,
+            Geometry::MultiPolygon(_) => GeometryType::MultiPolygon,
+            Geometry::GeometryCollection(_) => GeometryType::GeometryCollection,
+        }
+    }
+
+    /// Apply `f` to every coordinate of the geometry.
+    pub(crate) fn for_each_lng_lat_mut(&mut self, f: &mut impl FnMut(&mut LngLat)) {
+        match self {
+            Geometry::Point(point) => f(point),
+            Geometry::LineString(line_string) => line_string.0.iter_mut().for_each(f),
+            Geometry::Polygon(polygon) => polygon
+                .0
+                .iter_mut()
+                .flat_map(|ring| ring.0.iter_mut())
+                .for_each(f),
+            Geometry::MultiPoint(multi_point) => multi_point.0.iter_mut().for_each(f),
+            Geometry::MultiLineString(multi_line_string) => multi_line_string
+                .0
+                .iter_mut()
+                .flat_map(|line_string| line_string.0.iter_mut())
+                .for_each(f),
+            Geometry::MultiPolygon(multi_polygon) => multi_polygon
+                .0
+                .iter_mut()
+                .flat_map(|polygon| polygon.0.iter_mut())
+                .flat_map(|ring| ring.0.iter_mut())
+                .for_each(f),
+            Geometry::GeometryCollection(geometry_collection) => {
+                for geometry in &mut geometry_collection.0 {
+                    geometry.for_each_lng_lat_mut(f)
+                }
+            }
         }
     }
+
+    /// Convert every coordinate from in-memory units to units of `precision_nanos`.
+    #[cfg(feature = "writer")]
+    pub(crate) fn rescale_to_precision(&mut self, precision_nanos: i32) {
+        self.for_each_lng_lat_mut(&mut |lng_lat| lng_lat.rescale_to_precision(precision_nanos))
+    }
+
+    /// Convert every coordinate from units of `precision_nanos` to in-memory units.
+    pub(crate) fn rescale_from_precision(&mut self, precision_nanos: i32) {
+        self.for_each_lng_lat_mut(&mut |lng_lat| lng_lat.rescale_from_precision(precision_nanos))
+    }
 }
 
-#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// A coordinate, stored as integer units of 1e-7 degrees.
+///
+/// Points are ordered lexicographically by longitude, then latitude.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct LngLat {
     lng: i32,
     lat: i32,
@@ -76,7 +167,7 @@ impl Polygon {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct MultiPoint(Vec<Point>);
 impl MultiPoint {
     pub fn new(points: Vec<Point>) -> Self {
@@ -90,7 +181,7 @@ impl MultiPoint {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct MultiLineString(Vec<LineString>);
 impl MultiLineString {
     pub fn new(line_strings: Vec<LineString>) -> Self {
@@ -138,7 +229,7 @@ impl MultiPolygon {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct GeometryCollection(Vec<Geometry>);
 impl GeometryCollection {
     pub fn new(geometries: Vec<Geometry>) -> Self {
@@ -212,73 +303,135 @@ impl Debug for LngLat {
     }
 }
 
-impl Debug for LineString {
+// Geometries are displayed as well-known text (WKT), with coordinates in degrees, e.g.
+// `LINESTRING(0 0,1 1)`, which is the same syntax accepted by the `wkt!` macro. Their `Debug`
+// output is the same, except that a bare `LngLat` is debugged as just its coordinates.
+
+impl Display for Geometry {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "LINESTRING ")?;
-        fmt_points(&self.0, f)
+        match self {
+            Geometry::Point(g) => Display::fmt(g, f),
+            Geometry::LineString(g) => Display::fmt(g, f),
+            Geometry::Polygon(g) => Display::fmt(g, f),
+            Geometry::MultiPoint(g) => Display::fmt(g, f),
+            Geometry::MultiLineString(g) => Display::fmt(g, f),
+            Geometry::MultiPolygon(g) => Display::fmt(g, f),
+            Geometry::GeometryCollection(g) => Display::fmt(g, f),
+        }
     }
 }
 
-fn fmt_points(points: &[LngLat], f: &mut Formatter) -> std::fmt::Result {
-    if points.is_empty() {
-        return write!(f, "EMPTY");
+impl Display for LngLat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "POINT({self:?})")
     }
+}
 
-    write!(f, "(")?;
-    for (idx, point) in points.iter().enumerate() {
-        if idx == points.len() - 1 {
-            write!(f, "{point:?}")?;
-        } else {
-            write!(f, "{point:?},")?;
-        }
+impl Display for LineString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fmt_tagged(f, "LINESTRING", &self.0, Debug::fmt)
     }
-    write!(f, ")")
 }
 
-impl Debug for Polygon {
+impl Display for Polygon {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "POLYGON ")?;
-        fmt_polygon_rings_wkt(self, f)
+        fmt_tagged(f, "POLYGON", self.rings(), |ring, f| {
+            fmt_points(ring.points(), f)
+        })
     }
 }
 
-fn fmt_polygon_rings_wkt(polygon: &Polygon, f: &mut Formatter) -> std::fmt::Result {
-    if polygon.rings().is_empty() {
-        return write!(f, "EMPTY");
+impl Display for MultiPoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fmt_tagged(f, "MULTIPOINT", &self.0, Debug::fmt)
     }
+}
 
-    write!(f, "(")?;
-    for (idx, ring) in polygon.rings().iter().enumerate() {
-        if idx == polygon.rings().len() - 1 {
-            fmt_points(ring.points(), f)?;
-        } else {
-            fmt_points(ring.points(), f)?;
-            write!(f, ",")?;
-        }
+impl Display for MultiLineString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fmt_tagged(f, "MULTILINESTRING", &self.0, |line_string, f| {
+            fmt_points(line_string.points(), f)
+        })
     }
-    write!(f, ")")
 }
 
-impl Debug for MultiPolygon {
+impl Display for MultiPolygon {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if self.polygons().is_empty() {
-            write!(f, "MULTIPOLYGON EMPTY")?;
-        } else {
-            write!(f, "MULTIPOLYGON (")?;
-            for (idx, polygon) in self.polygons().iter().enumerate() {
-                if idx == self.polygons().len() - 1 {
-                    fmt_polygon_rings_wkt(polygon, f)?;
-                } else {
-                    fmt_polygon_rings_wkt(polygon, f)?;
-                    write!(f, ",")?;
+        fmt_tagged(f, "MULTIPOLYGON", &self.0, |polygon, f| {
+            fmt_wkt_list(polygon.rings(), f, |ring, f| fmt_points(ring.points(), f))
+        })
+    }
+}
+
+impl Display for GeometryCollection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fmt_tagged(f, "GEOMETRYCOLLECTION", &self.0, Display::fmt)
+    }
+}
+
+macro_rules! debug_as_display {
+    ($($geometry_type: ty),+) => {
+        $(
+            impl Debug for $geometry_type {
+                fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                    Display::fmt(self, f)
                 }
             }
-            write!(f, ")")?;
+        )+
+    };
+}
+debug_as_display!(
+    Geometry,
+    LineString,
+    Polygon,
+    MultiPoint,
+    MultiLineString,
+    MultiPolygon,
+    GeometryCollection
+);
+
+/// Writes `TAG(item,item)`, or `TAG EMPTY` if there are no items.
+fn fmt_tagged<T>(
+    f: &mut Formatter,
+    tag: &str,
+    items: &[T],
+    fmt_item: impl Fn(&T, &mut Formatter) -> std::fmt::Result,
+) -> std::fmt::Result {
+    write!(f, "{tag}")?;
+    if items.is_empty() {
+        write!(f, " ")?;
+    }
+    fmt_wkt_list(items, f, fmt_item)
+}
+
+/// Writes `(item,item)`, or `EMPTY` if there are no items.
+fn fmt_wkt_list<T>(
+    items: &[T],
+    f: &mut Formatter,
+    fmt_item: impl Fn(&T, &mut Formatter) -> std::fmt::Result,
+) -> std::fmt::Result {
+    if items.is_empty() {
+        return write!(f, "EMPTY");
+    }
+    write!(f, "(")?;
+    for (idx, item) in items.iter().enumerate() {
+        if idx > 0 {
+            write!(f, ",")?;
         }
-        Ok(())
+        fmt_item(item, f)?;
     }
+    write!(f, ")")
 }
 
+fn fmt_points(points: &[LngLat], f: &mut Formatter) -> std::fmt::Result {
+    fmt_wkt_list(points, f, Debug::fmt)
+}
+
+/// The precision of in-memory coordinates.
+///
+/// Files may store their coordinates with a different precision (see
+/// `Writer::set_coord_precision_nanos`), in which case they are converted to and from this
+/// precision when writing and reading.
 pub const COORD_PRECISION_NANOS: i32 = 100;
 
 /// Number of internal units (as returned from [`Lat::inner`]) in one degree.
@@ -286,20 +439,52 @@ pub const COORD_PRECISION_NANOS: i32 = 100;
 /// See [`COORD_PRECISION_NANOS`].
 pub const COORD_SCALE_FACTOR: f64 = (1_000_000_000 / COORD_PRECISION_NANOS) as f64;
 
+/// Whether every lng/lat in [-180, 180] can be represented as an `i32` at the given precision.
+pub(crate) fn is_valid_coord_precision(precision_nanos: i32) -> bool {
+    const MAX_NANOS: i64 = 180 * 1_000_000_000;
+    precision_nanos > 0 && MAX_NANOS / precision_nanos as i64 <= i32::MAX as i64
+}
+
 impl LngLat {
     #[inline]
     fn to_degrees(unscaled_value: i32) -> f64 {
         unscaled_value as f64 / COORD_SCALE_FACTOR
     }
 
+    /// Rounds to the nearest unit, so that quantization error is symmetric rather than biased
+    /// toward zero.
     fn to_unscaled(degrees: f64) -> i32 {
-        (degrees * COORD_SCALE_FACTOR) as i32
+        (degrees * COORD_SCALE_FACTOR).round() as i32
+    }
+
+    fn rescale(unscaled_value: i32, from_precision_nanos: i32, to_precision_nanos: i32) -> i32 {
+        let nanos = unscaled_value as i64 * from_precision_nanos as i64;
+        (nanos as f64 / to_precision_nanos as f64).round() as i32
+    }
+
+    /// Convert from in-memory units to units of `precision_nanos`, as stored in a file.
+    ///
+    /// This is potentially lossy.
+    #[cfg(feature = "writer")]
+    pub(crate) fn rescale_to_precision(&mut self, precision_nanos: i32) {
+        self.lng = Self::rescale(self.lng, COORD_PRECISION_NANOS, precision_nanos);
+        self.lat = Self::rescale(self.lat, COORD_PRECISION_NANOS, precision_nanos);
+    }
+
+    /// Convert from units of `precision_nanos`, as stored in a file, to in-memory units.
+    pub(crate) fn rescale_from_precision(&mut self, precision_nanos: i32) {
+        self.lng = Self::rescale(self.lng, precision_nanos, COORD_PRECISION_NANOS);
+        self.lat = Self::rescale(self.lat, precision_nanos, COORD_PRECISION_NANOS);
     }
 
     pub fn unscaled(lng: i32, lat: i32) -> Self {
         Self { lng, lat }
     }
 
+    /// Degrees are rounded to the nearest unit of [`COORD_PRECISION_NANOS`].
+    ///
+    /// Values too large to be represented (beyond roughly ±214.7°) saturate at the largest
+    /// representable value, and NaN becomes 0. Use [`LngLat::try_degrees`] to detect this.
     pub fn degrees(lng: f64, lat: f64) -> Self {
         Self {
             lng: Self::to_unscaled(lng),
@@ -307,6 +492,19 @@ impl LngLat {
         }
     }
 
+    /// Like [`LngLat::degrees`], but returns an error rather than saturating if either value is
+    /// too large to be represented, or isn't a number.
+    pub fn try_degrees(lng: f64, lat: f64) -> Result<Self> {
+        let is_representable = |degrees: f64| {
+            let unscaled = (degrees * COORD_SCALE_FACTOR).round();
+            unscaled >= i32::MIN as f64 && unscaled <= i32::MAX as f64
+        };
+        if !is_representable(lng) || !is_representable(lat) {
+            return Err(Error::CoordinateOutOfRange { lng, lat });
+        }
+        Ok(Self::degrees(lng, lat))
+    }
+
     pub fn lng_unscaled(&self) -> i32 {
         self.lng
     }
@@ -347,6 +545,66 @@ mod tests {
     use super::*;
     use crate::wkt;
 
+    #[test]
+    fn display_wkt() {
+        let geometries: Vec<(Geometry, &str)> = vec![
+            (wkt!(POINT(1 - 2.5)).into(), "POINT(1 -2.5)"),
+            (wkt!(LINESTRING(0 0,1 1)).into(), "LINESTRING(0 0,1 1)"),
+            (LineString::new(vec![]).into(), "LINESTRING EMPTY"),
+            (
+                wkt!(POLYGON((0 0,1 0,1 1,0 0),(0.1 0.1,0.2 0.1,0.2 0.2,0.1 0.1))).into(),
+                "POLYGON((0 0,1 0,1 1,0 0),(0.1 0.1,0.2 0.1,0.2 0.2,0.1 0.1))",
+            ),
+            (wkt!(MULTIPOINT(1 2,3 4)).into(), "MULTIPOINT(1 2,3 4)"),
+            (
+                wkt!(MULTILINESTRING((0 0,1 1),(2 2,3 3))).into(),
+                "MULTILINESTRING((0 0,1 1),(2 2,3 3))",
+            ),
+            (
+                wkt!(MULTIPOLYGON(((0 0,1 0,1 1,0 0)),((2 2,3 2,3 3,2 2)))).into(),
+                "MULTIPOLYGON(((0 0,1 0,1 1,0 0)),((2 2,3 2,3 3,2 2)))",
+            ),
+            (MultiPolygon::new(vec![]).into(), "MULTIPOLYGON EMPTY"),
+            (
+                wkt!(GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(0 0,1 1))).into(),
+                "GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(0 0,1 1))",
+            ),
+            (
+                GeometryCollection::new(vec![]).into(),
+                "GEOMETRYCOLLECTION EMPTY",
+            ),
+        ];
+        for (geometry, expected) in geometries {
+            assert_eq!(geometry.to_string(), expected);
+            assert_eq!(format!("{geometry:?}"), expected);
+        }
+        // A bare coordinate debugs as just its coordinates, for use within other output.
+        assert_eq!(format!("{:?}", wkt!(POINT(1 2))), "1 2");
+        assert_eq!(wkt!(POINT(1 2)).to_string(), "POINT(1 2)");
+    }
+
+    #[test]
+    fn lng_lat_order() {
+        let mut points = vec![
+            wkt!(POINT(1 0)),
+            wkt!(POINT(0 1)),
+            wkt!(POINT(0 0)),
+            wkt!(POINT(0 1)),
+        ];
+        points.sort();
+        points.dedup();
+        assert_eq!(
+            points,
+            vec![wkt!(POINT(0 0)), wkt!(POINT(0 1)), wkt!(POINT(1 0))]
+        );
+
+        let unique: std::collections::HashSet<LngLat> =
+            [wkt!(POINT(1 2)), wkt!(POINT(1 2)), wkt!(POINT(2 1))]
+                .into_iter()
+                .collect();
+        assert_eq!(unique.len(), 2);
+    }
+
     #[test]
     fn coordinate_scaling() {
         let coord = LngLat::degrees(-118.2562, 34.1060);
@@ -354,6 +612,74 @@ mod tests {
         assert_eq!(coord.lat_unscaled(), 341060000);
     }
 
+    #[test]
+    fn try_degrees() {
+        let coord = LngLat::try_degrees(-118.2562, 34.1060).unwrap();
+        assert_eq!(coord, LngLat::degrees(-118.2562, 34.1060));
+        assert!(LngLat::try_degrees(180.0, -90.0).is_ok());
+        assert!(LngLat::try_degrees(214.0, 0.0).is_ok());
+
+        // e.g. accidentally passing meters
+        assert!(matches!(
+            LngLat::try_degrees(5000.0, 0.0),
+            Err(Error::CoordinateOutOfRange { .. })
+        ));
+        assert!(LngLat::try_degrees(0.0, -215.0).is_err());
+        assert!(LngLat::try_degrees(f64::NAN, 0.0).is_err());
+        assert!(LngLat::try_degrees(0.0, f64::INFINITY).is_err());
+
+        // The infallible version saturates
+        assert_eq!(LngLat::degrees(5000.0, 0.0).lng_unscaled(), i32::MAX);
+    }
+
+    #[test]
+    fn coordinate_rounding() {
+        // Values exactly halfway between two units round away from zero.
+        let coord = LngLat::degrees(-118.25620005, 34.10600005);
+        assert_eq!(coord.lng_unscaled(), -1182562001);
+        assert_eq!(coord.lat_unscaled(), 341060001);
+
+        // Rather than truncating toward zero
+        let coord = LngLat::degrees(-118.25619995, 34.10599995);
+        assert_eq!(coord.lng_unscaled(), -1182562000);
+        assert_eq!(coord.lat_unscaled(), 341060000);
+    }
+
+    #[test]
+    #[cfg(feature = "writer")]
+    fn coord_precision() {
+        assert!(is_valid_coord_precision(COORD_PRECISION_NANOS));
+        assert!(is_valid_coord_precision(84));
+        assert!(!is_valid_coord_precision(83));
+        assert!(!is_valid_coord_precision(0));
+        assert!(!is_valid_coord_precision(-100));
+
+        // ~1m
+        let precision_nanos = 10_000;
+        let mut geometry = Geometry::from(LineString::new(vec![
+            LngLat::unscaled(-1182562490, 341060510),
+            LngLat::degrees(180.0, -90.0),
+        ]));
+        geometry.rescale_to_precision(precision_nanos);
+        assert_eq!(
+            geometry,
+            LineString::new(vec![
+                LngLat::unscaled(-11825625, 3410605),
+                LngLat::unscaled(18000000, -9000000)
+            ])
+            .into()
+        );
+        geometry.rescale_from_precision(precision_nanos);
+        assert_eq!(
+            geometry,
+            LineString::new(vec![
+                LngLat::unscaled(-1182562500, 341060500),
+                LngLat::degrees(180.0, -90.0),
+            ])
+            .into()
+        );
+    }
+
     #[test]
     fn bounds_checking() {
         let collection = wkt!(GEOMETRYCOLLECTION(
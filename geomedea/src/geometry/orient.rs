@@ -0,0 +1,76 @@
+use super::WindingOrder;
+use crate::{Geometry, Polygon};
+
+impl Geometry {
+    /// Rewind every polygon's rings, see [`Polygon::orient`].
+    pub fn orient(&mut self, exterior: WindingOrder) {
+        match self {
+            Geometry::Point(_)
+            | Geometry::LineString(_)
+            | Geometry::MultiPoint(_)
+            | Geometry::MultiLineString(_) => {}
+            Geometry::Polygon(polygon) => polygon.orient(exterior),
+            Geometry::MultiPolygon(multi_polygon) => {
+                for polygon in multi_polygon.polygons_mut() {
+                    polygon.orient(exterior)
+                }
+            }
+            Geometry::GeometryCollection(geometry_collection) => {
+                for geometry in geometry_collection.geometries_mut() {
+                    geometry.orient(exterior)
+                }
+            }
+        }
+    }
+}
+
+impl Polygon {
+    /// Reverse any ring which doesn't wind in the canonical orientation: `exterior` for the
+    /// exterior ring, and the opposite for interior rings.
+    ///
+    /// Rings without any area, e.g. because they're unclosed, are left as they are.
+    pub fn orient(&mut self, exterior: WindingOrder) {
+        for (ring_idx, ring) in self.rings_mut().iter_mut().enumerate() {
+            let expected = if ring_idx == 0 {
+                exterior
+            } else {
+                exterior.reversed()
+            };
+            if matches!(ring.winding_order(), Some(found) if found != expected) {
+                ring.0.reverse();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wkt;
+
+    #[test]
+    fn orient() {
+        let mut polygon = wkt!(POLYGON((0 0,0 10,10 10,10 0,0 0),(2 2,4 2,4 4,2 4,2 2)));
+        polygon.orient(WindingOrder::CounterClockwise);
+        assert_eq!(
+            polygon,
+            wkt!(POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,2 4,4 4,4 2,2 2)))
+        );
+        assert_eq!(
+            Geometry::from(polygon.clone()).validate_with_winding(WindingOrder::CounterClockwise),
+            Ok(())
+        );
+
+        let mut multi_polygon = Geometry::from(wkt!(MULTIPOLYGON(((0 0,10 0,10 10,0 0)))));
+        multi_polygon.orient(WindingOrder::Clockwise);
+        assert_eq!(
+            multi_polygon,
+            Geometry::from(wkt!(MULTIPOLYGON(((0 0,10 10,10 0,0 0)))))
+        );
+
+        // Already canonical, so unchanged.
+        let oriented = polygon.clone();
+        polygon.orient(WindingOrder::CounterClockwise);
+        assert_eq!(polygon, oriented);
+    }
+}
@@ -0,0 +1,303 @@
+//! Exact spatial predicates, used to refine the candidates found by the (bounding box based)
+//! spatial index.
+//!
+//! All computations are done on the unscaled integer coordinates, so results are exact.
+
+use crate::{Geometry, LngLat, Polygon};
+use std::cmp::Ordering;
+
+/// Is every part of `geometry` inside of `polygon`?
+///
+/// Points on the boundary of `polygon` are considered inside. Empty geometries are never within
+/// a polygon.
+pub(crate) fn is_within(geometry: &Geometry, polygon: &Polygon) -> bool {
+    let mut has_points = false;
+    let mut is_within = true;
+    for_each_path(geometry, &mut |path| {
+        if !is_within {
+            return;
+        }
+        has_points |= !path.is_empty();
+        if !path
+            .iter()
+            .all(|point| polygon_contains_point(polygon, point))
+        {
+            is_within = false;
+            return;
+        }
+        for segment in path.windows(2) {
+            if polygon_boundary_crosses(polygon, &segment[0], &segment[1]) {
+                is_within = false;
+                return;
+            }
+        }
+    });
+    has_points && is_within
+}
+
+/// Do `a` and `b` have any point in common, including on their boundaries?
+pub(crate) fn intersects(a: &Geometry, b: &Geometry) -> bool {
+    let mut a_paths = vec![];
+    for_each_path(a, &mut |path| a_paths.push(path));
+    let mut b_paths = vec![];
+    for_each_path(b, &mut |path| b_paths.push(path));
+
+    for a_path in &a_paths {
+        for (a_start, a_end) in segments(a_path) {
+            for b_path in &b_paths {
+                for (b_start, b_end) in segments(b_path) {
+                    if segments_intersect(a_start, a_end, b_start, b_end) {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+
+    // With no intersecting edges, the only remaining possibility is that one is entirely inside
+    // the other, so it's enough to check a single vertex.
+    fn is_inside(inner_paths: &[&[LngLat]], outer: &Geometry) -> bool {
+        let Some(vertex) = inner_paths.iter().find_map(|path| path.first()) else {
+            return false;
+        };
+        let mut is_inside = false;
+        for_each_polygon(outer, &mut |polygon| {
+            is_inside |= polygon_contains_point(polygon, vertex)
+        });
+        is_inside
+    }
+    is_inside(&a_paths, b) || is_inside(&b_paths, a)
+}
+
+/// Points on the boundary of `polygon` are considered inside.
+pub(crate) fn polygon_contains_point(polygon: &Polygon, point: &LngLat) -> bool {
+    let Some((exterior, interiors)) = polygon.rings().split_first() else {
+        return false;
+    };
+    match ring_position(exterior.points(), point) {
+        Position::Outside => false,
+        Position::Boundary => true,
+        Position::Inside => interiors
+            .iter()
+            .all(|interior| ring_position(interior.points(), point) != Position::Inside),
+    }
+}
+
+/// Does the segment from `a` to `b` properly cross (not just touch) any of `polygon`'s rings?
+fn polygon_boundary_crosses(polygon: &Polygon, a: &LngLat, b: &LngLat) -> bool {
+    polygon.rings().iter().any(|ring| {
+        ring.points()
+            .windows(2)
+            .any(|edge| segments_cross(a, b, &edge[0], &edge[1]))
+    })
+}
+
+/// Calls `f` with each sequence of connected points in the geometry. Each point of a
+/// (Multi)Point is its own path.
+pub(super) fn for_each_path<'a>(geometry: &'a Geometry, f: &mut impl FnMut(&'a [LngLat])) {
+    match geometry {
+        Geometry::Point(point) => f(std::slice::from_ref(point)),
+        Geometry::LineString(line_string) => f(line_string.points()),
+        Geometry::Polygon(polygon) => {
+            for ring in polygon.rings() {
+                f(ring.points())
+            }
+        }
+        Geometry::MultiPoint(multi_point) => {
+            for point in multi_point.points() {
+                f(std::slice::from_ref(point))
+            }
+        }
+        Geometry::MultiLineString(multi_line_string) => {
+            for line_string in multi_line_string.line_strings() {
+                f(line_string.points())
+            }
+        }
+        Geometry::MultiPolygon(multi_polygon) => {
+            for polygon in multi_polygon.polygons() {
+                for ring in polygon.rings() {
+                    f(ring.points())
+                }
+            }
+        }
+        Geometry::GeometryCollection(geometry_collection) => {
+            for geometry in geometry_collection.geometries() {
+                for_each_path(geometry, f)
+            }
+        }
+    }
+}
+
+pub(super) fn for_each_polygon<'a>(geometry: &'a Geometry, f: &mut impl FnMut(&'a Polygon)) {
+    match geometry {
+        Geometry::Point(_)
+        | Geometry::LineString(_)
+        | Geometry::MultiPoint(_)
+        | Geometry::MultiLineString(_) => {}
+        Geometry::Polygon(polygon) => f(polygon),
+        Geometry::MultiPolygon(multi_polygon) => {
+            for polygon in multi_polygon.polygons() {
+                f(polygon)
+            }
+        }
+        Geometry::GeometryCollection(geometry_collection) => {
+            for geometry in geometry_collection.geometries() {
+                for_each_polygon(geometry, f)
+            }
+        }
+    }
+}
+
+/// The segments of a path. A path with a single point is treated as a zero length segment.
+fn segments(path: &[LngLat]) -> impl Iterator<Item = (&LngLat, &LngLat)> {
+    let single_point = match path {
+        [point] => Some((point, point)),
+        _ => None,
+    };
+    single_point
+        .into_iter()
+        .chain(path.windows(2).map(|segment| (&segment[0], &segment[1])))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Position {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Where is `point` relative to the closed `ring`?
+fn ring_position(ring: &[LngLat], point: &LngLat) -> Position {
+    let mut is_inside = false;
+    for edge in ring.windows(2) {
+        let (a, b) = (&edge[0], &edge[1]);
+        let orientation = orientation(a, b, point);
+        if orientation == Ordering::Equal && is_between(a, b, point) {
+            return Position::Boundary;
+        }
+        // Count the edges crossed by a ray cast from `point` in the positive lng direction.
+        if (a.lat_unscaled() > point.lat_unscaled()) != (b.lat_unscaled() > point.lat_unscaled()) {
+            let is_upward = b.lat_unscaled() > a.lat_unscaled();
+            let crosses_ray = if is_upward {
+                orientation == Ordering::Greater
+            } else {
+                orientation == Ordering::Less
+            };
+            if crosses_ray {
+                is_inside = !is_inside;
+            }
+        }
+    }
+    if is_inside {
+        Position::Inside
+    } else {
+        Position::Outside
+    }
+}
+
+/// Do segments `a`-`b` and `c`-`d` cross at a single point which is interior to both?
+fn segments_cross(a: &LngLat, b: &LngLat, c: &LngLat, d: &LngLat) -> bool {
+    fn opposite(o1: Ordering, o2: Ordering) -> bool {
+        matches!(
+            (o1, o2),
+            (Ordering::Less, Ordering::Greater) | (Ordering::Greater, Ordering::Less)
+        )
+    }
+    opposite(orientation(c, d, a), orientation(c, d, b))
+        && opposite(orientation(a, b, c), orientation(a, b, d))
+}
+
+/// Do segments `a`-`b` and `c`-`d` have any point in common?
+pub(super) fn segments_intersect(a: &LngLat, b: &LngLat, c: &LngLat, d: &LngLat) -> bool {
+    let o1 = orientation(a, b, c);
+    let o2 = orientation(a, b, d);
+    let o3 = orientation(c, d, a);
+    let o4 = orientation(c, d, b);
+    if o1 != o2 && o3 != o4 {
+        return true;
+    }
+    (o1 == Ordering::Equal && is_between(a, b, c))
+        || (o2 == Ordering::Equal && is_between(a, b, d))
+        || (o3 == Ordering::Equal && is_between(c, d, a))
+        || (o4 == Ordering::Equal && is_between(c, d, b))
+}
+
+/// `Greater` if `c` is to the left of the line through `a` and `b`, `Less` if to the right, and
+/// `Equal` if the three points are colinear.
+fn orientation(a: &LngLat, b: &LngLat, c: &LngLat) -> Ordering {
+    let (ax, ay) = (a.lng_unscaled() as i128, a.lat_unscaled() as i128);
+    let (bx, by) = (b.lng_unscaled() as i128, b.lat_unscaled() as i128);
+    let (cx, cy) = (c.lng_unscaled() as i128, c.lat_unscaled() as i128);
+    ((bx - ax) * (cy - ay) - (by - ay) * (cx - ax)).cmp(&0)
+}
+
+/// Assuming `point` is colinear with `a` and `b`, is it between them?
+fn is_between(a: &LngLat, b: &LngLat, point: &LngLat) -> bool {
+    let lng = point.lng_unscaled();
+    let lat = point.lat_unscaled();
+    a.lng_unscaled().min(b.lng_unscaled()) <= lng
+        && lng <= a.lng_unscaled().max(b.lng_unscaled())
+        && a.lat_unscaled().min(b.lat_unscaled()) <= lat
+        && lat <= a.lat_unscaled().max(b.lat_unscaled())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wkt;
+
+    #[test]
+    fn contains_point() {
+        let polygon = wkt!(POLYGON((0 0,10 0,10 10,0 10,0 0),(4 4,6 4,6 6,4 6,4 4)));
+        assert!(polygon_contains_point(&polygon, &wkt!(POINT(1 1))));
+        assert!(!polygon_contains_point(&polygon, &wkt!(POINT(11 1))));
+        // in the hole
+        assert!(!polygon_contains_point(&polygon, &wkt!(POINT(5 5))));
+        // on the boundary
+        assert!(polygon_contains_point(&polygon, &wkt!(POINT(10 5))));
+        assert!(polygon_contains_point(&polygon, &wkt!(POINT(4 5))));
+    }
+
+    #[test]
+    fn within() {
+        let polygon = wkt!(POLYGON((0 0,10 0,10 10,0 10,0 0)));
+        assert!(is_within(&wkt!(POINT(1 1)).into(), &polygon));
+        assert!(is_within(&wkt!(LINESTRING(1 1,9 9)).into(), &polygon));
+        assert!(!is_within(&wkt!(LINESTRING(1 1,11 9)).into(), &polygon));
+        assert!(!is_within(&wkt!(LINESTRING EMPTY).into(), &polygon));
+
+        // All the vertices are inside, but the line leaves and re-enters the polygon.
+        let concave = wkt!(POLYGON((0 0,10 0,10 10,5 5,0 10,0 0)));
+        assert!(!is_within(&wkt!(LINESTRING(1 8,9 8)).into(), &concave));
+        assert!(is_within(&wkt!(LINESTRING(1 2,9 2)).into(), &concave));
+    }
+
+    #[test]
+    fn intersecting() {
+        let polygon: Geometry = wkt!(POLYGON((0 0,10 0,10 10,0 10,0 0))).into();
+        assert!(intersects(&wkt!(POINT(1 1)).into(), &polygon));
+        // on the boundary
+        assert!(intersects(&wkt!(POINT(10 1)).into(), &polygon));
+        assert!(!intersects(&wkt!(POINT(11 1)).into(), &polygon));
+        assert!(intersects(&wkt!(LINESTRING(-1 -1,1 1)).into(), &polygon));
+        assert!(!intersects(&wkt!(LINESTRING(11 0,20 20)).into(), &polygon));
+
+        // One polygon inside of another, with no intersecting edges.
+        let inner: Geometry = wkt!(POLYGON((4 4,6 4,6 6,4 6,4 4))).into();
+        assert!(intersects(&inner, &polygon));
+        assert!(intersects(&polygon, &inner));
+
+        assert!(intersects(
+            &wkt!(POINT(1 1)).into(),
+            &wkt!(POINT(1 1)).into()
+        ));
+        assert!(!intersects(
+            &wkt!(POINT(1 1)).into(),
+            &wkt!(POINT(1 2)).into()
+        ));
+        assert!(intersects(
+            &wkt!(POINT(1 1)).into(),
+            &wkt!(LINESTRING(0 0,2 2)).into()
+        ));
+    }
+}
@@ -0,0 +1,210 @@
+use super::COORD_SCALE_FACTOR;
+use crate::{
+    Geometry, GeometryCollection, LineString, LngLat, MultiLineString, MultiPolygon, Polygon,
+};
+
+/// A closed ring needs at least 4 points, i.e. a triangle, with the first point repeated.
+const MIN_RING_POINTS: usize = 4;
+
+impl Geometry {
+    /// Returns a copy of the geometry with vertices removed by the Douglas-Peucker algorithm, so
+    /// that no removed vertex was further than `epsilon_degrees` from the simplified line.
+    ///
+    /// Distances are measured in planar lng/lat space. Points are unchanged. See
+    /// [`LineString::simplify`] and [`Polygon::simplify`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon_degrees` is negative or NaN.
+    pub fn simplify(&self, epsilon_degrees: f64) -> Geometry {
+        let epsilon_unscaled = epsilon_unscaled(epsilon_degrees);
+        self.simplify_unscaled(epsilon_unscaled)
+    }
+
+    fn simplify_unscaled(&self, epsilon_unscaled: f64) -> Geometry {
+        match self {
+            Geometry::Point(_) | Geometry::MultiPoint(_) => self.clone(),
+            Geometry::LineString(line_string) => {
+                line_string.simplify_unscaled(epsilon_unscaled).into()
+            }
+            Geometry::Polygon(polygon) => polygon.simplify_unscaled(epsilon_unscaled).into(),
+            Geometry::MultiLineString(multi_line_string) => MultiLineString::new(
+                multi_line_string
+                    .line_strings()
+                    .iter()
+                    .map(|line_string| line_string.simplify_unscaled(epsilon_unscaled))
+                    .collect(),
+            )
+            .into(),
+            Geometry::MultiPolygon(multi_polygon) => MultiPolygon::new(
+                multi_polygon
+                    .polygons()
+                    .iter()
+                    .map(|polygon| polygon.simplify_unscaled(epsilon_unscaled))
+                    .collect(),
+            )
+            .into(),
+            Geometry::GeometryCollection(geometry_collection) => GeometryCollection::new(
+                geometry_collection
+                    .geometries()
+                    .iter()
+                    .map(|geometry| geometry.simplify_unscaled(epsilon_unscaled))
+                    .collect(),
+            )
+            .into(),
+        }
+    }
+}
+
+impl Polygon {
+    /// Returns a copy of the polygon with each ring simplified, see [`LineString::simplify`].
+    ///
+    /// A ring which would be left with fewer than 4 points, i.e. no longer a triangle, is kept as
+    /// it was.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon_degrees` is negative or NaN.
+    pub fn simplify(&self, epsilon_degrees: f64) -> Polygon {
+        self.simplify_unscaled(epsilon_unscaled(epsilon_degrees))
+    }
+
+    fn simplify_unscaled(&self, epsilon_unscaled: f64) -> Polygon {
+        Polygon::new(
+            self.rings()
+                .iter()
+                .map(|ring| {
+                    let simplified = ring.simplify_unscaled(epsilon_unscaled);
+                    if simplified.points_len() < MIN_RING_POINTS {
+                        ring.clone()
+                    } else {
+                        simplified
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+impl LineString {
+    /// Returns a copy of the line string with vertices removed by the Douglas-Peucker algorithm,
+    /// so that no removed vertex was further than `epsilon_degrees` from the simplified line.
+    ///
+    /// The first and last points are always kept. Distances are measured in planar lng/lat space.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon_degrees` is negative or NaN.
+    pub fn simplify(&self, epsilon_degrees: f64) -> LineString {
+        self.simplify_unscaled(epsilon_unscaled(epsilon_degrees))
+    }
+
+    fn simplify_unscaled(&self, epsilon_unscaled: f64) -> LineString {
+        let points = self.points();
+        if points.len() < 3 {
+            return self.clone();
+        }
+        let mut keep = vec![false; points.len()];
+        keep[0] = true;
+        keep[points.len() - 1] = true;
+
+        // The (start, end) indices of each span still to be simplified.
+        let mut spans = vec![(0, points.len() - 1)];
+        while let Some((start, end)) = spans.pop() {
+            let farthest = (start + 1..end)
+                .map(|idx| {
+                    let distance = segment_distance(&points[idx], &points[start], &points[end]);
+                    (idx, distance)
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((idx, distance)) = farthest {
+                if distance > epsilon_unscaled {
+                    keep[idx] = true;
+                    spans.push((start, idx));
+                    spans.push((idx, end));
+                }
+            }
+        }
+
+        LineString::new(
+            points
+                .iter()
+                .zip(keep)
+                .filter(|(_point, keep)| *keep)
+                .map(|(point, _keep)| point.clone())
+                .collect(),
+        )
+    }
+}
+
+fn epsilon_unscaled(epsilon_degrees: f64) -> f64 {
+    assert!(
+        epsilon_degrees >= 0.0,
+        "epsilon_degrees must not be negative, but was {epsilon_degrees}"
+    );
+    epsilon_degrees * COORD_SCALE_FACTOR
+}
+
+/// The planar distance, in unscaled units, from `point` to the segment from `start` to `end`.
+fn segment_distance(point: &LngLat, start: &LngLat, end: &LngLat) -> f64 {
+    let (x, y) = (point.lng_unscaled() as f64, point.lat_unscaled() as f64);
+    let (x1, y1) = (start.lng_unscaled() as f64, start.lat_unscaled() as f64);
+    let (x2, y2) = (end.lng_unscaled() as f64, end.lat_unscaled() as f64);
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let length_squared = dx * dx + dy * dy;
+    // How far along the segment the nearest point is, from 0 at `start` to 1 at `end`.
+    let fraction = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((x - x1) * dx + (y - y1) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    (x - (x1 + fraction * dx)).hypot(y - (y1 + fraction * dy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wkt;
+
+    #[test]
+    fn simplify_line_string() {
+        let line_string = wkt!(LINESTRING(0 0,1 0.1,2 -0.1,3 5,4 6,5 7,6 8,7 9,8 9,9 9));
+        assert_eq!(
+            line_string.simplify(1.0),
+            wkt!(LINESTRING(0 0,2 -0.1,3 5,7 9,9 9))
+        );
+        // Only collinear points are within a tolerance of 0.
+        assert_eq!(
+            wkt!(LINESTRING(0 0,1 0,2 0)).simplify(0.0),
+            wkt!(LINESTRING(0 0,2 0))
+        );
+    }
+
+    #[test]
+    fn simplify_polygon() {
+        let polygon = wkt!(POLYGON(
+            (0 0,10 0,10 10,5 10.1,0 10,0 0),
+            (4 4,4.1 4.1,5 4,4 4)
+        ));
+        // The hole would collapse, so is kept as it was.
+        assert_eq!(
+            polygon.simplify(0.5),
+            wkt!(POLYGON((0 0,10 0,10 10,0 10,0 0),(4 4,4.1 4.1,5 4,4 4)))
+        );
+
+        let geometry = Geometry::from(polygon);
+        assert_eq!(
+            geometry.simplify(0.5),
+            Geometry::from(wkt!(POLYGON(
+                (0 0,10 0,10 10,0 10,0 0),
+                (4 4,4.1 4.1,5 4,4 4)
+            )))
+        );
+    }
+
+    #[test]
+    fn simplify_point() {
+        let point: Geometry = wkt!(POINT(1 2)).into();
+        assert_eq!(point.simplify(1.0), point);
+    }
+}
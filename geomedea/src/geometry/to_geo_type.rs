@@ -0,0 +1,119 @@
+//! Converting geometries to their `geo_types` equivalents, e.g. to use the `geo` crate's
+//! algorithms.
+
+use crate::{
+    Geometry, GeometryCollection, LineString, LngLat, MultiLineString, MultiPoint, MultiPolygon,
+    Polygon,
+};
+
+/// Convert a geometry to its `geo_types` equivalent, in degrees.
+pub trait ToGeoType {
+    type GeoType;
+    fn to_geo_type(&self) -> Self::GeoType;
+}
+
+impl ToGeoType for LngLat {
+    type GeoType = geo_types::Point;
+    fn to_geo_type(&self) -> Self::GeoType {
+        geo_types::Point::new(self.lng_degrees(), self.lat_degrees())
+    }
+}
+
+impl ToGeoType for LineString {
+    type GeoType = geo_types::LineString;
+    fn to_geo_type(&self) -> Self::GeoType {
+        self.points()
+            .iter()
+            .map(|point| geo_types::coord! { x: point.lng_degrees(), y: point.lat_degrees() })
+            .collect()
+    }
+}
+
+impl ToGeoType for Polygon {
+    type GeoType = geo_types::Polygon;
+    fn to_geo_type(&self) -> Self::GeoType {
+        let mut rings = self.rings().iter().map(LineString::to_geo_type);
+        let exterior = rings
+            .next()
+            .unwrap_or_else(|| geo_types::LineString::new(vec![]));
+        geo_types::Polygon::new(exterior, rings.collect())
+    }
+}
+
+impl ToGeoType for MultiPoint {
+    type GeoType = geo_types::MultiPoint;
+    fn to_geo_type(&self) -> Self::GeoType {
+        self.points().iter().map(LngLat::to_geo_type).collect()
+    }
+}
+
+impl ToGeoType for MultiLineString {
+    type GeoType = geo_types::MultiLineString;
+    fn to_geo_type(&self) -> Self::GeoType {
+        self.line_strings()
+            .iter()
+            .map(LineString::to_geo_type)
+            .collect()
+    }
+}
+
+impl ToGeoType for MultiPolygon {
+    type GeoType = geo_types::MultiPolygon;
+    fn to_geo_type(&self) -> Self::GeoType {
+        self.polygons().iter().map(Polygon::to_geo_type).collect()
+    }
+}
+
+impl ToGeoType for GeometryCollection {
+    type GeoType = geo_types::GeometryCollection;
+    fn to_geo_type(&self) -> Self::GeoType {
+        self.geometries()
+            .iter()
+            .map(Geometry::to_geo_type)
+            .collect()
+    }
+}
+
+impl ToGeoType for Geometry {
+    type GeoType = geo_types::Geometry;
+    fn to_geo_type(&self) -> Self::GeoType {
+        match self {
+            Geometry::Point(g) => g.to_geo_type().into(),
+            Geometry::LineString(g) => g.to_geo_type().into(),
+            Geometry::Polygon(g) => g.to_geo_type().into(),
+            Geometry::MultiPoint(g) => g.to_geo_type().into(),
+            Geometry::MultiLineString(g) => g.to_geo_type().into(),
+            Geometry::MultiPolygon(g) => g.to_geo_type().into(),
+            Geometry::GeometryCollection(g) => g.to_geo_type().into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wkt;
+
+    #[test]
+    fn to_geo_type() {
+        let polygon =
+            Geometry::from(wkt!(POLYGON((0 0,1 0,1 1,0 0),(0.5 0.25,0.75 0.25,0.75 0.5,0.5 0.25))));
+        assert_eq!(
+            polygon.to_geo_type(),
+            geo_types::Geometry::from(
+                geo_types::wkt!(POLYGON((0. 0.,1. 0.,1. 1.,0. 0.),(0.5 0.25,0.75 0.25,0.75 0.5,0.5 0.25)))
+            )
+        );
+
+        let collection = Geometry::from(GeometryCollection::new(vec![
+            wkt!(POINT(1 2)).into(),
+            wkt!(MULTIPOINT(3 4,5 6)).into(),
+        ]));
+        assert_eq!(
+            collection.to_geo_type(),
+            geo_types::Geometry::from(
+                geo_types::wkt!(GEOMETRYCOLLECTION(POINT(1. 2.),MULTIPOINT(3. 4.,5. 6.)))
+            )
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Checking that polygons are well formed, see [`Geometry::validate`].
+
+use super::predicates::{for_each_polygon, segments_intersect};
+use crate::{Geometry, LineString, LngLat, Polygon};
+
+/// Which way the exterior ring of a polygon winds, see [`Geometry::validate_with_winding`].
+///
+/// Interior rings must wind the other way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindingOrder {
+    /// As required by GeoJSON (RFC 7946).
+    CounterClockwise,
+    Clockwise,
+}
+
+impl WindingOrder {
+    /// The winding order of interior rings, given this is the exterior's.
+    pub(super) fn reversed(self) -> Self {
+        match self {
+            WindingOrder::CounterClockwise => WindingOrder::Clockwise,
+            WindingOrder::Clockwise => WindingOrder::CounterClockwise,
+        }
+    }
+}
+
+/// Why a polygon ring is invalid.
+///
+/// `polygon` is the index of the polygon among every polygon in the geometry, in order, e.g. its
+/// index within a `MultiPolygon`. It's always 0 for a `Polygon`. `ring` is the index of the ring
+/// within that polygon, where 0 is the exterior.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The ring's first and last points differ.
+    UnclosedRing { polygon: usize, ring: usize },
+    /// A closed ring needs at least 4 points, i.e. a triangle, with the first point repeated.
+    TooFewPoints {
+        polygon: usize,
+        ring: usize,
+        points_len: usize,
+    },
+    /// The ring winds the wrong way for the requested [`WindingOrder`].
+    WrongWinding { polygon: usize, ring: usize },
+    /// Two of the ring's edges which aren't consecutive touch or cross.
+    SelfIntersection { polygon: usize, ring: usize },
+}
+
+impl Geometry {
+    /// Check that every ring of every polygon is closed, has at least 4 points, and doesn't
+    /// intersect itself, returning every problem found.
+    ///
+    /// Repeated consecutive points are allowed. Only each ring is checked against itself, not
+    /// against the polygon's other rings. Checking for self intersections compares every pair of a
+    /// ring's edges, so can be slow for rings with very many points.
+    ///
+    /// Geometries without polygons are always valid.
+    pub fn validate(&self) -> std::result::Result<(), Vec<ValidationError>> {
+        self.validate_rings(None)
+    }
+
+    /// Like [`Geometry::validate`], but also checks that exterior rings wind in `winding_order`,
+    /// and interior rings wind the other way.
+    pub fn validate_with_winding(
+        &self,
+        winding_order: WindingOrder,
+    ) -> std::result::Result<(), Vec<ValidationError>> {
+        self.validate_rings(Some(winding_order))
+    }
+
+    fn validate_rings(
+        &self,
+        winding_order: Option<WindingOrder>,
+    ) -> std::result::Result<(), Vec<ValidationError>> {
+        let mut errors = vec![];
+        let mut polygon_idx = 0;
+        for_each_polygon(self, &mut |polygon| {
+            polygon.validate(polygon_idx, winding_order, &mut errors);
+            polygon_idx += 1;
+        });
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Polygon {
+    fn validate(
+        &self,
+        polygon: usize,
+        winding_order: Option<WindingOrder>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for (ring_idx, ring) in self.rings().iter().enumerate() {
+            let points = ring.points();
+            if points.len() < 4 {
+                errors.push(ValidationError::TooFewPoints {
+                    polygon,
+                    ring: ring_idx,
+                    points_len: points.len(),
+                });
+            }
+            if points.first() != points.last() {
+                errors.push(ValidationError::UnclosedRing {
+                    polygon,
+                    ring: ring_idx,
+                });
+                // The remaining checks only make sense for a closed ring.
+                continue;
+            }
+            if let Some(winding_order) = winding_order {
+                let expected = if ring_idx == 0 {
+                    winding_order
+                } else {
+                    winding_order.reversed()
+                };
+                if matches!(ring.winding_order(), Some(found) if found != expected) {
+                    errors.push(ValidationError::WrongWinding {
+                        polygon,
+                        ring: ring_idx,
+                    });
+                }
+            }
+            if is_self_intersecting(points) {
+                errors.push(ValidationError::SelfIntersection {
+                    polygon,
+                    ring: ring_idx,
+                });
+            }
+        }
+    }
+}
+
+impl LineString {
+    /// The winding order of a ring, or None if it isn't closed or has no area.
+    pub(super) fn winding_order(&self) -> Option<WindingOrder> {
+        if self.points().first() != self.points().last() {
+            return None;
+        }
+        // Twice the signed area, by the shoelace formula.
+        let signed_area: i128 = self
+            .points()
+            .windows(2)
+            .map(|edge| {
+                let (a, b) = (&edge[0], &edge[1]);
+                a.lng_unscaled() as i128 * b.lat_unscaled() as i128
+                    - b.lng_unscaled() as i128 * a.lat_unscaled() as i128
+            })
+            .sum();
+        match signed_area.cmp(&0) {
+            std::cmp::Ordering::Greater => Some(WindingOrder::CounterClockwise),
+            std::cmp::Ordering::Less => Some(WindingOrder::Clockwise),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+/// Do any two edges of the closed ring, which aren't consecutive, have a point in common?
+fn is_self_intersecting(ring: &[LngLat]) -> bool {
+    let mut points = ring.to_vec();
+    points.dedup();
+    let edges: Vec<(&LngLat, &LngLat)> =
+        points.windows(2).map(|edge| (&edge[0], &edge[1])).collect();
+    for (i, (a, b)) in edges.iter().enumerate() {
+        for (j, (c, d)) in edges.iter().enumerate().skip(i + 2) {
+            // The first and last edges are consecutive, meeting at the ring's closing point.
+            if i == 0 && j == edges.len() - 1 {
+                continue;
+            }
+            if segments_intersect(a, b, c, d) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wkt;
+
+    #[test]
+    fn valid() {
+        let polygon =
+            Geometry::from(wkt!(POLYGON((0 0,10 0,10 10,0 10,0 0),(2 2,2 4,4 4,4 2,2 2))));
+        assert_eq!(polygon.validate(), Ok(()));
+        assert_eq!(
+            polygon.validate_with_winding(WindingOrder::CounterClockwise),
+            Ok(())
+        );
+        assert_eq!(
+            polygon.validate_with_winding(WindingOrder::Clockwise),
+            Err(vec![
+                ValidationError::WrongWinding {
+                    polygon: 0,
+                    ring: 0
+                },
+                ValidationError::WrongWinding {
+                    polygon: 0,
+                    ring: 1
+                },
+            ])
+        );
+
+        // Repeated points are fine.
+        let repeated = Geometry::from(wkt!(POLYGON((0 0,10 0,10 0,10 10,0 0))));
+        assert_eq!(repeated.validate(), Ok(()));
+
+        let line_string = Geometry::from(wkt!(LINESTRING(0 0,1 1)));
+        assert_eq!(line_string.validate(), Ok(()));
+    }
+
+    #[test]
+    fn invalid() {
+        let unclosed = Geometry::from(wkt!(POLYGON((0 0,10 0,10 10,0 10))));
+        assert_eq!(
+            unclosed.validate(),
+            Err(vec![ValidationError::UnclosedRing {
+                polygon: 0,
+                ring: 0
+            }])
+        );
+
+        let too_few = Geometry::from(wkt!(POLYGON((0 0,10 0,0 0))));
+        assert_eq!(
+            too_few.validate(),
+            Err(vec![ValidationError::TooFewPoints {
+                polygon: 0,
+                ring: 0,
+                points_len: 3
+            }])
+        );
+
+        let bowtie = Geometry::from(wkt!(MULTIPOLYGON(
+            ((0 0,10 0,10 10,0 10,0 0)),
+            ((0 0,10 10,10 0,0 10,0 0))
+        )));
+        assert_eq!(
+            bowtie.validate(),
+            Err(vec![ValidationError::SelfIntersection {
+                polygon: 1,
+                ring: 0
+            }])
+        );
+    }
+}
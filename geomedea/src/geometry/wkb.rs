@@ -0,0 +1,339 @@
+use crate::{
+    Error, Geometry, GeometryCollection, LineString, LngLat, MultiLineString, MultiPoint,
+    MultiPolygon, Polygon, Result,
+};
+
+const EWKB_Z_FLAG: u32 = 0x8000_0000;
+const EWKB_M_FLAG: u32 = 0x4000_0000;
+const EWKB_SRID_FLAG: u32 = 0x2000_0000;
+
+const POINT: u32 = 1;
+const LINE_STRING: u32 = 2;
+const POLYGON: u32 = 3;
+const MULTI_POINT: u32 = 4;
+const MULTI_LINE_STRING: u32 = 5;
+const MULTI_POLYGON: u32 = 6;
+const GEOMETRY_COLLECTION: u32 = 7;
+
+impl Geometry {
+    /// Parses EWKB, the extended WKB used by PostGIS, returning the geometry along with its SRID,
+    /// if it has one.
+    ///
+    /// Plain (ISO) WKB is also accepted. Any Z or M values are discarded.
+    pub fn from_ewkb(bytes: &[u8]) -> Result<(Geometry, Option<u32>)> {
+        let mut reader = WkbReader { bytes };
+        let (geometry, srid) = reader.read_geometry()?;
+        if !reader.bytes.is_empty() {
+            return Err(invalid_wkb(format!(
+                "{} unexpected bytes after the geometry",
+                reader.bytes.len()
+            )));
+        }
+        Ok((geometry, srid))
+    }
+
+    /// Serializes the geometry as little-endian EWKB, embedding `srid` if given.
+    ///
+    /// Without an SRID, this is plain WKB.
+    pub fn to_ewkb(&self, srid: Option<u32>) -> Vec<u8> {
+        let mut output = vec![];
+        write_geometry(&mut output, self, srid);
+        output
+    }
+}
+
+fn invalid_wkb(message: impl Into<String>) -> Error {
+    Error::InvalidWkb(message.into())
+}
+
+struct WkbReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl WkbReader<'_> {
+    fn read_bytes<const N: usize>(&mut self) -> Result<[u8; N]> {
+        if self.bytes.len() < N {
+            return Err(invalid_wkb("unexpected end of input"));
+        }
+        let (bytes, rest) = self.bytes.split_at(N);
+        self.bytes = rest;
+        Ok(bytes.try_into().expect("split at N"))
+    }
+
+    fn read_u32(&mut self, is_big_endian: bool) -> Result<u32> {
+        let bytes = self.read_bytes()?;
+        Ok(if is_big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn read_f64(&mut self, is_big_endian: bool) -> Result<f64> {
+        let bytes = self.read_bytes()?;
+        Ok(if is_big_endian {
+            f64::from_be_bytes(bytes)
+        } else {
+            f64::from_le_bytes(bytes)
+        })
+    }
+
+    fn read_geometry(&mut self) -> Result<(Geometry, Option<u32>)> {
+        let is_big_endian = match self.read_bytes::<1>()? {
+            [0] => true,
+            [1] => false,
+            [other] => return Err(invalid_wkb(format!("invalid byte order {other}"))),
+        };
+        let type_code = self.read_u32(is_big_endian)?;
+        let srid = if type_code & EWKB_SRID_FLAG != 0 {
+            Some(self.read_u32(is_big_endian)?)
+        } else {
+            None
+        };
+
+        // Dimensions beyond x and y are flagged by EWKB, or encoded in the type code by ISO WKB,
+        // e.g. 1001 is a Point with Z.
+        let mut extra_dimensions =
+            (type_code & EWKB_Z_FLAG != 0) as usize + (type_code & EWKB_M_FLAG != 0) as usize;
+        let iso_type_code = type_code & !(EWKB_Z_FLAG | EWKB_M_FLAG | EWKB_SRID_FLAG);
+        extra_dimensions += match iso_type_code / 1000 {
+            0 => 0,
+            1 | 2 => 1,
+            3 => 2,
+            _ => return Err(invalid_wkb(format!("unknown geometry type {type_code}"))),
+        };
+        let coord = Coord {
+            is_big_endian,
+            extra_dimensions,
+        };
+
+        let geometry = match iso_type_code % 1000 {
+            POINT => Geometry::Point(self.read_point(coord)?),
+            LINE_STRING => Geometry::LineString(self.read_line_string(coord)?),
+            POLYGON => Geometry::Polygon(self.read_polygon(coord)?),
+            MULTI_POINT => {
+                let mut points = vec![];
+                for _ in 0..self.read_u32(is_big_endian)? {
+                    let Geometry::Point(point) = self.read_geometry()?.0 else {
+                        return Err(invalid_wkb("MultiPoint contained a non-Point"));
+                    };
+                    points.push(point);
+                }
+                Geometry::MultiPoint(MultiPoint::new(points))
+            }
+            MULTI_LINE_STRING => {
+                let mut line_strings = vec![];
+                for _ in 0..self.read_u32(is_big_endian)? {
+                    let Geometry::LineString(line_string) = self.read_geometry()?.0 else {
+                        return Err(invalid_wkb("MultiLineString contained a non-LineString"));
+                    };
+                    line_strings.push(line_string);
+                }
+                Geometry::MultiLineString(MultiLineString::new(line_strings))
+            }
+            MULTI_POLYGON => {
+                let mut polygons = vec![];
+                for _ in 0..self.read_u32(is_big_endian)? {
+                    let Geometry::Polygon(polygon) = self.read_geometry()?.0 else {
+                        return Err(invalid_wkb("MultiPolygon contained a non-Polygon"));
+                    };
+                    polygons.push(polygon);
+                }
+                Geometry::MultiPolygon(MultiPolygon::new(polygons))
+            }
+            GEOMETRY_COLLECTION => {
+                let mut geometries = vec![];
+                for _ in 0..self.read_u32(is_big_endian)? {
+                    geometries.push(self.read_geometry()?.0);
+                }
+                Geometry::GeometryCollection(GeometryCollection::new(geometries))
+            }
+            _ => return Err(invalid_wkb(format!("unknown geometry type {type_code}"))),
+        };
+        Ok((geometry, srid))
+    }
+
+    fn read_point(&mut self, coord: Coord) -> Result<LngLat> {
+        let lng = self.read_f64(coord.is_big_endian)?;
+        let lat = self.read_f64(coord.is_big_endian)?;
+        for _ in 0..coord.extra_dimensions {
+            self.read_f64(coord.is_big_endian)?;
+        }
+        if lng.is_nan() && lat.is_nan() {
+            return Err(invalid_wkb("empty points aren't supported"));
+        }
+        LngLat::try_degrees(lng, lat)
+    }
+
+    fn read_line_string(&mut self, coord: Coord) -> Result<LineString> {
+        let point_count = self.read_u32(coord.is_big_endian)?;
+        let mut points = vec![];
+        for _ in 0..point_count {
+            points.push(self.read_point(coord)?);
+        }
+        Ok(LineString::new(points))
+    }
+
+    fn read_polygon(&mut self, coord: Coord) -> Result<Polygon> {
+        let ring_count = self.read_u32(coord.is_big_endian)?;
+        let mut rings = vec![];
+        for _ in 0..ring_count {
+            rings.push(self.read_line_string(coord)?);
+        }
+        Ok(Polygon::new(rings))
+    }
+}
+
+/// How the coordinates of a geometry are encoded.
+#[derive(Clone, Copy)]
+struct Coord {
+    is_big_endian: bool,
+    /// e.g. Z or M, which are skipped
+    extra_dimensions: usize,
+}
+
+fn write_u32(output: &mut Vec<u8>, value: u32) {
+    output.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_len(output: &mut Vec<u8>, len: usize) {
+    write_u32(
+        output,
+        u32::try_from(len).expect("WKB lengths fit in a u32"),
+    );
+}
+
+fn write_point(output: &mut Vec<u8>, point: &LngLat) {
+    output.extend_from_slice(&point.lng_degrees().to_le_bytes());
+    output.extend_from_slice(&point.lat_degrees().to_le_bytes());
+}
+
+fn write_line_string(output: &mut Vec<u8>, line_string: &LineString) {
+    write_len(output, line_string.points_len());
+    for point in line_string.points() {
+        write_point(output, point);
+    }
+}
+
+fn write_polygon(output: &mut Vec<u8>, polygon: &Polygon) {
+    write_len(output, polygon.rings().len());
+    for ring in polygon.rings() {
+        write_line_string(output, ring);
+    }
+}
+
+fn write_header(output: &mut Vec<u8>, type_code: u32, srid: Option<u32>) {
+    // little-endian
+    output.push(1);
+    match srid {
+        Some(srid) => {
+            write_u32(output, type_code | EWKB_SRID_FLAG);
+            write_u32(output, srid);
+        }
+        None => write_u32(output, type_code),
+    }
+}
+
+/// Only the outermost geometry has an SRID, nested geometries share it.
+fn write_geometry(output: &mut Vec<u8>, geometry: &Geometry, srid: Option<u32>) {
+    let type_code = match geometry {
+        Geometry::Point(_) => POINT,
+        Geometry::LineString(_) => LINE_STRING,
+        Geometry::Polygon(_) => POLYGON,
+        Geometry::MultiPoint(_) => MULTI_POINT,
+        Geometry::MultiLineString(_) => MULTI_LINE_STRING,
+        Geometry::MultiPolygon(_) => MULTI_POLYGON,
+        Geometry::GeometryCollection(_) => GEOMETRY_COLLECTION,
+    };
+    write_header(output, type_code, srid);
+
+    match geometry {
+        Geometry::Point(point) => write_point(output, point),
+        Geometry::LineString(line_string) => write_line_string(output, line_string),
+        Geometry::Polygon(polygon) => write_polygon(output, polygon),
+        Geometry::MultiPoint(multi_point) => {
+            write_len(output, multi_point.points().len());
+            for point in multi_point.points() {
+                write_header(output, POINT, None);
+                write_point(output, point);
+            }
+        }
+        Geometry::MultiLineString(multi_line_string) => {
+            write_len(output, multi_line_string.line_strings().len());
+            for line_string in multi_line_string.line_strings() {
+                write_header(output, LINE_STRING, None);
+                write_line_string(output, line_string);
+            }
+        }
+        Geometry::MultiPolygon(multi_polygon) => {
+            write_len(output, multi_polygon.polygons().len());
+            for polygon in multi_polygon.polygons() {
+                write_header(output, POLYGON, None);
+                write_polygon(output, polygon);
+            }
+        }
+        Geometry::GeometryCollection(geometry_collection) => {
+            write_len(output, geometry_collection.geometries().len());
+            for geometry in geometry_collection.geometries() {
+                write_geometry(output, geometry, None);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wkt;
+
+    #[test]
+    fn point_with_srid() {
+        let point: Geometry = wkt!(POINT(1 2)).into();
+        let ewkb = point.to_ewkb(Some(4326));
+        // As produced by PostGIS's `ST_AsEWKB('SRID=4326;POINT(1 2)')`
+        let expected = [
+            0x01, 0x01, 0x00, 0x00, 0x20, 0xe6, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0xf0, 0x3f, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+        ];
+        assert_eq!(ewkb, expected);
+        assert_eq!(Geometry::from_ewkb(&ewkb).unwrap(), (point, Some(4326)));
+    }
+
+    #[test]
+    fn round_trip_without_srid() {
+        let geometries: Vec<Geometry> = vec![
+            wkt!(LINESTRING(1 2,3 4)).into(),
+            wkt!(POLYGON((0 0,1 0,1 1,0 0),(0.1 0.1,0.2 0.1,0.2 0.2,0.1 0.1))).into(),
+            wkt!(MULTIPOINT(1 2,3 4)).into(),
+            wkt!(MULTILINESTRING((1 2,3 4),(5 6,7 8))).into(),
+            wkt!(MULTIPOLYGON(((0 0,1 0,1 1,0 0)),((2 2,3 2,3 3,2 2)))).into(),
+            GeometryCollection::new(vec![
+                wkt!(POINT(1 2)).into(),
+                wkt!(LINESTRING(1 2,3 4)).into(),
+            ])
+            .into(),
+        ];
+        for geometry in geometries {
+            let wkb = geometry.to_ewkb(None);
+            assert_eq!(Geometry::from_ewkb(&wkb).unwrap(), (geometry, None));
+        }
+    }
+
+    #[test]
+    fn big_endian_with_z() {
+        // POINT Z (1 2 3), with the EWKB Z flag
+        let mut ewkb = vec![0x00];
+        ewkb.extend_from_slice(&(POINT | EWKB_Z_FLAG).to_be_bytes());
+        for value in [1.0f64, 2.0, 3.0] {
+            ewkb.extend_from_slice(&value.to_be_bytes());
+        }
+        let (geometry, srid) = Geometry::from_ewkb(&ewkb).unwrap();
+        assert_eq!(geometry, wkt!(POINT(1 2)).into());
+        assert_eq!(srid, None);
+
+        assert!(matches!(
+            Geometry::from_ewkb(&ewkb[..ewkb.len() - 1]),
+            Err(Error::InvalidWkb(_))
+        ));
+    }
+}
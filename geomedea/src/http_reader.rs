@@ -1,109 +1,490 @@
 use crate::feature::Feature;
-use crate::io::async_ruszstd::MyRuzstdDecoder;
-use crate::packed_r_tree::{Node, PackedRTree, PackedRTreeHttpReader};
-use crate::{deserialize_from, serialized_size, Bounds, Header, Result, DEFAULT_PAGE_SIZE_GOAL};
+use crate::format::verify_page_checksum;
+use crate::geometry::predicates::intersects;
+use crate::geometry::{Bounded, COORD_PRECISION_NANOS};
+use crate::io::{decode_page, read_untrusted_len};
+use crate::packed_r_tree::{
+    Node, PackedRTree, PackedRTreeHttpReader, DEFAULT_BRANCHING_FACTOR,
+    DEFAULT_COMBINE_REQUEST_THRESHOLD,
+};
+use crate::reader::corrupt_feature;
+use crate::retry::FetchStats;
+use crate::{
+    deserialize_from, serialized_size, Bounds, Compression, Error, FileInfo, Geometry, Header,
+    LngLat, MaybeSend, MaybeSendBoxStream, Result, RetryPolicy, DEFAULT_PAGE_SIZE_GOAL,
+};
 use crate::{FeatureLocation, PageHeader};
 use bytes::{Bytes, BytesMut};
-use futures_util::{Stream, StreamExt};
+use futures_util::task::AtomicWaker;
+use futures_util::{Stream, StreamExt, TryStreamExt};
 use std::collections::VecDeque;
 use std::ops::Range;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use streaming_http_range_client::{HttpClient, HttpRange};
 
-use crate::asyncio::{AsyncRead, AsyncReadExt, BufReader, Take};
+use crate::asyncio::{AsyncRead, AsyncReadExt};
+
+/// Tunes the tradeoff an [`HttpReader`] makes between the number of requests and the number of
+/// bytes transferred, e.g. a slow origin favors fewer, larger requests, whereas a CDN might favor
+/// transferring fewer bytes.
+///
+/// The defaults are a reasonable starting point, see [`HttpReader::open_with_config`].
+#[derive(Debug, Clone)]
+pub struct HttpReaderConfig {
+    /// How many levels of the index, starting from the root, to fetch when opening the file. They're
+    /// kept for the lifetime of the reader, so no query requests them again. Defaults to 3.
+    pub index_prefetch_levels: u32,
+    /// Ranges of index nodes closer together than this many bytes are fetched by a single request.
+    /// Defaults to 16,000.
+    pub combine_request_threshold: u64,
+    /// See [`HttpReader::set_overfetch`]. Defaults to the default page size goal.
+    pub overfetch: u64,
+    /// See [`HttpReader::set_concurrent_requests`]. Defaults to 1.
+    pub concurrent_requests: usize,
+    /// How failed range requests are retried. Use [`RetryPolicy::none`] to fail on the first
+    /// error.
+    pub retry_policy: RetryPolicy,
+}
+
+impl Default for HttpReaderConfig {
+    fn default() -> Self {
+        Self {
+            index_prefetch_levels: 3,
+            combine_request_threshold: DEFAULT_COMBINE_REQUEST_THRESHOLD,
+            overfetch: DEFAULT_PAGE_SIZE_GOAL,
+            concurrent_requests: 1,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+}
 
+/// Reads a remote file using HTTP range requests.
+///
+/// The header is read once, when the file is opened, so a single reader can serve many queries,
+/// e.g. one per viewport while panning a map. Each query makes its requests with its own
+/// [`HttpClient::split_off`] of the reader's client, which shares its connection pool, so
+/// connections are reused between queries, and queries can run concurrently.
+///
+/// The server must return the file's bytes as they are. Ranges of a compressed response, e.g.
+/// from a CDN applying `Content-Encoding: gzip`, don't line up with the file, so opening a file
+/// whose header was compressed this way returns [`Error::UnexpectedContentEncoding`].
+///
+/// [`HttpClient`] can't send an `Accept-Encoding: identity` header, nor expose the headers of its
+/// responses, so this is detected after the fact, see [`HttpReader::open`].
+///
+/// For the same reason, requests can't carry any other headers either, e.g. for authentication
+/// or signing, so files which require them, like those in a private object store, can't be read.
+///
+/// Nothing is cached beyond a single reader, e.g. on disk between runs, since a cached range
+/// can't be validated without the response's `ETag`.
+///
+/// Each reader opens its own connections, even to the same host as another reader, since an
+/// [`HttpClient`] can only be created for a single url, and always with its own connection pool.
 #[derive(Debug)]
 pub struct HttpReader {
+    /// Each query splits off this client, taking whatever it has buffered, so the prefetched
+    /// index is copied out of it, into `prefetched_index`.
     http_client: HttpClient,
     header: Header,
+    /// The top of the index, fetched when the file was opened, which every query reads nodes from
+    /// without making a request, see [`HttpReaderConfig::index_prefetch_levels`].
+    prefetched_index: Bytes,
+    /// Where the dictionary is, until it's fetched, see [`HttpReader::open_header_only`].
+    dictionary_range: Option<Range<u64>>,
+    config: HttpReaderConfig,
+    fetch_stats: FetchStats,
 }
 
 impl HttpReader {
     #[cfg(feature = "writer")]
     pub async fn test_reader(data: &[u8]) -> Result<Self> {
         let http_client = HttpClient::test_client(data);
-        Self::new(http_client).await
+        Self::new(http_client, HttpReaderConfig::default()).await
     }
 
+    /// If the file can't be read because the server compressed its response, returns
+    /// [`Error::UnexpectedContentEncoding`] with the response's likely `Content-Encoding`, which
+    /// is checked by a separate request, only once opening has failed.
     pub async fn open(url: &str) -> Result<Self> {
-        let http_client = HttpClient::new(url);
-        Self::new(http_client).await
+        Self::open_with_config(url, HttpReaderConfig::default()).await
     }
 
-    async fn new(mut http_client: HttpClient) -> Result<Self> {
-        trace!("starting: opening http reader, reading header");
+    /// Like [`HttpReader::open`], but with non-default tuning.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.concurrent_requests` is 0.
+    pub async fn open_with_config(url: &str, config: HttpReaderConfig) -> Result<Self> {
+        let http_client = HttpClient::new(url);
+        let retry_policy = config.retry_policy.clone();
+        match Self::new(http_client, config).await {
+            Err(error) => Err(check_content_encoding(url, &retry_policy, error).await),
+            ok => ok,
+        }
+    }
 
-        // TODO: Figure out how big this should be
-        fn estimate_index_size(levels: u32) -> usize {
-            let nodes: usize = (0..levels).map(|level| 16usize.pow(level)).sum();
-            nodes * Node::serialized_size()
+    /// Like [`HttpReader::open`], but only fetches the header, e.g. to read the feature count and
+    /// extent of many files, without fetching the top of their index in anticipation of a query.
+    ///
+    /// Nor is the zstd dictionary fetched (if any), so [`Header`]'s dictionary is `None`, see
+    /// [`Writer::set_zstd_dictionary`](crate::Writer::set_zstd_dictionary).
+    ///
+    /// The reader can still be queried, but since none of the index was prefetched, the first
+    /// query makes more requests, including for the dictionary.
+    pub async fn open_header_only(url: &str) -> Result<Self> {
+        let config = header_only_config();
+        let retry_policy = config.retry_policy.clone();
+        match Self::read_header(HttpClient::new(url), config).await {
+            Err(error) => Err(check_content_encoding(url, &retry_policy, error).await),
+            ok => ok,
         }
+    }
 
-        let overfetch_by = estimate_index_size(3) as u64;
-        http_client
-            .set_range(0..(Self::header_size() + overfetch_by))
-            .await?;
-        let mut header_bytes = vec![0u8; Self::header_size() as usize];
+    async fn new(http_client: HttpClient, config: HttpReaderConfig) -> Result<Self> {
+        let mut reader = Self::read_header(http_client, config).await?;
+        reader.fetch_dictionary().await?;
+        reader.prefetch_index().await?;
+        Ok(reader)
+    }
 
-        http_client.read_exact(&mut header_bytes).await?;
-        let header = deserialize_from(&*header_bytes)?;
+    /// Opens the file without fetching its dictionary, or any of its index.
+    async fn read_header(mut http_client: HttpClient, config: HttpReaderConfig) -> Result<Self> {
+        trace!("starting: opening http reader, reading header");
+        assert!(
+            config.concurrent_requests > 0,
+            "concurrent_requests must be positive"
+        );
+
+        // We don't know the size of the index until we've read the header, so guess that it's
+        // complete with the default branching factor for the initial request.
+        // No index is deep enough that its lower levels would overflow.
+        let overfetch_by: u64 = (0..config.index_prefetch_levels)
+            .map_while(|level| (DEFAULT_BRANCHING_FACTOR as u64).checked_pow(level))
+            .fold(0, u64::saturating_add)
+            .saturating_mul(Node::serialized_size() as u64);
+        // The flags which follow the magic and format version say how long the rest of the header
+        // is. Only the header is read, leaving the client at the start of the index, or of the
+        // dictionary, if any.
+        let fetch_stats = FetchStats::default();
+        let mut header_bytes = vec![0u8; Header::PREFIX_SIZE as usize];
+        config
+            .retry_policy
+            .read_first_range(
+                &mut http_client,
+                0..Header::max_serialized_size().saturating_add(overfetch_by),
+                &mut header_bytes,
+                &fetch_stats,
+            )
+            .await?;
+        let header_size = Header::serialized_size_from_prefix(&header_bytes)?;
+        header_bytes.resize(header_size as usize, 0);
+        config
+            .retry_policy
+            .read_range(
+                &mut http_client,
+                HttpRange::Range(Header::PREFIX_SIZE..header_size),
+                &mut header_bytes[Header::PREFIX_SIZE as usize..],
+                &fetch_stats,
+            )
+            .await?;
+        let (header, dictionary_range) = Header::read_without_dictionary(&*header_bytes)?;
         Ok(Self {
             http_client,
             header,
+            prefetched_index: Bytes::new(),
+            dictionary_range,
+            config,
+            fetch_stats,
         })
     }
 
+    /// Fetches the dictionary, unless it's already been fetched, or the file doesn't have one.
+    async fn fetch_dictionary(&mut self) -> Result<()> {
+        let Some(dictionary_range) = self.dictionary_range.clone() else {
+            return Ok(());
+        };
+        let mut dictionary = vec![0u8; (dictionary_range.end - dictionary_range.start) as usize];
+        self.config
+            .retry_policy
+            .read_range(
+                &mut self.http_client,
+                HttpRange::Range(dictionary_range),
+                &mut dictionary,
+                &self.fetch_stats,
+            )
+            .await?;
+        self.header.dictionary = Some(Arc::from(dictionary));
+        self.dictionary_range = None;
+        Ok(())
+    }
+
+    /// Now that we know the actual shape of the index, read all of its top levels, which are
+    /// usually already buffered by the request which read the header.
+    async fn prefetch_index(&mut self) -> Result<()> {
+        let index_start = self.header_size();
+        let prefetch_size = self.index_prefetch_size();
+        if prefetch_size == 0 {
+            return Ok(());
+        }
+        let prefetch_range = HttpRange::Range(index_start..index_start + prefetch_size);
+        if !self.http_client.contains(&prefetch_range) {
+            debug!("re-requesting top {prefetch_size} bytes of the index");
+        }
+        let mut prefetched_index = vec![0u8; prefetch_size as usize];
+        self.config
+            .retry_policy
+            .read_range(
+                &mut self.http_client,
+                prefetch_range,
+                &mut prefetched_index,
+                &self.fetch_stats,
+            )
+            .await?;
+        self.prefetched_index = Bytes::from(prefetched_index);
+        Ok(())
+    }
+
+    /// The number of bytes prefetched from the start of the index when opening the file.
+    pub fn index_prefetch_size(&self) -> u64 {
+        index_prefetch_size(&self.header, self.config.index_prefetch_levels)
+    }
+
+    pub fn config(&self) -> &HttpReaderConfig {
+        &self.config
+    }
+
+    pub fn concurrent_requests(&self) -> usize {
+        self.config.concurrent_requests
+    }
+
+    /// The maximum number of requests for feature pages which [`HttpReader::select_bbox`] makes
+    /// at once. Features are still yielded in order.
+    ///
+    /// Features which are near each other in the file are fetched by a single request, so
+    /// concurrent requests help most when the selected features are scattered across the file.
+    ///
+    /// Defaults to 1, i.e. pages are fetched sequentially.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `concurrent_requests` is 0.
+    pub fn set_concurrent_requests(&mut self, concurrent_requests: usize) {
+        assert!(
+            concurrent_requests > 0,
+            "concurrent_requests must be positive"
+        );
+        self.config.concurrent_requests = concurrent_requests;
+    }
+
+    pub fn overfetch(&self) -> u64 {
+        self.config.overfetch
+    }
+
+    /// When selecting features, how many bytes past a feature's page to request, in anticipation
+    /// of reading nearby features. Features within twice this distance of each other are fetched
+    /// by the same request.
+    ///
+    /// Larger values mean fewer requests, but more unused bytes. Defaults to the default page
+    /// size goal.
+    pub fn set_overfetch(&mut self, overfetch: u64) {
+        self.config.overfetch = overfetch;
+    }
+
     // TODO: usize?
-    fn header_size() -> u64 {
-        let header = Header::default();
-        serialized_size(&header)
-            .expect("calculation of serialization size of default header should succeed")
+    fn header_size(&self) -> u64 {
+        // The dictionary is last, so until it's fetched, the header ends with it.
+        if let Some(dictionary_range) = &self.dictionary_range {
+            return dictionary_range.end;
+        }
+        serialized_size(&self.header)
+            .expect("calculation of serialization size of header should succeed")
     }
 
     pub async fn select_all(&mut self) -> Result<FeatureStream> {
+        self.fetch_dictionary().await?;
         let mut http_client = self.http_client.split_off();
 
         let features_count = self.header.feature_count;
         if features_count == 0 {
             warn!("features_count == 0");
         }
-        let index_size = PackedRTree::new(features_count).index_size();
+        let index_size =
+            PackedRTree::new(features_count, self.header.branching_factor).index_size();
 
         // fast forward over index, and request all the feature data.
-        let feature_base = Self::header_size() + index_size;
+        let feature_base = self.header_size() + index_size;
         debug!("features_count: {features_count:?} index_size: {index_size:?} feature_base: {feature_base:?}");
-        http_client
-            .seek_to_range(HttpRange::RangeFrom(feature_base..))
+        self.config
+            .retry_policy
+            .seek_to_range(
+                &mut http_client,
+                HttpRange::RangeFrom(feature_base..),
+                &self.fetch_stats,
+            )
             .await?;
 
-        let select_all = SelectAll::new(features_count);
-        let stream = Selection::SelectAll(select_all)
-            .into_feature_buffer_stream(self.header.is_compressed, http_client);
-        Ok(FeatureStream::new(stream))
+        let select_all = SelectAll::new(features_count, feature_base);
+        let stream = Selection::SelectAll(select_all).into_feature_buffer_stream(
+            &self.header,
+            http_client,
+            self.config.retry_policy.clone(),
+            self.fetch_stats.clone(),
+        );
+        Ok(FeatureStream::new(
+            stream,
+            self.header.coord_precision_nanos,
+        ))
     }
 
     pub async fn select_bbox(&mut self, bounds: &Bounds) -> Result<FeatureStream> {
-        let http_client = self.http_client.split_off();
+        self.select_bboxes(std::slice::from_ref(bounds)).await
+    }
 
+    /// Select the features which intersect any of `bounds`, e.g. several disjoint viewports.
+    ///
+    /// The index is searched once for all of them, so index ranges they share are only fetched
+    /// once, and a feature which intersects more than one is only yielded once.
+    pub async fn select_bboxes(&mut self, bounds: &[Bounds]) -> Result<FeatureStream> {
+        self.fetch_dictionary().await?;
         let feature_count = self.header.feature_count;
         if feature_count == 0 {
             warn!("features_count == 0");
         }
         debug!("feature_count: {feature_count:?}");
-        let index_starting_offset = Self::header_size();
+        let index_starting_offset = self.header_size();
+
+        let mut index_reader = self.index_reader();
+        // Pages can only be read forwards, so the whole index search finishes before any features
+        // are fetched, so that the locations can be sorted.
+        let mut feature_locations = index_reader
+            .select_bboxes(bounds)
+            .try_collect::<Vec<_>>()
+            .await?;
+        feature_locations.sort();
+        feature_locations.dedup();
+        let feature_start = index_starting_offset + index_reader.tree().index_size();
+        let http_client = index_reader.into_http_client();
+        debug!("feature_locations: {feature_locations:?}");
+        let feature_locations = futures_util::stream::iter(feature_locations.into_iter().map(Ok));
+
+        let select_bbox = SelectBbox::new(feature_start, feature_locations, self.config.overfetch);
+        if self.config.concurrent_requests > 1 {
+            let stream = select_bbox.into_concurrent_feature_buffer_stream(
+                &self.header,
+                http_client,
+                self.config.concurrent_requests,
+                self.config.retry_policy.clone(),
+                self.fetch_stats.clone(),
+            );
+            return Ok(FeatureStream::new(
+                stream,
+                self.header.coord_precision_nanos,
+            ));
+        }
+        let stream = Selection::SelectBbox(select_bbox).into_feature_buffer_stream(
+            &self.header,
+            http_client,
+            self.config.retry_policy.clone(),
+            self.fetch_stats.clone(),
+        );
+        Ok(FeatureStream::new(
+            stream,
+            self.header.coord_precision_nanos,
+        ))
+    }
+
+    /// Select the features which intersect `geometry`.
+    ///
+    /// Like [`Reader::select_within`](crate::Reader::select_within), this first uses the spatial
+    /// index to fetch every feature whose bounding box intersects the geometry's bounding box, and
+    /// then filters out those that don't actually intersect the geometry. Every candidate must
+    /// still be downloaded.
+    pub async fn select_intersecting(&mut self, geometry: &Geometry) -> Result<FeatureStream> {
+        let feature_stream = self.select_bbox(&geometry.bounds()).await?;
+        Ok(feature_stream.filter_intersecting(geometry.clone()))
+    }
 
-        let mut index_reader =
-            PackedRTreeHttpReader::new(feature_count, http_client, index_starting_offset);
-        let feature_locations = index_reader.select_bbox(bounds);
+    /// Select the (up to) `k` features nearest to `point`, closest first.
+    ///
+    /// Distance is measured to each feature's bounding box, in planar lng/lat space, so this is
+    /// exact for points but only approximate for other geometries.
+    pub async fn select_nearest(&mut self, point: &LngLat, k: usize) -> Result<Vec<Feature>> {
+        self.fetch_dictionary().await?;
+        let index_starting_offset = self.header_size();
+        let mut index_reader = self.index_reader();
+        let nearest = index_reader.nearest(point, k).await?;
+        debug!("nearest: {nearest:?}");
         let feature_start = index_starting_offset + index_reader.tree().index_size();
         let http_client = index_reader.into_http_client();
-        // debug!("feature_locations: {feature_locations:?}");
 
-        let select_bbox = SelectBbox::new(feature_start, feature_locations);
-        let stream = Selection::SelectBbox(select_bbox)
-            .into_feature_buffer_stream(self.header.is_compressed, http_client);
-        Ok(FeatureStream::new(stream))
+        // Features are fetched in file order, then restored to the order of nearness.
+        let mut locations: Vec<(usize, FeatureLocation)> =
+            nearest.into_iter().enumerate().collect();
+        locations.sort_by_key(|(_rank, location)| {
+            (location.page_starting_offset, location.feature_offset)
+        });
+        let ranks: Vec<usize> = locations.iter().map(|(rank, _location)| *rank).collect();
+
+        let feature_locations =
+            futures_util::stream::iter(locations.into_iter().map(|(_rank, location)| Ok(location)));
+        let select_bbox = SelectBbox::new(feature_start, feature_locations, self.config.overfetch);
+        let stream = Selection::SelectBbox(select_bbox).into_feature_buffer_stream(
+            &self.header,
+            http_client,
+            self.config.retry_policy.clone(),
+            self.fetch_stats.clone(),
+        );
+        let mut feature_stream = FeatureStream::new(stream, self.header.coord_precision_nanos);
+
+        let expected = ranks.len() as u64;
+        let mut features = Vec::with_capacity(ranks.len());
+        for rank in ranks {
+            // A corrupt index can point past the last feature.
+            let Some(feature) = feature_stream.next().await.transpose()? else {
+                return Err(Error::FeatureCountMismatch {
+                    found: features.len() as u64,
+                    expected,
+                });
+            };
+            features.push((rank, feature));
+        }
+        features.sort_by_key(|(rank, _feature)| *rank);
+        Ok(features
+            .into_iter()
+            .map(|(_rank, feature)| feature)
+            .collect())
+    }
+
+    /// The number of features whose bounds intersect `bounds`, i.e. how many features
+    /// [`HttpReader::select_bbox`] would yield.
+    ///
+    /// Only the index is read, so no feature data is fetched.
+    pub async fn count_bbox(&mut self, bounds: &Bounds) -> Result<u64> {
+        let mut index_reader = self.index_reader();
+        let mut feature_locations = std::pin::pin!(index_reader.select_bbox(bounds));
+        let mut count = 0;
+        while let Some(feature_location) = feature_locations.next().await {
+            feature_location?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn index_reader(&mut self) -> PackedRTreeHttpReader {
+        let mut index_reader = PackedRTreeHttpReader::new(
+            self.header.feature_count,
+            self.header.branching_factor,
+            self.http_client.split_off(),
+            self.header_size(),
+        );
+        index_reader.set_combine_request_threshold(self.config.combine_request_threshold);
+        index_reader.set_retry_policy(self.config.retry_policy.clone());
+        index_reader.set_fetch_stats(self.fetch_stats.clone());
+        index_reader.set_prefetched_index(self.prefetched_index.clone());
+        index_reader
     }
 
     pub fn http_client(&self) -> &HttpClient {
@@ -112,35 +493,157 @@ impl HttpReader {
     pub fn header(&self) -> &Header {
         &self.header
     }
+
+    /// The number of range requests made since the file was opened, including by opening it, and
+    /// by all of its queries so far, including retries.
+    ///
+    /// Reads of bytes which an earlier request already fetched don't make a request.
+    pub fn request_count(&self) -> u64 {
+        self.fetch_stats.request_count()
+    }
+
+    /// The number of bytes requested since the file was opened, like [`HttpReader::request_count`].
+    ///
+    /// This is the length of each range requested, which may extend past the end of the file.
+    /// [`HttpReader::select_all`] streams the rest of the file with a single request, so its
+    /// bytes are only counted as they're read.
+    pub fn bytes_fetched(&self) -> u64 {
+        self.fetch_stats.bytes_fetched()
+    }
+
+    /// Summarizes the file using only its header.
+    pub fn info(&self) -> FileInfo {
+        FileInfo::new(self.header.clone())
+    }
+}
+
+/// If opening `url` failed because it isn't a geomedea file, checks whether that's because the
+/// server compressed its response, in which case an [`Error::UnexpectedContentEncoding`] is
+/// returned instead of `error`.
+async fn check_content_encoding(url: &str, retry_policy: &RetryPolicy, error: Error) -> Error {
+    if !matches!(
+        error,
+        Error::UnsupportedFormat { found: None, .. } | Error::UnexpectedContentEncoding(_)
+    ) {
+        return error;
+    }
+    match probe_content_encoding(HttpClient::new(url), retry_policy).await {
+        Ok(Some(content_encoding)) => {
+            Error::UnexpectedContentEncoding(content_encoding.to_string())
+        }
+        Ok(None) => error,
+        Err(probe_error) => {
+            debug!("unable to check the Content-Encoding of {url}: {probe_error}");
+            error
+        }
+    }
+}
+
+/// The `Content-Encoding` which the server appears to apply to a range request for the start of
+/// the file, if any.
+///
+/// [`HttpClient`] doesn't expose the response's headers, so only the magic number is requested,
+/// and a compressed response is recognized by its own magic number, or otherwise by being longer
+/// than requested, e.g. because the server compressed the whole file rather than the range.
+/// Without a magic number, it's assumed to be brotli, the common encoding which has none.
+async fn probe_content_encoding(
+    mut http_client: HttpClient,
+    retry_policy: &RetryPolicy,
+) -> Result<Option<&'static str>> {
+    let mut magic = [0u8; crate::HEADER_MAGIC.len()];
+    retry_policy
+        .read_first_range(
+            &mut http_client,
+            0..magic.len() as u64,
+            &mut magic,
+            &FetchStats::default(),
+        )
+        .await?;
+    if let Some(content_encoding) = crate::detect_content_encoding(&magic) {
+        return Ok(Some(content_encoding));
+    }
+    let mut past_range = [0u8; 1];
+    if http_client.read(&mut past_range).await? > 0 {
+        return Ok(Some("br"));
+    }
+    Ok(None)
+}
+
+fn header_only_config() -> HttpReaderConfig {
+    HttpReaderConfig {
+        index_prefetch_levels: 0,
+        ..Default::default()
+    }
+}
+
+/// The size of the top `levels` levels of the index, or of the whole index if it's not that deep.
+fn index_prefetch_size(header: &Header, levels: u32) -> u64 {
+    PackedRTree::new(header.feature_count, header.branching_factor).top_levels_size(levels as usize)
 }
 
 struct SelectAll {
     features_left_in_document: u64,
+    /// Where the feature data starts within the file.
+    feature_base: u64,
+    /// Where the page after the current one starts within the file.
+    next_page_start: u64,
+    /// Where the next feature will be read from.
+    location: FeatureLocation,
+    pages_opened: u64,
 }
 
 struct SelectBbox {
     feature_start: u64,
     current_batch: VecDeque<FeatureLocation>,
-    feature_locations: Box<dyn Stream<Item = Result<FeatureLocation>> + Unpin>,
+    feature_locations: MaybeSendBoxStream<'static, Result<FeatureLocation>>,
     first_item_of_next_batch: Option<FeatureLocation>,
+    overfetch: u64,
 }
 
 impl SelectBbox {
     fn new(
         feature_start: u64,
-        feature_locations: impl Stream<Item = Result<FeatureLocation>> + 'static,
+        feature_locations: impl Stream<Item = Result<FeatureLocation>> + MaybeSend + 'static,
+        overfetch: u64,
     ) -> Self {
         Self {
-            feature_locations: Box::new(Box::pin(feature_locations)),
+            feature_locations: crate::box_stream(feature_locations),
             feature_start,
             current_batch: VecDeque::new(),
             first_item_of_next_batch: None,
+            overfetch,
         }
     }
 
     /// Returns the location of the Feature as well as a suggested byte range within the Feature buffer
     /// if a request needs to be made.
     async fn next_feature_location(&mut self) -> Result<Option<(FeatureLocation, Range<u64>)>> {
+        self.fill_batch().await?;
+        let Some(end_of_last_in_batch) = self.current_batch.back().map(|last_in_batch| {
+            last_in_batch.page_starting_offset + (self.overfetch as f64 * 1.1) as u64
+        }) else {
+            return Ok(None);
+        };
+        let next = self
+            .current_batch
+            .pop_front()
+            .expect("if there is a back, there is also a front");
+
+        let start_of_batch = next.page_starting_offset;
+        Ok(Some((next, start_of_batch..end_of_last_in_batch)))
+    }
+
+    /// The locations of the next batch of features, which are near enough to each other to be
+    /// fetched by a single request.
+    async fn next_batch(&mut self) -> Result<Option<Vec<FeatureLocation>>> {
+        self.fill_batch().await?;
+        if self.current_batch.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(self.current_batch.drain(..).collect()))
+    }
+
+    async fn fill_batch(&mut self) -> Result<()> {
         if self.current_batch.is_empty() {
             // Else determine next batch
             let mut prev_page_starting_offset = None;
@@ -159,7 +662,7 @@ impl SelectBbox {
                     continue;
                 };
 
-                let close_enough = DEFAULT_PAGE_SIZE_GOAL * 2;
+                let close_enough = self.overfetch * 2;
                 if next.page_starting_offset < batch_starting_offset + close_enough {
                     // It's close enough, add it to the batch
                     prev_page_starting_offset = Some(next.page_starting_offset);
@@ -170,26 +673,72 @@ impl SelectBbox {
                 }
             }
         }
+        Ok(())
+    }
 
-        let Some(end_of_last_in_batch) = self.current_batch.back().map(|last_in_batch| {
-            last_in_batch.page_starting_offset + (DEFAULT_PAGE_SIZE_GOAL as f64 * 1.1) as u64
-        }) else {
-            return Ok(None);
+    /// Like [`Selection::into_feature_buffer_stream`], but each batch is fetched by its own
+    /// request, with up to `concurrent_requests` in flight at once. Features are still yielded
+    /// in order.
+    fn into_concurrent_feature_buffer_stream(
+        mut self,
+        header: &Header,
+        mut http_client: HttpClient,
+        concurrent_requests: usize,
+        retry_policy: RetryPolicy,
+        fetch_stats: FetchStats,
+    ) -> impl Stream<Item = Result<FeatureBuffer>> {
+        let header = header.clone();
+        let feature_start = self.feature_start;
+        let overfetch = self.overfetch;
+        let batches = async_stream::try_stream! {
+            while let Some(batch) = self.next_batch().await? {
+                yield batch;
+            }
         };
-        let next = self
-            .current_batch
-            .pop_front()
-            .expect("if there is a back, there is also a front");
-
-        let start_of_batch = next.page_starting_offset;
-        Ok(Some((next, start_of_batch..end_of_last_in_batch)))
+        batches
+            .map(move |batch: Result<Vec<FeatureLocation>>| {
+                let header = header.clone();
+                let http_client = http_client.split_off();
+                let retry_policy = retry_policy.clone();
+                let fetch_stats = fetch_stats.clone();
+                async move {
+                    let locations = futures_util::stream::iter(batch?.into_iter().map(Ok));
+                    // The whole batch is within the overfetch, so it's fetched by one request.
+                    let mut selection =
+                        Selection::SelectBbox(SelectBbox::new(feature_start, locations, overfetch));
+                    let mut page_reader =
+                        AsyncPageReader::new(&header, http_client, retry_policy, fetch_stats);
+                    let mut feature_buffers = vec![];
+                    while let Some(feature_buffer) =
+                        selection.next_feature_buffer(&mut page_reader).await?
+                    {
+                        feature_buffers.push(feature_buffer);
+                    }
+                    Ok::<_, Error>(feature_buffers)
+                }
+            })
+            .buffered(concurrent_requests)
+            .flat_map(|feature_buffers| {
+                let feature_buffers: Vec<Result<FeatureBuffer>> = match feature_buffers {
+                    Ok(feature_buffers) => feature_buffers.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                futures_util::stream::iter(feature_buffers)
+            })
     }
 }
 
 #[derive(Debug)]
 struct AsyncPageReader {
+    /// Only `None` while moving to another page, or after that failed, which ends the stream, see
+    /// [`Selection::into_feature_buffer_stream`].
     current_page: Option<CurrentPage>,
-    is_compressed: bool,
+    compression: Compression,
+    has_page_checksums: bool,
+    /// The zstd dictionary every page was compressed with, if any.
+    dictionary: Option<Arc<[u8]>>,
+    retry_policy: RetryPolicy,
+    fetch_stats: FetchStats,
 }
 
 #[derive(Debug)]
@@ -199,174 +748,120 @@ struct CurrentPage {
     page_decoder: Box<dyn AsyncPageDecoder>,
 }
 
-#[async_trait::async_trait(?Send)]
-trait AsyncPageDecoder: std::fmt::Debug + AsyncRead + Unpin {
+#[cfg_attr(
+    all(feature = "send", not(target_arch = "wasm32")),
+    async_trait::async_trait
+)]
+#[cfg_attr(
+    not(all(feature = "send", not(target_arch = "wasm32"))),
+    async_trait::async_trait(?Send)
+)]
+trait AsyncPageDecoder: std::fmt::Debug + AsyncRead + Unpin + MaybeSend {
     fn was_read_to_end(&self) -> bool;
     async fn ff_to_feature_offset(&mut self, offset_within_page: u32) -> Result<()>;
     fn into_inner(self: Box<Self>) -> HttpClient;
 }
 
+/// A page which was read in full and decoded up front, so that a page whose response is cut off
+/// can be requested again, and its checksum (if any) verified before any of its features are read.
 #[derive(Debug)]
-struct ZstdPageDecoder {
-    zstd_decoder: Take<MyRuzstdDecoder<BufReader<Take<HttpClient>>>>,
-    decoded_page_length: u32,
-}
-
-impl ZstdPageDecoder {
-    // MJK DEBUG:  decoded_page_length = 0, first time through as part of init
-    // MJK DEBUG:  decoded_page_length = 156
-    fn new(http_client: Take<HttpClient>, decoded_page_length: u32) -> Self {
-        // TODO: implement BufReader for http_client?
-        let buffered = BufReader::new(http_client);
-        // let zstd_decoder = ZstdDecoder::new(buffered).take(decoded_page_length as u64);
-        let zstd_decoder = MyRuzstdDecoder::new(buffered).take(decoded_page_length as u64);
+struct BufferedPageDecoder {
+    /// Positioned after the page.
+    inner: HttpClient,
+    decoded: std::io::Cursor<Vec<u8>>,
+}
+
+impl BufferedPageDecoder {
+    /// A page with no features, for before any page has been read.
+    fn empty(inner: HttpClient) -> Self {
         Self {
-            decoded_page_length,
-            zstd_decoder,
+            inner,
+            decoded: std::io::Cursor::new(vec![]),
         }
     }
 
-    fn offset_within_page(&self) -> u32 {
-        self.decoded_page_length - self.zstd_decoder.limit() as u32
+    /// Decodes the `encoded` page, which has already been read from `inner`.
+    fn from_encoded(
+        inner: HttpClient,
+        encoded: &[u8],
+        compression: Compression,
+        page_header: &PageHeader,
+        dictionary: Option<&[u8]>,
+    ) -> Result<Self> {
+        verify_page_checksum(encoded, page_header.checksum())?;
+        let decoded = decode_page(
+            encoded,
+            compression,
+            page_header.decoded_page_length(),
+            dictionary,
+        )?;
+        Ok(Self {
+            inner,
+            decoded: std::io::Cursor::new(decoded),
+        })
     }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-impl AsyncRead for ZstdPageDecoder {
+impl AsyncRead for BufferedPageDecoder {
     fn poll_read(
         mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
+        _cx: &mut Context<'_>,
         buf: &mut tokio::io::ReadBuf<'_>,
     ) -> Poll<std::io::Result<()>> {
-        Pin::new(&mut self.zstd_decoder).poll_read(cx, buf)
+        let read_size = std::io::Read::read(&mut self.decoded, buf.initialize_unfilled())?;
+        buf.advance(read_size);
+        Poll::Ready(Ok(()))
     }
 }
 
 #[cfg(target_arch = "wasm32")]
-impl AsyncRead for ZstdPageDecoder {
+impl AsyncRead for BufferedPageDecoder {
     fn poll_read(
         mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
+        _cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<std::io::Result<usize>> {
-        debug!("ZstdPageDecoder.poll_read");
-        Pin::new(&mut self.zstd_decoder).poll_read(cx, buf)
-    }
-}
-
-#[async_trait::async_trait(?Send)]
-impl AsyncPageDecoder for ZstdPageDecoder {
-    async fn ff_to_feature_offset(&mut self, offset_within_page: u32) -> Result<()> {
-        assert!(
-            offset_within_page >= self.offset_within_page(),
-            "shouldn't rewind"
-        );
-        let distance = offset_within_page - self.offset_within_page();
-        let skipped = crate::asyncio::copy(
-            &mut (&mut self.zstd_decoder).take(distance as u64),
-            &mut crate::asyncio::sink(),
-        )
-        .await?;
-        assert_eq!(skipped, distance as u64);
-        Ok(())
-    }
-
-    fn was_read_to_end(&self) -> bool {
-        self.zstd_decoder.get_ref().get_ref().buffer().is_empty() && self.zstd_decoder.limit() == 0
-    }
-
-    fn into_inner(self: Box<Self>) -> HttpClient {
-        self.zstd_decoder
-            .into_inner()
-            .into_inner()
-            .into_inner()
-            .into_inner()
+        Poll::Ready(std::io::Read::read(&mut self.decoded, buf))
     }
 }
 
-#[derive(Debug)]
-struct UncompressedPageDecoder {
-    inner: Take<HttpClient>,
-    encoded_page_length: u32,
-}
-
-impl UncompressedPageDecoder {
-    fn new(inner: Take<HttpClient>) -> Self {
-        Self {
-            encoded_page_length: inner.limit() as u32,
-            inner,
-        }
-    }
-
-    fn offset_within_page(&self) -> u32 {
-        self.encoded_page_length - self.inner.limit() as u32
-    }
-}
-
-#[async_trait::async_trait(?Send)]
-impl AsyncPageDecoder for UncompressedPageDecoder {
+#[cfg_attr(
+    all(feature = "send", not(target_arch = "wasm32")),
+    async_trait::async_trait
+)]
+#[cfg_attr(
+    not(all(feature = "send", not(target_arch = "wasm32"))),
+    async_trait::async_trait(?Send)
+)]
+impl AsyncPageDecoder for BufferedPageDecoder {
     async fn ff_to_feature_offset(&mut self, offset_within_page: u32) -> Result<()> {
         assert!(
-            offset_within_page >= self.offset_within_page(),
+            offset_within_page as u64 >= self.decoded.position(),
             "shouldn't rewind"
         );
-        let distance = offset_within_page - self.offset_within_page();
-        let skipped = crate::asyncio::copy(
-            &mut (&mut self.inner).take(distance as u64),
-            &mut crate::asyncio::sink(),
-        )
-        .await?;
-        assert_eq!(skipped, distance as u64);
+        self.decoded.set_position(offset_within_page as u64);
         Ok(())
     }
 
     fn was_read_to_end(&self) -> bool {
-        self.inner.limit() == 0
+        self.decoded.position() >= self.decoded.get_ref().len() as u64
     }
 
     fn into_inner(self: Box<Self>) -> HttpClient {
-        self.inner.into_inner()
-    }
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-impl AsyncRead for UncompressedPageDecoder {
-    fn poll_read(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-        buf: &mut tokio::io::ReadBuf<'_>,
-    ) -> Poll<std::io::Result<()>> {
-        Pin::new(&mut self.inner).poll_read(cx, buf)
-    }
-}
-
-#[cfg(target_arch = "wasm32")]
-impl AsyncRead for UncompressedPageDecoder {
-    fn poll_read(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-        buf: &mut [u8],
-    ) -> Poll<std::io::Result<usize>> {
-        Pin::new(&mut self.inner).poll_read(cx, buf)
-    }
-}
-
-fn new_page_decoder(
-    inner: Take<HttpClient>,
-    is_compressed: bool,
-    decoded_page_length: u32,
-) -> Box<dyn AsyncPageDecoder> {
-    if is_compressed {
-        Box::new(ZstdPageDecoder::new(inner, decoded_page_length))
-    } else {
-        Box::new(UncompressedPageDecoder::new(inner))
+        self.inner
     }
 }
 
 impl AsyncPageReader {
-    fn new(is_compressed: bool, reader: HttpClient) -> Self {
+    fn new(
+        header: &Header,
+        reader: HttpClient,
+        retry_policy: RetryPolicy,
+        fetch_stats: FetchStats,
+    ) -> Self {
         // "fake" initial page decoder with an empty reader.
-        let page_decoder = new_page_decoder(reader.take(0), is_compressed, 0);
+        let page_decoder = Box::new(BufferedPageDecoder::empty(reader));
 
         let current_page = Some(CurrentPage {
             page_starting_offset: None,
@@ -375,7 +870,11 @@ impl AsyncPageReader {
 
         Self {
             current_page,
-            is_compressed,
+            compression: header.compression,
+            has_page_checksums: header.has_page_checksums,
+            dictionary: header.dictionary.clone(),
+            retry_policy,
+            fetch_stats,
         }
     }
 
@@ -393,47 +892,6 @@ impl AsyncPageReader {
             .take()
             .expect("current_page is always replaced")
         {
-            CurrentPage {
-                page_decoder,
-                page_starting_offset: None,
-            } => {
-                debug!("first content read - we haven't started any page yet.");
-                let mut http_client: HttpClient = page_decoder.into_inner();
-                let page_header_start = feature_start + location.page_starting_offset;
-
-                debug!("page_header overfetch: {overfetch:?}");
-                let page_header_end = page_header_start + PageHeader::serialized_size() as u64;
-                let page_header_range = HttpRange::Range(page_header_start..page_header_end);
-                if http_client.contains(&page_header_range) {
-                    http_client.seek_to_range(page_header_range).await?;
-                } else {
-                    let page_header_range =
-                        HttpRange::Range(page_header_start..page_header_end + overfetch);
-                    http_client.seek_to_range(page_header_range).await?;
-                }
-
-                let mut bytes = vec![0; PageHeader::serialized_size()];
-                http_client.read_exact(&mut bytes).await?;
-                let page_header: PageHeader = deserialize_from(&*bytes)?;
-
-                let page_content_end = page_header_end + page_header.encoded_page_length() as u64;
-                let page_content_range = HttpRange::Range(page_header_end..page_content_end);
-                if http_client.contains(&page_content_range) {
-                    http_client.seek_to_range(page_content_range).await?;
-                } else {
-                    let page_content_range =
-                        HttpRange::Range(page_header_end..page_content_end + overfetch);
-                    http_client.seek_to_range(page_content_range).await?;
-                }
-                (
-                    new_page_decoder(
-                        http_client.take(page_header.encoded_page_length() as u64),
-                        self.is_compressed,
-                        page_header.decoded_page_length(),
-                    ),
-                    location.page_starting_offset,
-                )
-            }
             CurrentPage {
                 page_decoder,
                 page_starting_offset: Some(page_starting_offset),
@@ -443,47 +901,29 @@ impl AsyncPageReader {
             }
             CurrentPage {
                 page_decoder,
-                page_starting_offset: Some(page_starting_offset),
+                page_starting_offset,
             } => {
-                debug!(
-                    "We're currently reading an earlier page, and need to fast forward to the proper page."
-                );
-                assert!(
-                    location.page_starting_offset > page_starting_offset,
-                    "Trying to fast forward to page {location:?} from current page with starting offset {page_starting_offset}"
-                );
-                let mut http_client: HttpClient = page_decoder.into_inner();
-                let page_header_start = feature_start + location.page_starting_offset;
-                let page_header_end = page_header_start + PageHeader::serialized_size() as u64;
-
-                let page_header_range = HttpRange::Range(page_header_start..page_header_end);
-                if http_client.contains(&page_header_range) {
-                    http_client.seek_to_range(page_header_range).await?;
-                } else {
-                    let page_header_range =
-                        HttpRange::Range(page_header_start..page_header_end + overfetch);
-                    http_client.seek_to_range(page_header_range).await?;
-                }
-
-                let mut bytes = vec![0; PageHeader::serialized_size()];
-                http_client.read_exact(&mut bytes).await?;
-                let page_header: PageHeader = deserialize_from(&*bytes)?;
-
-                let page_content_end = page_header_end + page_header.encoded_page_length() as u64;
-                let page_content_range = HttpRange::Range(page_header_end..page_content_end);
-                if http_client.contains(&page_content_range) {
-                    http_client.seek_to_range(page_content_range).await?;
-                } else {
-                    let page_content_range =
-                        HttpRange::Range(page_header_end..page_content_end + overfetch);
-                    http_client.seek_to_range(page_content_range).await?;
+                match page_starting_offset {
+                    None => debug!("first content read - we haven't started any page yet."),
+                    Some(page_starting_offset) => {
+                        debug!(
+                            "We're currently reading an earlier page, and need to fast forward to the proper page."
+                        );
+                        assert!(
+                            location.page_starting_offset > page_starting_offset,
+                            "Trying to fast forward to page {location:?} from current page with starting offset {page_starting_offset}"
+                        );
+                    }
                 }
+                let page_decoder = self
+                    .read_page_at(
+                        page_decoder.into_inner(),
+                        feature_start + location.page_starting_offset,
+                        overfetch,
+                    )
+                    .await?;
                 (
-                    new_page_decoder(
-                        http_client.take(page_header.encoded_page_length() as u64),
-                        self.is_compressed,
-                        page_header.decoded_page_length(),
-                    ),
+                    Box::new(page_decoder) as Box<dyn AsyncPageDecoder>,
                     location.page_starting_offset,
                 )
             }
@@ -500,6 +940,77 @@ impl AsyncPageReader {
         Ok(())
     }
 
+    /// Reads the page whose header starts at `page_header_start`, requesting `overfetch` more bytes
+    /// than the page needs, unless they're already buffered.
+    ///
+    /// The page is read in full before any of it is decoded, so if the connection is dropped
+    /// partway through, the page can be requested again.
+    async fn read_page_at(
+        &self,
+        mut http_client: HttpClient,
+        page_header_start: u64,
+        overfetch: u64,
+    ) -> Result<BufferedPageDecoder> {
+        debug!("page_header overfetch: {overfetch:?}");
+        let page_header_size = PageHeader::serialized_size(self.has_page_checksums);
+        let page_header_end = page_header_start + page_header_size as u64;
+        let mut page_header_range = HttpRange::Range(page_header_start..page_header_end);
+        if !http_client.contains(&page_header_range) {
+            page_header_range = HttpRange::Range(page_header_start..page_header_end + overfetch);
+        }
+        let mut bytes = vec![0; page_header_size];
+        self.retry_policy
+            .read_range(
+                &mut http_client,
+                page_header_range,
+                &mut bytes,
+                &self.fetch_stats,
+            )
+            .await?;
+        let (page_header, _page_bounds) = PageHeader::read_from(&*bytes, self.has_page_checksums)?;
+
+        let page_content_end = page_header_end + page_header.encoded_page_length() as u64;
+        let page_content_range = HttpRange::Range(page_header_end..page_content_end);
+        let page_content_range = if http_client.contains(&page_content_range) {
+            page_content_range
+        } else {
+            HttpRange::Range(page_header_end..page_content_end + overfetch)
+        };
+        self.retry_policy
+            .seek_to_range(
+                &mut http_client,
+                page_content_range.clone(),
+                &self.fetch_stats,
+            )
+            .await?;
+        let mut attempt = 1;
+        let encoded = loop {
+            match read_untrusted_len(&mut http_client, page_header.encoded_page_length() as u64)
+                .await
+            {
+                Ok(encoded) => break encoded,
+                Err(error) => {
+                    self.retry_policy
+                        .recover(
+                            &mut attempt,
+                            error.into(),
+                            &mut http_client,
+                            &page_content_range,
+                            &self.fetch_stats,
+                        )
+                        .await?
+                }
+            }
+        };
+        BufferedPageDecoder::from_encoded(
+            http_client,
+            &encoded,
+            self.compression,
+            &page_header,
+            self.dictionary.as_deref(),
+        )
+    }
+
     fn current_page_was_read_to_end(&self) -> bool {
         let curent_page = self
             .current_page
@@ -508,7 +1019,13 @@ impl AsyncPageReader {
         curent_page.page_decoder.was_read_to_end()
     }
 
-    async fn next_page(&mut self) -> Result<()> {
+    /// Reads the page which starts at `page_start`, immediately following the current page, and
+    /// returns where the page after it starts.
+    ///
+    /// Only `SelectAll` reads pages in sequence, from its open ended range. Each page is read in
+    /// full before any of it is decoded, so if the connection is dropped partway through, the
+    /// rest of the file can be requested again from the start of the page.
+    async fn next_page(&mut self, page_start: u64) -> Result<u64> {
         assert!(self.current_page_was_read_to_end());
         let CurrentPage {
             page_starting_offset: _,
@@ -517,29 +1034,57 @@ impl AsyncPageReader {
 
         let mut http_client: HttpClient = page_decoder.into_inner();
 
-        let mut page_header_buffer = vec![0u8; PageHeader::serialized_size()];
-        // TODO poison on error
-        http_client.read_exact(&mut page_header_buffer).await?;
-
-        let next_page_header: PageHeader = deserialize_from(&*page_header_buffer)?;
+        let mut attempt = 1;
+        let (next_page_header, encoded) = loop {
+            match read_page(&mut http_client, self.has_page_checksums).await {
+                Ok(page) => break page,
+                Err(error) => {
+                    self.retry_policy
+                        .recover(
+                            &mut attempt,
+                            error,
+                            &mut http_client,
+                            &HttpRange::RangeFrom(page_start..),
+                            &self.fetch_stats,
+                        )
+                        .await?
+                }
+            }
+        };
         info!("read next PageHeader: {next_page_header:?}");
+        let page_header_size = PageHeader::serialized_size(self.has_page_checksums);
+        let page_len = (page_header_size + encoded.len()) as u64;
+        self.fetch_stats.record_bytes(page_len);
 
-        // dbg!(&next_page_header);
-        let reader = http_client.take(next_page_header.encoded_page_length() as u64);
-        let next_page_decoder = new_page_decoder(
-            reader,
-            self.is_compressed,
-            next_page_header.decoded_page_length(),
-        );
+        let next_page_decoder = BufferedPageDecoder::from_encoded(
+            http_client,
+            &encoded,
+            self.compression,
+            &next_page_header,
+            self.dictionary.as_deref(),
+        )?;
 
         self.current_page = Some(CurrentPage {
             page_starting_offset: None, // FIXME: Do I care? Maybe I will for SelectBBox Does this need to be on pageHeader, or I can use CountingReader
-            page_decoder: next_page_decoder,
+            page_decoder: Box::new(next_page_decoder),
         });
-        Ok(())
+        Ok(page_start + page_len)
     }
 }
 
+/// Reads the next page header from `http_client`, and the encoded page which follows it.
+async fn read_page(
+    http_client: &mut HttpClient,
+    has_page_checksums: bool,
+) -> Result<(PageHeader, Vec<u8>)> {
+    let mut page_header_buffer = vec![0u8; PageHeader::serialized_size(has_page_checksums)];
+    http_client.read_exact(&mut page_header_buffer).await?;
+    let (page_header, _page_bounds) =
+        PageHeader::read_from(&*page_header_buffer, has_page_checksums)?;
+    let encoded = read_untrusted_len(http_client, page_header.encoded_page_length() as u64).await?;
+    Ok((page_header, encoded))
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 impl AsyncRead for AsyncPageReader {
     fn poll_read(
@@ -594,12 +1139,16 @@ impl AsyncRead for AsyncPageReader {
 }
 
 impl SelectAll {
-    fn new(features_left: u64) -> Self {
-        // let mut page_header_buffer = vec![0u8; PageHeader::serialized_size()];
-        // http_client.read_exact(&mut page_header_buffer).await?;
-        // let page_header: PageHeader = deserialize_from(&*page_header_buffer)?;
+    fn new(features_left: u64, feature_base: u64) -> Self {
         Self {
             features_left_in_document: features_left,
+            feature_base,
+            next_page_start: feature_base,
+            location: FeatureLocation {
+                page_starting_offset: 0,
+                feature_offset: 0,
+            },
+            pages_opened: 0,
         }
     }
 }
@@ -612,10 +1161,12 @@ enum Selection {
 impl Selection {
     pub fn into_feature_buffer_stream(
         mut self,
-        is_compressed: bool,
+        header: &Header,
         http_client: HttpClient,
-    ) -> impl Stream<Item = Result<Bytes>> {
-        let mut page_reader = AsyncPageReader::new(is_compressed, http_client);
+        retry_policy: RetryPolicy,
+        fetch_stats: FetchStats,
+    ) -> impl Stream<Item = Result<FeatureBuffer>> {
+        let mut page_reader = AsyncPageReader::new(header, http_client, retry_policy, fetch_stats);
         async_stream::try_stream! {
             loop {
                 match self.next_feature_buffer(&mut page_reader).await? {
@@ -631,9 +1182,9 @@ impl Selection {
     async fn next_feature_buffer(
         &mut self,
         page_reader: &mut AsyncPageReader,
-    ) -> Result<Option<Bytes>> {
+    ) -> Result<Option<FeatureBuffer>> {
         trace!("");
-        match self {
+        let (location, page_index) = match self {
             Selection::SelectAll(select_all) => {
                 if select_all.features_left_in_document == 0 {
                     // TODO: restore this assert on wasm32
@@ -643,10 +1194,20 @@ impl Selection {
                 }
 
                 if page_reader.current_page_was_read_to_end() {
-                    page_reader.next_page().await?;
+                    let page_start = select_all.next_page_start;
+                    select_all.next_page_start = page_reader.next_page(page_start).await?;
+                    select_all.location = FeatureLocation {
+                        page_starting_offset: page_start - select_all.feature_base,
+                        feature_offset: 0,
+                    };
+                    select_all.pages_opened += 1;
                 }
 
                 select_all.features_left_in_document -= 1;
+                (
+                    select_all.location.clone(),
+                    Some(select_all.pages_opened - 1),
+                )
             }
             Selection::SelectBbox(select_bbox) => {
                 let Some((next_location, feature_batch_range)) =
@@ -658,12 +1219,14 @@ impl Selection {
                 page_reader
                     .ff_to_location(
                         select_bbox.feature_start,
-                        next_location,
+                        next_location.clone(),
                         feature_batch_range,
                     )
                     .await?;
+                // Pages are jumped to, so which of them this is isn't known.
+                (next_location, None)
             }
-        }
+        };
 
         let mut len_bytes = [0u8; 8];
         page_reader.read_exact(&mut len_bytes).await?;
@@ -672,32 +1235,177 @@ impl Selection {
         let mut feature_buffer = BytesMut::zeroed(feature_len as usize);
         page_reader.read_exact(&mut feature_buffer).await?;
 
-        Ok(Some(feature_buffer.freeze()))
+        if let Selection::SelectAll(select_all) = self {
+            // The whole feature was read from the page, so its length fits within the page's.
+            select_all.location.feature_offset += 8 + feature_len as u32;
+        }
+
+        Ok(Some(FeatureBuffer {
+            bytes: feature_buffer.freeze(),
+            location,
+            page_index,
+        }))
     }
 }
 
+/// A serialized feature, and where it was read from, to report if it fails to decode.
+pub(crate) struct FeatureBuffer {
+    pub(crate) bytes: Bytes,
+    pub(crate) location: FeatureLocation,
+    /// Only known when the pages were read in order, rather than jumped to.
+    pub(crate) page_index: Option<u64>,
+}
+
+/// The features selected by a query, see e.g. [`HttpReader::select_bbox`].
+///
+/// With the `send` feature, on native targets, this is `Send`, see [`MaybeSend`].
 pub struct FeatureStream {
-    inner: Box<dyn Stream<Item = Result<Feature>> + Unpin>,
+    inner: MaybeSendBoxStream<'static, Result<Feature>>,
+    handle: FeatureStreamHandle,
 }
 
 impl FeatureStream {
-    fn new(stream: impl Stream<Item = Result<Bytes>> + 'static) -> Self {
+    pub(crate) fn new(
+        stream: impl Stream<Item = Result<FeatureBuffer>> + MaybeSend + 'static,
+        coord_precision_nanos: i32,
+    ) -> Self {
+        let handle = FeatureStreamHandle::default();
+        let progress = handle.clone();
         let inner = stream.map(move |feature_buffer| {
-            let feature = deserialize_from::<_, Feature>(feature_buffer?.as_ref())?;
+            let FeatureBuffer {
+                bytes,
+                location,
+                page_index,
+            } = feature_buffer?;
+            // Include the length prefix of each feature
+            progress
+                .state
+                .bytes_read
+                .fetch_add(8 + bytes.len() as u64, Ordering::Relaxed);
+            let mut feature = deserialize_from::<_, Feature>(bytes.as_ref())
+                .map_err(corrupt_feature(&location, page_index))?;
+            if coord_precision_nanos != COORD_PRECISION_NANOS {
+                feature
+                    .geometry_mut()
+                    .rescale_from_precision(coord_precision_nanos);
+            }
             // trace!("yielding feature: {feature:?}");
             Ok(feature)
         });
         Self {
-            inner: Box::new(Box::pin(inner)),
+            inner: crate::box_stream(inner),
+            handle,
         }
     }
+
+    /// A handle for observing the progress of this stream from elsewhere, e.g. to update a
+    /// progress bar, or to abort it.
+    pub fn handle(&self) -> FeatureStreamHandle {
+        self.handle.clone()
+    }
+}
+
+impl FeatureStream {
+    fn filter_intersecting(self, geometry: Geometry) -> Self {
+        let inner = self.inner.filter(move |feature| {
+            let keep = match feature {
+                Ok(feature) => intersects(feature.geometry(), &geometry),
+                // Don't swallow errors
+                Err(_) => true,
+            };
+            futures_util::future::ready(keep)
+        });
+        Self {
+            inner: crate::box_stream(inner),
+            handle: self.handle,
+        }
+    }
+}
+
+impl FeatureStream {
+    /// Allows inspecting the next feature without consuming it, via
+    /// [`Peekable::peek`](futures_util::stream::Peekable::peek).
+    pub fn peekable(self) -> futures_util::stream::Peekable<Self> {
+        StreamExt::peekable(self)
+    }
+
+    /// Yields each feature's geometry as a `geo_types::Geometry`, along with its properties.
+    #[cfg(feature = "geo-types")]
+    pub fn into_geo(
+        self,
+    ) -> impl Stream<Item = Result<(geo_types::Geometry, crate::Properties)>> + Unpin {
+        use crate::ToGeoType;
+        self.map_ok(|feature| {
+            let (geometry, properties) = feature.into_inner();
+            (geometry.to_geo_type(), properties)
+        })
+    }
 }
 
 impl Stream for FeatureStream {
     type Item = Result<Feature>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Pin::new(&mut self.inner).poll_next(cx)
+        // Register before checking, so an abort from another task while we're pending wakes us.
+        self.handle.state.waker.register(cx.waker());
+        if self.handle.is_aborted() {
+            // Drop the underlying stream, and with it any in-flight or further requests.
+            self.inner = crate::box_stream(futures_util::stream::empty());
+            return Poll::Ready(None);
+        }
+        let next = Pin::new(&mut self.inner).poll_next(cx);
+        if let Poll::Ready(Some(Ok(_))) = &next {
+            self.handle
+                .state
+                .features_read
+                .fetch_add(1, Ordering::Relaxed);
+        }
+        next
+    }
+}
+
+/// Observes the progress of a [`FeatureStream`], and can abort it.
+///
+/// Get one with [`FeatureStream::handle`]. Handles are cheap to clone.
+#[derive(Debug, Clone, Default)]
+pub struct FeatureStreamHandle {
+    state: Arc<FeatureStreamState>,
+}
+
+#[derive(Debug, Default)]
+struct FeatureStreamState {
+    features_read: AtomicU64,
+    bytes_read: AtomicU64,
+    is_aborted: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl FeatureStreamHandle {
+    /// The number of features the stream has yielded so far.
+    pub fn features_read(&self) -> u64 {
+        self.state.features_read.load(Ordering::Relaxed)
+    }
+
+    /// The number of bytes of (decompressed) feature data read so far.
+    ///
+    /// This includes features which were read but then filtered out, e.g. by
+    /// [`HttpReader::select_intersecting`], but it's not the number of bytes transferred, which
+    /// also includes page headers and any overfetching, and is smaller when compressed.
+    pub fn bytes_read(&self) -> u64 {
+        self.state.bytes_read.load(Ordering::Relaxed)
+    }
+
+    /// Stop the stream: it will yield no more features, and make no further requests.
+    ///
+    /// Dropping the stream has the same effect, but this can be called from elsewhere, e.g. a
+    /// "cancel" button.
+    pub fn abort(&self) {
+        self.state.is_aborted.store(true, Ordering::Relaxed);
+        self.state.waker.wake();
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.state.is_aborted.load(Ordering::Relaxed)
     }
 }
 
@@ -705,8 +1413,8 @@ impl Stream for FeatureStream {
 #[cfg(feature = "writer")]
 mod test {
     use super::*;
-    use crate::feature::PropertyValue;
-    use crate::{ensure_logging, wkt, Geometry, LngLat};
+    use crate::feature::{Properties, PropertyValue};
+    use crate::{ensure_logging, wkt, Geometry, LngLat, Reader};
 
     #[tokio::test]
     async fn select_all_uncompressed() {
@@ -746,6 +1454,32 @@ mod test {
         assert_eq!(remainder.len(), 4);
     }
 
+    #[tokio::test]
+    async fn progress_and_abort() {
+        let bytes = crate::test_data::small_pages(10, true);
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let mut stream = reader.select_all().await.unwrap();
+        let handle = stream.handle();
+        assert_eq!(handle.features_read(), 0);
+        assert_eq!(handle.bytes_read(), 0);
+
+        for _ in 0..3 {
+            stream.next().await.unwrap().unwrap();
+        }
+        assert_eq!(handle.features_read(), 3);
+        let bytes_read = handle.bytes_read();
+        assert!(bytes_read > 0);
+
+        stream.next().await.unwrap().unwrap();
+        assert!(handle.bytes_read() > bytes_read);
+
+        handle.abort();
+        assert!(handle.is_aborted());
+        assert!(stream.next().await.is_none());
+        assert!(stream.next().await.is_none());
+        assert_eq!(handle.features_read(), 4);
+    }
+
     #[tokio::test]
     async fn bbox_uncompressed() {
         bbox(false).await
@@ -783,6 +1517,571 @@ mod test {
         assert_eq!(remainder.len(), 3);
     }
 
+    #[tokio::test]
+    async fn bbox_concurrent_requests() {
+        ensure_logging();
+        let bytes = crate::test_data::small_pages(50, true);
+        let bounds = wkt!(RECT(5 5,40 40));
+
+        async fn names(stream: FeatureStream) -> Vec<PropertyValue> {
+            let features: Vec<_> = stream.collect().await;
+            features
+                .into_iter()
+                .map(|feature| feature.unwrap().property("name").unwrap().clone())
+                .collect()
+        }
+
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let expected = names(reader.select_bbox(&bounds).await.unwrap()).await;
+        assert_eq!(expected.len(), 36);
+
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        // Small enough that every page is fetched by its own request.
+        reader.set_overfetch(1);
+        reader.set_concurrent_requests(4);
+        let actual = names(reader.select_bbox(&bounds).await.unwrap()).await;
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn select_bboxes() {
+        ensure_logging();
+        let bytes = crate::test_data::small_pages(100, true);
+        let all_bounds = [
+            wkt!(RECT(10 10,12 12)),
+            wkt!(RECT(80.5 80.5,81.5 81.5)),
+            // overlaps the first
+            wkt!(RECT(11 11,13 13)),
+        ];
+
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let selected: Vec<_> = reader
+            .select_bboxes(&all_bounds)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        let selected: Vec<Feature> = selected.into_iter().map(Result::unwrap).collect();
+
+        let mut local_features = Reader::new(bytes.as_slice())
+            .unwrap()
+            .select_bboxes(&all_bounds)
+            .unwrap();
+        let mut expected = vec![];
+        while let Some(feature) = local_features.try_next().unwrap() {
+            expected.push(feature);
+        }
+        assert_eq!(expected.len(), 5);
+        assert_eq!(selected, expected);
+    }
+
+    #[tokio::test]
+    async fn select_bbox_out_of_order_index() {
+        ensure_logging();
+        let mut bytes = crate::test_data::small_pages(20, true);
+        let reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let header = reader.header();
+        let tree = PackedRTree::new(header.feature_count, header.branching_factor);
+        let index_end = (reader.header_size() + tree.index_size()) as usize;
+
+        // Swap the first and last leaves, so the index is no longer in the order the features are
+        // stored. The search covers everything, so the now inaccurate parent bounds don't matter.
+        let node_size = Node::serialized_size();
+        let leaves = &mut bytes[index_end - 20 * node_size..index_end];
+        let (first, rest) = leaves.split_at_mut(node_size);
+        first.swap_with_slice(&mut rest[18 * node_size..]);
+
+        let everything = wkt!(RECT(-1 -1,100 100));
+        let mut features = Reader::new(bytes.as_slice())
+            .unwrap()
+            .select_bbox(&everything)
+            .unwrap();
+        let mut expected = vec![];
+        while let Some(feature) = features.try_next().unwrap() {
+            expected.push(feature);
+        }
+        assert_eq!(expected.len(), 20);
+
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let selected: Vec<_> = reader
+            .select_bbox(&everything)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        let selected: Vec<Feature> = selected.into_iter().map(Result::unwrap).collect();
+        assert_eq!(selected, expected);
+    }
+
+    #[tokio::test]
+    async fn count_bbox() {
+        ensure_logging();
+        let bytes = crate::test_data::small_pages(1000, true);
+        let bounds = wkt!(RECT(5 5,40 40));
+
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let selected: Vec<_> = reader.select_bbox(&bounds).await.unwrap().collect().await;
+        assert!(!selected.is_empty());
+
+        // Without any pages, reading a feature would fail.
+        let header = reader.header();
+        let index_end = reader.header_size()
+            + PackedRTree::new(header.feature_count, header.branching_factor).index_size();
+        let index_only = HttpClient::test_client(&bytes[..index_end as usize]);
+        let mut reader = HttpReader::new(index_only, HttpReaderConfig::default())
+            .await
+            .unwrap();
+        let count = reader.count_bbox(&bounds).await.unwrap();
+        assert_eq!(count, selected.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn prefetches_top_of_index() {
+        // 1 root node, over 4 leaves
+        let bytes = crate::test_data::small_pages(4, true);
+        let reader = HttpReader::test_reader(&bytes).await.unwrap();
+        assert_eq!(
+            reader.index_prefetch_size(),
+            5 * Node::serialized_size() as u64
+        );
+
+        // levels of 1, 3, 40, 625, and 10,000 nodes
+        let bytes = crate::test_data::points(10_000, true);
+        let reader = HttpReader::test_reader(&bytes).await.unwrap();
+        assert_eq!(
+            reader.index_prefetch_size(),
+            44 * Node::serialized_size() as u64
+        );
+
+        let config = HttpReaderConfig {
+            index_prefetch_levels: 4,
+            ..Default::default()
+        };
+        let mut reader = HttpReader::new(HttpClient::test_client(&bytes), config)
+            .await
+            .unwrap();
+        assert_eq!(
+            reader.index_prefetch_size(),
+            669 * Node::serialized_size() as u64
+        );
+        let features: Vec<_> = reader
+            .select_bbox(&wkt!(RECT(0 0,1 1)))
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(features.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn empty_file() {
+        let bytes = crate::Writer::new(vec![], true).unwrap().finish().unwrap();
+        // Shorter than the most the header could need.
+        assert!((bytes.len() as u64) < Header::max_serialized_size());
+
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        assert_eq!(reader.header().feature_count, 0);
+        let features: Vec<_> = reader.select_all().await.unwrap().collect().await;
+        assert!(features.is_empty());
+    }
+
+    #[tokio::test]
+    async fn header_only() {
+        let bytes = crate::test_data::small_pages(50, true);
+        let mut reader =
+            HttpReader::read_header(HttpClient::test_client(&bytes), header_only_config())
+                .await
+                .unwrap();
+        assert_eq!(reader.index_prefetch_size(), 0);
+        assert_eq!(reader.request_count(), 1);
+        assert_eq!(reader.bytes_fetched(), Header::max_serialized_size());
+
+        let info = reader.info();
+        assert_eq!(info.feature_count(), 50);
+        assert_eq!(info.page_count(), reader.header().page_count);
+        assert_eq!(info.extent(), Some(reader.header().extent.clone()));
+
+        // The reader can still be queried.
+        let features: Vec<_> = reader
+            .select_bbox(&wkt!(RECT(5 5,20 20)))
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert!(!features.is_empty());
+        assert!(features.iter().all(|feature| feature.is_ok()));
+    }
+
+    #[tokio::test]
+    async fn header_only_with_dictionary() {
+        let mut writer = crate::Writer::new(vec![], true).unwrap();
+        writer.set_page_size_goal(200);
+        writer
+            .set_zstd_dictionary(Some(crate::ZstdDictionary::Train { max_size: 1024 }))
+            .unwrap();
+        for idx in 0..1000 {
+            let mut properties = Properties::empty();
+            properties.insert(
+                "kind".to_string(),
+                ["road", "river", "rail"][idx % 3].into(),
+            );
+            properties.insert("rank".to_string(), PropertyValue::Int64(idx as i64 % 7));
+            let feature = Feature::new(
+                LngLat::degrees(idx as f64 / 10.0, idx as f64 / 20.0).into(),
+                properties,
+            );
+            writer.add_feature(&feature).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+        let dictionary_len = Reader::new(bytes.as_slice())
+            .unwrap()
+            .header()
+            .dictionary
+            .as_ref()
+            .expect("a trained dictionary")
+            .len() as u64;
+
+        let mut reader =
+            HttpReader::read_header(HttpClient::test_client(&bytes), header_only_config())
+                .await
+                .unwrap();
+        assert!(reader.header().dictionary.is_none());
+        // The dictionary doesn't fit in the first request, but isn't fetched.
+        assert!(dictionary_len > Header::max_serialized_size());
+        assert_eq!(reader.request_count(), 1);
+        assert_eq!(reader.bytes_fetched(), Header::max_serialized_size());
+
+        // Until the first query.
+        let bounds = wkt!(RECT(10 5,20 10));
+        let features: Vec<_> = reader
+            .select_bbox(&bounds)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(features.len(), 101);
+        assert!(reader.header().dictionary.is_some());
+        assert!(reader.bytes_fetched() > dictionary_len);
+    }
+
+    #[tokio::test]
+    async fn fetch_stats() {
+        let bytes = crate::test_data::points(10_000, true);
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        assert!(reader.request_count() > 0);
+        let opened_bytes_fetched = reader.bytes_fetched();
+        assert!(opened_bytes_fetched > reader.index_prefetch_size());
+
+        let requests_before = reader.request_count();
+        let features: Vec<_> = reader
+            .select_bbox(&wkt!(RECT(0 0,1 1)))
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(features.len(), 2);
+        assert!(reader.request_count() > requests_before);
+        // A small query only fetches a small part of the file.
+        let query_bytes_fetched = reader.bytes_fetched() - opened_bytes_fetched;
+        assert!(query_bytes_fetched > 0);
+        assert!(
+            query_bytes_fetched < bytes.len() as u64 / 2,
+            "{query_bytes_fetched} of {}",
+            bytes.len()
+        );
+
+        // Selecting everything fetches every page.
+        let before_select_all = reader.bytes_fetched();
+        let features: Vec<_> = reader.select_all().await.unwrap().collect().await;
+        assert_eq!(features.len(), 10_000);
+        let feature_start = reader.header_size() + reader.index_reader().tree().index_size();
+        assert_eq!(
+            reader.bytes_fetched() - before_select_all,
+            bytes.len() as u64 - feature_start
+        );
+    }
+
+    #[cfg(all(feature = "send", not(target_arch = "wasm32")))]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn send() {
+        let bytes = crate::test_data::small_pages(50, true);
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+
+        // A stream can be consumed on another task.
+        let stream = reader.select_bbox(&wkt!(RECT(5 5,20 20))).await.unwrap();
+        let features = tokio::spawn(stream.try_collect::<Vec<_>>())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(features.len(), 16);
+
+        // As can the whole query.
+        let features = tokio::spawn(async move {
+            let stream = reader.select_all().await?;
+            stream.try_collect::<Vec<_>>().await
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(features.len(), 50);
+    }
+
+    #[tokio::test]
+    async fn repeated_queries_reuse_header_and_index() {
+        ensure_logging();
+        let bytes = crate::test_data::small_pages(50, true);
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let header = reader.header();
+        let index_size =
+            PackedRTree::new(header.feature_count, header.branching_factor).index_size();
+        assert_eq!(reader.index_prefetch_size(), index_size);
+
+        // Counting only reads the header and the index, which were fetched when the file was
+        // opened, so no query, including the first, makes another request.
+        let requests_when_opened = reader.request_count();
+        let bounds = wkt!(RECT(5 5,20 20));
+        for _ in 0..3 {
+            assert_eq!(reader.count_bbox(&bounds).await.unwrap(), 16);
+            assert_eq!(reader.request_count(), requests_when_opened);
+        }
+
+        for bounds in [bounds, wkt!(RECT(10 10,40 40))] {
+            let features: Vec<_> = reader
+                .select_bbox(&bounds)
+                .await
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+            assert!(!features.is_empty());
+            let mut new_reader = HttpReader::test_reader(&bytes).await.unwrap();
+            let expected: Vec<_> = new_reader
+                .select_bbox(&bounds)
+                .await
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(features, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn with_config() {
+        let bytes = crate::test_data::small_pages(50, true);
+        let bounds = wkt!(RECT(5 5,40 40));
+        let config = HttpReaderConfig {
+            index_prefetch_levels: 1,
+            combine_request_threshold: 0,
+            overfetch: 1,
+            concurrent_requests: 2,
+            retry_policy: RetryPolicy::none(),
+        };
+        let mut reader = HttpReader::new(HttpClient::test_client(&bytes), config)
+            .await
+            .unwrap();
+        assert_eq!(reader.concurrent_requests(), 2);
+        let features: Vec<_> = reader.select_bbox(&bounds).await.unwrap().collect().await;
+        let features = features.into_iter().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(features.len(), 36);
+
+        let nearest = reader
+            .select_nearest(&LngLat::degrees(10.2, 10.2), 1)
+            .await
+            .unwrap();
+        assert_eq!(nearest[0].geometry(), &wkt!(POINT(10 10)).into());
+    }
+
+    #[tokio::test]
+    async fn prefetch_every_level() {
+        let bytes = crate::test_data::small_pages(50, true);
+        let config = HttpReaderConfig {
+            index_prefetch_levels: u32::MAX,
+            ..Default::default()
+        };
+        let mut reader = HttpReader::new(HttpClient::test_client(&bytes), config)
+            .await
+            .unwrap();
+        let header = reader.header();
+        let index_size =
+            PackedRTree::new(header.feature_count, header.branching_factor).index_size();
+        assert_eq!(reader.index_prefetch_size(), index_size);
+        let features: Vec<_> = reader.select_all().await.unwrap().collect().await;
+        assert_eq!(features.len(), 50);
+    }
+
+    #[tokio::test]
+    async fn bbox_with_branching_factor() {
+        ensure_logging();
+
+        let mut writer = crate::Writer::new(vec![], true).unwrap();
+        writer.set_branching_factor(2).unwrap();
+        // Use small page size to make sure we're testing multiple pages
+        writer.set_page_size_goal(100);
+        let bytes = crate::test_data::_points(10, writer);
+
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let bounds = wkt!(RECT(2 2,4 4));
+        let stream = reader.select_bbox(&bounds).await.unwrap();
+        let features: Vec<_> = stream.collect().await;
+        let features: Vec<Feature> = features.into_iter().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(features.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn corrupt_feature() {
+        ensure_logging();
+        let mut bytes = crate::test_data::small_pages(4, false);
+        crate::test_data::corrupt_last_feature(&mut bytes);
+
+        let reader = Reader::new(bytes.as_slice()).unwrap();
+        let mut feature_iter = reader.select_all().unwrap();
+        let expected = loop {
+            match feature_iter.try_next() {
+                Ok(Some(_feature)) => continue,
+                Ok(None) => panic!("expected an error"),
+                Err(error) => break crate::test_data::corrupt_feature_location(error),
+            }
+        };
+        assert!(expected.0.is_some());
+
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let stream = reader.select_all().await.unwrap();
+        let error = stream.try_collect::<Vec<_>>().await.unwrap_err();
+        assert_eq!(crate::test_data::corrupt_feature_location(error), expected);
+
+        // Pages are jumped to, so which page it's on isn't known.
+        let stream = reader.select_bbox(&wkt!(RECT(-1 -1, 4 4))).await.unwrap();
+        let error = stream.try_collect::<Vec<_>>().await.unwrap_err();
+        let (page_index, page_starting_offset, feature_offset) =
+            crate::test_data::corrupt_feature_location(error);
+        assert_eq!(page_index, None);
+        assert_eq!(
+            (page_starting_offset, feature_offset),
+            (expected.1, expected.2)
+        );
+    }
+
+    #[tokio::test]
+    async fn select_all_with_coord_precision() {
+        ensure_logging();
+
+        let mut writer = crate::Writer::new(vec![], true).unwrap();
+        writer.set_coord_precision_nanos(10_000).unwrap();
+        writer
+            .add_feature(&Feature::new(
+                wkt!(POINT(1.2345678 2.3456789)).into(),
+                Properties::empty(),
+            ))
+            .unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let stream = reader.select_all().await.unwrap();
+        let features: Vec<_> = stream.collect().await;
+        let features: Vec<Feature> = features.into_iter().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(
+            features[0].geometry(),
+            &Geometry::Point(LngLat::unscaled(12345700, 23456800))
+        );
+    }
+
+    #[tokio::test]
+    async fn select_intersecting() {
+        ensure_logging();
+        let bytes = crate::test_data::small_pages(4, true);
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+
+        // (1 1) is within the triangle's bbox, but not the triangle itself.
+        let triangle = Geometry::from(wkt!(POLYGON((0.5 0,3.5 0,3.5 4.5,0.5 0))));
+        let stream = reader.select_intersecting(&triangle).await.unwrap();
+        let features: Vec<_> = stream.collect().await;
+        let geometries: Vec<Geometry> = features
+            .into_iter()
+            .map(|feature| feature.unwrap().geometry().clone())
+            .collect();
+        assert_eq!(
+            geometries,
+            vec![wkt!(POINT(3 3)).into(), wkt!(POINT(2 2)).into()]
+        );
+    }
+
+    #[tokio::test]
+    async fn peekable() {
+        let bytes = crate::test_data::small_pages(3, true);
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let mut stream = reader.select_all().await.unwrap().peekable();
+
+        let peeked = Pin::new(&mut stream)
+            .peek()
+            .await
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .geometry()
+            .clone();
+        assert_eq!(peeked, wkt!(POINT(0 0)).into());
+        let next = stream.next().await.unwrap().unwrap();
+        assert_eq!(next.geometry(), &peeked);
+
+        let remaining: Vec<Geometry> = stream
+            .map(|feature| feature.unwrap().geometry().clone())
+            .collect()
+            .await;
+        assert_eq!(
+            remaining,
+            vec![wkt!(POINT(1 1)).into(), wkt!(POINT(2 2)).into()]
+        );
+    }
+
+    #[tokio::test]
+    async fn select_nearest() {
+        ensure_logging();
+        let bytes = crate::test_data::small_pages(4, true);
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+
+        let features = reader
+            .select_nearest(&wkt!(POINT(2.2 2.2)), 3)
+            .await
+            .unwrap();
+        let geometries: Vec<Geometry> = features
+            .into_iter()
+            .map(|feature| feature.geometry().clone())
+            .collect();
+        assert_eq!(
+            geometries,
+            vec![
+                wkt!(POINT(2 2)).into(),
+                wkt!(POINT(3 3)).into(),
+                wkt!(POINT(1 1)).into()
+            ]
+        );
+
+        let features = reader.select_nearest(&wkt!(POINT(0 0)), 10).await.unwrap();
+        assert_eq!(features.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn truncated_compressed_page() {
+        ensure_logging();
+        let mut bytes = crate::test_data::points(4, true);
+
+        // Claim the (only) page is shorter than it actually is, so the zstd decoder runs out of
+        // input mid-frame.
+        let page_header_start = (serialized_size(&Header::default()).unwrap()
+            + PackedRTree::new(4, DEFAULT_BRANCHING_FACTOR).index_size())
+            as usize;
+        let encoded_len_bytes = &mut bytes[page_header_start..page_header_start + 4];
+        let encoded_page_length = u32::from_le_bytes(encoded_len_bytes.try_into().unwrap());
+        encoded_len_bytes.copy_from_slice(&(encoded_page_length - 4).to_le_bytes());
+
+        let mut reader = HttpReader::test_reader(&bytes).await.unwrap();
+        let stream = reader.select_all().await.unwrap();
+        let results: Vec<Result<Feature>> = stream.collect().await;
+        assert!(results.iter().any(|result| result.is_err()));
+    }
+
     #[tokio::test]
     async fn bbox_compressed_larger_file() {
         ensure_logging();
@@ -801,4 +2100,149 @@ mod test {
         }
         assert_eq!(count, 140);
     }
+
+    #[tokio::test]
+    async fn retries() {
+        use crate::test_data::Failure;
+        ensure_logging();
+
+        let bytes = crate::test_data::points(1000, true);
+        let url = crate::test_data::flaky_server(
+            bytes.clone(),
+            vec![
+                // opening the file
+                Some(Failure::Status(503)),
+                None,
+                // the open ended request of select_all
+                Some(Failure::Truncate),
+            ],
+        )
+        .await;
+        let config = HttpReaderConfig {
+            retry_policy: RetryPolicy {
+                max_attempts: 2,
+                initial_backoff: std::time::Duration::from_millis(1),
+                max_backoff: std::time::Duration::from_millis(1),
+            },
+            ..Default::default()
+        };
+        let mut reader = HttpReader::open_with_config(&url, config).await.unwrap();
+        let features: Vec<_> = reader
+            .select_all()
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        let expected: Vec<_> = Reader::new(&*bytes)
+            .unwrap()
+            .select_all()
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(features, expected);
+    }
+
+    #[tokio::test]
+    async fn bbox_retries() {
+        use crate::test_data::Failure;
+        ensure_logging();
+
+        let bytes = crate::test_data::points(1000, true);
+        let bounds = wkt!(RECT(10 10,20 20));
+        let expected: Vec<_> = Reader::new(&*bytes)
+            .unwrap()
+            .select_bbox(&bounds)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(expected.len(), 11);
+
+        // Cut off each request in turn, including those for the pages, which are read from the
+        // same responses as their page headers.
+        for truncated_request in 0..8 {
+            let mut failures = vec![None; truncated_request];
+            failures.push(Some(Failure::Truncate));
+            let url = crate::test_data::flaky_server(bytes.clone(), failures).await;
+            let config = HttpReaderConfig {
+                retry_policy: RetryPolicy {
+                    max_attempts: 2,
+                    initial_backoff: std::time::Duration::from_millis(1),
+                    max_backoff: std::time::Duration::from_millis(1),
+                },
+                ..Default::default()
+            };
+            let mut reader = HttpReader::open_with_config(&url, config).await.unwrap();
+            let features: Vec<_> = reader
+                .select_bbox(&bounds)
+                .await
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(features, expected, "truncated request {truncated_request}");
+        }
+    }
+
+    #[tokio::test]
+    async fn doesnt_retry_missing_file() {
+        let bytes = crate::test_data::points(10, true);
+        let url = crate::test_data::flaky_server(
+            bytes,
+            vec![Some(crate::test_data::Failure::Status(404))],
+        )
+        .await;
+        let error = HttpReader::open(&url).await.unwrap_err();
+        assert!(matches!(
+            error,
+            Error::HTTP(streaming_http_range_client::Error::HttpFailed { status: 404 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn unexpected_content_encoding() {
+        use crate::test_data::Failure;
+
+        // Brotli has no magic number, so this is only recognized by the response being longer than
+        // requested.
+        let compressed = b"\x1b\x07\x00\xf8 not a geomedea file".to_vec();
+        let url = crate::test_data::flaky_server(
+            compressed.clone(),
+            // Opening the file, and then checking its encoding.
+            vec![
+                Some(Failure::ContentEncoding("br")),
+                Some(Failure::ContentEncoding("br")),
+            ],
+        )
+        .await;
+        let error = HttpReader::open(&url).await.unwrap_err();
+        assert!(
+            matches!(&error, Error::UnexpectedContentEncoding(found) if found == "br"),
+            "{error:?}"
+        );
+
+        let gzip = b"\x1f\x8b\x08\x00 not a geomedea file".to_vec();
+        let url = crate::test_data::flaky_server(
+            gzip,
+            vec![
+                Some(Failure::ContentEncoding("gzip")),
+                Some(Failure::ContentEncoding("gzip")),
+            ],
+        )
+        .await;
+        let error = HttpReader::open(&url).await.unwrap_err();
+        assert!(
+            matches!(&error, Error::UnexpectedContentEncoding(found) if found == "gzip"),
+            "{error:?}"
+        );
+
+        // Served as requested, it's just not a geomedea file.
+        let url = crate::test_data::flaky_server(compressed, vec![]).await;
+        let error = HttpReader::open(&url).await.unwrap_err();
+        assert!(
+            matches!(error, Error::UnsupportedFormat { found: None, .. }),
+            "{error:?}"
+        );
+    }
 }
@@ -5,7 +5,7 @@ use std::ops::Range;
 
 use crate::io::CountingReader;
 use crate::packed_r_tree::{Node, PackedRTree};
-use crate::{deserialize_from, Feature, Header, PageHeader, Result};
+use crate::{deserialize_from, Bounds, Feature, Header, PageHeader, Result};
 
 struct CountingDeserializer<'a> {
     counting_reader: CountingReader<&'a [u8]>,
@@ -26,18 +26,15 @@ impl<'a> CountingDeserializer<'a> {
 
     fn deserialize_index(
         &mut self,
-        byte_len: usize,
-        num_leaf_nodes: u64,
+        tree: PackedRTree,
         label: impl Into<String>,
     ) -> Result<Counted<'a, IndexInspector>> {
+        let byte_len = tree.index_size() as usize;
         let start = self.counting_reader.total_bytes_read() as usize;
         let mut bytes = vec![0; byte_len];
         self.counting_reader.read_exact(&mut bytes)?;
         let location = start..self.counting_reader.total_bytes_read() as usize;
-        let item = IndexInspector {
-            tree: PackedRTree::new(num_leaf_nodes),
-            bytes,
-        };
+        let item = IndexInspector { tree, bytes };
         Ok(Counted {
             label: label.into(),
             item,
@@ -134,6 +131,8 @@ impl<T: Debug> Debug for Counted<'_, T> {
 
 struct InspectedPage<'a> {
     page_header: Counted<'a, PageHeader>,
+    page_bounds: Counted<'a, Bounds>,
+    page_checksum: Option<Counted<'a, u64>>,
     // (feature size, feature)
     features: Vec<(Counted<'a, u64>, Counted<'a, Feature>)>,
 }
@@ -150,17 +149,20 @@ impl<'a> Inspector<'a> {
         let mut deserializer = CountingDeserializer::new(bytes);
 
         let header = deserializer.deserialize::<Header>("header")?;
-        let tree = PackedRTree::new(header.item.feature_count);
-        let index = deserializer.deserialize_index(
-            tree.index_size() as usize,
-            header.item.feature_count,
-            "index",
-        )?;
+        let tree = PackedRTree::new(header.item.feature_count, header.item.branching_factor);
+        let index = deserializer.deserialize_index(tree, "index")?;
 
         let mut pages = vec![];
         for page_idx in 0..header.item.page_count {
             let page_header =
                 deserializer.deserialize::<PageHeader>(format!("page #{page_idx}"))?;
+            let page_bounds =
+                deserializer.deserialize::<Bounds>(format!("page #{page_idx} bounds"))?;
+            let page_checksum = if header.item.has_page_checksums {
+                Some(deserializer.deserialize::<u64>(format!("page #{page_idx} checksum"))?)
+            } else {
+                None
+            };
             let mut features = vec![];
             for feature_idx in 0..page_header.item.feature_count() {
                 let feature_size =
@@ -171,6 +173,8 @@ impl<'a> Inspector<'a> {
             }
             pages.push(InspectedPage {
                 page_header,
+                page_bounds,
+                page_checksum,
                 features,
             });
         }
@@ -190,10 +194,16 @@ impl Debug for Inspector<'_> {
         writeln!(f, "{:?}", self.index)?;
         for InspectedPage {
             page_header,
+            page_bounds,
+            page_checksum,
             features,
         } in &self.pages
         {
             writeln!(f, "{page_header:?}")?;
+            writeln!(f, "{page_bounds:?}")?;
+            if let Some(page_checksum) = page_checksum {
+                writeln!(f, "{page_checksum:?}")?;
+            }
             for (feature_len, feature) in features {
                 writeln!(f, "{feature_len:?}")?;
                 writeln!(f, "{feature:?}")?;
@@ -241,31 +251,40 @@ mod tests {
         let output = format!("{:?}", inspector);
         println!("{}", output);
         let expected = r#"== header ==
-0..17 (17 bytes): 0x[00, 02, 00, 00, 00, 00, 00, 00, 00, 03, 00, 00, 00, 00, 00, 00, 00]:
+0..41 (41 bytes): 0x[67, 6D, 64, 65, 61, 00, 01, 00, 00, 02, 00, 00, 00, 00, 00, 00, 00, 03, 00, 00, 00, 00, 00, 00, 00, 80, 69, 67, FF, 00, D3, CE, FE, 80, 77, 8E, 06, 00, 0E, 27, 07]:
 Header {
-    is_compressed: false,
+    compression: None,
     page_count: 2,
     feature_count: 3,
+    branching_factor: 16,
+    coord_precision_nanos: 100,
+    has_page_checksums: false,
+    extent: RECT(-1 -2,11 12),
+    dictionary: None,
 }
 == index ==
-17..129 (112 bytes): 0x[80, 69, 67, FF, 00, D3, CE, FE, 80, 77, 8E, 06, 00, 0E, 27, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 80, 77, 8E, 06, 00, 0E, 27, 07, 80, 77, 8E, 06, 00, 0E, 27, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 80, 96, 98, 00, 00, 2D, 31, 01, 80, 96, 98, 00, 00, 2D, 31, 01, 00, 00, 00, 00, 00, 00, 00, 00, 40, 00, 00, 00, 80, 69, 67, FF, 00, D3, CE, FE, 80, 69, 67, FF, 00, D3, CE, FE, 8C, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]:
+41..153 (112 bytes): 0x[80, 69, 67, FF, 00, D3, CE, FE, 80, 77, 8E, 06, 00, 0E, 27, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 80, 77, 8E, 06, 00, 0E, 27, 07, 80, 77, 8E, 06, 00, 0E, 27, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 80, 96, 98, 00, 00, 2D, 31, 01, 80, 96, 98, 00, 00, 2D, 31, 01, 00, 00, 00, 00, 00, 00, 00, 00, 40, 00, 00, 00, 80, 69, 67, FF, 00, D3, CE, FE, 80, 69, 67, FF, 00, D3, CE, FE, 9C, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]:
 level 0: 0x[80, 69, 67, FF, 00, D3, CE, FE, 80, 77, 8E, 06, 00, 0E, 27, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]
 Node { bounds: RECT(-1 -2,11 12), offset: FeatureLocation { page_starting_offset: 0, feature_offset: 0 } }
-level 1: 0x[80, 77, 8E, 06, 00, 0E, 27, 07, 80, 77, 8E, 06, 00, 0E, 27, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 80, 96, 98, 00, 00, 2D, 31, 01, 80, 96, 98, 00, 00, 2D, 31, 01, 00, 00, 00, 00, 00, 00, 00, 00, 40, 00, 00, 00, 80, 69, 67, FF, 00, D3, CE, FE, 80, 69, 67, FF, 00, D3, CE, FE, 8C, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]
-Node { bounds: RECT(11 12,11 12), offset: FeatureLocation { page_starting_offset: 0, feature_offset: 0 } }, Node { bounds: RECT(1 2,1 2), offset: FeatureLocation { page_starting_offset: 0, feature_offset: 64 } }, Node { bounds: RECT(-1 -2,-1 -2), offset: FeatureLocation { page_starting_offset: 140, feature_offset: 0 } }
+level 1: 0x[80, 77, 8E, 06, 00, 0E, 27, 07, 80, 77, 8E, 06, 00, 0E, 27, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 80, 96, 98, 00, 00, 2D, 31, 01, 80, 96, 98, 00, 00, 2D, 31, 01, 00, 00, 00, 00, 00, 00, 00, 00, 40, 00, 00, 00, 80, 69, 67, FF, 00, D3, CE, FE, 80, 69, 67, FF, 00, D3, CE, FE, 9C, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]
+Node { bounds: RECT(11 12,11 12), offset: FeatureLocation { page_starting_offset: 0, feature_offset: 0 } }, Node { bounds: RECT(1 2,1 2), offset: FeatureLocation { page_starting_offset: 0, feature_offset: 64 } }, Node { bounds: RECT(-1 -2,-1 -2), offset: FeatureLocation { page_starting_offset: 156, feature_offset: 0 } }
 
 == page #0 ==
-129..141 (12 bytes): 0x[80, 00, 00, 00, 80, 00, 00, 00, 02, 00, 00, 00]:
+153..165 (12 bytes): 0x[80, 00, 00, 00, 80, 00, 00, 00, 02, 00, 00, 00]:
 PageHeader {
     encoded_page_length: 128,
     decoded_page_length: 128,
     feature_count: 2,
+    checksum: None,
 }
+== page #0 bounds ==
+165..181 (16 bytes): 0x[80, 96, 98, 00, 00, 2D, 31, 01, 80, 77, 8E, 06, 00, 0E, 27, 07]:
+RECT(1 2,11 12)
 == feature #0 len ==
-141..149 (8 bytes): 0x[38, 00, 00, 00, 00, 00, 00, 00]:
+181..189 (8 bytes): 0x[38, 00, 00, 00, 00, 00, 00, 00]:
 56
 == feature #0 ==
-149..205 (56 bytes): 0x[00, 00, 00, 00, 80, 77, 8E, 06, 00, 0E, 27, 07, 01, 00, 00, 00, 00, 00, 00, 00, 09, 00, 00, 00, 00, 00, 00, 00, 73, 6F, 6D, 65, 5F, 70, 72, 6F, 70, 0C, 00, 00, 00, 07, 00, 00, 00, 00, 00, 00, 00, 76, 61, 6C, 75, 65, 2D, 31]:
+189..245 (56 bytes): 0x[00, 00, 00, 00, 80, 77, 8E, 06, 00, 0E, 27, 07, 01, 00, 00, 00, 00, 00, 00, 00, 09, 00, 00, 00, 00, 00, 00, 00, 73, 6F, 6D, 65, 5F, 70, 72, 6F, 70, 0C, 00, 00, 00, 07, 00, 00, 00, 00, 00, 00, 00, 76, 61, 6C, 75, 65, 2D, 31]:
 Feature {
     geometry: POINT(11 12),
     properties: Properties {
@@ -275,10 +294,10 @@ Feature {
     },
 }
 == feature #1 len ==
-205..213 (8 bytes): 0x[38, 00, 00, 00, 00, 00, 00, 00]:
+245..253 (8 bytes): 0x[38, 00, 00, 00, 00, 00, 00, 00]:
 56
 == feature #1 ==
-213..269 (56 bytes): 0x[00, 00, 00, 00, 80, 96, 98, 00, 00, 2D, 31, 01, 01, 00, 00, 00, 00, 00, 00, 00, 09, 00, 00, 00, 00, 00, 00, 00, 73, 6F, 6D, 65, 5F, 70, 72, 6F, 70, 0C, 00, 00, 00, 07, 00, 00, 00, 00, 00, 00, 00, 76, 61, 6C, 75, 65, 2D, 30]:
+253..309 (56 bytes): 0x[00, 00, 00, 00, 80, 96, 98, 00, 00, 2D, 31, 01, 01, 00, 00, 00, 00, 00, 00, 00, 09, 00, 00, 00, 00, 00, 00, 00, 73, 6F, 6D, 65, 5F, 70, 72, 6F, 70, 0C, 00, 00, 00, 07, 00, 00, 00, 00, 00, 00, 00, 76, 61, 6C, 75, 65, 2D, 30]:
 Feature {
     geometry: POINT(1 2),
     properties: Properties {
@@ -288,17 +307,21 @@ Feature {
     },
 }
 == page #1 ==
-269..281 (12 bytes): 0x[40, 00, 00, 00, 40, 00, 00, 00, 01, 00, 00, 00]:
+309..321 (12 bytes): 0x[40, 00, 00, 00, 40, 00, 00, 00, 01, 00, 00, 00]:
 PageHeader {
     encoded_page_length: 64,
     decoded_page_length: 64,
     feature_count: 1,
+    checksum: None,
 }
+== page #1 bounds ==
+321..337 (16 bytes): 0x[80, 69, 67, FF, 00, D3, CE, FE, 80, 69, 67, FF, 00, D3, CE, FE]:
+RECT(-1 -2,-1 -2)
 == feature #0 len ==
-281..289 (8 bytes): 0x[38, 00, 00, 00, 00, 00, 00, 00]:
+337..345 (8 bytes): 0x[38, 00, 00, 00, 00, 00, 00, 00]:
 56
 == feature #0 ==
-289..345 (56 bytes): 0x[00, 00, 00, 00, 80, 69, 67, FF, 00, D3, CE, FE, 01, 00, 00, 00, 00, 00, 00, 00, 09, 00, 00, 00, 00, 00, 00, 00, 73, 6F, 6D, 65, 5F, 70, 72, 6F, 70, 0C, 00, 00, 00, 07, 00, 00, 00, 00, 00, 00, 00, 76, 61, 6C, 75, 65, 2D, 32]:
+345..401 (56 bytes): 0x[00, 00, 00, 00, 80, 69, 67, FF, 00, D3, CE, FE, 01, 00, 00, 00, 00, 00, 00, 00, 09, 00, 00, 00, 00, 00, 00, 00, 73, 6F, 6D, 65, 5F, 70, 72, 6F, 70, 0C, 00, 00, 00, 07, 00, 00, 00, 00, 00, 00, 00, 76, 61, 6C, 75, 65, 2D, 32]:
 Feature {
     geometry: POINT(-1 -2),
     properties: Properties {
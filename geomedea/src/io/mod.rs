@@ -1,4 +1,5 @@
-pub(crate) mod async_ruszstd;
+use crate::Compression;
+
 mod counting_reader;
 pub use counting_reader::CountingReader;
 
@@ -6,3 +7,115 @@ pub use counting_reader::CountingReader;
 mod counting_writer;
 #[cfg(feature = "writer")]
 pub use counting_writer::CountingWriter;
+
+/// Lengths read from a file aren't trusted for allocating up front, beyond this many bytes.
+const MAX_UNTRUSTED_CAPACITY: u64 = 1024 * 1024;
+
+/// The initial capacity of a buffer for `len` bytes, where `len` was read from a file, which
+/// might be corrupt or malicious.
+pub(crate) fn untrusted_capacity(len: u64) -> usize {
+    len.min(MAX_UNTRUSTED_CAPACITY) as usize
+}
+
+/// Reads exactly `len` bytes, where `len` was read from a file, without allocating them all up
+/// front.
+pub(crate) async fn read_untrusted_len<R: crate::asyncio::AsyncRead + Unpin>(
+    reader: &mut R,
+    len: u64,
+) -> std::io::Result<Vec<u8>> {
+    use crate::asyncio::AsyncReadExt;
+
+    let mut bytes = Vec::with_capacity(untrusted_capacity(len));
+    reader.take(len).read_to_end(&mut bytes).await?;
+    if bytes.len() as u64 != len {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            format!("expected {len} bytes, but only {} remain", bytes.len()),
+        ));
+    }
+    Ok(bytes)
+}
+
+/// A zstd frame decoder, which can also decode frames compressed with `dictionary`.
+pub(crate) fn zstd_frame_decoder(
+    dictionary: Option<&[u8]>,
+) -> std::io::Result<ruzstd::FrameDecoder> {
+    let invalid_data =
+        |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+    let mut frame_decoder = ruzstd::FrameDecoder::new();
+    if let Some(dictionary) = dictionary {
+        // PERF: The dictionary is decoded again for every page.
+        let dictionary = ruzstd::decoding::dictionary::Dictionary::decode_dict(dictionary)
+            .map_err(|e| invalid_data(format!("Invalid zstd dictionary: {e:?}")))?;
+        frame_decoder
+            .add_dict(dictionary)
+            .map_err(|e| invalid_data(format!("Error adding zstd dictionary: {e:?}")))?;
+    }
+    Ok(frame_decoder)
+}
+
+/// Decodes a whole page at once, which was encoded with `compression`, checking that it decodes
+/// to `decoded_page_length` bytes.
+pub(crate) fn decode_page(
+    encoded: &[u8],
+    compression: Compression,
+    decoded_page_length: u32,
+    dictionary: Option<&[u8]>,
+) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut decoded = Vec::with_capacity(untrusted_capacity(decoded_page_length as u64));
+    // Empty pages (of empty files) have no content to decode, not even an empty frame.
+    if decoded_page_length > 0 {
+        match compression {
+            Compression::None => decoded.extend_from_slice(encoded),
+            Compression::Zstd => {
+                ruzstd::StreamingDecoder::new_with_decoder(
+                    encoded,
+                    zstd_frame_decoder(dictionary)?,
+                )
+                .map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Error creating ZstdDecoder: {e:?}"),
+                    )
+                })?
+                .read_to_end(&mut decoded)?;
+            }
+            Compression::Lz4 => {
+                lz4_flex::frame::FrameDecoder::new(encoded).read_to_end(&mut decoded)?;
+            }
+            Compression::Brotli => {
+                brotli_decompressor::Decompressor::new(encoded, 4096).read_to_end(&mut decoded)?;
+            }
+        }
+    }
+    if decoded.len() != decoded_page_length as usize {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "page decoded to {} bytes, expected {decoded_page_length}",
+                decoded.len()
+            ),
+        ));
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn read_untrusted_len_of_truncated_input() {
+        let mut input: &[u8] = &[1, 2, 3];
+        let error = read_untrusted_len(&mut input, u32::MAX as u64)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+
+        let mut input: &[u8] = &[1, 2, 3];
+        assert_eq!(read_untrusted_len(&mut input, 2).await.unwrap(), [1, 2]);
+        assert_eq!(input, [3]);
+    }
+}
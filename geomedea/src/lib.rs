@@ -1,17 +1,25 @@
 #[macro_use]
 extern crate log;
 
+mod async_reader;
+pub use async_reader::AsyncReader;
 mod bounds;
 mod error;
 mod feature;
+#[cfg(feature = "geojson")]
+pub mod geojson;
 mod geometry;
 mod http_reader;
-pub use http_reader::{FeatureStream, HttpReader};
+pub use http_reader::{FeatureStream, FeatureStreamHandle, HttpReader, HttpReaderConfig};
 mod format;
 pub mod inspector;
 pub(crate) mod io;
+mod manifest;
 mod packed_r_tree;
+mod projection;
 mod reader;
+mod retry;
+pub use retry::RetryPolicy;
 #[cfg(test)]
 #[cfg(feature = "writer")]
 mod test_data;
@@ -19,25 +27,79 @@ mod wkt;
 
 #[cfg(feature = "writer")]
 mod writer;
+#[cfg(all(feature = "parallel", target_arch = "wasm32"))]
+compile_error!("the `parallel` feature isn't supported on wasm32");
 
 pub use bounds::Bounds;
 pub use error::{Error, Result};
-use format::{FeatureLocation, PageHeader};
+pub use format::FeatureLocation;
+use format::PageHeader;
+#[cfg(feature = "geo-types")]
+pub use geometry::ToGeoType;
 pub use geometry::{
-    Geometry, GeometryCollection, LineString, LngLat, MultiLineString, MultiPoint, MultiPolygon,
-    Point, Polygon,
+    Geometry, GeometryCollection, GeometryType, LineString, LngLat, MultiLineString, MultiPoint,
+    MultiPolygon, Point, Polygon, ValidationError, WindingOrder,
 };
-pub use reader::{FeatureIter, Reader};
+pub use manifest::{read_manifest, Manifest};
+pub use packed_r_tree::Node;
+#[cfg(feature = "geo-types")]
+pub use reader::GeoFeatureIter;
+pub use reader::{
+    FeatureIter, FileInfo, FileSection, GeometryTypesIter, PropertiesIter, Reader,
+    ReverseFeatureIter,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use reader::{MmapFeatureIter, MmapReader};
 #[cfg(feature = "writer")]
-pub use writer::Writer;
+pub use writer::{
+    suggest_page_size, transcode, PageSizeTarget, WriteStats, Writer, ZstdDictionary,
+};
 
 #[cfg(target_arch = "wasm32")]
 use futures_util::io as asyncio;
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::io as asyncio;
 
-pub use crate::feature::{Feature, Properties, PropertyValue};
+/// `Send`, because the `send` feature is enabled.
+#[cfg(all(feature = "send", not(target_arch = "wasm32")))]
+pub trait MaybeSend: Send {}
+#[cfg(all(feature = "send", not(target_arch = "wasm32")))]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// `Send` with the `send` feature on native targets, otherwise implemented for every type.
+///
+/// Futures on wasm32 needn't be `Send`, and many of its types, e.g. those of `wasm-bindgen`,
+/// aren't, so by default [`FeatureStream`] and the futures of [`HttpReader`] and [`AsyncReader`]
+/// queries aren't `Send` either. With the `send` feature, they are, so they can be moved between
+/// threads, e.g. spawned onto a multithreaded runtime, but the source of an [`AsyncReader`] must
+/// then also be `Send`.
+#[cfg(not(all(feature = "send", not(target_arch = "wasm32"))))]
+pub trait MaybeSend {}
+#[cfg(not(all(feature = "send", not(target_arch = "wasm32"))))]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// A boxed stream, which is `Send` when [`MaybeSend`] is.
+#[cfg(all(feature = "send", not(target_arch = "wasm32")))]
+type MaybeSendBoxStream<'a, T> = futures_util::stream::BoxStream<'a, T>;
+#[cfg(not(all(feature = "send", not(target_arch = "wasm32"))))]
+type MaybeSendBoxStream<'a, T> = futures_util::stream::LocalBoxStream<'a, T>;
+
+fn box_stream<'a, T>(
+    stream: impl futures_util::Stream<Item = T> + MaybeSend + 'a,
+) -> MaybeSendBoxStream<'a, T> {
+    Box::pin(stream)
+}
+
+pub use crate::feature::{
+    Feature, FeatureBuilder, Properties, PropertyType, PropertyValue, Schema,
+};
+use geometry::{is_valid_coord_precision, COORD_PRECISION_NANOS};
+use packed_r_tree::DEFAULT_BRANCHING_FACTOR;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::Arc;
 
 // How large should we make each page of feature data
 // before starting a new page.
@@ -67,12 +129,465 @@ where
     Ok(bincode::deserialize_from(reader)?)
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+/// Like [`deserialize_from`], but with a [`serde::de::DeserializeSeed`].
+pub(crate) fn deserialize_from_seed<'de, R, S>(reader: R, seed: S) -> Result<S::Value>
+where
+    R: std::io::Read,
+    S: serde::de::DeserializeSeed<'de>,
+{
+    use bincode::Options;
+    // The same options used by `bincode::deserialize_from`
+    Ok(bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .deserialize_from_seed(seed, reader)?)
+}
+
+/// How the contents of each page are compressed.
+///
+/// Zstd pages are decoded as they're read, whereas a whole page compressed with any other codec is
+/// decoded at once, so is held in memory while its features are read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Zstd,
+    Lz4,
+    Brotli,
+}
+
+impl Compression {
+    /// The value stored in the header of files whose pages are compressed, see
+    /// [`HEADER_FLAG_IS_COMPRESSED`].
+    fn to_u8(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Zstd => 1,
+            Compression::Lz4 => 2,
+            Compression::Brotli => 3,
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Zstd),
+            2 => Some(Compression::Lz4),
+            3 => Some(Compression::Brotli),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Compression {
+    /// Zstd is the default codec for compressed files.
+    fn from(is_compressed: bool) -> Self {
+        if is_compressed {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Header {
-    is_compressed: bool,
+    compression: Compression,
     // REVIEW: do we need page count?
     page_count: u64,
     feature_count: u64,
+    branching_factor: u16,
+    coord_precision_nanos: i32,
+    /// Each page header and bounds is followed by a checksum of the page's contents.
+    has_page_checksums: bool,
+    /// The bounds of all the features, or empty if there are none.
+    extent: Bounds,
+    /// The zstd dictionary every page was compressed with, if any.
+    dictionary: Option<Arc<[u8]>>,
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Self {
+            compression: Compression::None,
+            page_count: 0,
+            feature_count: 0,
+            branching_factor: DEFAULT_BRANCHING_FACTOR,
+            coord_precision_nanos: COORD_PRECISION_NANOS,
+            has_page_checksums: false,
+            extent: Bounds::empty(),
+            dictionary: None,
+        }
+    }
+}
+
+/// Every file starts with these bytes, followed by its [`FORMAT_VERSION`].
+const HEADER_MAGIC: [u8; 6] = *b"gmdea\0";
+/// Incremented whenever the file layout changes in a way which older readers can't detect.
+pub(crate) const FORMAT_VERSION: u16 = 1;
+
+// After the magic and format version, the first byte of the header is a set of flags.
+//
+// Originally the header started with `is_compressed: bool`, and had neither the magic nor the
+// format version, so those legacy files are rejected rather than misread.
+/// The pages are compressed, with the codec which follows the extent.
+const HEADER_FLAG_IS_COMPRESSED: u8 = 0b1;
+/// A (non-default) branching factor follows the feature count.
+const HEADER_FLAG_HAS_BRANCHING_FACTOR: u8 = 0b10;
+/// A (non-default) coordinate precision follows the branching factor (if any).
+const HEADER_FLAG_HAS_COORD_PRECISION: u8 = 0b100;
+/// A zstd dictionary, used to compress every page, follows the compression (if any). It's last, so
+/// everything before it can be read without reading the dictionary.
+const HEADER_FLAG_HAS_DICTIONARY: u8 = 0b1000;
+/// Each page's bounds are followed by a checksum of the page's encoded contents. This doesn't add
+/// any fields to the header.
+const HEADER_FLAG_HAS_PAGE_CHECKSUMS: u8 = 0b10000;
+const HEADER_KNOWN_FLAGS: u8 = HEADER_FLAG_IS_COMPRESSED
+    | HEADER_FLAG_HAS_BRANCHING_FACTOR
+    | HEADER_FLAG_HAS_COORD_PRECISION
+    | HEADER_FLAG_HAS_DICTIONARY
+    | HEADER_FLAG_HAS_PAGE_CHECKSUMS;
+
+impl Header {
+    /// The size of the largest possible header, i.e. with all optional fields present, not
+    /// counting the contents of the dictionary, see [`Header::read_without_dictionary`].
+    pub(crate) fn max_serialized_size() -> u64 {
+        let header = Header {
+            branching_factor: DEFAULT_BRANCHING_FACTOR + 1,
+            coord_precision_nanos: COORD_PRECISION_NANOS + 1,
+            compression: Compression::Lz4,
+            dictionary: Some(Arc::from(Vec::new())),
+            ..Default::default()
+        };
+        serialized_size(&header).expect("valid serialization size")
+    }
+
+    /// The size of the magic, format version and flags which start every header. The flags say
+    /// which optional fields follow, see [`Header::serialized_size_from_prefix`].
+    pub(crate) const PREFIX_SIZE: u64 = HEADER_MAGIC.len() as u64 + 2 + 1;
+
+    /// The size of the header which starts with `prefix`, its first [`Header::PREFIX_SIZE`]
+    /// bytes, not counting the contents of its dictionary, see [`Header::read_without_dictionary`].
+    ///
+    /// Unlike [`Header::max_serialized_size`], this is exact, so a reader which can't rewind can
+    /// read the header without reading past it into the index.
+    pub(crate) fn serialized_size_from_prefix(prefix: &[u8]) -> Result<u64> {
+        Self::check_format(prefix)?;
+        let Some(&flags) = prefix.get(Self::PREFIX_SIZE as usize - 1) else {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        };
+        let has_flag = |flag: u8| flags & flag != 0;
+        // Only the presence of each optional field matters, not its value.
+        let header = Header {
+            branching_factor: if has_flag(HEADER_FLAG_HAS_BRANCHING_FACTOR) {
+                DEFAULT_BRANCHING_FACTOR + 1
+            } else {
+                DEFAULT_BRANCHING_FACTOR
+            },
+            coord_precision_nanos: if has_flag(HEADER_FLAG_HAS_COORD_PRECISION) {
+                COORD_PRECISION_NANOS + 1
+            } else {
+                COORD_PRECISION_NANOS
+            },
+            compression: if has_flag(HEADER_FLAG_IS_COMPRESSED) {
+                Compression::Lz4
+            } else {
+                Compression::None
+            },
+            dictionary: has_flag(HEADER_FLAG_HAS_DICTIONARY).then(|| Arc::from(Vec::new())),
+            ..Default::default()
+        };
+        serialized_size(&header)
+    }
+
+    /// Like [`deserialize_from`], but first checks that this is a geomedea file of a supported
+    /// format version, returning [`Error::UnsupportedFormat`] if not.
+    pub(crate) fn read_from(reader: impl std::io::Read) -> Result<Header> {
+        let reader = Self::check_format(reader)?;
+        deserialize_from(reader)
+    }
+
+    /// Like [`Header::read_from`], but leaves the dictionary (if any) unread, so only the first
+    /// [`Header::max_serialized_size`] bytes of the file are needed. Returns the byte range of
+    /// the dictionary within the file, so it can be read separately.
+    pub(crate) fn read_without_dictionary(
+        reader: impl std::io::Read,
+    ) -> Result<(Header, Option<Range<u64>>)> {
+        let reader = Self::check_format(reader)?;
+        let (mut header, dictionary_len) = deserialize_from_seed(
+            reader,
+            HeaderVisitor {
+                read_dictionary: false,
+            },
+        )?;
+        let Some(dictionary_len) = dictionary_len else {
+            return Ok((header, None));
+        };
+        // The dictionary's contents follow its length, which is serialized like an empty one.
+        header.dictionary = Some(Arc::from(Vec::new()));
+        let dictionary_start = serialized_size(&header)?;
+        header.dictionary = None;
+        Ok((
+            header,
+            Some(dictionary_start..dictionary_start + dictionary_len),
+        ))
+    }
+
+    /// Returns `reader`, as if it were unread, after checking that it's a geomedea file of a
+    /// supported format version.
+    fn check_format(mut reader: impl std::io::Read) -> Result<impl std::io::Read> {
+        use std::io::Read;
+
+        let mut magic = [0u8; HEADER_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != HEADER_MAGIC {
+            if let Some(content_encoding) = detect_content_encoding(&magic) {
+                return Err(Error::UnexpectedContentEncoding(
+                    content_encoding.to_string(),
+                ));
+            }
+            return Err(Error::UnsupportedFormat {
+                found: None,
+                expected: FORMAT_VERSION,
+            });
+        }
+        let format_version: u16 = deserialize_from(&mut reader)?;
+        if format_version != FORMAT_VERSION {
+            return Err(Error::UnsupportedFormat {
+                found: Some(format_version),
+                expected: FORMAT_VERSION,
+            });
+        }
+        let prefix = [&magic[..], &format_version.to_le_bytes()[..]].concat();
+        Ok(std::io::Cursor::new(prefix).chain(reader))
+    }
+}
+
+/// The `Content-Encoding` which `bytes`, from the start of what should have been a geomedea file,
+/// appear to be compressed with, e.g. by a server or proxy which compresses its responses.
+///
+/// Only gzip and zstd have distinctive enough magic numbers: a zlib (deflate) header is only two
+/// bytes with a weak check, and brotli has none, so they're not detected here, but see
+/// [`HttpReader::open`], which can tell that a response was compressed by its length.
+fn detect_content_encoding(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        // The gzip magic number, followed by its only compression method, deflate.
+        [0x1F, 0x8B, 0x08, ..] => Some("gzip"),
+        [0x28, 0xB5, 0x2F, 0xFD, ..] => Some("zstd"),
+        _ => None,
+    }
+}
+
+impl Serialize for Header {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut flags = 0;
+        let mut field_count = 6;
+        let is_compressed = self.compression != Compression::None;
+        if is_compressed {
+            flags |= HEADER_FLAG_IS_COMPRESSED;
+            field_count += 1;
+        }
+        if self.has_page_checksums {
+            flags |= HEADER_FLAG_HAS_PAGE_CHECKSUMS;
+        }
+        let has_branching_factor = self.branching_factor != DEFAULT_BRANCHING_FACTOR;
+        if has_branching_factor {
+            flags |= HEADER_FLAG_HAS_BRANCHING_FACTOR;
+            field_count += 1;
+        }
+        let has_coord_precision = self.coord_precision_nanos != COORD_PRECISION_NANOS;
+        if has_coord_precision {
+            flags |= HEADER_FLAG_HAS_COORD_PRECISION;
+            field_count += 1;
+        }
+        if self.dictionary.is_some() {
+            flags |= HEADER_FLAG_HAS_DICTIONARY;
+            field_count += 1;
+        }
+
+        let mut tuple_serializer = serializer.serialize_tuple(field_count)?;
+        tuple_serializer.serialize_element(&HEADER_MAGIC)?;
+        tuple_serializer.serialize_element(&FORMAT_VERSION)?;
+        tuple_serializer.serialize_element(&flags)?;
+        tuple_serializer.serialize_element(&self.page_count)?;
+        tuple_serializer.serialize_element(&self.feature_count)?;
+        if has_branching_factor {
+            tuple_serializer.serialize_element(&self.branching_factor)?;
+        }
+        if has_coord_precision {
+            tuple_serializer.serialize_element(&self.coord_precision_nanos)?;
+        }
+        tuple_serializer.serialize_element(&self.extent)?;
+        if is_compressed {
+            tuple_serializer.serialize_element(&self.compression.to_u8())?;
+        }
+        if let Some(dictionary) = &self.dictionary {
+            // Length prefixed
+            tuple_serializer.serialize_element(&**dictionary)?;
+        }
+        tuple_serializer.end()
+    }
+}
+
+/// Deserializes a [`Header`], along with the length of its dictionary, if it has one.
+///
+/// Unless `read_dictionary` is set, the dictionary's contents are left unread.
+struct HeaderVisitor {
+    read_dictionary: bool,
+}
+
+impl<'de> de::DeserializeSeed<'de> for HeaderVisitor {
+    type Value = (Header, Option<u64>);
+
+    fn deserialize<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // The trailing optional fields are only read if their flag is set. The dictionary is read
+        // a byte at a time, so it counts as many fields.
+        deserializer.deserialize_tuple(usize::MAX, self)
+    }
+}
+
+impl<'de> Visitor<'de> for HeaderVisitor {
+    type Value = (Header, Option<u64>);
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a geomedea header")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let magic: [u8; 6] = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        if magic != HEADER_MAGIC {
+            return Err(de::Error::custom("not a geomedea file"));
+        }
+        let format_version: u16 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        if format_version != FORMAT_VERSION {
+            return Err(de::Error::custom(format!(
+                "unsupported format version: {format_version}"
+            )));
+        }
+        let flags: u8 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        if flags & !HEADER_KNOWN_FLAGS != 0 {
+            return Err(de::Error::custom(format!(
+                "unknown header flags: {flags:#010b}"
+            )));
+        }
+        let page_count = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+        let feature_count = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(4, &self))?;
+        let mut field_idx = 5;
+        let branching_factor = if flags & HEADER_FLAG_HAS_BRANCHING_FACTOR != 0 {
+            let branching_factor = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(field_idx, &self))?;
+            field_idx += 1;
+            branching_factor
+        } else {
+            DEFAULT_BRANCHING_FACTOR
+        };
+        let coord_precision_nanos = if flags & HEADER_FLAG_HAS_COORD_PRECISION != 0 {
+            let coord_precision_nanos = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(field_idx, &self))?;
+            field_idx += 1;
+            coord_precision_nanos
+        } else {
+            COORD_PRECISION_NANOS
+        };
+        let extent = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(field_idx, &self))?;
+        field_idx += 1;
+        let compression = if flags & HEADER_FLAG_IS_COMPRESSED != 0 {
+            let compression: u8 = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(field_idx, &self))?;
+            field_idx += 1;
+            match Compression::from_u8(compression) {
+                Some(Compression::None) | None => {
+                    return Err(de::Error::custom(format!(
+                        "unknown compression: {compression}"
+                    )));
+                }
+                Some(compression) => compression,
+            }
+        } else {
+            Compression::None
+        };
+        let mut dictionary = None;
+        let dictionary_len = if flags & HEADER_FLAG_HAS_DICTIONARY != 0 {
+            let dictionary_len: u64 = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(field_idx, &self))?;
+            if self.read_dictionary {
+                let mut bytes = Vec::with_capacity(crate::io::untrusted_capacity(dictionary_len));
+                for _ in 0..dictionary_len {
+                    let byte: u8 = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(field_idx, &self))?;
+                    bytes.push(byte);
+                }
+                dictionary = Some(Arc::from(bytes));
+            }
+            Some(dictionary_len)
+        } else {
+            None
+        };
+        if branching_factor < 2 {
+            return Err(de::Error::custom(format!(
+                "invalid branching factor: {branching_factor}"
+            )));
+        }
+        if !is_valid_coord_precision(coord_precision_nanos) {
+            return Err(de::Error::custom(format!(
+                "invalid coordinate precision: {coord_precision_nanos}"
+            )));
+        }
+        Ok((
+            Header {
+                compression,
+                page_count,
+                feature_count,
+                branching_factor,
+                coord_precision_nanos,
+                has_page_checksums: flags & HEADER_FLAG_HAS_PAGE_CHECKSUMS != 0,
+                extent,
+                dictionary,
+            },
+            dictionary_len,
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for Header {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let (header, _dictionary_len) = de::DeserializeSeed::deserialize(
+            HeaderVisitor {
+                read_dictionary: true,
+            },
+            deserializer,
+        )?;
+        Ok(header)
+    }
 }
 
 #[cfg(test)]
@@ -127,7 +642,7 @@ mod tests {
             let writer = Writer::new(&mut output, is_compressed).unwrap();
             writer.finish().unwrap();
         }
-        assert_eq!(output.len(), 29);
+        assert_eq!(output.len(), 53);
 
         let reader = Reader::new(output.as_slice()).unwrap();
         assert!(reader.select_all().unwrap().try_next().unwrap().is_none());
@@ -158,16 +673,308 @@ mod tests {
     #[test]
     fn serialize_header() {
         let header = Header {
-            is_compressed: false,
+            compression: Compression::None,
             page_count: 1,
             feature_count: 3,
+            branching_factor: DEFAULT_BRANCHING_FACTOR,
+            coord_precision_nanos: COORD_PRECISION_NANOS,
+            has_page_checksums: false,
+            extent: Bounds::empty(),
+            dictionary: None,
         };
         let mut output = vec![];
         serialize_into(&mut output, &header).unwrap();
         let expected: &[u8] = &[
+            0x67, 0x6D, 0x64, 0x65, 0x61, 0x00, 0x01, 0x00, // magic and format version
             0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0xFF, 0xFF, 0x7F, 0x00, 0x00, 0x00,
+            0x80, 0x00, 0x00, 0x00, 0x80,
+        ];
+        assert_eq!(expected, &output);
+        let prefix = &output[..Header::PREFIX_SIZE as usize];
+        assert_eq!(
+            Header::serialized_size_from_prefix(prefix).unwrap(),
+            output.len() as u64
+        );
+    }
+
+    #[test]
+    fn serialize_header_with_branching_factor() {
+        let header = Header {
+            compression: Compression::Zstd,
+            page_count: 1,
+            feature_count: 3,
+            branching_factor: 4,
+            coord_precision_nanos: COORD_PRECISION_NANOS,
+            has_page_checksums: false,
+            extent: Bounds::empty(),
+            dictionary: None,
+        };
+        let mut output = vec![];
+        serialize_into(&mut output, &header).unwrap();
+        let expected: &[u8] = &[
+            0x67, 0x6D, 0x64, 0x65, 0x61, 0x00, 0x01, 0x00, // magic and format version
+            0x03, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x04, 0x00, 0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0xFF, 0xFF, 0x7F, 0x00,
+            0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x80, 0x01,
+        ];
+        assert_eq!(expected, &output);
+
+        let round_trip: Header = deserialize_from(output.as_slice()).unwrap();
+        assert_eq!(round_trip.compression, Compression::Zstd);
+        assert_eq!(round_trip.page_count, 1);
+        assert_eq!(round_trip.feature_count, 3);
+        assert_eq!(round_trip.branching_factor, 4);
+    }
+
+    #[test]
+    fn deserialize_legacy_header() {
+        let input: &[u8] = &[
+            0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00,
         ];
+        assert!(deserialize_from::<_, Header>(input).is_err());
+        assert!(matches!(
+            Header::read_from(input),
+            Err(Error::UnsupportedFormat {
+                found: None,
+                expected: FORMAT_VERSION
+            })
+        ));
+    }
+
+    #[test]
+    fn unsupported_format() {
+        let header = Header::default();
+        let mut output = vec![];
+        serialize_into(&mut output, &header).unwrap();
+        assert_eq!(Header::read_from(output.as_slice()).unwrap().page_count, 0);
+
+        let mut future_version = output.clone();
+        future_version[6] = 2;
+        assert!(matches!(
+            Header::read_from(future_version.as_slice()),
+            Err(Error::UnsupportedFormat {
+                found: Some(2),
+                expected: FORMAT_VERSION
+            })
+        ));
+
+        let not_geomedea = b"{\"type\": \"FeatureCollection\"}";
+        assert!(matches!(
+            Reader::new(not_geomedea.as_slice()),
+            Err(Error::UnsupportedFormat { found: None, .. })
+        ));
+
+        let gzip = [0x1F, 0x8B, 0x08, 0x00, 0x00, 0x00];
+        match Reader::new(gzip.as_slice()) {
+            Err(Error::UnexpectedContentEncoding(found)) => assert_eq!(found, "gzip"),
+            other => panic!("expected gzip to be detected, got {other:?}"),
+        }
+
+        let zstd = [0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x00];
+        match Reader::new(zstd.as_slice()) {
+            Err(Error::UnexpectedContentEncoding(found)) => assert_eq!(found, "zstd"),
+            other => panic!("expected zstd to be detected, got {other:?}"),
+        }
+
+        // A zlib header is too weak a signal to report, lots of other data looks like one.
+        let deflate = [0x78, 0x9C, 0x4B, 0xCF, 0x4D, 0x49];
+        assert!(matches!(
+            Reader::new(deflate.as_slice()),
+            Err(Error::UnsupportedFormat { found: None, .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_header_flags() {
+        let header = Header::default();
+        let mut output = vec![];
+        serialize_into(&mut output, &header).unwrap();
+        output[8] = 0b100000;
+        assert!(deserialize_from::<_, Header>(output.as_slice()).is_err());
+    }
+
+    #[test]
+    fn serialize_header_with_extent() {
+        let header = Header {
+            page_count: 1,
+            feature_count: 3,
+            branching_factor: 4,
+            coord_precision_nanos: 10_000,
+            extent: wkt!(RECT(1 2,3 4)),
+            ..Default::default()
+        };
+        let mut output = vec![];
+        serialize_into(&mut output, &header).unwrap();
+        let expected: &[u8] = &[
+            0x67, 0x6D, 0x64, 0x65, 0x61, 0x00, 0x01, 0x00, // magic and format version
+            0x06, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x10, 0x27, 0x00, 0x00, 0x80, 0x96, 0x98, 0x00, 0x00,
+            0x2D, 0x31, 0x01, 0x80, 0xC3, 0xC9, 0x01, 0x00, 0x5A, 0x62, 0x02,
+        ];
+        assert_eq!(expected, &output);
+        // Plus the compression and the length of an empty dictionary
+        assert_eq!(Header::max_serialized_size(), output.len() as u64 + 1 + 8);
+        let prefix = &output[..Header::PREFIX_SIZE as usize];
+        assert_eq!(
+            Header::serialized_size_from_prefix(prefix).unwrap(),
+            output.len() as u64
+        );
+
+        let round_trip: Header = deserialize_from(output.as_slice()).unwrap();
+        assert_eq!(round_trip.coord_precision_nanos, 10_000);
+        assert_eq!(round_trip.extent, wkt!(RECT(1 2,3 4)));
+    }
+
+    #[test]
+    fn serialize_header_with_dictionary() {
+        let header = Header {
+            compression: Compression::Zstd,
+            page_count: 1,
+            feature_count: 3,
+            dictionary: Some(Arc::from([7, 8, 9].as_slice())),
+            ..Default::default()
+        };
+        let mut output = vec![];
+        serialize_into(&mut output, &header).unwrap();
+        let expected: &[u8] = &[
+            0x67, 0x6D, 0x64, 0x65, 0x61, 0x00, 0x01, 0x00, // magic and format version
+            0x09, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0xFF, 0xFF, 0x7F, 0x00, 0x00, 0x00,
+            0x80, 0x00, 0x00, 0x00, 0x80, 0x01, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x07, 0x08, 0x09,
+        ];
+        assert_eq!(expected, &output);
+
+        let round_trip = Header::read_from(output.as_slice()).unwrap();
+        assert_eq!(round_trip.dictionary.as_deref(), Some([7, 8, 9].as_slice()));
+
+        // Everything else can be read without the dictionary's contents.
+        let prefix = &output[..50];
+        let (without_dictionary, dictionary_range) =
+            Header::read_without_dictionary(prefix).unwrap();
+        assert_eq!(without_dictionary.feature_count, 3);
+        assert!(without_dictionary.dictionary.is_none());
+        assert_eq!(dictionary_range, Some(50..53));
+        assert_eq!(&output[50..53], &[7, 8, 9]);
+        assert_eq!(Header::serialized_size_from_prefix(&output).unwrap(), 50);
+    }
+
+    #[test]
+    fn serialize_header_with_compression() {
+        let header = Header {
+            compression: Compression::Brotli,
+            page_count: 1,
+            feature_count: 3,
+            ..Default::default()
+        };
+        let mut output = vec![];
+        serialize_into(&mut output, &header).unwrap();
+        let expected: &[u8] = &[
+            0x67, 0x6D, 0x64, 0x65, 0x61, 0x00, 0x01, 0x00, // magic and format version
+            0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0xFF, 0xFF, 0x7F, 0x00, 0x00, 0x00,
+            0x80, 0x00, 0x00, 0x00, 0x80, 0x03,
+        ];
+        assert_eq!(expected, &output);
+
+        let round_trip: Header = deserialize_from(output.as_slice()).unwrap();
+        assert_eq!(round_trip.compression, Compression::Brotli);
+
+        let mut unknown_compression = output.clone();
+        unknown_compression[41] = 9;
+        assert!(deserialize_from::<_, Header>(unknown_compression.as_slice()).is_err());
+
+        // Uncompressed files don't set the flag, rather than storing a codec of none.
+        let mut uncompressed = output.clone();
+        uncompressed[41] = 0;
+        assert!(deserialize_from::<_, Header>(uncompressed.as_slice()).is_err());
+    }
+
+    #[test]
+    fn compression_from_is_compressed() {
+        assert_eq!(Compression::from(false), Compression::None);
+        assert_eq!(Compression::from(true), Compression::Zstd);
+    }
+
+    #[test]
+    fn serialize_header_with_coord_precision() {
+        let header = Header {
+            compression: Compression::None,
+            page_count: 1,
+            feature_count: 3,
+            branching_factor: 4,
+            coord_precision_nanos: 10_000,
+            has_page_checksums: false,
+            extent: Bounds::empty(),
+            dictionary: None,
+        };
+        let mut output = vec![];
+        serialize_into(&mut output, &header).unwrap();
+        let expected: &[u8] = &[
+            0x67, 0x6D, 0x64, 0x65, 0x61, 0x00, 0x01, 0x00, // magic and format version
+            0x06, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x10, 0x27, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0x7F, 0xFF,
+            0xFF, 0xFF, 0x7F, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x80,
+        ];
         assert_eq!(expected, &output);
+
+        let round_trip: Header = deserialize_from(output.as_slice()).unwrap();
+        assert_eq!(round_trip.branching_factor, 4);
+        assert_eq!(round_trip.coord_precision_nanos, 10_000);
+    }
+
+    #[test]
+    fn roundtrip_with_coord_precision() {
+        let mut output = vec![];
+        {
+            let mut writer = Writer::new(&mut output, true).unwrap();
+            // ~1m
+            writer.set_coord_precision_nanos(10_000).unwrap();
+            let geometry = Geometry::from(wkt! { LINESTRING(1.2345678 2.3456789,3.000004 4) });
+            writer
+                .add_feature(&Feature::new(geometry, Properties::empty()))
+                .unwrap();
+            writer.finish().unwrap();
+        }
+
+        let reader = Reader::new(output.as_slice()).unwrap();
+        assert_eq!(reader.header().coord_precision_nanos, 10_000);
+        let mut features = reader.select_bbox(&wkt!(RECT(2.9 3.9,3.1 4.1))).unwrap();
+        let feature = features.try_next().unwrap().unwrap();
+        assert_eq!(
+            feature.geometry(),
+            &Geometry::from(LineString::new(vec![
+                LngLat::unscaled(12345700, 23456800),
+                LngLat::unscaled(30000000, 40000000),
+            ]))
+        );
+        assert!(features.try_next().unwrap().is_none());
+    }
+
+    #[test]
+    fn roundtrip_with_branching_factor() {
+        let mut output = vec![];
+        {
+            let mut writer = Writer::new(&mut output, false).unwrap();
+            writer.set_branching_factor(2).unwrap();
+            for idx in 0..10 {
+                let geometry = Geometry::from(LngLat::degrees(idx as f64, idx as f64));
+                writer
+                    .add_feature(&Feature::new(geometry, Properties::empty()))
+                    .unwrap();
+            }
+            writer.finish().unwrap();
+        }
+
+        let reader = Reader::new(output.as_slice()).unwrap();
+        assert_eq!(reader.header().branching_factor, 2);
+        let mut features = reader.select_bbox(&wkt!(RECT(2 2,4 4))).unwrap();
+        let mut count = 0;
+        while features.try_next().unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 3);
     }
 }
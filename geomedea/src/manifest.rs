@@ -0,0 +1,76 @@
+use crate::{Bounds, Header, Result};
+use std::io::Read;
+
+/// A cheap summary of a geomedea file, e.g. for catalogs which list many files.
+///
+/// Every geomedea file begins with its header, which includes the bounds of all of the file's
+/// features. So a file's manifest is always within its first [`Manifest::max_byte_len`] bytes, and
+/// there's no need for a separate sidecar file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    feature_count: u64,
+    bounds: Option<Bounds>,
+}
+
+impl Manifest {
+    pub fn feature_count(&self) -> u64 {
+        self.feature_count
+    }
+
+    /// The bounds of all the features in the file, or `None` if the file has no features.
+    pub fn bounds(&self) -> Option<&Bounds> {
+        self.bounds.as_ref()
+    }
+
+    /// The coordinate reference system of the file.
+    ///
+    /// Coordinates are always WGS84, in lng/lat order.
+    pub fn crs(&self) -> &'static str {
+        "EPSG:4326"
+    }
+
+    /// Reading this many bytes from the start of a file is always enough for [`read_manifest`].
+    pub fn max_byte_len() -> u64 {
+        Header::max_serialized_size()
+    }
+}
+
+/// Read the [`Manifest`] from the start of a geomedea file, without reading any features.
+///
+/// `reader` can be the entire file, or just its first [`Manifest::max_byte_len`] bytes.
+pub fn read_manifest(reader: impl Read) -> Result<Manifest> {
+    let (header, _dictionary_range) = Header::read_without_dictionary(reader)?;
+    let bounds = (header.feature_count > 0).then_some(header.extent);
+    Ok(Manifest {
+        feature_count: header.feature_count,
+        bounds,
+    })
+}
+
+#[cfg(test)]
+#[cfg(feature = "writer")]
+mod tests {
+    use super::*;
+    use crate::{test_data, wkt};
+
+    #[test]
+    fn manifest() {
+        let bytes = test_data::small_pages(4, true);
+        let manifest = read_manifest(bytes.as_slice()).unwrap();
+        assert_eq!(manifest.feature_count(), 4);
+        assert_eq!(manifest.bounds(), Some(&wkt!(RECT(0 0,3 3))));
+        assert_eq!(manifest.crs(), "EPSG:4326");
+
+        // The start of the file is enough
+        let prefix = &bytes[..Manifest::max_byte_len() as usize];
+        assert_eq!(read_manifest(prefix).unwrap(), manifest);
+    }
+
+    #[test]
+    fn empty_manifest() {
+        let bytes = test_data::points(0, false);
+        let manifest = read_manifest(bytes.as_slice()).unwrap();
+        assert_eq!(manifest.feature_count(), 0);
+        assert_eq!(manifest.bounds(), None);
+    }
+}
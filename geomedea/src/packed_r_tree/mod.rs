@@ -3,6 +3,8 @@ mod reader;
 mod writer;
 
 pub use reader::http::PackedRTreeHttpReader;
+pub(crate) use reader::http::DEFAULT_COMBINE_REQUEST_THRESHOLD;
+pub(crate) use reader::select_bboxes_in_slice;
 pub use reader::PackedRTreeReader;
 use std::cell::OnceCell;
 #[cfg(feature = "writer")]
@@ -10,13 +12,14 @@ pub use writer::PackedRTreeWriter;
 
 use crate::{Bounds, FeatureLocation};
 use serde::{Deserialize, Serialize};
-use std::cmp::min;
+use std::cmp::{min, Ordering};
 use std::fmt::Debug;
 use std::ops::Range;
 
-// TODO: make configurable and store in PackedRTree
-const BRANCHING_FACTOR: u64 = 16;
+/// Used unless the Writer was configured otherwise.
+pub(crate) const DEFAULT_BRANCHING_FACTOR: u16 = 16;
 
+/// A node of the spatial index, see [`Reader::index_nodes`](crate::Reader::index_nodes).
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Node {
     bounds: Bounds,
@@ -29,6 +32,16 @@ impl Node {
         Self { bounds, offset }
     }
 
+    /// For a leaf node, the bounds of its feature, otherwise the bounds of all its children.
+    pub fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    /// For a leaf node, where its feature is stored. This is meaningless for any other node.
+    pub fn feature_location(&self) -> &FeatureLocation {
+        &self.offset
+    }
+
     pub fn serialized_size() -> usize {
         let node_size = 28;
         #[cfg(debug_assertions)]
@@ -54,16 +67,50 @@ impl Node {
     }
 }
 
+/// A node queued during a nearest neighbor search.
+///
+/// Ordered so that a `BinaryHeap` pops the node closest to the search point first, with ties broken
+/// by node index so that results are deterministic.
+#[derive(Debug)]
+struct NearestCandidate {
+    distance_squared: u128,
+    node_idx: u64,
+    node: Node,
+}
+
+impl PartialEq for NearestCandidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for NearestCandidate {}
+
+impl PartialOrd for NearestCandidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NearestCandidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.distance_squared, other.node_idx).cmp(&(self.distance_squared, self.node_idx))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PackedRTree {
     num_leaf_nodes: u64,
+    branching_factor: u64,
     node_ranges_by_level: OnceCell<Vec<Range<u64>>>,
 }
 
 impl PackedRTree {
-    pub(crate) fn new(num_leaf_nodes: u64) -> Self {
+    pub(crate) fn new(num_leaf_nodes: u64, branching_factor: u16) -> Self {
+        debug_assert!(branching_factor >= 2);
         Self {
             num_leaf_nodes,
+            branching_factor: u64::from(branching_factor),
             node_ranges_by_level: OnceCell::new(),
         }
     }
@@ -82,8 +129,8 @@ impl PackedRTree {
 
         let mut level_nodes = self.num_leaf_nodes;
         while level_nodes > 1 {
-            let full_parents = level_nodes / BRANCHING_FACTOR;
-            if full_parents * BRANCHING_FACTOR == level_nodes {
+            let full_parents = level_nodes / self.branching_factor;
+            if full_parents * self.branching_factor == level_nodes {
                 level_nodes = full_parents;
             } else {
                 level_nodes = full_parents + 1;
@@ -94,6 +141,13 @@ impl PackedRTree {
         levels
     }
 
+    /// The size in bytes of the top `levels` levels of the tree, starting from the root, or the
+    /// whole tree if it's not that deep.
+    pub(crate) fn top_levels_size(&self, levels: usize) -> u64 {
+        let node_count: u64 = self.nodes_per_level().iter().take(levels).sum();
+        node_count * Node::serialized_size() as u64
+    }
+
     pub fn node_count(&self) -> u64 {
         self.nodes_per_level().iter().sum()
     }
@@ -142,9 +196,9 @@ impl PackedRTree {
         let parent_position_in_level = parent_position_in_level?;
         let child_level = range_iter.next()?;
 
-        let children_start = child_level.start + parent_position_in_level * BRANCHING_FACTOR;
+        let children_start = child_level.start + parent_position_in_level * self.branching_factor;
 
-        Some(children_start..min(children_start + BRANCHING_FACTOR, child_level.end))
+        Some(children_start..min(children_start + self.branching_factor, child_level.end))
     }
 
     fn level_for_node_idx(&self, node_idx: u64) -> usize {
@@ -185,7 +239,7 @@ mod tests {
 
     #[test]
     fn is_leaf_node() {
-        let tree = PackedRTree::new(257);
+        let tree = PackedRTree::new(257, 16);
         assert!(!tree.is_leaf_node(0));
         assert!(!tree.is_leaf_node(3));
         assert!(!tree.is_leaf_node(19));
@@ -195,65 +249,88 @@ mod tests {
 
     #[test]
     fn nodes_per_level() {
-        assert_eq!(Vec::<u64>::new(), PackedRTree::new(0).nodes_per_level());
-        assert_eq!(vec![1], PackedRTree::new(1).nodes_per_level());
-        assert_eq!(vec![1, 2], PackedRTree::new(2).nodes_per_level());
-        assert_eq!(vec![1, 4], PackedRTree::new(4).nodes_per_level());
-        assert_eq!(vec![1, 15], PackedRTree::new(15).nodes_per_level());
-        assert_eq!(vec![1, 16], PackedRTree::new(16).nodes_per_level());
-        assert_eq!(vec![1, 2, 17], PackedRTree::new(17).nodes_per_level());
-        assert_eq!(vec![1, 2, 32], PackedRTree::new(32).nodes_per_level());
-        assert_eq!(vec![1, 3, 33], PackedRTree::new(33).nodes_per_level());
-        assert_eq!(vec![1, 16, 255], PackedRTree::new(255).nodes_per_level());
-        assert_eq!(vec![1, 16, 256], PackedRTree::new(256).nodes_per_level());
-        assert_eq!(vec![1, 2, 17, 257], PackedRTree::new(257).nodes_per_level());
+        assert_eq!(Vec::<u64>::new(), PackedRTree::new(0, 16).nodes_per_level());
+        assert_eq!(vec![1], PackedRTree::new(1, 16).nodes_per_level());
+        assert_eq!(vec![1, 2], PackedRTree::new(2, 16).nodes_per_level());
+        assert_eq!(vec![1, 4], PackedRTree::new(4, 16).nodes_per_level());
+        assert_eq!(vec![1, 15], PackedRTree::new(15, 16).nodes_per_level());
+        assert_eq!(vec![1, 16], PackedRTree::new(16, 16).nodes_per_level());
+        assert_eq!(vec![1, 2, 17], PackedRTree::new(17, 16).nodes_per_level());
+        assert_eq!(vec![1, 2, 32], PackedRTree::new(32, 16).nodes_per_level());
+        assert_eq!(vec![1, 3, 33], PackedRTree::new(33, 16).nodes_per_level());
+        assert_eq!(
+            vec![1, 16, 255],
+            PackedRTree::new(255, 16).nodes_per_level()
+        );
+        assert_eq!(
+            vec![1, 16, 256],
+            PackedRTree::new(256, 16).nodes_per_level()
+        );
+        assert_eq!(
+            vec![1, 2, 17, 257],
+            PackedRTree::new(257, 16).nodes_per_level()
+        );
+    }
+
+    #[test]
+    fn nodes_per_level_with_branching_factor() {
+        assert_eq!(Vec::<u64>::new(), PackedRTree::new(0, 2).nodes_per_level());
+        assert_eq!(vec![1], PackedRTree::new(1, 2).nodes_per_level());
+        assert_eq!(vec![1, 2], PackedRTree::new(2, 2).nodes_per_level());
+        assert_eq!(vec![1, 2, 3], PackedRTree::new(3, 2).nodes_per_level());
+        assert_eq!(vec![1, 2, 4, 8], PackedRTree::new(8, 2).nodes_per_level());
+        assert_eq!(vec![1, 2, 8], PackedRTree::new(8, 4).nodes_per_level());
+        assert_eq!(vec![1, 3, 9], PackedRTree::new(9, 4).nodes_per_level());
     }
 
     #[test]
     fn byte_ranges_by_level() {
-        assert_eq!(vec![(0..28)], PackedRTree::new(1).byte_ranges_by_level());
+        assert_eq!(
+            vec![(0..28)],
+            PackedRTree::new(1, 16).byte_ranges_by_level()
+        );
         assert_eq!(
             vec![(0..28), (28..112)],
-            PackedRTree::new(3).byte_ranges_by_level()
+            PackedRTree::new(3, 16).byte_ranges_by_level()
         );
     }
     #[test]
     fn node_ranges_by_level() {
-        assert_eq!(vec![(0..1)], PackedRTree::new(1).node_ranges_by_level());
+        assert_eq!(vec![(0..1)], PackedRTree::new(1, 16).node_ranges_by_level());
         assert_eq!(
             vec![(0..1), (1..3)],
-            PackedRTree::new(2).node_ranges_by_level()
+            PackedRTree::new(2, 16).node_ranges_by_level()
         );
         assert_eq!(
             vec![(0..1), (1..17)],
-            PackedRTree::new(16).node_ranges_by_level()
+            PackedRTree::new(16, 16).node_ranges_by_level()
         );
         assert_eq!(
             vec![(0..1), (1..3), (3..20)],
-            PackedRTree::new(17).node_ranges_by_level()
+            PackedRTree::new(17, 16).node_ranges_by_level()
         );
         assert_eq!(
             vec![(0..1), (1..17), (17..273)],
-            PackedRTree::new(256).node_ranges_by_level()
+            PackedRTree::new(256, 16).node_ranges_by_level()
         );
         assert_eq!(
             vec![(0..1), (1..3), (3..20), (20..277)],
-            PackedRTree::new(257).node_ranges_by_level()
+            PackedRTree::new(257, 16).node_ranges_by_level()
         );
     }
 
     #[test]
     fn node_count() {
-        assert_eq!(1, PackedRTree::new(1).node_count());
-        assert_eq!(2 + 1, PackedRTree::new(2).node_count());
-        assert_eq!(16 + 1, PackedRTree::new(16).node_count());
-        assert_eq!(256 + 16 + 1, PackedRTree::new(256).node_count());
-        assert_eq!(257 + 17 + 2 + 1, PackedRTree::new(257).node_count());
+        assert_eq!(1, PackedRTree::new(1, 16).node_count());
+        assert_eq!(2 + 1, PackedRTree::new(2, 16).node_count());
+        assert_eq!(16 + 1, PackedRTree::new(16, 16).node_count());
+        assert_eq!(256 + 16 + 1, PackedRTree::new(256, 16).node_count());
+        assert_eq!(257 + 17 + 2 + 1, PackedRTree::new(257, 16).node_count());
     }
 
     #[test]
     fn level_for_node_idx() {
-        let tree = PackedRTree::new(250);
+        let tree = PackedRTree::new(250, 16);
         assert_eq!(tree.level_for_node_idx(17), 0);
         assert_eq!(tree.level_for_node_idx(266), 0);
         assert_eq!(tree.level_for_node_idx(16), 1);
@@ -266,28 +343,39 @@ mod tests {
 
         #[test]
         fn empty() {
-            assert_eq!(None, PackedRTree::new(0).children_range(0));
-            assert_eq!(None, PackedRTree::new(0).children_range(5));
+            assert_eq!(None, PackedRTree::new(0, 16).children_range(0));
+            assert_eq!(None, PackedRTree::new(0, 16).children_range(5));
         }
 
         #[test]
         fn single_node() {
-            assert_eq!(None, PackedRTree::new(1).children_range(0));
+            assert_eq!(None, PackedRTree::new(1, 16).children_range(0));
         }
 
         #[test]
         fn two_levels() {
-            assert_eq!(Some(1..3), PackedRTree::new(2).children_range(0));
-            assert_eq!(Some(1..4), PackedRTree::new(3).children_range(0));
-            assert_eq!(None, PackedRTree::new(3).children_range(2));
-            assert_eq!(None, PackedRTree::new(3).children_range(5));
+            assert_eq!(Some(1..3), PackedRTree::new(2, 16).children_range(0));
+            assert_eq!(Some(1..4), PackedRTree::new(3, 16).children_range(0));
+            assert_eq!(None, PackedRTree::new(3, 16).children_range(2));
+            assert_eq!(None, PackedRTree::new(3, 16).children_range(5));
         }
 
         #[test]
         fn three_levels() {
-            assert_eq!(Some(1..3), PackedRTree::new(17).children_range(0));
-            assert_eq!(Some(3..19), PackedRTree::new(17).children_range(1));
-            assert_eq!(Some(19..20), PackedRTree::new(17).children_range(2));
+            assert_eq!(Some(1..3), PackedRTree::new(17, 16).children_range(0));
+            assert_eq!(Some(3..19), PackedRTree::new(17, 16).children_range(1));
+            assert_eq!(Some(19..20), PackedRTree::new(17, 16).children_range(2));
+        }
+
+        #[test]
+        fn with_branching_factor() {
+            let tree = PackedRTree::new(8, 2);
+            assert_eq!(Some(1..3), tree.children_range(0));
+            assert_eq!(Some(3..5), tree.children_range(1));
+            assert_eq!(Some(5..7), tree.children_range(2));
+            assert_eq!(Some(7..9), tree.children_range(3));
+            assert_eq!(Some(13..15), tree.children_range(6));
+            assert_eq!(None, tree.children_range(7));
         }
     }
 }
@@ -1,8 +1,9 @@
 use crate::bounds::Bounds;
-use crate::packed_r_tree::{Node, PackedRTree};
+use crate::io::untrusted_capacity;
+use crate::packed_r_tree::{NearestCandidate, Node, PackedRTree};
 use crate::FeatureLocation;
-use crate::{deserialize_from, Result};
-use std::collections::VecDeque;
+use crate::{deserialize_from, LngLat, Result};
+use std::collections::{BinaryHeap, VecDeque};
 use std::io::Read;
 use std::ops::Range;
 
@@ -13,8 +14,8 @@ pub struct PackedRTreeReader<R> {
 }
 
 impl<R: Read> PackedRTreeReader<R> {
-    pub(crate) fn new(num_leaf_nodes: u64, read: R) -> Self {
-        let tree = PackedRTree::new(num_leaf_nodes);
+    pub(crate) fn new(num_leaf_nodes: u64, branching_factor: u16, read: R) -> Self {
+        let tree = PackedRTree::new(num_leaf_nodes, branching_factor);
         Self {
             read,
             tree,
@@ -22,25 +23,54 @@ impl<R: Read> PackedRTreeReader<R> {
         }
     }
 
-    pub fn select_bbox(mut self, bbox: &Bounds) -> Result<Vec<FeatureLocation>> {
-        if self.tree.num_leaf_nodes == 0 {
+    pub fn select_bbox(self, bbox: &Bounds) -> Result<Vec<FeatureLocation>> {
+        self.select_bboxes(std::slice::from_ref(bbox))
+    }
+
+    /// The locations of the features which intersect any of `bboxes`, each only once, found in a
+    /// single traversal of the tree.
+    ///
+    /// Locations are sorted in the order they're stored, so they can be read sequentially.
+    pub fn select_bboxes(mut self, bboxes: &[Bounds]) -> Result<Vec<FeatureLocation>> {
+        let tree = self.tree.clone();
+        search_bboxes(&tree, bboxes, |node_range| self.read_node_range(node_range))
+    }
+
+    /// The locations of the (up to) `k` features whose bounds are nearest to `point`, closest first.
+    ///
+    /// Distances are planar, measured in lng/lat space.
+    pub fn nearest(mut self, point: &LngLat, k: usize) -> Result<Vec<FeatureLocation>> {
+        if self.tree.num_leaf_nodes == 0 || k == 0 {
             return Ok(vec![]);
         }
 
-        let mut results = vec![];
-        let mut queue = VecDeque::new();
-        queue.push_back(0..1);
+        // Unlike a bbox search, which can skip over whole subtrees, we can't know ahead of time
+        // which nodes we'll need, and we can't seek backwards, so we read the entire index.
+        let nodes = self.read_node_range(0..self.tree.node_count())?;
 
-        while let Some(node_range) = queue.pop_front() {
-            for (node_idx, node) in self.read_node_range(node_range)? {
-                if !node.bounds.intersects(bbox) {
-                    continue;
+        let mut results = vec![];
+        let mut queue = BinaryHeap::new();
+        let (_, root) = nodes[0].clone();
+        queue.push(NearestCandidate {
+            distance_squared: root.bounds.distance_squared(point),
+            node_idx: 0,
+            node: root,
+        });
+
+        while let Some(candidate) = queue.pop() {
+            if self.tree.is_leaf_node(candidate.node_idx) {
+                results.push(candidate.node.offset);
+                if results.len() == k {
+                    break;
                 }
-                if self.tree.is_leaf_node(node_idx) {
-                    results.push(node.offset);
-                } else if let Some(children) = self.tree.children_range(node_idx) {
-                    // TODO: merge
-                    queue.push_back(children);
+            } else if let Some(children) = self.tree.children_range(candidate.node_idx) {
+                for child_idx in children {
+                    let (_, node) = nodes[child_idx as usize].clone();
+                    queue.push(NearestCandidate {
+                        distance_squared: node.bounds.distance_squared(point),
+                        node_idx: child_idx,
+                        node,
+                    });
                 }
             }
         }
@@ -48,7 +78,7 @@ impl<R: Read> PackedRTreeReader<R> {
         Ok(results)
     }
 
-    fn read_node_range(&mut self, nodes: Range<u64>) -> Result<Vec<(u64, Node)>> {
+    pub(crate) fn read_node_range(&mut self, nodes: Range<u64>) -> Result<Vec<(u64, Node)>> {
         assert!(self.node_position <= nodes.start);
         assert!(!nodes.is_empty());
 
@@ -58,8 +88,8 @@ impl<R: Read> PackedRTreeReader<R> {
         }
 
         assert!(nodes.end > nodes.start);
-        let len = (nodes.end - nodes.start) as usize;
-        let mut results = Vec::with_capacity(len);
+        // The node count comes from the header, so the index might be much shorter.
+        let mut results = Vec::with_capacity(untrusted_capacity(nodes.end - nodes.start));
         for node_idx in nodes.clone() {
             let node = deserialize_from(&mut self.read)?;
             self.node_position += 1;
@@ -71,47 +101,148 @@ impl<R: Read> PackedRTreeReader<R> {
     }
 }
 
+/// Like [`PackedRTreeReader::select_bboxes`], but for an index which is entirely in memory, e.g.
+/// memory mapped, so only the nodes which are visited are deserialized, rather than skipping
+/// past every node before them.
+pub(crate) fn select_bboxes_in_slice(
+    tree: &PackedRTree,
+    index: &[u8],
+    bboxes: &[Bounds],
+) -> Result<Vec<FeatureLocation>> {
+    let node_size = Node::serialized_size();
+    search_bboxes(tree, bboxes, |node_range| {
+        node_range
+            .map(|node_idx| {
+                let node_start = node_idx as usize * node_size;
+                let node = deserialize_from(index.get(node_start..).unwrap_or_default())?;
+                Ok((node_idx, node))
+            })
+            .collect()
+    })
+}
+
+/// The search of [`PackedRTreeReader::select_bboxes`] and [`select_bboxes_in_slice`], which differ
+/// only in how they read each range of nodes, which are visited in ascending order.
+fn search_bboxes(
+    tree: &PackedRTree,
+    bboxes: &[Bounds],
+    mut read_node_range: impl FnMut(Range<u64>) -> Result<Vec<(u64, Node)>>,
+) -> Result<Vec<FeatureLocation>> {
+    if tree.num_leaf_nodes == 0 {
+        return Ok(vec![]);
+    }
+
+    let mut results = vec![];
+    let mut queue = VecDeque::new();
+    queue.push_back(0..1);
+
+    while let Some(node_range) = queue.pop_front() {
+        for (node_idx, node) in read_node_range(node_range)? {
+            if !bboxes.iter().any(|bbox| node.bounds.intersects(bbox)) {
+                continue;
+            }
+            if tree.is_leaf_node(node_idx) {
+                results.push(node.offset);
+            } else if let Some(children) = tree.children_range(node_idx) {
+                queue.push_back(children);
+            }
+        }
+    }
+
+    results.sort();
+    results.dedup();
+    Ok(results)
+}
+
 pub(crate) mod http {
-    use crate::asyncio::AsyncReadExt;
-    use crate::packed_r_tree::{Node, PackedRTree};
+    use crate::packed_r_tree::{NearestCandidate, Node, PackedRTree};
+    use crate::retry::FetchStats;
     use crate::FeatureLocation;
-    use crate::Result;
-    use crate::{deserialize_from, Bounds};
+    use crate::{deserialize_from, Bounds, LngLat};
+    use crate::{Result, RetryPolicy};
+    use bytes::Bytes;
     use futures_util::Stream;
-    use std::collections::VecDeque;
+    use std::collections::{BinaryHeap, VecDeque};
     use std::ops::Range;
     use streaming_http_range_client::{HttpClient, HttpRange};
 
+    // TODO: do something less arbitrary
+    /// Node ranges closer together than this many bytes are fetched in a single request.
+    pub(crate) const DEFAULT_COMBINE_REQUEST_THRESHOLD: u64 = 16_000;
+
     pub struct PackedRTreeHttpReader {
         http_client: HttpClient,
         index_starting_byte: u64,
         tree: PackedRTree,
+        combine_request_threshold: u64,
+        retry_policy: RetryPolicy,
+        fetch_stats: FetchStats,
+        /// The first bytes of the index, which nodes are read from rather than requested.
+        prefetched_index: Bytes,
     }
 
     impl PackedRTreeHttpReader {
         pub(crate) fn new(
             feature_count: u64,
+            branching_factor: u16,
             http_client: HttpClient,
             index_starting_offset: u64,
         ) -> Self {
-            let tree = PackedRTree::new(feature_count);
+            let tree = PackedRTree::new(feature_count, branching_factor);
             Self {
                 http_client,
                 index_starting_byte: index_starting_offset,
                 tree,
+                combine_request_threshold: DEFAULT_COMBINE_REQUEST_THRESHOLD,
+                retry_policy: RetryPolicy::default(),
+                fetch_stats: FetchStats::default(),
+                prefetched_index: Bytes::new(),
             }
         }
 
+        /// Node ranges closer together than this many bytes are fetched in a single request.
+        pub(crate) fn set_combine_request_threshold(&mut self, combine_request_threshold: u64) {
+            self.combine_request_threshold = combine_request_threshold;
+        }
+
+        pub(crate) fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+            self.retry_policy = retry_policy;
+        }
+
+        pub(crate) fn set_fetch_stats(&mut self, fetch_stats: FetchStats) {
+            self.fetch_stats = fetch_stats;
+        }
+
+        /// The first bytes of the index, already fetched, e.g. when the file was opened, so nodes
+        /// within them are read without a request.
+        pub(crate) fn set_prefetched_index(&mut self, prefetched_index: Bytes) {
+            self.prefetched_index = prefetched_index;
+        }
+
         pub fn select_bbox(
             &mut self,
             bbox: &Bounds,
         ) -> impl Stream<Item = Result<FeatureLocation>> {
-            trace!("select_bbox {bbox:?}");
+            self.select_bboxes(std::slice::from_ref(bbox))
+        }
+
+        /// The locations of the features which intersect any of `bboxes`, each only once, found
+        /// in a single traversal of the tree, so no part of the index is fetched more than once.
+        pub fn select_bboxes(
+            &mut self,
+            bboxes: &[Bounds],
+        ) -> impl Stream<Item = Result<FeatureLocation>> {
+            trace!("select_bboxes {bboxes:?}");
 
             let mut http_client = self.http_client.split_off();
             let index_starting_byte = self.index_starting_byte;
             let tree = self.tree.clone();
-            let bbox = bbox.clone();
+            let bboxes = bboxes.to_vec();
+            let combine_request_node_threshold =
+                self.combine_request_threshold / Node::serialized_size() as u64;
+            let retry_policy = self.retry_policy.clone();
+            let fetch_stats = self.fetch_stats.clone();
+            let prefetched_index = self.prefetched_index.clone();
 
             async_stream::try_stream! {
                 if tree.num_leaf_nodes == 0 {
@@ -124,8 +255,8 @@ pub(crate) mod http {
                 while let Some(node_range) = queue.pop_front() {
                     let level = tree.level_for_node_idx(node_range.start);
                     trace!("next node_range {node_range:?} (level {level})");
-                    for (node, node_idx) in Self::read_node_range(&mut http_client, index_starting_byte, node_range.clone()).await?.into_iter().zip(node_range) {
-                        if !node.bounds.intersects(&bbox) {
+                    for (node, node_idx) in Self::read_node_range(&mut http_client, &retry_policy, &fetch_stats, index_starting_byte, &prefetched_index, node_range.clone()).await?.into_iter().zip(node_range) {
+                        if !bboxes.iter().any(|bbox| node.bounds.intersects(bbox)) {
                             continue;
                         }
                         if tree.is_leaf_node(node_idx) {
@@ -155,11 +286,7 @@ pub(crate) mod http {
                                 continue;
                             }
 
-                            // TODO: do something less arbitrary
-                            let combine_request_threshold = 16_000;
-                            let combine_request_node_threshold =
-                                combine_request_threshold / Node::serialized_size();
-                            if tail.end + combine_request_node_threshold as u64 > children.start {
+                            if tail.end + combine_request_node_threshold > children.start {
                                 trace!("merging children: {children:?} with nearby existing range {tail:?}");
                                 debug_assert!(
                                     children.start >= tail.end,
@@ -179,31 +306,141 @@ pub(crate) mod http {
             }
         }
 
+        /// The locations of the (up to) `k` features whose bounds are nearest to `point`, closest
+        /// first.
+        ///
+        /// Distances are planar, measured in lng/lat space.
+        pub async fn nearest(&mut self, point: &LngLat, k: usize) -> Result<Vec<FeatureLocation>> {
+            trace!("nearest {point:?}, k: {k}");
+            let tree = &self.tree;
+            if tree.num_leaf_nodes == 0 || k == 0 {
+                return Ok(vec![]);
+            }
+
+            let http_client = &mut self.http_client;
+            let retry_policy = &self.retry_policy;
+            let fetch_stats = &self.fetch_stats;
+            let index_starting_byte = self.index_starting_byte;
+            let prefetched_index = &self.prefetched_index;
+            let root = Self::read_node_range(
+                http_client,
+                retry_policy,
+                fetch_stats,
+                index_starting_byte,
+                prefetched_index,
+                0..1,
+            )
+            .await?
+            .remove(0);
+
+            let mut results = vec![];
+            let mut queue = BinaryHeap::new();
+            queue.push(NearestCandidate {
+                distance_squared: root.bounds.distance_squared(point),
+                node_idx: 0,
+                node: root,
+            });
+
+            let combine_request_node_threshold =
+                self.combine_request_threshold / Node::serialized_size() as u64;
+            while let Some(candidate) = queue.pop() {
+                if tree.is_leaf_node(candidate.node_idx) {
+                    results.push(candidate.node.offset);
+                    if results.len() == k {
+                        break;
+                    }
+                    continue;
+                }
+                let Some(children) = tree.children_range(candidate.node_idx) else {
+                    continue;
+                };
+
+                // Expanding a node costs a request, so also expand any other queued nodes whose
+                // children are nearby, fetching all of them at once.
+                let mut request_range = children.clone();
+                let mut expanding = vec![children];
+                queue.retain(|other| {
+                    if tree.is_leaf_node(other.node_idx) {
+                        return true;
+                    }
+                    let Some(other_children) = tree.children_range(other.node_idx) else {
+                        return true;
+                    };
+                    let is_nearby = other_children.start
+                        < request_range.end + combine_request_node_threshold
+                        && request_range.start
+                            < other_children.end + combine_request_node_threshold;
+                    if !is_nearby {
+                        return true;
+                    }
+                    request_range = request_range.start.min(other_children.start)
+                        ..request_range.end.max(other_children.end);
+                    expanding.push(other_children);
+                    false
+                });
+
+                trace!("expanding {expanding:?} with request for {request_range:?}");
+                let nodes = Self::read_node_range(
+                    http_client,
+                    retry_policy,
+                    fetch_stats,
+                    index_starting_byte,
+                    prefetched_index,
+                    request_range.clone(),
+                )
+                .await?;
+                for child_idx in expanding.into_iter().flatten() {
+                    let node = nodes[(child_idx - request_range.start) as usize].clone();
+                    queue.push(NearestCandidate {
+                        distance_squared: node.bounds.distance_squared(point),
+                        node_idx: child_idx,
+                        node,
+                    });
+                }
+            }
+
+            Ok(results)
+        }
+
         pub(crate) fn into_http_client(self) -> HttpClient {
             self.http_client
         }
 
+        /// Reads the nodes in `node_range`, copying those within `prefetched_index`, and only
+        /// requesting the rest.
         async fn read_node_range(
             http_client: &mut HttpClient,
+            retry_policy: &RetryPolicy,
+            fetch_stats: &FetchStats,
             index_starting_byte: u64,
+            prefetched_index: &[u8],
             node_range: Range<u64>,
         ) -> Result<Vec<Node>> {
-            let start_byte =
-                index_starting_byte + node_range.start * Node::serialized_size() as u64;
-            let end_byte = index_starting_byte + node_range.end * Node::serialized_size() as u64;
-            let range = HttpRange::Range(start_byte..end_byte);
-            http_client.seek_to_range(range).await?;
-
-            let node_range_len = (node_range.end - node_range.start) as usize;
-            let mut nodes = Vec::with_capacity(node_range_len);
-            for _node_id in node_range {
-                let mut node_bytes = vec![0u8; Node::serialized_size()];
-                http_client.read_exact(&mut node_bytes).await?;
-                let node: Node = deserialize_from(&*node_bytes)?;
-                nodes.push(node)
+            // Relative to the start of the index.
+            let start_byte = node_range.start * Node::serialized_size() as u64;
+            let end_byte = node_range.end * Node::serialized_size() as u64;
+            let mut node_bytes = vec![0u8; (end_byte - start_byte) as usize];
+
+            let prefetched_len =
+                (prefetched_index.len() as u64).clamp(start_byte, end_byte) - start_byte;
+            let (prefetched, fetched) = node_bytes.split_at_mut(prefetched_len as usize);
+            if let Some(prefetched_index) = prefetched_index.get(start_byte as usize..) {
+                prefetched.copy_from_slice(&prefetched_index[..prefetched.len()]);
+            }
+            if !fetched.is_empty() {
+                let range = HttpRange::Range(
+                    index_starting_byte + start_byte + prefetched_len
+                        ..index_starting_byte + end_byte,
+                );
+                retry_policy
+                    .read_range(http_client, range, fetched, fetch_stats)
+                    .await?;
             }
 
-            Ok(nodes)
+            node_bytes
+                .chunks_exact(Node::serialized_size())
+                .map(deserialize_from)
+                .collect()
         }
         pub fn tree(&self) -> &PackedRTree {
             &self.tree
@@ -213,8 +450,10 @@ pub(crate) mod http {
     #[cfg(test)]
     mod tests {
         use super::super::tests::example_index;
-        use crate::packed_r_tree::PackedRTreeHttpReader;
+        use crate::packed_r_tree::{Node, PackedRTreeHttpReader};
+        use crate::retry::FetchStats;
         use crate::{wkt, FeatureLocation};
+        use bytes::Bytes;
         use futures_util::StreamExt;
         use streaming_http_range_client::HttpClient;
 
@@ -226,7 +465,7 @@ pub(crate) mod http {
             http_client.set_range(0..1).await.unwrap();
 
             // Search
-            let mut reader = PackedRTreeHttpReader::new(4, http_client, 0);
+            let mut reader = PackedRTreeHttpReader::new(4, 16, http_client, 0);
             let mut location_stream = Box::pin(reader.select_bbox(&wkt!(RECT(0.5 0.5,0.75 0.75))));
 
             let mut locations = vec![];
@@ -245,7 +484,7 @@ pub(crate) mod http {
             let mut http_client = HttpClient::test_client(&index_bytes);
             // avoid some dumb precondition of HttpClient
             http_client.set_range(0..1).await.unwrap();
-            let mut reader = PackedRTreeHttpReader::new(4, http_client, 0);
+            let mut reader = PackedRTreeHttpReader::new(4, 16, http_client, 0);
             let mut location_stream = Box::pin(reader.select_bbox(&wkt!(RECT(1.5 1.5,2.0 2.0))));
 
             let mut locations = vec![];
@@ -266,6 +505,54 @@ pub(crate) mod http {
                 ]
             );
         }
+
+        #[tokio::test]
+        async fn prefetched_index() {
+            let index_bytes = example_index();
+            let mut http_client = HttpClient::test_client(&index_bytes);
+            // avoid some dumb precondition of HttpClient
+            http_client.set_range(0..1).await.unwrap();
+
+            let mut reader = PackedRTreeHttpReader::new(4, 16, http_client, 0);
+            // Only the root is prefetched, so only the leaves are requested.
+            let node_size = Node::serialized_size();
+            reader.set_prefetched_index(Bytes::copy_from_slice(&index_bytes[..node_size]));
+            let fetch_stats = FetchStats::default();
+            reader.set_fetch_stats(fetch_stats.clone());
+
+            let mut location_stream = Box::pin(reader.select_bbox(&wkt!(RECT(1.5 1.5,2.0 2.0))));
+            let mut locations = vec![];
+            while let Some(next) = location_stream.next().await.transpose().unwrap() {
+                locations.push(next);
+            }
+            assert_eq!(locations.len(), 2);
+            assert_eq!(fetch_stats.request_count(), 1);
+            assert_eq!(fetch_stats.bytes_fetched(), 4 * node_size as u64);
+        }
+
+        #[tokio::test]
+        async fn http_nearest() {
+            let index_bytes = example_index();
+            let mut http_client = HttpClient::test_client(&index_bytes);
+            // avoid some dumb precondition of HttpClient
+            http_client.set_range(0..1).await.unwrap();
+
+            let mut reader = PackedRTreeHttpReader::new(4, 16, http_client, 0);
+            let locations = reader.nearest(&wkt!(POINT(3.5 3.5)), 2).await.unwrap();
+            assert_eq!(
+                locations,
+                vec![
+                    FeatureLocation {
+                        page_starting_offset: 10,
+                        feature_offset: 1
+                    },
+                    FeatureLocation {
+                        page_starting_offset: 10,
+                        feature_offset: 0
+                    }
+                ]
+            );
+        }
     }
 }
 
@@ -277,7 +564,7 @@ mod tests {
     use crate::{wkt, FeatureLocation};
 
     pub(crate) fn example_index() -> Vec<u8> {
-        let mut writer = PackedRTreeWriter::new(4).unwrap();
+        let mut writer = PackedRTreeWriter::new(4, 16);
         writer
             .push_leaf(Node {
                 bounds: wkt!(RECT(0 0,1 1)),
@@ -324,7 +611,7 @@ mod tests {
         let index_bytes = example_index();
 
         // Search
-        let reader = PackedRTreeReader::new(4, index_bytes.as_slice());
+        let reader = PackedRTreeReader::new(4, 16, index_bytes.as_slice());
         let locations = reader.select_bbox(&wkt!(RECT(0.5 0.5,0.75 0.75))).unwrap();
         assert_eq!(
             locations,
@@ -334,7 +621,7 @@ mod tests {
             }]
         );
 
-        let reader = PackedRTreeReader::new(4, index_bytes.as_slice());
+        let reader = PackedRTreeReader::new(4, 16, index_bytes.as_slice());
         let locations = reader.select_bbox(&wkt!(RECT(1.5 1.5,2.0 2.0))).unwrap();
         assert_eq!(
             locations,
@@ -351,6 +638,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn select_bboxes_overlapping() {
+        let index_bytes = example_index();
+        let reader = PackedRTreeReader::new(4, 16, index_bytes.as_slice());
+        let locations = reader
+            .select_bboxes(&[
+                wkt!(RECT(2.5 2.5,3.5 3.5)),
+                wkt!(RECT(0.5 0.5,2.5 2.5)),
+                wkt!(RECT(0 0,4 4)),
+            ])
+            .unwrap();
+        assert_eq!(
+            locations,
+            vec![
+                FeatureLocation {
+                    page_starting_offset: 0,
+                    feature_offset: 0
+                },
+                FeatureLocation {
+                    page_starting_offset: 0,
+                    feature_offset: 1
+                },
+                FeatureLocation {
+                    page_starting_offset: 10,
+                    feature_offset: 0
+                },
+                FeatureLocation {
+                    page_starting_offset: 10,
+                    feature_offset: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn nearest() {
+        let index_bytes = example_index();
+
+        let reader = PackedRTreeReader::new(4, 16, index_bytes.as_slice());
+        let locations = reader.nearest(&wkt!(POINT(3.5 3.5)), 2).unwrap();
+        assert_eq!(
+            locations,
+            vec![
+                FeatureLocation {
+                    page_starting_offset: 10,
+                    feature_offset: 1
+                },
+                FeatureLocation {
+                    page_starting_offset: 10,
+                    feature_offset: 0
+                }
+            ]
+        );
+
+        let reader = PackedRTreeReader::new(4, 16, index_bytes.as_slice());
+        let locations = reader.nearest(&wkt!(POINT(-1 - 1)), 10).unwrap();
+        assert_eq!(locations.len(), 4);
+        assert_eq!(
+            locations[0],
+            FeatureLocation {
+                page_starting_offset: 0,
+                feature_offset: 0
+            }
+        );
+    }
+
+    #[test]
+    fn nearest_truncated_index() {
+        let index_bytes = example_index();
+        // A corrupt header can claim far more features than the index holds.
+        let reader = PackedRTreeReader::new(1 << 40, 16, index_bytes.as_slice());
+        assert!(reader.nearest(&wkt!(POINT(3.5 3.5)), 2).is_err());
+    }
+
     // not currently implemented - I'm not sure if we should.
     #[ignore]
     #[test]
@@ -360,7 +721,7 @@ mod tests {
         let too_far_west = wkt!(POINT(170.0 50));
         let too_far_east = wkt!(POINT(-170.0 50));
 
-        let mut writer = PackedRTreeWriter::new(4).unwrap();
+        let mut writer = PackedRTreeWriter::new(4, 16);
 
         writer
             .push_leaf(Node {
@@ -403,7 +764,7 @@ mod tests {
         writer.write(&mut output).unwrap();
 
         // Search
-        let reader = PackedRTreeReader::new(4, output.as_slice());
+        let reader = PackedRTreeReader::new(4, 16, output.as_slice());
         let locations: Vec<_> = reader
             .select_bbox(&wkt!(RECT(179 49,-179 51)))
             .unwrap()
@@ -413,4 +774,39 @@ mod tests {
 
         assert_eq!(locations, vec![0, 10]);
     }
+
+    #[test]
+    fn select_bbox_spanning_antimeridian() {
+        let points = [
+            wkt!(POINT(179.0 50)),
+            wkt!(POINT(-179.0 50)),
+            wkt!(POINT(170.0 50)),
+            wkt!(POINT(-170.0 50)),
+        ];
+        let mut writer = PackedRTreeWriter::new(4, 16);
+        for (idx, point) in points.iter().enumerate() {
+            writer
+                .push_leaf(Node {
+                    bounds: point.bounds(),
+                    offset: FeatureLocation {
+                        page_starting_offset: idx as u64 * 10,
+                        feature_offset: 0,
+                    },
+                })
+                .unwrap();
+        }
+        let mut output = vec![];
+        writer.write(&mut output).unwrap();
+
+        let spanning = Bounds::new(LngLat::degrees(179.0, 49.0), LngLat::degrees(-179.0, 51.0));
+        let reader = PackedRTreeReader::new(4, 16, output.as_slice());
+        let mut locations: Vec<_> = reader
+            .select_bbox(&spanning)
+            .unwrap()
+            .iter()
+            .map(|l| l.page_starting_offset)
+            .collect();
+        locations.sort();
+        assert_eq!(locations, vec![0, 10]);
+    }
 }
@@ -1,35 +1,22 @@
-use crate::packed_r_tree::{Node, PackedRTree, BRANCHING_FACTOR};
-use crate::{deserialize_from, serialize_into};
+use crate::packed_r_tree::{Node, PackedRTree};
+use crate::serialize_into;
 use crate::{Error, Result};
-use memmap2::MmapMut;
-use std::fs::File;
-use std::io::{BufReader, Write};
-use tempfile::tempfile;
+use std::io::{BufWriter, Write};
 
 pub struct PackedRTreeWriter {
-    mmap: MmapMut,
     tree: PackedRTree,
     sorted_leaf_nodes: Vec<Node>,
-    temp_index_file: File,
     features_added: u64,
 }
 
 impl PackedRTreeWriter {
-    pub fn new(leaf_node_count: u64) -> Result<Self> {
-        let temp_index_file = tempfile()?;
-        let tree = PackedRTree::new(leaf_node_count);
-        let size = tree.index_size();
-        temp_index_file.set_len(size)?;
-
-        let mmap = unsafe { MmapMut::map_mut(&temp_index_file)? };
-
-        Ok(Self {
-            mmap,
+    pub fn new(leaf_node_count: u64, branching_factor: u16) -> Self {
+        let tree = PackedRTree::new(leaf_node_count, branching_factor);
+        Self {
             tree,
             sorted_leaf_nodes: vec![],
-            temp_index_file,
             features_added: 0,
-        })
+        }
     }
 
     pub fn push_leaf(&mut self, leaf: Node) -> Result<()> {
@@ -38,7 +25,7 @@ impl PackedRTreeWriter {
         Ok(())
     }
 
-    pub fn write<W: Write>(mut self, mut writer: W) -> Result<()> {
+    pub fn write<W: Write>(self, writer: W) -> Result<()> {
         if self.features_added != self.tree.num_leaf_nodes {
             return Err(Error::FeatureCountMismatch {
                 expected: self.tree.num_leaf_nodes,
@@ -50,36 +37,34 @@ impl PackedRTreeWriter {
             return Ok(());
         }
 
-        // PERF: don't write this to vec
-        let mut nodes_for_this_level = self.sorted_leaf_nodes.to_vec();
-        let mut byte_ranges = self.tree.byte_ranges_by_level();
-        byte_ranges.reverse();
-        for byte_range_of_level in byte_ranges {
-            let mut writer = &mut self.mmap[byte_range_of_level.clone()];
-            for node in nodes_for_this_level {
-                serialize_into(&mut writer, &node)?;
+        // The index is written from the root down, but each level is computed from the level
+        // below it, so compute the inner levels first. Together they're only a fraction of the
+        // size of the leaves.
+        let level_count = self.tree.node_ranges_by_level().len();
+        let branching_factor = self.tree.branching_factor as usize;
+        let parent = |children: &[Node]| {
+            let mut parent = Node::empty_inner_node();
+            for child in children {
+                parent.bounds.extend(&child.bounds);
             }
-            nodes_for_this_level = {
-                let prev_level: &[u8] = &self.mmap[byte_range_of_level.clone()];
-                prev_level
-                    .chunks(Node::serialized_size() * BRANCHING_FACTOR as usize)
-                    .map(|children_bytes| {
-                        let mut parent = Node::empty_inner_node();
-                        for child_bytes in children_bytes.chunks(Node::serialized_size()) {
-                            let child: Node = deserialize_from(child_bytes)?;
-                            parent.bounds.extend(&child.bounds);
-                        }
-                        Ok(parent)
-                    })
-                    .collect::<Result<Vec<Node>>>()?
-            };
+            parent
+        };
+        let mut inner_levels: Vec<Vec<Node>> = Vec::with_capacity(level_count - 1);
+        for _ in 1..level_count {
+            let children = inner_levels.last().unwrap_or(&self.sorted_leaf_nodes);
+            let parents = children.chunks(branching_factor).map(parent).collect();
+            inner_levels.push(parents);
         }
+        debug_assert_eq!(inner_levels.last().map_or(1, Vec::len), 1, "single root");
 
-        self.mmap.flush()?;
-
-        // REVIEW: Do we need to ensure mmap has synced?
-        // REVIEW: vs copying from memmap?
-        std::io::copy(&mut BufReader::new(self.temp_index_file), &mut writer)?;
+        let mut writer = BufWriter::new(writer);
+        for node in inner_levels.iter().rev().flatten() {
+            serialize_into(&mut writer, node)?;
+        }
+        for node in &self.sorted_leaf_nodes {
+            serialize_into(&mut writer, node)?;
+        }
+        writer.flush()?;
         Ok(())
     }
 }
@@ -88,12 +73,13 @@ impl PackedRTreeWriter {
 mod tests {
     use super::*;
     use crate::bounds::Bounds;
-    use crate::{wkt, FeatureLocation, LngLat};
+    use crate::packed_r_tree::select_bboxes_in_slice;
+    use crate::{deserialize_from, wkt, FeatureLocation, LngLat};
 
     #[test]
     fn write_empty() {
         let mut output: Vec<u8> = vec![];
-        let tree = PackedRTreeWriter::new(0).unwrap();
+        let tree = PackedRTreeWriter::new(0, 16);
         tree.write(&mut output).unwrap();
 
         let expected: Vec<u8> = vec![];
@@ -103,7 +89,7 @@ mod tests {
     #[test]
     fn write_single() {
         let mut output: Vec<u8> = vec![];
-        let mut tree = PackedRTreeWriter::new(1).unwrap();
+        let mut tree = PackedRTreeWriter::new(1, 16);
         tree.push_leaf(Node {
             bounds: Bounds::from_corners(&LngLat::unscaled(1, 2), &LngLat::unscaled(3, 4)),
             offset: FeatureLocation {
@@ -133,22 +119,22 @@ mod tests {
     #[test]
     fn write_multiple_layers() {
         let mut output: Vec<u8> = vec![];
-        let mut tree = PackedRTreeWriter::new(17).unwrap();
+        let mut tree = PackedRTreeWriter::new(17, 16);
 
         for offset in 0..17 {
             let bounds = Bounds::from_corners(
                 &LngLat::degrees(offset as f64, offset as f64),
                 &LngLat::degrees(offset as f64 * 2.0, offset as f64 * 2.0),
             );
-            tree.push_leaf(Node {
+            let node = Node {
                 bounds,
                 // 10 features per page
                 offset: FeatureLocation {
                     page_starting_offset: offset / 10, // fake page size
                     feature_offset: offset as u32 % 10,
                 },
-            })
-            .unwrap();
+            };
+            tree.push_leaf(node).unwrap();
         }
 
         tree.write(&mut output).unwrap();
@@ -174,4 +160,53 @@ mod tests {
         assert_eq!(level_2[0].bounds, wkt!(RECT(0 0, 0 0)));
         assert_eq!(level_2[16].bounds, wkt!(RECT(16 16, 32 32)));
     }
+
+    #[test]
+    fn write_large_index_in_memory() {
+        // A million leaves on a 1000x1000 grid. The whole index is built in memory and written
+        // straight to the output, without a tempfile.
+        let leaf_count = 1_000_000;
+        let leaf_bounds = |i: u64| {
+            let point = LngLat::unscaled((i % 1000) as i32, (i / 1000) as i32);
+            Bounds::from_corners(&point, &point)
+        };
+        let mut tree = PackedRTreeWriter::new(leaf_count, 16);
+        for i in 0..leaf_count {
+            tree.push_leaf(Node {
+                bounds: leaf_bounds(i),
+                offset: FeatureLocation {
+                    page_starting_offset: i / 100,
+                    feature_offset: (i % 100) as u32,
+                },
+            })
+            .unwrap();
+        }
+        let mut output: Vec<u8> = vec![];
+        tree.write(&mut output).unwrap();
+
+        let tree = PackedRTree::new(leaf_count, 16);
+        assert_eq!(output.len() as u64, tree.index_size());
+
+        let root: Node = deserialize_from(output.as_slice()).unwrap();
+        let extent = Bounds::from_corners(&LngLat::unscaled(0, 0), &LngLat::unscaled(999, 999));
+        assert_eq!(root.bounds, extent);
+
+        let selected = select_bboxes_in_slice(
+            &tree,
+            &output,
+            &[Bounds::from_corners(
+                &LngLat::unscaled(10, 20),
+                &LngLat::unscaled(11, 21),
+            )],
+        )
+        .unwrap();
+        let expected: Vec<_> = [20_010, 20_011, 21_010, 21_011]
+            .into_iter()
+            .map(|i: u64| FeatureLocation {
+                page_starting_offset: i / 100,
+                feature_offset: (i % 100) as u32,
+            })
+            .collect();
+        assert_eq!(selected, expected);
+    }
 }
@@ -0,0 +1,397 @@
+//! Deserialize a feature with only some of its properties, or only its properties, skipping over
+//! the rest.
+//!
+//! The skipped values are never allocated, which matters when only a few of many properties are
+//! needed, or when geometries are large. This relies on the (non self-describing) bincode
+//! encoding of [`Feature`]: its geometry is serialized before its properties, and the variant
+//! indices below must match the declaration order of [`PropertyValue`] and [`Geometry`].
+
+use crate::{Feature, Geometry, LngLat, Properties, PropertyValue};
+use serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, SeqAccess, Unexpected, VariantAccess, Visitor,
+};
+use std::fmt::Formatter;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Deserializes a [`Feature`] whose [`Properties`] contain only the given keys.
+#[derive(Clone, Copy)]
+pub(crate) struct ProjectedFeature<'a> {
+    pub(crate) keys: &'a [Arc<str>],
+}
+
+impl<'de> DeserializeSeed<'de> for ProjectedFeature<'_> {
+    type Value = Feature;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Feature, D::Error> {
+        deserializer.deserialize_struct("Feature", &["geometry", "properties"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for ProjectedFeature<'_> {
+    type Value = Feature;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a feature")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Feature, A::Error> {
+        let geometry = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let properties = seq
+            .next_element_seed(ProjectedProperties { keys: self.keys })?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Feature::new(geometry, properties))
+    }
+}
+
+/// Deserializes only the [`Properties`] of a [`Feature`], skipping over its [`Geometry`].
+#[derive(Clone, Copy)]
+pub(crate) struct FeatureProperties;
+
+impl<'de> DeserializeSeed<'de> for FeatureProperties {
+    type Value = Properties;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Properties, D::Error> {
+        deserializer.deserialize_struct("Feature", &["geometry", "properties"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for FeatureProperties {
+    type Value = Properties;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a feature")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Properties, A::Error> {
+        seq.next_element_seed(SkipGeometry)?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        seq.next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ProjectedProperties<'a> {
+    keys: &'a [Arc<str>],
+}
+
+impl<'de> DeserializeSeed<'de> for ProjectedProperties<'_> {
+    type Value = Properties;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Properties, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ProjectedProperties<'_> {
+    type Value = Properties;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a sequence of properties")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Properties, A::Error> {
+        let mut properties = Properties::empty();
+        while let Some(property) = seq.next_element_seed(ProjectedProperty { keys: self.keys })? {
+            if let Some((key, value)) = property {
+                properties.insert_interned(key, value);
+            }
+        }
+        Ok(properties)
+    }
+}
+
+/// A single `(key, value)` property, which is `None` if the key isn't one of `keys`.
+#[derive(Clone, Copy)]
+struct ProjectedProperty<'a> {
+    keys: &'a [Arc<str>],
+}
+
+impl<'de> DeserializeSeed<'de> for ProjectedProperty<'_> {
+    type Value = Option<(Arc<str>, PropertyValue)>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_tuple(2, self)
+    }
+}
+
+impl<'de> Visitor<'de> for ProjectedProperty<'_> {
+    type Value = Option<(Arc<str>, PropertyValue)>;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a property")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let key_idx = seq
+            .next_element_seed(KeyIndex { keys: self.keys })?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        match key_idx {
+            Some(key_idx) => {
+                let value = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Ok(Some((self.keys[key_idx].clone(), value)))
+            }
+            None => {
+                seq.next_element_seed(SkipPropertyValue)?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// The position of a property's key within `keys`, compared without allocating the key.
+#[derive(Clone, Copy)]
+struct KeyIndex<'a> {
+    keys: &'a [Arc<str>],
+}
+
+impl<'de> DeserializeSeed<'de> for KeyIndex<'_> {
+    type Value = Option<usize>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeyIndex<'_> {
+    type Value = Option<usize>;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a property key")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(self.keys.iter().position(|key| &**key == v))
+    }
+}
+
+#[derive(Clone, Copy)]
+struct SkipStr;
+
+impl<'de> DeserializeSeed<'de> for SkipStr {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for SkipStr {
+    type Value = ();
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a string")
+    }
+
+    fn visit_str<E: de::Error>(self, _v: &str) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+/// Skips each element of a sequence with the seed `S`.
+#[derive(Clone, Copy)]
+struct SkipSeq<S>(S);
+
+impl<'de, S: DeserializeSeed<'de> + Copy> DeserializeSeed<'de> for SkipSeq<S> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de> + Copy> Visitor<'de> for SkipSeq<S> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while seq.next_element_seed(self.0)?.is_some() {}
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+struct SkipPropertyValue;
+
+const PROPERTY_VALUE_VARIANTS: &[&str] = &[
+    "Bool", "Int8", "UInt8", "Int16", "UInt16", "Int32", "UInt32", "Int64", "UInt64", "Float32",
+    "Float64", "Bytes", "String", "Vec", "Map",
+];
+
+impl<'de> DeserializeSeed<'de> for SkipPropertyValue {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_enum("PropertyValue", PROPERTY_VALUE_VARIANTS, self)
+    }
+}
+
+impl<'de> Visitor<'de> for SkipPropertyValue {
+    type Value = ();
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a property value")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<(), A::Error> {
+        let (variant_idx, variant): (u32, _) = data.variant()?;
+        match variant_idx {
+            0 => variant.newtype_variant::<bool>().map(drop),
+            1 => variant.newtype_variant::<i8>().map(drop),
+            2 => variant.newtype_variant::<u8>().map(drop),
+            3 => variant.newtype_variant::<i16>().map(drop),
+            4 => variant.newtype_variant::<u16>().map(drop),
+            5 => variant.newtype_variant::<i32>().map(drop),
+            6 => variant.newtype_variant::<u32>().map(drop),
+            7 => variant.newtype_variant::<i64>().map(drop),
+            8 => variant.newtype_variant::<u64>().map(drop),
+            9 => variant.newtype_variant::<f32>().map(drop),
+            10 => variant.newtype_variant::<f64>().map(drop),
+            11 => variant.newtype_variant_seed(SkipSeq(PhantomData::<u8>)),
+            12 => variant.newtype_variant_seed(SkipStr),
+            13 => variant.newtype_variant_seed(SkipSeq(SkipPropertyValue)),
+            // With no keys, every nested property is skipped.
+            14 => variant.newtype_variant_seed(SkipSeq(ProjectedProperty { keys: &[] })),
+            _ => Err(de::Error::invalid_value(
+                Unexpected::Unsigned(variant_idx as u64),
+                &self,
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct SkipGeometry;
+
+const GEOMETRY_VARIANTS: &[&str] = &[
+    "Point",
+    "LineString",
+    "Polygon",
+    "MultiPoint",
+    "MultiLineString",
+    "MultiPolygon",
+    "GeometryCollection",
+];
+
+impl<'de> DeserializeSeed<'de> for SkipGeometry {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_enum("Geometry", GEOMETRY_VARIANTS, self)
+    }
+}
+
+impl<'de> Visitor<'de> for SkipGeometry {
+    type Value = ();
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a geometry")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<(), A::Error> {
+        // The geometry newtypes (e.g. `LineString(Vec<LngLat>)`) are encoded as their inner value.
+        let points = SkipSeq(PhantomData::<LngLat>);
+        let (variant_idx, variant): (u32, _) = data.variant()?;
+        match variant_idx {
+            0 => variant.newtype_variant::<LngLat>().map(drop),
+            1 | 3 => variant.newtype_variant_seed(points),
+            2 | 4 => variant.newtype_variant_seed(SkipSeq(points)),
+            5 => variant.newtype_variant_seed(SkipSeq(SkipSeq(points))),
+            6 => variant.newtype_variant_seed(SkipSeq(SkipGeometry)),
+            _ => Err(de::Error::invalid_value(
+                Unexpected::Unsigned(variant_idx as u64),
+                &self,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deserialize_from_seed, wkt};
+
+    #[test]
+    fn projected_feature() {
+        let mut nested = Properties::empty();
+        nested.insert("inner".to_string(), PropertyValue::Bool(true));
+
+        let mut properties = Properties::empty();
+        let skipped_values = [
+            PropertyValue::Bool(true),
+            PropertyValue::Int8(-1),
+            PropertyValue::UInt8(1),
+            PropertyValue::Int16(-2),
+            PropertyValue::UInt16(2),
+            PropertyValue::Int32(-3),
+            PropertyValue::UInt32(3),
+            PropertyValue::Int64(-4),
+            PropertyValue::UInt64(4),
+            PropertyValue::Float32(5.5),
+            PropertyValue::Float64(6.5),
+            PropertyValue::Bytes(vec![1, 2, 3]),
+            PropertyValue::String("skipped".to_string()),
+            PropertyValue::Vec(vec![PropertyValue::String("nested".to_string())]),
+            PropertyValue::Map(nested),
+        ];
+        for (idx, value) in skipped_values.into_iter().enumerate() {
+            properties.insert(format!("skipped-{idx}"), value);
+            if idx == 7 {
+                properties.insert("name".to_string(), "Seattle".into());
+            }
+        }
+        properties.insert("population".to_string(), PropertyValue::UInt32(737_015));
+        let feature = Feature::new(wkt!(POINT(1 2)).into(), properties);
+
+        let bytes = bincode::serialize(&feature).unwrap();
+        let keys: Vec<Arc<str>> = vec!["population".into(), "name".into(), "missing".into()];
+        let projected: Feature =
+            deserialize_from_seed(bytes.as_slice(), ProjectedFeature { keys: &keys }).unwrap();
+
+        assert_eq!(projected.geometry(), feature.geometry());
+        // Properties keep their order from the file.
+        let entries: Vec<_> = projected.properties().iter().collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("name", &PropertyValue::from("Seattle")),
+                ("population", &PropertyValue::UInt32(737_015)),
+            ]
+        );
+    }
+
+    #[test]
+    fn feature_properties() {
+        let geometries: Vec<Geometry> = vec![
+            wkt!(POINT(1 2)).into(),
+            wkt!(LINESTRING(1 2,3 4)).into(),
+            wkt!(POLYGON((0 0,1 0,1 1,0 0))).into(),
+            wkt!(MULTIPOINT(1 2,3 4)).into(),
+            wkt!(MULTILINESTRING((1 2,3 4),(5 6,7 8))).into(),
+            wkt!(MULTIPOLYGON(((0 0,1 0,1 1,0 0)),((2 2,3 2,3 3,2 2)))).into(),
+            wkt!(GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(1 2,3 4))).into(),
+        ];
+        for geometry in geometries {
+            let mut properties = Properties::empty();
+            properties.insert("name".to_string(), "Seattle".into());
+            let feature = Feature::new(geometry, properties.clone());
+
+            let mut bytes = bincode::serialize(&feature).unwrap();
+            // Make sure the whole feature was consumed
+            bytes.push(0xFF);
+            let mut reader = bytes.as_slice();
+            let read_properties = deserialize_from_seed(&mut reader, FeatureProperties).unwrap();
+            assert_eq!(read_properties, properties);
+            assert_eq!(reader, &[0xFF]);
+        }
+    }
+}
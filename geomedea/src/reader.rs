@@ -1,19 +1,38 @@
-use crate::io::CountingReader;
-use crate::packed_r_tree::{PackedRTree, PackedRTreeReader};
+use crate::format::verify_page_checksum;
+use crate::geometry::predicates::is_within;
+use crate::geometry::{Bounded, COORD_PRECISION_NANOS};
+use crate::io::{decode_page, untrusted_capacity, zstd_frame_decoder, CountingReader};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::packed_r_tree::select_bboxes_in_slice;
+use crate::packed_r_tree::{Node, PackedRTree, PackedRTreeReader};
+use crate::projection::{FeatureProperties, ProjectedFeature};
 use crate::{
-    deserialize_from, serialized_size, Bounds, Error, Feature, FeatureLocation, Header, PageHeader,
-    Result,
+    deserialize_from, deserialize_from_seed, serialized_size, Bounds, Compression, Error, Feature,
+    FeatureLocation, GeometryType, Header, LngLat, PageHeader, Polygon, Properties, Result,
 };
 use ruzstd::{FrameDecoder, StreamingDecoder as ZstdDecoder};
-use std::io::{Read, Take};
+use std::io::{Read, Seek, SeekFrom, Take};
 use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    memmap2::Mmap,
+    std::{fs::File, path::Path},
+};
 
 struct PageReader<'r, R: Read + 'r> {
     // Getting rid of this Option would be nice
     // But it's tricky... when we advance pages we need to move the Reader out of the CurrentPage
     // Maybe there's a way we can mutate CurrentPage instead of moving things out of it.
     current_page: Option<CurrentPage<'r, R>>,
-    is_compressed: bool,
+    compression: Compression,
+    has_page_checksums: bool,
+    /// The zstd dictionary every page was compressed with, if any.
+    dictionary: Option<Arc<[u8]>>,
+    /// How many pages have been opened, or None once pages were jumped over without reading
+    /// their headers, so the index of the current page is unknown.
+    pages_opened: Option<u64>,
 }
 
 struct CurrentPage<'r, R: Read> {
@@ -22,15 +41,18 @@ struct CurrentPage<'r, R: Read> {
 }
 
 impl<'r, R: Read + 'r> PageReader<'r, R> {
-    fn new(reader: R, is_compressed: bool) -> Result<Self> {
+    fn new(reader: R, file_header: &Header) -> Result<Self> {
         let mut reader = CountingReader::new(reader, "PageReader");
+        let compression = file_header.compression;
+        let has_page_checksums = file_header.has_page_checksums;
 
         // PERF: This might be a waste for bbox queries which might not even use the first page
-        let header: PageHeader = deserialize_from(&mut reader)?;
+        let (header, _page_bounds) = PageHeader::read_from(&mut reader, has_page_checksums)?;
         let page_decoder = new_page_decoder(
             reader.take(header.encoded_page_length() as u64),
-            is_compressed,
-            header.decoded_page_length(),
+            compression,
+            &header,
+            file_header.dictionary.as_deref(),
         )?;
 
         let current_page = Some(CurrentPage {
@@ -40,10 +62,79 @@ impl<'r, R: Read + 'r> PageReader<'r, R> {
 
         Ok(Self {
             current_page,
-            is_compressed,
+            compression,
+            has_page_checksums,
+            dictionary: file_header.dictionary.clone(),
+            pages_opened: Some(1),
         })
     }
 
+    /// Unlike [`PageReader::new`], the first page isn't opened until
+    /// [`PageReader::ff_past_pages_not_intersecting`] is called.
+    fn new_unopened(reader: R, file_header: &Header) -> Self {
+        let reader = CountingReader::new(reader, "PageReader");
+        Self {
+            current_page: Some(CurrentPage::exhausted(reader)),
+            compression: file_header.compression,
+            has_page_checksums: file_header.has_page_checksums,
+            dictionary: file_header.dictionary.clone(),
+            pages_opened: Some(0),
+        }
+    }
+
+    /// Like [`PageReader::ff_past_any_header`], but pages whose bounds don't intersect `bounds`
+    /// are skipped without being decoded, until reaching a page which does intersect, or until
+    /// `features_left` features have been skipped.
+    ///
+    /// Returns the number of features in the skipped pages.
+    fn ff_past_pages_not_intersecting(
+        &mut self,
+        bounds: &Bounds,
+        features_left: u64,
+    ) -> Result<u64> {
+        let current_page = self
+            .current_page
+            .take()
+            .expect("current_page is always replaced");
+        if !current_page.page_decoder.was_read_to_end() {
+            self.current_page = Some(current_page);
+            return Ok(0);
+        }
+
+        let mut reader = current_page.page_decoder.into_inner();
+        let mut skipped_feature_count = 0;
+        loop {
+            let page_starting_offset = reader.total_bytes_read();
+            let (header, page_bounds) =
+                PageHeader::read_from(&mut reader, self.has_page_checksums)?;
+            self.pages_opened = self.pages_opened.map(|count| count + 1);
+            if page_bounds.intersects(bounds) {
+                debug!("opening new page: {header:?}");
+                let page_decoder = new_page_decoder(
+                    reader.take(header.encoded_page_length() as u64),
+                    self.compression,
+                    &header,
+                    self.dictionary.as_deref(),
+                )?;
+                self.current_page = Some(CurrentPage {
+                    page_decoder,
+                    page_starting_offset,
+                });
+                return Ok(skipped_feature_count);
+            }
+
+            debug!("skipping page which doesn't intersect {bounds:?}: {header:?}");
+            let mut page_content = reader.take(header.encoded_page_length() as u64);
+            std::io::copy(&mut page_content, &mut std::io::sink())?;
+            reader = page_content.into_inner();
+            skipped_feature_count += header.feature_count() as u64;
+            if skipped_feature_count >= features_left {
+                self.current_page = Some(CurrentPage::exhausted(reader));
+                return Ok(skipped_feature_count);
+            }
+        }
+    }
+
     fn ff_past_any_header(&mut self) -> Result<()> {
         let CurrentPage {
             page_decoder,
@@ -56,12 +147,15 @@ impl<'r, R: Read + 'r> PageReader<'r, R> {
         if page_decoder.was_read_to_end() {
             let mut reader = page_decoder.into_inner();
             let page_starting_offset = reader.total_bytes_read();
-            let header: PageHeader = deserialize_from(&mut reader)?;
+            let (header, _page_bounds) =
+                PageHeader::read_from(&mut reader, self.has_page_checksums)?;
+            self.pages_opened = self.pages_opened.map(|count| count + 1);
             debug!("opening new page: {header:?}");
             let page_decoder = new_page_decoder(
                 reader.take(header.encoded_page_length() as u64),
-                self.is_compressed,
-                header.decoded_page_length(),
+                self.compression,
+                &header,
+                self.dictionary.as_deref(),
             )?;
             self.current_page = Some(CurrentPage {
                 page_decoder,
@@ -76,6 +170,23 @@ impl<'r, R: Read + 'r> PageReader<'r, R> {
         Ok(())
     }
 
+    /// The index of the current page, if it's known, see [`PageReader::pages_opened`].
+    fn page_index(&self) -> Option<u64> {
+        self.pages_opened.and_then(|count| count.checked_sub(1))
+    }
+
+    /// Where the next feature will be read from.
+    fn location(&self) -> FeatureLocation {
+        let current_page = self
+            .current_page
+            .as_ref()
+            .expect("current_page is always replaced");
+        FeatureLocation {
+            page_starting_offset: current_page.page_starting_offset,
+            feature_offset: current_page.page_decoder.offset_within_page(),
+        }
+    }
+
     fn ff_to_location(&mut self, location: FeatureLocation) -> Result<()> {
         // First get to the right page.
         let (mut page_decoder, page_starting_offset) = match self
@@ -109,16 +220,25 @@ impl<'r, R: Read + 'r> PageReader<'r, R> {
                     reader.total_bytes_read()
                 );
                 let distance = location.page_starting_offset - reader.total_bytes_read();
+                if distance > 0 {
+                    // Whether whole pages were skipped, rather than only the rest of the
+                    // current one, is unknown without reading their headers.
+                    self.pages_opened = None;
+                } else {
+                    self.pages_opened = self.pages_opened.map(|count| count + 1);
+                }
                 let mut reader = {
                     let mut ff = reader.take(distance);
                     std::io::copy(&mut ff, &mut std::io::sink())?;
                     ff.into_inner()
                 };
-                let header: PageHeader = deserialize_from(&mut reader)?;
+                let (header, _page_bounds) =
+                    PageHeader::read_from(&mut reader, self.has_page_checksums)?;
                 let page_decoder = new_page_decoder(
                     reader.take(header.encoded_page_length() as u64),
-                    self.is_compressed,
-                    header.decoded_page_length(),
+                    self.compression,
+                    &header,
+                    self.dictionary.as_deref(),
                 )?;
                 (page_decoder, location.page_starting_offset)
             }
@@ -134,6 +254,16 @@ impl<'r, R: Read + 'r> PageReader<'r, R> {
     }
 }
 
+impl<'r, R: Read + 'r> CurrentPage<'r, R> {
+    /// A page with nothing left to read, so the next page at `reader` is opened when it's needed.
+    fn exhausted(reader: CountingReader<R>) -> Self {
+        Self {
+            page_starting_offset: reader.total_bytes_read(),
+            page_decoder: Box::new(UncompressedPageDecoder::new(reader.take(0))),
+        }
+    }
+}
+
 impl<'r, R: Read + 'r> Read for PageReader<'r, R> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         let CurrentPage {
@@ -157,6 +287,8 @@ impl<'r, R: Read + 'r> Read for PageReader<'r, R> {
 
 trait PageDecoder<'r, R: Read + 'r>: Read + 'r {
     fn ff_to_feature_offset(&mut self, offset_within_page: u32) -> Result<()>;
+    /// How far into the decoded page has been read.
+    fn offset_within_page(&self) -> u32;
     fn was_read_to_end(&self) -> bool;
     fn into_inner(self: Box<Self>) -> CountingReader<R>;
 }
@@ -167,8 +299,12 @@ struct ZstdPageDecoder<R: Read> {
 }
 
 impl<R: Read> ZstdPageDecoder<R> {
-    fn new(read: Take<CountingReader<R>>, decoded_page_length: u32) -> Result<Self> {
-        let zstd_decoder = ZstdDecoder::new(read)
+    fn new(
+        read: Take<CountingReader<R>>,
+        decoded_page_length: u32,
+        dictionary: Option<&[u8]>,
+    ) -> Result<Self> {
+        let zstd_decoder = ZstdDecoder::new_with_decoder(read, zstd_frame_decoder(dictionary)?)
             .map_err(|e| {
                 Error::IO(std::io::Error::new(
                     std::io::ErrorKind::InvalidData,
@@ -205,6 +341,10 @@ impl<'r, R: Read + 'r> PageDecoder<'r, R> for ZstdPageDecoder<R> {
         Ok(())
     }
 
+    fn offset_within_page(&self) -> u32 {
+        self.offset_within_decoded_page_content()
+    }
+
     fn was_read_to_end(&self) -> bool {
         self.zstd_decoder.limit() == 0
     }
@@ -259,6 +399,10 @@ impl<'r, R: Read + 'r> PageDecoder<'r, R> for UncompressedPageDecoder<R> {
         Ok(())
     }
 
+    fn offset_within_page(&self) -> u32 {
+        self.current_feature_offset
+    }
+
     fn was_read_to_end(&self) -> bool {
         self.inner.limit() == 0
     }
@@ -268,99 +412,778 @@ impl<'r, R: Read + 'r> PageDecoder<'r, R> for UncompressedPageDecoder<R> {
     }
 }
 
+/// Decodes the whole page up front, for codecs which aren't streamed, see [`Compression`], or to
+/// verify the page's checksum.
+struct BufferedPageDecoder<R: Read> {
+    /// Positioned after the page.
+    inner: CountingReader<R>,
+    decoded: std::io::Cursor<Vec<u8>>,
+}
+
+impl<R: Read> BufferedPageDecoder<R> {
+    fn new(
+        mut read: Take<CountingReader<R>>,
+        compression: Compression,
+        page_header: &PageHeader,
+        dictionary: Option<&[u8]>,
+    ) -> Result<Self> {
+        let mut encoded = Vec::with_capacity(untrusted_capacity(read.limit()));
+        read.read_to_end(&mut encoded)?;
+        verify_page_checksum(&encoded, page_header.checksum())?;
+        let decoded = decode_page(
+            &encoded,
+            compression,
+            page_header.decoded_page_length(),
+            dictionary,
+        )?;
+        Ok(Self {
+            inner: read.into_inner(),
+            decoded: std::io::Cursor::new(decoded),
+        })
+    }
+}
+
+impl<R: Read> Read for BufferedPageDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Read::read(&mut self.decoded, buf)
+    }
+}
+
+impl<'r, R: Read + 'r> PageDecoder<'r, R> for BufferedPageDecoder<R> {
+    fn ff_to_feature_offset(&mut self, offset_within_page: u32) -> Result<()> {
+        assert!(
+            self.decoded.position() <= offset_within_page as u64,
+            "Requested {offset_within_page:?} which is before current offset: {}",
+            self.decoded.position()
+        );
+        self.decoded.set_position(offset_within_page as u64);
+        Ok(())
+    }
+
+    fn offset_within_page(&self) -> u32 {
+        self.decoded.position() as u32
+    }
+
+    fn was_read_to_end(&self) -> bool {
+        self.decoded.position() >= self.decoded.get_ref().len() as u64
+    }
+
+    fn into_inner(self: Box<Self>) -> CountingReader<R> {
+        self.inner
+    }
+}
+
 #[derive(Debug)]
 pub struct Reader<'r, R: Read + 'r> {
     inner: R,
     header: Header,
+    /// The serialized root node, which is read along with the header, so `inner` is positioned
+    /// just after it. Empty if there are no features.
+    index_root: Vec<u8>,
     _marker: &'r PhantomData<()>,
 }
 
+/// A part of a file which is missing, see [`Error::TruncatedFile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileSection {
+    Index,
+    /// The header of the page with this index, counting from 0.
+    PageHeader {
+        page: u64,
+    },
+    /// The contents of the page with this index, counting from 0.
+    Page {
+        page: u64,
+    },
+}
+
+impl std::fmt::Display for FileSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileSection::Index => write!(f, "the index"),
+            FileSection::PageHeader { page } => write!(f, "the header of page {page}"),
+            FileSection::Page { page } => write!(f, "page {page}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FileInfo {
     header: Header,
     index_size: u64,
+    extent: Option<Bounds>,
 }
 impl FileInfo {
+    pub(crate) fn new(header: Header) -> Self {
+        let index_size =
+            PackedRTree::new(header.feature_count, header.branching_factor).index_size();
+        let extent = (header.feature_count > 0).then(|| header.extent.clone());
+        Self {
+            header,
+            index_size,
+            extent,
+        }
+    }
     pub fn index_size(&self) -> u64 {
         self.index_size
     }
     pub fn header_size(&self) -> Result<u64> {
         serialized_size(&self.header)
     }
+    pub fn feature_count(&self) -> u64 {
+        self.header.feature_count
+    }
+    pub fn page_count(&self) -> u64 {
+        self.header.page_count
+    }
+    /// The bounds of all the features, as stored in the header.
+    ///
+    /// `None` if there are no features.
+    pub fn extent(&self) -> Option<Bounds> {
+        self.extent.clone()
+    }
+}
+
+impl<'r, R: Read + Seek + 'r> Reader<'r, R> {
+    /// Like [`Reader::new`], but first verifies that the feature count in the header agrees with
+    /// the rest of the file.
+    ///
+    /// The index layout is derived from the header's feature count, so a corrupt feature count
+    /// can cause `select_all` and `select_bbox` to disagree. This makes the same checks as
+    /// [`Reader::validate`], and also returns [`Error::IndexSizeMismatch`] if any bytes follow the
+    /// last page. It walks every page header, so it requires seeking, but does not decode any
+    /// features.
+    pub fn new_strict(reader: R) -> Result<Self> {
+        let mut reader = Self::new(reader)?;
+        let position = reader.inner.stream_position()?;
+        let pages_end = reader.validate_lengths(position)?;
+        let file_len = reader.inner.seek(SeekFrom::End(0))?;
+        if pages_end != file_len {
+            return Err(Error::IndexSizeMismatch {
+                feature_count: reader.header.feature_count,
+            });
+        }
+        reader.inner.seek(SeekFrom::Start(position))?;
+        Ok(reader)
+    }
+
+    /// Checks that the file isn't truncated, i.e. that the whole index is present, followed by
+    /// every page the header lists, and that those pages hold as many features as the header says.
+    ///
+    /// Returns [`Error::TruncatedFile`], naming the first missing part of the file. Only the page
+    /// headers are read, not the features, and the reader is left where it was. Any bytes after
+    /// the last page are ignored, e.g. another concatenated file.
+    pub fn validate(&mut self) -> Result<()> {
+        let position = self.inner.stream_position()?;
+        let result = self.validate_lengths(position);
+        self.inner.seek(SeekFrom::Start(position))?;
+        result.map(|_pages_end| ())
+    }
+
+    /// The checks of [`Reader::validate`], returning the offset just past the last page.
+    ///
+    /// `position` is where `inner` was left after reading the header and the index root.
+    fn validate_lengths(&mut self, position: u64) -> Result<u64> {
+        let file_len = self.inner.seek(SeekFrom::End(0))?;
+        let truncated = |section, expected_len| Error::TruncatedFile {
+            section,
+            expected_len,
+            file_len,
+        };
+
+        let index_start = position - self.index_root.len() as u64;
+        let index_size =
+            PackedRTree::new(self.header.feature_count, self.header.branching_factor).index_size();
+        let mut page_start = index_start + index_size;
+        if page_start > file_len {
+            return Err(truncated(FileSection::Index, page_start));
+        }
+
+        let page_header_size = PageHeader::serialized_size(self.header.has_page_checksums) as u64;
+        let mut found_feature_count = 0;
+        for page in 0..self.header.page_count {
+            let page_header_end = page_start + page_header_size;
+            if page_header_end > file_len {
+                return Err(truncated(FileSection::PageHeader { page }, page_header_end));
+            }
+            self.inner.seek(SeekFrom::Start(page_start))?;
+            let (page_header, _page_bounds) =
+                PageHeader::read_from(&mut self.inner, self.header.has_page_checksums)?;
+            let page_end = page_header_end + page_header.encoded_page_length() as u64;
+            if page_end > file_len {
+                return Err(truncated(FileSection::Page { page }, page_end));
+            }
+            found_feature_count += page_header.feature_count() as u64;
+            page_start = page_end;
+        }
+
+        if found_feature_count != self.header.feature_count {
+            return Err(Error::FeatureCountMismatch {
+                found: found_feature_count,
+                expected: self.header.feature_count,
+            });
+        }
+        Ok(page_start)
+    }
+
+    /// Like [`Reader::select_all`], but in the reverse order, i.e. ascending Hilbert order.
+    ///
+    /// Pages are read from last to first, so this requires seeking, and each page is decoded in
+    /// full before its features are yielded, so a page of features is held in memory at a time.
+    pub fn select_all_rev(self) -> Result<ReverseFeatureIter<R>> {
+        let header = self.header.clone();
+        let ((), mut reader) = self.read_index(|_index_reader| Ok(()))?;
+
+        let mut pages = Vec::with_capacity(untrusted_capacity(header.page_count));
+        for _ in 0..header.page_count {
+            let page_start = reader.stream_position()?;
+            let (page_header, _page_bounds) =
+                PageHeader::read_from(&mut reader, header.has_page_checksums)?;
+            reader.seek(SeekFrom::Current(page_header.encoded_page_length() as i64))?;
+            pages.push((page_start, page_header.feature_count()));
+        }
+
+        Ok(ReverseFeatureIter {
+            reader,
+            header,
+            pages,
+            current_page: vec![],
+        })
+    }
+
+    /// A measure of how well features are spatially clustered into pages, where lower is better.
+    ///
+    /// This is the total perimeter of every page's bounds, relative to the perimeter of the bounds
+    /// of all the features. For example, if every page's bounds covered all the features, the score
+    /// would be the page count. Poor locality means spatial queries must read more pages.
+    pub fn locality_score(&mut self) -> Result<f64> {
+        let index_start = self.inner.stream_position()? - self.index_root.len() as u64;
+        let index_size =
+            PackedRTree::new(self.header.feature_count, self.header.branching_factor).index_size();
+        self.inner.seek(SeekFrom::Start(index_start + index_size))?;
+
+        fn half_perimeter(bounds: &Bounds) -> f64 {
+            bounds.unscaled_lng_width() as f64 + bounds.unscaled_lat_height() as f64
+        }
+
+        let mut all_page_bounds = vec![];
+        for _ in 0..self.header.page_count {
+            let (page_header, page_bounds) =
+                PageHeader::read_from(&mut self.inner, self.header.has_page_checksums)?;
+            if page_header.feature_count() > 0 {
+                all_page_bounds.push(page_bounds);
+            }
+            self.inner
+                .seek(SeekFrom::Current(page_header.encoded_page_length() as i64))?;
+        }
+        self.inner
+            .seek(SeekFrom::Start(index_start + self.index_root.len() as u64))?;
+
+        let Some((first, rest)) = all_page_bounds.split_first() else {
+            return Ok(0.0);
+        };
+        let mut extent = first.clone();
+        for page_bounds in rest {
+            extent.extend(page_bounds);
+        }
+        let extent_half_perimeter = half_perimeter(&extent);
+        if extent_half_perimeter == 0.0 {
+            // All the features are at a single point.
+            return Ok(0.0);
+        }
+        let total_half_perimeter: f64 = all_page_bounds.iter().map(half_perimeter).sum();
+        Ok(total_half_perimeter / extent_half_perimeter)
+    }
+}
+
+/// Reads a memory mapped local file, jumping directly to the parts of the index and the pages
+/// which are needed, rather than reading past everything before them like [`Reader`] does.
+///
+/// Unlike [`Reader`], queries borrow rather than consume the `MmapReader`, so repeated queries
+/// against a large file don't need to reopen it, or even re-read the header.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct MmapReader {
+    mmap: Mmap,
+    header: Header,
+    tree: PackedRTree,
+    /// The byte offset of the index within the file.
+    index_start: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl MmapReader {
+    /// Memory map the local file at `path`.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated by this or any other process while the
+    /// `MmapReader` exists, otherwise reading is undefined behavior. See [`Mmap::map`].
+    pub unsafe fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+        let mmap = Mmap::map(&file)?;
+        let mut remaining = &mmap[..];
+        let header = Header::read_from(&mut remaining)?;
+        let index_start = mmap.len() - remaining.len();
+        let tree = PackedRTree::new(header.feature_count, header.branching_factor);
+        if (remaining.len() as u64) < tree.index_size() {
+            return Err(Error::IndexSizeMismatch {
+                feature_count: header.feature_count,
+            });
+        }
+        Ok(Self {
+            mmap,
+            header,
+            tree,
+            index_start,
+        })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Like [`Reader::select_bbox`], but only the index nodes which intersect `bounds` are read,
+    /// and only the pages which contain a selected feature are decoded.
+    pub fn select_bbox(&self, bounds: &Bounds) -> Result<MmapFeatureIter<'_>> {
+        self.select_bboxes(std::slice::from_ref(bounds))
+    }
+
+    /// Like [`Reader::select_bboxes`], but only the index nodes which intersect any of `bounds`
+    /// are read, and only the pages which contain a selected feature are decoded.
+    pub fn select_bboxes(&self, bounds: &[Bounds]) -> Result<MmapFeatureIter<'_>> {
+        let index_end = self.index_start + self.tree.index_size() as usize;
+        let index = &self.mmap[self.index_start..index_end];
+        debug!("select_bboxes with bounds: {bounds:?}");
+        let locations = select_bboxes_in_slice(&self.tree, index, bounds)?;
+        debug!("locations: {locations:?}");
+        Ok(MmapFeatureIter {
+            pages: &self.mmap[index_end..],
+            header: &self.header,
+            locations: locations.into_iter().peekable(),
+            current_page: None,
+        })
+    }
+}
+
+/// Iterates over the features selected by [`MmapReader::select_bboxes`], see
+/// [`MmapFeatureIter::try_next`].
+#[cfg(not(target_arch = "wasm32"))]
+pub struct MmapFeatureIter<'a> {
+    /// Everything after the index.
+    pages: &'a [u8],
+    header: &'a Header,
+    locations: std::iter::Peekable<std::vec::IntoIter<FeatureLocation>>,
+    /// The selected features of the page currently being read, and where that page starts.
+    current_page: Option<(FeatureIter<'a, &'a [u8]>, u64)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl MmapFeatureIter<'_> {
+    pub fn try_next(&mut self) -> Result<Option<Feature>> {
+        loop {
+            if let Some((current_page, page_starting_offset)) = &mut self.current_page {
+                // The page is read on its own, so locations within it are relative to it.
+                let feature = current_page.try_next().map_err(|error| match error {
+                    Error::CorruptFeature {
+                        feature_offset,
+                        source,
+                        ..
+                    } => Error::CorruptFeature {
+                        page_index: None,
+                        page_starting_offset: *page_starting_offset,
+                        feature_offset,
+                        source,
+                    },
+                    error => error,
+                })?;
+                if let Some(feature) = feature {
+                    return Ok(Some(feature));
+                }
+            }
+            let Some(next) = self.locations.peek() else {
+                return Ok(None);
+            };
+
+            // Start reading from the page itself, so locations are relative to it.
+            let page_starting_offset = next.page_starting_offset;
+            let mut page_locations = vec![];
+            while let Some(location) = self
+                .locations
+                .next_if(|location| location.page_starting_offset == page_starting_offset)
+            {
+                page_locations.push(FeatureLocation {
+                    page_starting_offset: 0,
+                    feature_offset: location.feature_offset,
+                });
+            }
+            let page = usize::try_from(page_starting_offset)
+                .ok()
+                .and_then(|start| self.pages.get(start..))
+                .ok_or_else(|| {
+                    Error::IO(std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        format!("page at {page_starting_offset} is past the end of the file"),
+                    ))
+                })?;
+            let feature_iter = FeatureIter {
+                features_left: page_locations.len() as u64,
+                selection: Selection::Bbox(Box::new(page_locations.into_iter())),
+                page_reader: PageReader::new(page, self.header)?,
+                coord_precision_nanos: self.header.coord_precision_nanos,
+                within: None,
+                peeked: None,
+                projection: None,
+            };
+            self.current_page = Some((feature_iter, page_starting_offset));
+        }
+    }
 }
 
 impl<'r, R: Read + 'r> Reader<'r, R> {
     pub fn new(mut reader: R) -> Result<Self> {
-        let header: Header = deserialize_from(&mut reader)?;
+        let header = Header::read_from(&mut reader)?;
+        Self::with_header(reader, header)
+    }
+
+    /// `reader` must be positioned at the start of the index.
+    fn with_header(mut reader: R, header: Header) -> Result<Self> {
+        let mut index_root = vec![];
+        if header.feature_count > 0 {
+            index_root.resize(Node::serialized_size(), 0);
+            reader.read_exact(&mut index_root)?;
+        }
         Ok(Self {
             inner: reader,
             header,
+            index_root,
             _marker: &PhantomData,
         })
     }
 
+    /// Calls `read_index` with a reader of the entire index, returning its result along with the
+    /// inner reader, positioned at the start of the first page.
+    fn read_index<T>(self, read_index: impl FnOnce(&mut dyn Read) -> Result<T>) -> Result<(T, R)> {
+        let index_size =
+            PackedRTree::new(self.header.feature_count, self.header.branching_factor).index_size();
+        let mut index_reader = self
+            .index_root
+            .as_slice()
+            .chain(self.inner)
+            .take(index_size);
+        let value = read_index(&mut index_reader)?;
+        // Skip past any remaining index bytes
+        std::io::copy(&mut index_reader, &mut std::io::sink())?;
+        let (_index_root, reader) = index_reader.into_inner().into_inner();
+        Ok((value, reader))
+    }
+
     pub fn header(&self) -> &Header {
         &self.header
     }
 
-    pub fn info(&self) -> FileInfo {
-        FileInfo {
-            header: self.header.clone(),
-            index_size: PackedRTree::new(self.header.feature_count).index_size(),
+    /// The range of node indices on each level of the spatial index, starting with the root, so
+    /// the last level is the leaves, one per feature.
+    ///
+    /// This is determined by the header alone, so nothing more is read.
+    pub fn index_levels(&self) -> Vec<Range<u64>> {
+        PackedRTree::new(self.header.feature_count, self.header.branching_factor)
+            .node_ranges_by_level()
+            .to_vec()
+    }
+
+    /// Every node of the spatial index, along with its node index, in stored order, i.e. the
+    /// root first, see [`Reader::index_levels`].
+    ///
+    /// This is useful for inspecting the quality of the index, or for custom spatial queries.
+    pub fn index_nodes(self) -> Result<impl Iterator<Item = (u64, Node)>> {
+        let header = self.header.clone();
+        let (nodes, _reader) = self.read_index(|index_reader| {
+            let node_count =
+                PackedRTree::new(header.feature_count, header.branching_factor).node_count();
+            if node_count == 0 {
+                return Ok(vec![]);
+            }
+            PackedRTreeReader::new(header.feature_count, header.branching_factor, index_reader)
+                .read_node_range(0..node_count)
+        })?;
+        Ok(nodes.into_iter())
+    }
+
+    /// Skips the rest of the file without reading any features, returning the inner reader,
+    /// positioned just after the end of the file, along with the length of the file.
+    ///
+    /// This allows reading files which have been concatenated, e.g. by
+    /// `cat a.geomedea b.geomedea`, since a new `Reader` can be created from the returned reader
+    /// to read the next file.
+    pub fn skip_to_end(self) -> Result<(R, u64)> {
+        let header = self.header.clone();
+        let index_size =
+            PackedRTree::new(header.feature_count, header.branching_factor).index_size();
+        let ((), mut reader) = self.read_index(|_index_reader| Ok(()))?;
+
+        let mut pages = CountingReader::new(&mut reader, "skip_to_end");
+        for _ in 0..header.page_count {
+            let (page_header, _page_bounds) =
+                PageHeader::read_from(&mut pages, header.has_page_checksums)?;
+            let encoded_page_length = page_header.encoded_page_length() as u64;
+            let skipped = std::io::copy(
+                &mut (&mut pages).take(encoded_page_length),
+                &mut std::io::sink(),
+            )?;
+            if skipped != encoded_page_length {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
         }
+        let file_len = serialized_size(&header)? + index_size + pages.total_bytes_read();
+        Ok((reader, file_len))
     }
 
+    pub fn info(&self) -> FileInfo {
+        FileInfo::new(self.header.clone())
+    }
+
+    /// Read every feature, in the order they're stored.
+    ///
+    /// This is not the order they were added to the `Writer`: features are sorted along a Hilbert
+    /// curve, in descending order of the Hilbert value of each feature's center, so that nearby
+    /// features are stored in the same page. See [`Reader::select_all_rev`] for the reverse order.
     pub fn select_all(self) -> Result<FeatureIter<'r, R>> {
-        let reader = {
-            let index_size = PackedRTree::new(self.header.feature_count).index_size();
-            let mut index_reader = self.inner.take(index_size);
-            std::io::copy(&mut index_reader, &mut std::io::sink())?;
-            index_reader.into_inner()
-        };
-        let page_reader = PageReader::new(reader, self.header.is_compressed)?;
+        let header = self.header.clone();
+        let ((), reader) = self.read_index(|_index_reader| Ok(()))?;
+        let page_reader = PageReader::new(reader, &header)?;
         Ok(FeatureIter {
             selection: Selection::All,
             page_reader,
-            features_left: self.header.feature_count,
+            features_left: header.feature_count,
+            coord_precision_nanos: header.coord_precision_nanos,
+            within: None,
+            peeked: None,
+            projection: None,
         })
     }
 
+    /// Read the single feature at `location`, e.g. as returned by
+    /// `Writer::finish_with_feature_locations`.
+    ///
+    /// Returns `None` if the file has no features.
+    pub fn read_feature_at(self, location: FeatureLocation) -> Result<Option<Feature>> {
+        let header = self.header.clone();
+        let ((), reader) = self.read_index(|_index_reader| Ok(()))?;
+        let page_reader = PageReader::new(reader, &header)?;
+        let mut feature_iter = FeatureIter {
+            selection: Selection::Bbox(Box::new(std::iter::once(location))),
+            page_reader,
+            features_left: header.feature_count,
+            coord_precision_nanos: header.coord_precision_nanos,
+            within: None,
+            peeked: None,
+            projection: None,
+        };
+        feature_iter.try_next()
+    }
+
+    /// Read the properties of every feature, in the same order as [`Reader::select_all`].
+    ///
+    /// Each feature's geometry must still be read, since it's stored before the properties, but
+    /// it's skipped over without being decoded or allocated. This is much faster than
+    /// [`Reader::select_all`] for files with large geometries, when only the properties are needed.
+    pub fn properties_only(self) -> Result<PropertiesIter<'r, R>> {
+        Ok(PropertiesIter {
+            feature_iter: self.select_all()?,
+        })
+    }
+
+    /// Read the [`GeometryType`] of every feature, in the same order as [`Reader::select_all`],
+    /// e.g. to count how many of each there are.
+    ///
+    /// Only the start of each geometry is decoded, the rest of the feature is skipped over without
+    /// being allocated.
+    pub fn geometry_types(self) -> Result<GeometryTypesIter<'r, R>> {
+        Ok(GeometryTypesIter {
+            feature_iter: self.select_all()?,
+        })
+    }
+
+    /// Like [`Reader::select_all`], but each feature's properties only include those named in
+    /// `keys`.
+    ///
+    /// Every feature must still be read, but the values of other properties are skipped without
+    /// being allocated.
+    pub fn select_all_with_projection(self, keys: &[&str]) -> Result<FeatureIter<'r, R>> {
+        let mut feature_iter = self.select_all()?;
+        feature_iter.projection = Some(keys.iter().map(|key| Arc::from(*key)).collect());
+        Ok(feature_iter)
+    }
+
+    /// Like [`Reader::select_bbox`], but each feature's properties only include those named in
+    /// `keys`. See [`Reader::select_all_with_projection`].
+    pub fn select_bbox_with_projection(
+        self,
+        bounds: &Bounds,
+        keys: &[&str],
+    ) -> Result<FeatureIter<'r, R>> {
+        let mut feature_iter = self.select_bbox(bounds)?;
+        feature_iter.projection = Some(keys.iter().map(|key| Arc::from(*key)).collect());
+        Ok(feature_iter)
+    }
+
+    /// Select the features which are entirely within `polygon`.
+    ///
+    /// This is a two phase "candidate then refine" query: the spatial index is first used to find
+    /// every feature whose bounding box intersects the polygon's bounding box, exactly like
+    /// [`Reader::select_bbox`]. Each of those candidates must then be read and decoded before it's
+    /// precisely compared against the polygon. So a query polygon which covers only a small
+    /// fraction of its own bounding box may read many more features than it returns.
+    pub fn select_within(self, polygon: &Polygon) -> Result<FeatureIter<'r, R>> {
+        let mut feature_iter = self.select_bbox(&polygon.bounds())?;
+        feature_iter.within = Some(polygon.clone());
+        Ok(feature_iter)
+    }
+
+    /// Select the (up to) `k` features nearest to `point`, closest first.
+    ///
+    /// Distance is measured to each feature's bounding box, in planar lng/lat space, so this is
+    /// exact for points but only approximate for other geometries.
+    pub fn select_nearest(self, point: &LngLat, k: usize) -> Result<Vec<Feature>> {
+        let header = self.header.clone();
+        let (nearest, reader) = self.read_index(|index_reader| {
+            let rtree_reader =
+                PackedRTreeReader::new(header.feature_count, header.branching_factor, index_reader);
+            let nearest = rtree_reader.nearest(point, k)?;
+            debug!("nearest: {nearest:?}");
+            Ok(nearest)
+        })?;
+
+        // We can only read forward, so read the features in file order, then restore the order
+        // of nearness.
+        let mut locations: Vec<(usize, FeatureLocation)> =
+            nearest.into_iter().enumerate().collect();
+        locations.sort_by_key(|(_rank, location)| {
+            (location.page_starting_offset, location.feature_offset)
+        });
+        let ranks: Vec<usize> = locations.iter().map(|(rank, _location)| *rank).collect();
+
+        let page_reader = PageReader::new(reader, &header)?;
+        let mut feature_iter = FeatureIter {
+            selection: Selection::Bbox(Box::new(
+                locations.into_iter().map(|(_rank, location)| location),
+            )),
+            page_reader,
+            features_left: header.feature_count,
+            coord_precision_nanos: header.coord_precision_nanos,
+            within: None,
+            peeked: None,
+            projection: None,
+        };
+        let expected = ranks.len() as u64;
+        let mut features = Vec::with_capacity(ranks.len());
+        for rank in ranks {
+            // A corrupt index can point past the last feature.
+            let Some(feature) = feature_iter.try_next()? else {
+                return Err(Error::FeatureCountMismatch {
+                    found: features.len() as u64,
+                    expected,
+                });
+            };
+            features.push((rank, feature));
+        }
+        features.sort_by_key(|(rank, _feature)| *rank);
+        Ok(features
+            .into_iter()
+            .map(|(_rank, feature)| feature)
+            .collect())
+    }
+
     pub fn select_bbox(self, bounds: &Bounds) -> Result<FeatureIter<'r, R>> {
-        let (items, reader) = {
-            let index_size = PackedRTree::new(self.header.feature_count).index_size();
-            let mut index_reader = self.inner.take(index_size);
-            let rtree_reader = PackedRTreeReader::new(self.header.feature_count, &mut index_reader);
-            debug!("select_bbox with bounds: {bounds:?}");
-            let items = rtree_reader.select_bbox(bounds)?;
+        self.select_bboxes(std::slice::from_ref(bounds))
+    }
+
+    /// Select the features which intersect any of `bounds`, e.g. several disjoint viewports.
+    ///
+    /// The index is searched once for all of them, and a feature which intersects more than one
+    /// is only yielded once.
+    pub fn select_bboxes(self, bounds: &[Bounds]) -> Result<FeatureIter<'r, R>> {
+        let header = self.header.clone();
+        let (items, reader) = self.read_index(|index_reader| {
+            let rtree_reader =
+                PackedRTreeReader::new(header.feature_count, header.branching_factor, index_reader);
+            debug!("select_bboxes with bounds: {bounds:?}");
+            let items = rtree_reader.select_bboxes(bounds)?;
             debug!("items: {items:?}");
-            // Skip past any remaining index bytes
-            std::io::copy(&mut index_reader, &mut std::io::sink())?;
-            (items, index_reader.into_inner())
-        };
-        let page_reader = PageReader::new(reader, self.header.is_compressed)?;
+            Ok(items)
+        })?;
+        let page_reader = PageReader::new(reader, &header)?;
         Ok(FeatureIter {
             selection: Selection::Bbox(Box::new(items.into_iter())),
             page_reader,
-            features_left: self.header.feature_count,
+            features_left: header.feature_count,
+            coord_precision_nanos: header.coord_precision_nanos,
+            within: None,
+            peeked: None,
+            projection: None,
+        })
+    }
+
+    /// Select the features which intersect `bounds` by scanning the pages, rather than searching
+    /// the index.
+    ///
+    /// Pages whose bounds don't intersect `bounds` are skipped without being decoded, but the
+    /// features of every other page are read. This can be faster than [`Reader::select_bbox`]
+    /// when `bounds` covers much of the file, since the index isn't read at all.
+    pub fn select_bbox_by_page(self, bounds: &Bounds) -> Result<FeatureIter<'r, R>> {
+        let header = self.header.clone();
+        let ((), reader) = self.read_index(|_index_reader| Ok(()))?;
+        let page_reader = PageReader::new_unopened(reader, &header);
+        Ok(FeatureIter {
+            selection: Selection::BboxByPage(bounds.clone()),
+            page_reader,
+            features_left: header.feature_count,
+            coord_precision_nanos: header.coord_precision_nanos,
+            within: None,
+            peeked: None,
+            projection: None,
         })
     }
 }
 
+/// Pages with a checksum are read and verified in full before being decoded, so a corrupt page is
+/// caught before any of its features are read.
 fn new_page_decoder<'r, R: Read + 'r>(
     inner: Take<CountingReader<R>>,
-    is_compressed: bool,
-    decoded_page_length: u32,
+    compression: Compression,
+    page_header: &PageHeader,
+    dictionary: Option<&[u8]>,
 ) -> Result<Box<dyn PageDecoder<'r, R>>> {
-    let page_decoder: Box<dyn PageDecoder<'r, R>> = if is_compressed {
-        if decoded_page_length == 0 {
-            // else we error when trying to read the header from an empty reader.
-            // zstd does not require this workaround - is this a bug in ruzstd?
-            Box::new(UncompressedPageDecoder::new(inner))
-        } else {
-            Box::new(ZstdPageDecoder::<R>::new(inner, decoded_page_length)?)
+    let decoded_page_length = page_header.decoded_page_length();
+    if page_header.checksum().is_some() {
+        return Ok(Box::new(BufferedPageDecoder::<R>::new(
+            inner,
+            compression,
+            page_header,
+            dictionary,
+        )?));
+    }
+    let page_decoder: Box<dyn PageDecoder<'r, R>> = match compression {
+        Compression::None => Box::new(UncompressedPageDecoder::new(inner)),
+        Compression::Zstd => {
+            if decoded_page_length == 0 {
+                // else we error when trying to read the header from an empty reader.
+                // zstd does not require this workaround - is this a bug in ruzstd?
+                Box::new(UncompressedPageDecoder::new(inner))
+            } else {
+                Box::new(ZstdPageDecoder::<R>::new(
+                    inner,
+                    decoded_page_length,
+                    dictionary,
+                )?)
+            }
         }
-    } else {
-        Box::new(UncompressedPageDecoder::new(inner))
+        Compression::Lz4 | Compression::Brotli => Box::new(BufferedPageDecoder::<R>::new(
+            inner,
+            compression,
+            page_header,
+            dictionary,
+        )?),
     };
     Ok(page_decoder)
 }
@@ -368,6 +1191,8 @@ fn new_page_decoder<'r, R: Read + 'r>(
 enum Selection {
     All,
     Bbox(Box<dyn Iterator<Item = FeatureLocation>>),
+    /// Read every page which intersects the bounds, yielding the features which intersect it.
+    BboxByPage(Bounds),
 }
 
 // TODO: can we remove this lifetime?
@@ -375,14 +1200,79 @@ pub struct FeatureIter<'r, R: Read> {
     page_reader: PageReader<'r, R>,
     selection: Selection,
     features_left: u64,
+    coord_precision_nanos: i32,
+    /// Only yield features within this polygon
+    within: Option<Polygon>,
+    /// A feature read by `peek`, but not yet yielded by `try_next`
+    peeked: Option<Feature>,
+    /// Only keep these properties
+    projection: Option<Vec<Arc<str>>>,
 }
 
-impl<R: Read> FeatureIter<'_, R> {
+impl<'r, R: Read> FeatureIter<'r, R> {
     pub fn try_next(&mut self) -> Result<Option<Feature>> {
+        if let Some(peeked) = self.peeked.take() {
+            return Ok(Some(peeked));
+        }
+        loop {
+            let Some(feature) = self.next_candidate()? else {
+                return Ok(None);
+            };
+            if let Selection::BboxByPage(bounds) = &self.selection {
+                if !feature.geometry().bounds().intersects(bounds) {
+                    trace!("skipping feature not intersecting bounds: {feature:?}");
+                    continue;
+                }
+            }
+            match &self.within {
+                Some(polygon) if !is_within(feature.geometry(), polygon) => {
+                    trace!("skipping feature not within polygon: {feature:?}");
+                    continue;
+                }
+                _ => return Ok(Some(feature)),
+            }
+        }
+    }
+
+    /// Yields each feature's geometry as a `geo_types::Geometry`, along with its properties.
+    #[cfg(feature = "geo-types")]
+    pub fn into_geo(self) -> GeoFeatureIter<'r, R> {
+        GeoFeatureIter { feature_iter: self }
+    }
+
+    /// Returns the feature which the next call to `try_next` will return, without consuming it.
+    pub fn peek(&mut self) -> Result<Option<&Feature>> {
+        if self.peeked.is_none() {
+            self.peeked = self.try_next()?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+
+    fn next_candidate(&mut self) -> Result<Option<Feature>> {
+        let Some((location, _feature_size)) = self.ff_to_next_candidate()? else {
+            return Ok(None);
+        };
+        let page_index = self.page_reader.page_index();
+        let mut feature: Feature = match &self.projection {
+            Some(keys) => deserialize_from_seed(&mut self.page_reader, ProjectedFeature { keys }),
+            None => deserialize_from(&mut self.page_reader),
+        }
+        .map_err(corrupt_feature(&location, page_index))?;
+        if self.coord_precision_nanos != COORD_PRECISION_NANOS {
+            feature
+                .geometry_mut()
+                .rescale_from_precision(self.coord_precision_nanos);
+        }
+        debug!("read feature: {feature:?}");
+        Ok(Some(feature))
+    }
+
+    /// Advances to the start of the next selected feature, returning its location and serialized
+    /// size, or None if there are none left.
+    fn ff_to_next_candidate(&mut self) -> Result<Option<(FeatureLocation, u64)>> {
         if self.features_left == 0 {
             return Ok(None);
         }
-        self.features_left -= 1;
         match &mut self.selection {
             Selection::All => {
                 self.page_reader.ff_past_any_header()?;
@@ -393,12 +1283,142 @@ impl<R: Read> FeatureIter<'_, R> {
                 };
                 self.page_reader.ff_to_location(next)?;
             }
+            Selection::BboxByPage(bounds) => {
+                self.features_left -= self
+                    .page_reader
+                    .ff_past_pages_not_intersecting(bounds, self.features_left)?;
+                if self.features_left == 0 {
+                    return Ok(None);
+                }
+            }
+        }
+        self.features_left -= 1;
+        let location = self.page_reader.location();
+        let page_index = self.page_reader.page_index();
+        let feature_size: u64 = deserialize_from(&mut self.page_reader)
+            .map_err(corrupt_feature(&location, page_index))?;
+        Ok(Some((location, feature_size)))
+    }
+}
+
+/// Adds the location of the feature to a failure to decode it, e.g. because the file is truncated.
+pub(crate) fn corrupt_feature(
+    location: &FeatureLocation,
+    page_index: Option<u64>,
+) -> impl Fn(Error) -> Error + '_ {
+    move |error| match error {
+        Error::Bincode(source) => Error::CorruptFeature {
+            page_index,
+            page_starting_offset: location.page_starting_offset,
+            feature_offset: location.feature_offset,
+            source,
+        },
+        error => error,
+    }
+}
+
+/// Iterates over features as `geo_types`, see [`FeatureIter::into_geo`].
+#[cfg(feature = "geo-types")]
+pub struct GeoFeatureIter<'r, R: Read> {
+    feature_iter: FeatureIter<'r, R>,
+}
+
+#[cfg(feature = "geo-types")]
+impl<R: Read> GeoFeatureIter<'_, R> {
+    pub fn try_next(&mut self) -> Result<Option<(geo_types::Geometry, Properties)>> {
+        use crate::ToGeoType;
+        let Some(feature) = self.feature_iter.try_next()? else {
+            return Ok(None);
+        };
+        let (geometry, properties) = feature.into_inner();
+        Ok(Some((geometry.to_geo_type(), properties)))
+    }
+}
+
+/// Iterates over the [`GeometryType`] of every feature, see [`Reader::geometry_types`].
+pub struct GeometryTypesIter<'r, R: Read> {
+    feature_iter: FeatureIter<'r, R>,
+}
+
+impl<R: Read> GeometryTypesIter<'_, R> {
+    pub fn try_next(&mut self) -> Result<Option<GeometryType>> {
+        let Some((location, feature_size)) = self.feature_iter.ff_to_next_candidate()? else {
+            return Ok(None);
+        };
+        // A feature starts with its geometry, which starts with its variant index.
+        let page_index = self.feature_iter.page_reader.page_index();
+        let variant_index: u32 = deserialize_from(&mut self.feature_iter.page_reader)
+            .map_err(corrupt_feature(&location, page_index))?;
+        let geometry_type = GeometryType::from_variant_index(variant_index).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unknown geometry variant {variant_index}"),
+            )
+        })?;
+        let rest = feature_size.saturating_sub(serialized_size(&variant_index)?);
+        let skipped = std::io::copy(
+            &mut (&mut self.feature_iter.page_reader).take(rest),
+            &mut std::io::sink(),
+        )?;
+        if skipped != rest {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(Some(geometry_type))
+    }
+}
+
+/// Iterates over the [`Properties`] of every feature, see [`Reader::properties_only`].
+pub struct PropertiesIter<'r, R: Read> {
+    feature_iter: FeatureIter<'r, R>,
+}
+
+impl<R: Read> PropertiesIter<'_, R> {
+    pub fn try_next(&mut self) -> Result<Option<Properties>> {
+        let Some((location, _feature_size)) = self.feature_iter.ff_to_next_candidate()? else {
+            return Ok(None);
+        };
+        let page_index = self.feature_iter.page_reader.page_index();
+        let properties =
+            deserialize_from_seed(&mut self.feature_iter.page_reader, FeatureProperties)
+                .map_err(corrupt_feature(&location, page_index))?;
+        debug!("read properties: {properties:?}");
+        Ok(Some(properties))
+    }
+}
+
+/// Iterates over every feature in reverse, see [`Reader::select_all_rev`].
+pub struct ReverseFeatureIter<R: Read + Seek> {
+    reader: R,
+    header: Header,
+    /// The starting offset and feature count of each page not yet read.
+    pages: Vec<(u64, u32)>,
+    /// The remaining features of the current page, the next of which is last.
+    current_page: Vec<Feature>,
+}
+
+impl<R: Read + Seek> ReverseFeatureIter<R> {
+    pub fn try_next(&mut self) -> Result<Option<Feature>> {
+        loop {
+            if let Some(feature) = self.current_page.pop() {
+                return Ok(Some(feature));
+            }
+            let Some((page_start, feature_count)) = self.pages.pop() else {
+                return Ok(None);
+            };
+            self.reader.seek(SeekFrom::Start(page_start))?;
+            let mut page_features = FeatureIter {
+                selection: Selection::All,
+                page_reader: PageReader::new(&mut self.reader, &self.header)?,
+                features_left: feature_count as u64,
+                coord_precision_nanos: self.header.coord_precision_nanos,
+                within: None,
+                peeked: None,
+                projection: None,
+            };
+            while let Some(feature) = page_features.try_next()? {
+                self.current_page.push(feature);
+            }
         }
-        let _feature_size: u64 = deserialize_from(&mut self.page_reader)?;
-        // dbg!(_feature_size);
-        let feature = deserialize_from(&mut self.page_reader)?;
-        debug!("read feature: {feature:?}");
-        Ok(Some(feature))
     }
 }
 
@@ -406,7 +1426,8 @@ impl<R: Read> FeatureIter<'_, R> {
 #[cfg(feature = "writer")]
 mod tests {
     use super::*;
-    use crate::{ensure_logging, test_data, wkt, Geometry};
+    use crate::{ensure_logging, serialize_into, test_data, wkt, Geometry};
+    use std::io::Write;
 
     #[test]
     fn select_all_with_uncompressed_single_page() {
@@ -456,6 +1477,304 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate() {
+        for is_compressed in [false, true] {
+            let output = test_data::small_pages(4, is_compressed);
+            let mut reader = Reader::new(std::io::Cursor::new(&output)).unwrap();
+            reader.validate().unwrap();
+            // The reader is left where it was
+            let mut features = reader.select_all().unwrap();
+            let mut feature_count = 0;
+            while features.try_next().unwrap().is_some() {
+                feature_count += 1;
+            }
+            assert_eq!(feature_count, 4);
+
+            let info = Reader::new(output.as_slice()).unwrap().info();
+            let index_end = info.header_size().unwrap() + info.index_size();
+            let page_count = info.page_count();
+            let truncated_at = |len: u64| {
+                let mut reader =
+                    Reader::new(std::io::Cursor::new(&output[..len as usize])).unwrap();
+                match reader.validate().unwrap_err() {
+                    Error::TruncatedFile {
+                        section, file_len, ..
+                    } => {
+                        assert_eq!(file_len, len);
+                        section
+                    }
+                    other => panic!("expected TruncatedFile, found {other:?}"),
+                }
+            };
+            assert_eq!(truncated_at(index_end - 1), FileSection::Index);
+            assert_eq!(
+                truncated_at(index_end + 1),
+                FileSection::PageHeader { page: 0 }
+            );
+            assert_eq!(
+                truncated_at(output.len() as u64 - 1),
+                FileSection::Page {
+                    page: page_count - 1
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let mut output = test_data::points(4, false);
+        output.truncate(output.len() - 3);
+
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let mut feature_iter = reader.select_all().unwrap();
+        for _ in 0..3 {
+            assert!(feature_iter.try_next().unwrap().is_some());
+        }
+        let error = feature_iter.try_next().unwrap_err();
+        let (page_index, page_starting_offset, feature_offset) =
+            test_data::corrupt_feature_location(error);
+        assert_eq!(page_index, Some(0));
+        assert_eq!(page_starting_offset, 0);
+        assert!(feature_offset > 0);
+    }
+
+    #[test]
+    fn corrupt_feature_on_later_page() {
+        let mut output = test_data::small_pages(4, false);
+        test_data::corrupt_last_feature(&mut output);
+
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let mut feature_iter = reader.select_all().unwrap();
+        for _ in 0..3 {
+            assert!(feature_iter.try_next().unwrap().is_some());
+        }
+        let error = feature_iter.try_next().unwrap_err();
+        let (page_index, page_starting_offset, _feature_offset) =
+            test_data::corrupt_feature_location(error);
+        assert!(page_index.unwrap() > 0);
+        assert!(page_starting_offset > 0);
+
+        // Only the last feature is selected, so the pages before it are jumped over, and which
+        // page it's on isn't known.
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let mut feature_iter = reader.select_bbox(&wkt!(RECT(-1 -1, 0.5 0.5))).unwrap();
+        let error = loop {
+            match feature_iter.try_next() {
+                Ok(Some(_feature)) => continue,
+                Ok(None) => panic!("expected an error"),
+                Err(error) => break error,
+            }
+        };
+        let (page_index, bbox_page_starting_offset, _feature_offset) =
+            test_data::corrupt_feature_location(error);
+        assert_eq!(page_index, None);
+        assert_eq!(bbox_page_starting_offset, page_starting_offset);
+    }
+
+    #[cfg(feature = "geo-types")]
+    #[test]
+    fn select_all_into_geo() {
+        let output = test_data::small_pages(4, true);
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let mut features = reader.select_all().unwrap().into_geo();
+        let mut geometries = vec![];
+        while let Some((geometry, _properties)) = features.try_next().unwrap() {
+            geometries.push(geometry);
+        }
+        assert_eq!(
+            geometries,
+            vec![
+                geo_types::Geometry::from(geo_types::point!(x: 3.0, y: 3.0)),
+                geo_types::Geometry::from(geo_types::point!(x: 2.0, y: 2.0)),
+                geo_types::Geometry::from(geo_types::point!(x: 1.0, y: 1.0)),
+                geo_types::Geometry::from(geo_types::point!(x: 0.0, y: 0.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn select_all_rev() {
+        for is_compressed in [false, true] {
+            let output = test_data::small_pages(10, is_compressed);
+            let mut forward = vec![];
+            let mut features = Reader::new(output.as_slice())
+                .unwrap()
+                .select_all()
+                .unwrap();
+            while let Some(feature) = features.try_next().unwrap() {
+                forward.push(feature);
+            }
+
+            let mut reverse = vec![];
+            let mut features = Reader::new(std::io::Cursor::new(&output))
+                .unwrap()
+                .select_all_rev()
+                .unwrap();
+            while let Some(feature) = features.try_next().unwrap() {
+                reverse.push(feature);
+            }
+
+            assert_eq!(forward.len(), 10);
+            reverse.reverse();
+            assert_eq!(reverse, forward);
+        }
+    }
+
+    #[test]
+    fn strict_with_uncompressed_multiple_pages() {
+        strict(false);
+    }
+
+    #[test]
+    fn strict_with_compressed_multiple_pages() {
+        strict(true);
+    }
+
+    fn strict(is_compressed: bool) {
+        let output = test_data::small_pages(4, is_compressed);
+        let reader = Reader::new_strict(std::io::Cursor::new(&output)).unwrap();
+        let mut features = reader.select_all().unwrap();
+        let mut count = 0;
+        while features.try_next().unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn strict_with_wrong_feature_count() {
+        let mut output = test_data::points(20, false);
+        // feature_count follows the magic, format version, flags byte and page_count
+        output[17..25].copy_from_slice(&19u64.to_le_bytes());
+
+        // The non-strict reader trusts the header
+        assert!(Reader::new(output.as_slice()).is_ok());
+
+        // The smaller index means the first page header is read from the end of the index, so
+        // whichever check fails first depends on what those bytes look like.
+        let err = Reader::new_strict(std::io::Cursor::new(&output)).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::IndexSizeMismatch { feature_count: 19 }
+                    | Error::FeatureCountMismatch { expected: 19, .. }
+                    | Error::TruncatedFile { .. }
+            ),
+            "unexpected error: {err:?}"
+        );
+    }
+
+    #[test]
+    fn strict_agrees_with_validate() {
+        let output = test_data::small_pages(4, false);
+
+        // Trailing bytes are ignored by `validate`, e.g. another concatenated file, but not by
+        // `new_strict`.
+        let mut trailing = output.clone();
+        trailing.extend_from_slice(&[0; 40]);
+        let mut reader = Reader::new(std::io::Cursor::new(&trailing)).unwrap();
+        reader.validate().unwrap();
+        let err = Reader::new_strict(std::io::Cursor::new(&trailing)).unwrap_err();
+        assert!(
+            matches!(err, Error::IndexSizeMismatch { feature_count: 4 }),
+            "unexpected error: {err:?}"
+        );
+
+        // Both report a truncated file the same way.
+        let truncated = &output[..output.len() - 1];
+        let mut reader = Reader::new(std::io::Cursor::new(truncated)).unwrap();
+        let validate_err = reader.validate().unwrap_err();
+        let strict_err = Reader::new_strict(std::io::Cursor::new(truncated)).unwrap_err();
+        assert!(
+            matches!(
+                (&validate_err, &strict_err),
+                (
+                    Error::TruncatedFile {
+                        section: FileSection::Page { .. },
+                        ..
+                    },
+                    Error::TruncatedFile {
+                        section: FileSection::Page { .. },
+                        ..
+                    }
+                )
+            ),
+            "unexpected errors: {validate_err:?}, {strict_err:?}"
+        );
+    }
+
+    #[test]
+    fn within() {
+        let output = test_data::small_pages(4, true);
+        let reader = Reader::new(output.as_slice()).unwrap();
+
+        // (1 1) is within the triangle's bbox, but not the triangle itself.
+        let triangle = wkt!(POLYGON((0.5 0,3.5 0,3.5 4.5,0.5 0)));
+        let mut features = reader.select_within(&triangle).unwrap();
+        assert_eq!(
+            features.try_next().unwrap().unwrap().geometry(),
+            &wkt!(POINT(3 3)).into()
+        );
+        assert_eq!(
+            features.try_next().unwrap().unwrap().geometry(),
+            &wkt!(POINT(2 2)).into()
+        );
+        assert!(features.try_next().unwrap().is_none());
+    }
+
+    #[test]
+    fn peek() {
+        let output = test_data::small_pages(3, true);
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let mut features = reader.select_all().unwrap();
+
+        let peeked = features.peek().unwrap().unwrap().geometry().clone();
+        assert_eq!(peeked, wkt!(POINT(0 0)).into());
+        // Peeking again doesn't advance
+        assert_eq!(features.peek().unwrap().unwrap().geometry(), &peeked);
+        assert_eq!(features.try_next().unwrap().unwrap().geometry(), &peeked);
+
+        assert_eq!(
+            features.try_next().unwrap().unwrap().geometry(),
+            &wkt!(POINT(1 1)).into()
+        );
+        assert_eq!(
+            features.peek().unwrap().unwrap().geometry(),
+            &wkt!(POINT(2 2)).into()
+        );
+        assert_eq!(
+            features.try_next().unwrap().unwrap().geometry(),
+            &wkt!(POINT(2 2)).into()
+        );
+        assert!(features.peek().unwrap().is_none());
+        assert!(features.try_next().unwrap().is_none());
+    }
+
+    #[test]
+    fn nearest() {
+        let output = test_data::small_pages(4, true);
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let features = reader.select_nearest(&wkt!(POINT(2.2 2.2)), 3).unwrap();
+        let geometries: Vec<_> = features.iter().map(Feature::geometry).collect();
+        assert_eq!(
+            geometries,
+            vec![
+                &wkt!(POINT(2 2)).into(),
+                &wkt!(POINT(3 3)).into(),
+                &wkt!(POINT(1 1)).into()
+            ]
+        );
+
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let features = reader.select_nearest(&wkt!(POINT(0 0)), 10).unwrap();
+        assert_eq!(features.len(), 4);
+
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let features = reader.select_nearest(&wkt!(POINT(0 0)), 0).unwrap();
+        assert!(features.is_empty());
+    }
+
     #[test]
     fn bbox_with_uncompressed_single_page() {
         bbox(false, false);
@@ -496,4 +1815,369 @@ mod tests {
         );
         assert!(features.try_next().unwrap().is_none());
     }
+
+    #[test]
+    fn select_bboxes() {
+        let output = test_data::small_pages(100, true);
+        let select = |bounds: &[Bounds]| {
+            let mut features = Reader::new(output.as_slice())
+                .unwrap()
+                .select_bboxes(bounds)
+                .unwrap();
+            let mut geometries = vec![];
+            while let Some(feature) = features.try_next().unwrap() {
+                geometries.push(feature.geometry().clone());
+            }
+            geometries
+        };
+
+        let geometries = select(&[
+            wkt!(RECT(10 10,12 12)),
+            wkt!(RECT(80.5 80.5,81.5 81.5)),
+            // overlaps the first
+            wkt!(RECT(11 11,13 13)),
+        ]);
+        // Each feature is only selected once, in the order they're stored.
+        assert_eq!(geometries.len(), 5);
+        for expected in [
+            wkt!(POINT(10 10)),
+            wkt!(POINT(11 11)),
+            wkt!(POINT(12 12)),
+            wkt!(POINT(13 13)),
+            wkt!(POINT(81 81)),
+        ] {
+            assert!(geometries.contains(&Geometry::Point(expected)));
+        }
+
+        assert!(select(&[]).is_empty());
+    }
+
+    #[test]
+    fn index_nodes() {
+        let output = test_data::small_pages(20, true);
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let levels = reader.index_levels();
+        assert_eq!(levels, vec![0..1, 1..3, 3..23]);
+        let extent = reader.info().extent().unwrap();
+
+        let nodes: Vec<(u64, Node)> = reader.index_nodes().unwrap().collect();
+        assert_eq!(
+            nodes
+                .iter()
+                .map(|(node_idx, _)| *node_idx)
+                .collect::<Vec<_>>(),
+            (0..23).collect::<Vec<_>>()
+        );
+        assert_eq!(nodes[0].1.bounds(), &extent);
+
+        let mut leaf_bounds = Bounds::empty();
+        for (_, leaf) in &nodes[levels[2].start as usize..] {
+            leaf_bounds.extend(leaf.bounds());
+        }
+        assert_eq!(leaf_bounds, extent);
+
+        let empty = test_data::small_pages(0, true);
+        let reader = Reader::new(empty.as_slice()).unwrap();
+        assert!(reader.index_levels().is_empty());
+        assert_eq!(reader.index_nodes().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn mmap_reader() {
+        for is_compressed in [false, true] {
+            let output = test_data::small_pages(100, is_compressed);
+            let mut file = tempfile::NamedTempFile::new().unwrap();
+            file.write_all(&output).unwrap();
+            file.flush().unwrap();
+
+            // Safety: the temp file isn't modified while it's mapped.
+            let mmap_reader = unsafe { MmapReader::open(file.path()) }.unwrap();
+            assert_eq!(mmap_reader.header().feature_count, 100);
+            for bounds in [
+                wkt!(RECT(10 10,20 20)),
+                wkt!(RECT(55.5 55.5,56.5 56.5)),
+                wkt!(RECT(-10 -10,200 200)),
+                wkt!(RECT(200 200,210 210)),
+            ] {
+                let mut expected = vec![];
+                let mut features = Reader::new(output.as_slice())
+                    .unwrap()
+                    .select_bbox(&bounds)
+                    .unwrap();
+                while let Some(feature) = features.try_next().unwrap() {
+                    expected.push(feature);
+                }
+
+                // The same reader is used for every query.
+                let mut actual = vec![];
+                let mut features = mmap_reader.select_bbox(&bounds).unwrap();
+                while let Some(feature) = features.try_next().unwrap() {
+                    actual.push(feature);
+                }
+                assert_eq!(actual, expected);
+            }
+
+            let bounds = [
+                wkt!(RECT(10 10,12 12)),
+                wkt!(RECT(80.5 80.5,81.5 81.5)),
+                wkt!(RECT(11 11,13 13)),
+            ];
+            let mut expected = vec![];
+            let mut features = Reader::new(output.as_slice())
+                .unwrap()
+                .select_bboxes(&bounds)
+                .unwrap();
+            while let Some(feature) = features.try_next().unwrap() {
+                expected.push(feature);
+            }
+            assert_eq!(expected.len(), 5);
+            let mut actual = vec![];
+            let mut features = mmap_reader.select_bboxes(&bounds).unwrap();
+            while let Some(feature) = features.try_next().unwrap() {
+                actual.push(feature);
+            }
+            assert_eq!(actual, expected);
+        }
+    }
+
+    /// A file of empty pages with the given bounds, which is enough to compute a locality score.
+    fn with_page_bounds(all_page_bounds: &[Bounds]) -> Vec<u8> {
+        let header = Header {
+            page_count: all_page_bounds.len() as u64,
+            ..Default::default()
+        };
+        let mut output = vec![];
+        serialize_into(&mut output, &header).unwrap();
+        // With no features, the index is empty.
+        for page_bounds in all_page_bounds {
+            serialize_into(&mut output, &PageHeader::new(0, 0, 1)).unwrap();
+            serialize_into(&mut output, page_bounds).unwrap();
+        }
+        output
+    }
+
+    #[test]
+    fn locality_score() {
+        let sorted = with_page_bounds(&[
+            wkt!(RECT(0 0,1 1)),
+            wkt!(RECT(1 1,2 2)),
+            wkt!(RECT(2 2,3 3)),
+        ]);
+        let mut reader = Reader::new(std::io::Cursor::new(sorted)).unwrap();
+        assert_eq!(reader.locality_score().unwrap(), 1.0);
+
+        // Every page covers everything.
+        let unsorted = with_page_bounds(&[
+            wkt!(RECT(0 0,3 3)),
+            wkt!(RECT(0 0,3 3)),
+            wkt!(RECT(0 0,3 3)),
+        ]);
+        let mut reader = Reader::new(std::io::Cursor::new(unsorted)).unwrap();
+        assert_eq!(reader.locality_score().unwrap(), 3.0);
+
+        // Points along a line are sorted into pages which don't overlap.
+        let output = test_data::small_pages(4, true);
+        let mut reader = Reader::new(std::io::Cursor::new(output)).unwrap();
+        assert!(reader.locality_score().unwrap() <= 1.0);
+        // The reader is still usable afterwards.
+        let mut features = reader.select_all().unwrap();
+        let mut count = 0;
+        while features.try_next().unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn projection() {
+        let output = test_data::small_pages(4, true);
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let mut features = reader.select_all_with_projection(&["name"]).unwrap();
+        let mut count = 0;
+        while let Some(feature) = features.try_next().unwrap() {
+            assert_eq!(feature.properties().iter().count(), 1);
+            assert!(feature.property("name").is_some());
+            count += 1;
+        }
+        assert_eq!(count, 4);
+
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let mut features = reader
+            .select_bbox_with_projection(&wkt!(RECT(1 1,2 2)), &["missing"])
+            .unwrap();
+        let feature = features.try_next().unwrap().unwrap();
+        assert_eq!(feature.geometry(), &wkt!(POINT(2 2)).into());
+        assert!(feature.properties().is_empty());
+    }
+
+    #[test]
+    fn bbox_by_page() {
+        let output = test_data::small_pages(4, true);
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let mut features = reader.select_bbox_by_page(&wkt!(RECT(1 1,2 2))).unwrap();
+        assert_eq!(
+            features.try_next().unwrap().unwrap().geometry(),
+            &wkt!(POINT(2 2)).into()
+        );
+        assert_eq!(
+            features.try_next().unwrap().unwrap().geometry(),
+            &wkt!(POINT(1 1)).into()
+        );
+        assert!(features.try_next().unwrap().is_none());
+    }
+
+    #[test]
+    fn bbox_by_page_skips_pages() {
+        let mut output = test_data::small_pages(4, true);
+
+        // Corrupt the first page, which holds POINT(3 3) and POINT(2 2).
+        let info = Reader::new(output.as_slice()).unwrap().info();
+        let first_page_start = (info.header_size().unwrap() + info.index_size()) as usize;
+        let (page_header, page_bounds) =
+            PageHeader::read_from(&output[first_page_start..], false).unwrap();
+        assert_eq!(page_bounds, wkt!(RECT(2 2,3 3)));
+        let content_start = first_page_start + PageHeader::serialized_size(false);
+        let content_end = content_start + page_header.encoded_page_length() as usize;
+        output[content_start..content_end].fill(0xFF);
+
+        let reader = Reader::new(output.as_slice()).unwrap();
+        assert!(reader
+            .select_all()
+            .and_then(|mut features| features.try_next())
+            .is_err());
+
+        // The corrupt page doesn't intersect, so it's never decoded.
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let mut features = reader
+            .select_bbox_by_page(&wkt!(RECT(0 0,0.5 0.5)))
+            .unwrap();
+        assert_eq!(
+            features.try_next().unwrap().unwrap().geometry(),
+            &wkt!(POINT(0 0)).into()
+        );
+        assert!(features.try_next().unwrap().is_none());
+    }
+
+    #[test]
+    fn properties_only() {
+        for is_compressed in [false, true] {
+            let output = test_data::small_pages(4, is_compressed);
+            let expected: Vec<Properties> = {
+                let mut features = Reader::new(output.as_slice())
+                    .unwrap()
+                    .select_all()
+                    .unwrap();
+                let mut expected = vec![];
+                while let Some(feature) = features.try_next().unwrap() {
+                    expected.push(feature.properties().clone());
+                }
+                expected
+            };
+            assert_eq!(expected.len(), 4);
+
+            let reader = Reader::new(output.as_slice()).unwrap();
+            let mut properties_iter = reader.properties_only().unwrap();
+            let mut actual = vec![];
+            while let Some(properties) = properties_iter.try_next().unwrap() {
+                actual.push(properties);
+            }
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn geometry_types() {
+        let geometries: Vec<Geometry> = vec![
+            wkt!(POINT(1 1)).into(),
+            wkt!(LINESTRING(0 0,1 1)).into(),
+            wkt!(POLYGON((0 0,1 0,1 1,0 0))).into(),
+            wkt!(GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(0 0,1 1))).into(),
+        ];
+        for is_compressed in [false, true] {
+            let mut writer = crate::Writer::new(vec![], is_compressed).unwrap();
+            writer.set_page_size_goal(50);
+            for geometry in &geometries {
+                writer
+                    .add_feature(&Feature::new(geometry.clone(), Properties::empty()))
+                    .unwrap();
+            }
+            let output = writer.finish().unwrap();
+
+            let mut expected = vec![];
+            let mut features = Reader::new(output.as_slice())
+                .unwrap()
+                .select_all()
+                .unwrap();
+            while let Some(feature) = features.try_next().unwrap() {
+                expected.push(feature.geometry().geometry_type());
+            }
+            assert_eq!(expected.len(), geometries.len());
+
+            let mut geometry_types = Reader::new(output.as_slice())
+                .unwrap()
+                .geometry_types()
+                .unwrap();
+            let mut actual = vec![];
+            while let Some(geometry_type) = geometry_types.try_next().unwrap() {
+                actual.push(geometry_type);
+            }
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn info() {
+        let output = test_data::small_pages(4, true);
+        let info = Reader::new(output.as_slice()).unwrap().info();
+        assert_eq!(info.feature_count(), 4);
+        assert_eq!(info.page_count(), 2);
+        assert_eq!(info.extent(), Some(wkt!(RECT(0 0,3 3))));
+
+        // Reading the info doesn't interfere with selecting.
+        let mut features = Reader::new(output.as_slice())
+            .unwrap()
+            .select_bbox(&wkt!(RECT(0 0,1 1)))
+            .unwrap();
+        assert!(features.try_next().unwrap().is_some());
+        assert!(features.try_next().unwrap().is_some());
+        assert!(features.try_next().unwrap().is_none());
+
+        let output = test_data::small_pages(0, true);
+        let info = Reader::new(output.as_slice()).unwrap().info();
+        assert_eq!(info.feature_count(), 0);
+        assert_eq!(info.extent(), None);
+    }
+
+    #[test]
+    fn concatenated_files() {
+        let first = test_data::small_pages(4, true);
+        let second = test_data::small_pages(3, false);
+        let empty = test_data::small_pages(0, true);
+        let mut concatenated = first.clone();
+        concatenated.extend_from_slice(&second);
+        concatenated.extend_from_slice(&empty);
+
+        let mut stream = std::io::Cursor::new(concatenated);
+        let mut feature_counts = vec![];
+        let mut file_lens = vec![];
+        while stream.position() < stream.get_ref().len() as u64 {
+            let file_start = stream.position();
+            let (_, file_len) = Reader::new(&mut stream).unwrap().skip_to_end().unwrap();
+            assert_eq!(stream.position(), file_start + file_len);
+
+            stream.set_position(file_start);
+            let mut features = Reader::new(&mut stream).unwrap().select_all().unwrap();
+            let mut feature_count = 0;
+            while features.try_next().unwrap().is_some() {
+                feature_count += 1;
+            }
+            drop(features);
+
+            stream.set_position(file_start + file_len);
+            feature_counts.push(feature_count);
+            file_lens.push(file_len as usize);
+        }
+        assert_eq!(feature_counts, vec![4, 3, 0]);
+        assert_eq!(file_lens, vec![first.len(), second.len(), empty.len()]);
+    }
 }
@@ -0,0 +1,324 @@
+use crate::{Error, Result};
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use streaming_http_range_client::{HttpClient, HttpRange};
+
+use crate::asyncio::AsyncReadExt;
+
+/// How an [`HttpReader`](crate::HttpReader) retries range requests which fail, e.g. due to a 503
+/// or a dropped connection.
+///
+/// Range requests are idempotent, so a failed request is simply made again, after waiting
+/// `initial_backoff`, doubling each subsequent wait up to `max_backoff`. Only failures of the
+/// connection, timeouts and server (5xx) errors are retried, e.g. not a 404.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// The most times a request is made, including the first. 1 disables retries.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Fail on the first error.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// How long to wait after the given (1-based) failed attempt.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Only failures of the connection, timeouts and server errors are worth retrying, not e.g. a
+    /// missing file, or malformed contents.
+    fn is_retryable(error: &Error) -> bool {
+        use streaming_http_range_client::Error as HttpError;
+        match error {
+            Error::HTTP(HttpError::HttpFailed { status }) => *status >= 500,
+            Error::HTTP(HttpError::External(error)) => error
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(is_retryable_request),
+            Error::HTTP(HttpError::IO(error)) | Error::IO(error) => is_retryable_io(error),
+            _ => false,
+        }
+    }
+
+    /// Whether to make another attempt after `attempt` failed with `error`, waiting first if so.
+    async fn should_retry(&self, attempt: u32, error: &Error) -> bool {
+        if attempt >= self.max_attempts || !Self::is_retryable(error) {
+            return false;
+        }
+        let backoff = self.backoff(attempt);
+        warn!(
+            "attempt {attempt} of {} failed, retrying in {backoff:?}: {error:?}",
+            self.max_attempts
+        );
+        futures_timer::Delay::new(backoff).await;
+        true
+    }
+
+    /// Called after reading `range` from `http_client` failed with `error`. If it's worth
+    /// retrying, requests `range` again, so that the read can be attempted again from its start.
+    /// Otherwise returns `error`.
+    ///
+    /// `attempt` is the (1-based) attempt which failed, and is incremented for each retry.
+    pub(crate) async fn recover(
+        &self,
+        attempt: &mut u32,
+        mut error: Error,
+        http_client: &mut HttpClient,
+        range: &HttpRange,
+        fetch_stats: &FetchStats,
+    ) -> Result<()> {
+        while self.should_retry(*attempt, &error).await {
+            *attempt += 1;
+            match refetch(http_client, range, fetch_stats).await {
+                Ok(()) => return Ok(()),
+                Err(refetch_error) => error = refetch_error,
+            }
+        }
+        Err(error)
+    }
+
+    /// [`HttpClient::seek_to_range`], retrying on failure.
+    pub(crate) async fn seek_to_range(
+        &self,
+        http_client: &mut HttpClient,
+        range: HttpRange,
+        fetch_stats: &FetchStats,
+    ) -> Result<()> {
+        let mut attempt = 1;
+        fetch_stats.record_seek(http_client, &range);
+        if let Err(error) = http_client.seek_to_range(range.clone()).await {
+            self.recover(&mut attempt, error.into(), http_client, &range, fetch_stats)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Seeks to `range` and reads from its start into `buf`, retrying both on failure.
+    ///
+    /// `range` may extend past `buf`, in which case the rest of it remains buffered in
+    /// `http_client` for subsequent reads.
+    pub(crate) async fn read_range(
+        &self,
+        http_client: &mut HttpClient,
+        range: HttpRange,
+        buf: &mut [u8],
+        fetch_stats: &FetchStats,
+    ) -> Result<()> {
+        let mut attempt = 1;
+        fetch_stats.record_seek(http_client, &range);
+        if let Err(error) = http_client.seek_to_range(range.clone()).await {
+            self.recover(&mut attempt, error.into(), http_client, &range, fetch_stats)
+                .await?;
+        }
+        self.read_exact(attempt, http_client, &range, buf, fetch_stats)
+            .await
+    }
+
+    /// Like [`RetryPolicy::read_range`], but for a new `http_client`, which can't seek until it
+    /// has requested a range.
+    pub(crate) async fn read_first_range(
+        &self,
+        http_client: &mut HttpClient,
+        range: Range<u64>,
+        buf: &mut [u8],
+        fetch_stats: &FetchStats,
+    ) -> Result<()> {
+        let mut attempt = 1;
+        let range = HttpRange::Range(range);
+        if let Err(error) = refetch(http_client, &range, fetch_stats).await {
+            self.recover(&mut attempt, error, http_client, &range, fetch_stats)
+                .await?;
+        }
+        self.read_exact(attempt, http_client, &range, buf, fetch_stats)
+            .await
+    }
+
+    /// Reads `buf` from the start of `range`, which `http_client` has just requested.
+    async fn read_exact(
+        &self,
+        mut attempt: u32,
+        http_client: &mut HttpClient,
+        range: &HttpRange,
+        buf: &mut [u8],
+        fetch_stats: &FetchStats,
+    ) -> Result<()> {
+        loop {
+            match http_client.read_exact(buf).await {
+                Ok(_) => return Ok(()),
+                Err(error) => {
+                    self.recover(&mut attempt, error.into(), http_client, range, fetch_stats)
+                        .await?
+                }
+            }
+        }
+    }
+}
+
+/// Requests `range` anew, unlike [`HttpClient::seek_to_range`], which relies on whatever
+/// `http_client` has buffered, which a failed attempt may have left partially read.
+async fn refetch(
+    http_client: &mut HttpClient,
+    range: &HttpRange,
+    fetch_stats: &FetchStats,
+) -> Result<()> {
+    match range {
+        HttpRange::Range(range) => {
+            fetch_stats.record_request();
+            fetch_stats.record_bytes(range.end.saturating_sub(range.start));
+            http_client.set_range(range.clone()).await?;
+        }
+        HttpRange::RangeFrom(range) => {
+            // An open ended range can only be requested by extending a bounded one. Its bytes are
+            // counted as they're read, see `FetchStats::record_bytes`.
+            fetch_stats.record_request();
+            http_client.set_range(range.start..range.start + 1).await?;
+            fetch_stats.record_request();
+            http_client.extend_to_end().await?;
+        }
+    }
+    Ok(())
+}
+
+/// The body of a response is read as an [`std::io::Error`] wrapping the error from reqwest.
+fn is_retryable_io(error: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+
+    if let Some(request_error) = error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<reqwest::Error>())
+    {
+        return is_retryable_request(request_error);
+    }
+    matches!(
+        error.kind(),
+        ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+            | ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+    )
+}
+
+/// Whether the request couldn't be sent, e.g. the connection was refused, or it timed out, or its
+/// response was cut off, rather than e.g. its url was invalid.
+fn is_retryable_request(error: &reqwest::Error) -> bool {
+    error.is_request() || error.is_timeout() || error.is_body()
+}
+
+/// Counts the range requests made by an [`HttpReader`](crate::HttpReader), including those of
+/// its queries, which share the counts.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FetchStats {
+    request_count: Arc<AtomicU64>,
+    bytes_fetched: Arc<AtomicU64>,
+}
+
+impl FetchStats {
+    pub(crate) fn request_count(&self) -> u64 {
+        self.request_count.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn bytes_fetched(&self) -> u64 {
+        self.bytes_fetched.load(Ordering::Relaxed)
+    }
+
+    /// Seeking to a range which is already buffered doesn't make a request. Otherwise, the whole
+    /// of a bounded range is counted as fetched, but an open ended range can only be counted as
+    /// it's read, see [`FetchStats::record_bytes`].
+    fn record_seek(&self, http_client: &HttpClient, range: &HttpRange) {
+        if http_client.contains(range) {
+            return;
+        }
+        self.record_request();
+        if let HttpRange::Range(range) = range {
+            self.record_bytes(range.end.saturating_sub(range.start));
+        }
+    }
+
+    fn record_request(&self) {
+        self.request_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts bytes read from an open ended range.
+    pub(crate) fn record_bytes(&self, len: u64) {
+        self.bytes_fetched.fetch_add(len, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff(100), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn doesnt_retry_malformed_contents() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(60),
+            max_backoff: Duration::from_secs(60),
+        };
+        // Would wait a minute if it retried.
+        let error = Error::InvalidBranchingFactor(0);
+        assert!(!policy.should_retry(1, &error).await);
+
+        // e.g. a truncated file
+        let error = Error::IO(std::io::ErrorKind::UnexpectedEof.into());
+        assert!(!policy.should_retry(1, &error).await);
+    }
+
+    #[test]
+    fn retryable_errors() {
+        use std::io::ErrorKind;
+        use streaming_http_range_client::Error as HttpError;
+
+        let server_error = Error::HTTP(HttpError::HttpFailed { status: 503 });
+        assert!(RetryPolicy::is_retryable(&server_error));
+        let not_found = Error::HTTP(HttpError::HttpFailed { status: 404 });
+        assert!(!RetryPolicy::is_retryable(&not_found));
+
+        let reset = Error::HTTP(HttpError::IO(ErrorKind::ConnectionReset.into()));
+        assert!(RetryPolicy::is_retryable(&reset));
+        let timed_out = Error::IO(ErrorKind::TimedOut.into());
+        assert!(RetryPolicy::is_retryable(&timed_out));
+
+        let other = Error::IO(std::io::Error::new(ErrorKind::Other, "not from a request"));
+        assert!(!RetryPolicy::is_retryable(&other));
+        let external = Error::HTTP(HttpError::External("not from a request".into()));
+        assert!(!RetryPolicy::is_retryable(&external));
+    }
+}
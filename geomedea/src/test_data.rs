@@ -1,5 +1,5 @@
 use crate::feature::Properties;
-use crate::{Feature, Geometry, LngLat, Writer};
+use crate::{Error, Feature, Geometry, LngLat, Writer};
 
 pub fn small_pages(feature_count: usize, is_compressed: bool) -> Vec<u8> {
     let output = vec![];
@@ -26,3 +26,121 @@ pub fn _points(feature_count: usize, mut writer: Writer<Vec<u8>>) -> Vec<u8> {
     }
     writer.finish().unwrap()
 }
+
+/// Makes the last feature of an uncompressed file from [`small_pages`] or [`points`] fail to
+/// decode, by making its property value invalid UTF-8.
+pub fn corrupt_last_feature(bytes: &mut [u8]) {
+    let position = bytes
+        .windows(5)
+        .rposition(|window| window == b"prop-")
+        .expect("uncompressed file with a property value");
+    bytes[position] = 0xFF;
+}
+
+/// The page index, page starting offset, and feature offset of an `Error::CorruptFeature`.
+pub fn corrupt_feature_location(error: Error) -> (Option<u64>, u64, u32) {
+    let Error::CorruptFeature {
+        page_index,
+        page_starting_offset,
+        feature_offset,
+        ..
+    } = error
+    else {
+        panic!("expected CorruptFeature, got {error:?}");
+    };
+    (page_index, page_starting_offset, feature_offset)
+}
+
+/// How [`flaky_server`] fails a request.
+#[derive(Debug, Clone, Copy)]
+pub enum Failure {
+    /// Respond with this status, e.g. 503.
+    Status(u16),
+    /// Respond with the requested range, but drop the connection halfway through its body.
+    Truncate,
+    /// Respond with the whole file, rather than the requested range, and with this
+    /// `Content-Encoding`, as a server which compresses its responses might. The file should be
+    /// compressed already, since it's served as is.
+    ContentEncoding(&'static str),
+}
+
+/// Serves `bytes` over HTTP, failing the first requests as given, one `Failure` per request, or
+/// `None` to serve it normally. Returns the url of the file.
+pub async fn flaky_server(bytes: Vec<u8>, failures: Vec<Option<Failure>>) -> String {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let bytes = Arc::new(bytes);
+    let failures = Arc::new(Mutex::new(VecDeque::from(failures)));
+    tokio::spawn(async move {
+        loop {
+            let (stream, _addr) = listener.accept().await.unwrap();
+            let bytes = bytes.clone();
+            let failures = failures.clone();
+            tokio::spawn(async move {
+                let mut stream = BufReader::new(stream);
+                // Serve requests until the client closes the connection.
+                loop {
+                    let mut line = String::new();
+                    if stream.read_line(&mut line).await.unwrap_or(0) == 0 {
+                        return;
+                    }
+                    let mut range = None;
+                    loop {
+                        line.clear();
+                        stream.read_line(&mut line).await.unwrap();
+                        let header = line.trim_end().to_ascii_lowercase();
+                        if header.is_empty() {
+                            break;
+                        }
+                        if let Some(value) = header.strip_prefix("range: bytes=") {
+                            range = Some(value.to_string());
+                        }
+                    }
+
+                    let len = bytes.len();
+                    let (start, end) = range
+                        .as_deref()
+                        .and_then(|range| range.split_once('-'))
+                        .expect("a range request");
+                    let start = start.parse::<usize>().unwrap().min(len);
+                    let end = match end {
+                        "" => len,
+                        end => (end.parse::<usize>().unwrap() + 1).min(len),
+                    };
+                    let body = &bytes[start..end];
+
+                    let failure = failures.lock().unwrap().pop_front().flatten();
+                    if let Some(Failure::Status(status)) = failure {
+                        let response =
+                            format!("HTTP/1.1 {status} Failure\r\ncontent-length: 0\r\n\r\n");
+                        stream.write_all(response.as_bytes()).await.unwrap();
+                        continue;
+                    }
+                    let (content_encoding, body) = match failure {
+                        Some(Failure::ContentEncoding(content_encoding)) => (
+                            format!("content-encoding: {content_encoding}\r\n"),
+                            &bytes[..],
+                        ),
+                        _ => (String::new(), body),
+                    };
+                    let response = format!(
+                        "HTTP/1.1 206 Partial Content\r\ncontent-length: {}\r\n{content_encoding}\r\n",
+                        body.len()
+                    );
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                    if let Some(Failure::Truncate) = failure {
+                        stream.write_all(&body[..body.len() / 2]).await.unwrap();
+                        stream.flush().await.unwrap();
+                        return;
+                    }
+                    stream.write_all(body).await.unwrap();
+                }
+            });
+        }
+    });
+    format!("http://{addr}/file.geomedea")
+}
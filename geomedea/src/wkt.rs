@@ -116,6 +116,8 @@ macro_rules! wkt_internal {
     (MULTIPOLYGON $($tail: tt)*) => {
         compile_error!("Invalid MULTIPOLYGON wkt")
     };
+    // The corners may be in any order, so these bounds never span the antimeridian, see
+    // `Bounds::new`.
     (RECT ($x1: literal $y1: literal, $x2: literal $y2: literal)) => {
         $crate::Bounds::from_corners(&wkt!(POINT($x1 $y1)), &wkt!(POINT($x2 $y2)))
     };
@@ -1,151 +1,1172 @@
 use crate::bounds::Bounds;
-use crate::geometry::Bounded;
-use crate::io::CountingWriter;
+use crate::feature::PropertyKeyInterner;
+use crate::format::page_checksum;
+use crate::geometry::{is_valid_coord_precision, Bounded, COORD_PRECISION_NANOS};
+use crate::io::{untrusted_capacity, CountingWriter};
 use crate::packed_r_tree::{Node, PackedRTreeWriter};
 use crate::{
-    deserialize_from, serialize_into, serialized_size, Feature, FeatureLocation, Header,
-    PageHeader, Result, DEFAULT_PAGE_SIZE_GOAL,
+    deserialize_from, serialize_into, serialized_size, Compression, Error, Feature,
+    FeatureLocation, Header, PageHeader, Result, Schema, WindingOrder, DEFAULT_PAGE_SIZE_GOAL,
 };
 use byteorder::{LittleEndian, WriteBytesExt};
+use futures_util::{Stream, StreamExt};
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
 use std::marker::PhantomData;
+use std::sync::Arc;
 use tempfile::tempfile;
 
 mod hilbert;
+#[cfg(feature = "parallel")]
+mod parallel;
+
+/// Offsets within a page are stored as a u32, so no page, and therefore no feature (including
+/// its length prefix), can be larger than this.
+const MAX_PAGE_SIZE: u64 = u32::MAX as u64;
+
+/// zstd interprets level 0 as "use the library's default level".
+const DEFAULT_COMPRESSION_LEVEL: i32 = 0;
+
+/// Brotli's maximum quality (11) is very slow to write, for a small improvement in size.
+const BROTLI_QUALITY: u32 = 9;
+/// The log2 of brotli's window size, the library's default.
+const BROTLI_LG_WINDOW_SIZE: u32 = 22;
+
+const DEFAULT_MEMORY_BUDGET: u64 = 16 * 1024 * 1024;
+
+/// Which size of each page [`Writer::set_page_size_target`] bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageSizeTarget {
+    /// Bound the decoded, i.e. uncompressed, size of each page, see [`Writer::set_page_size_goal`].
+    Decoded(u64),
+    /// Bound the encoded size of each page, i.e. the bytes on disk, which is what gets fetched
+    /// when reading the page over HTTP.
+    ///
+    /// For compressed files, measuring the encoded size means flushing the compressor
+    /// periodically, which costs a little compression. For uncompressed files, this is the same as
+    /// `Decoded`.
+    Encoded(u64),
+}
+
+impl PageSizeTarget {
+    fn goal(self) -> u64 {
+        match self {
+            PageSizeTarget::Decoded(goal) | PageSizeTarget::Encoded(goal) => goal,
+        }
+    }
+}
+
+/// The first bytes of a dictionary in zstd's format, as opposed to "raw content" dictionaries.
+const ZSTD_DICTIONARY_MAGIC: [u8; 4] = [0x37, 0xA4, 0x30, 0xEC];
+
+/// Pages of a compressed file can be compressed with a shared zstd dictionary, see
+/// [`Writer::set_zstd_dictionary`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZstdDictionary {
+    /// Train a dictionary of at most `max_size` bytes from the added features.
+    Train { max_size: usize },
+    /// A dictionary in zstd's format, e.g. from `zstd --train`.
+    Provided(Vec<u8>),
+}
+
+/// Whether a page should be finished, given its size so far, see [`Writer::set_page_size_goal`].
+fn is_page_full(page_length: u64, page_size_goal: u64) -> bool {
+    page_length > page_size_goal
+}
+
+/// Suggests a [`Writer::set_page_size_goal`] which would put roughly
+/// `target_features_per_page` features in each page, based on the average size of the features
+/// in `sample`.
+///
+/// Returns the default page size goal if `sample` is empty.
+///
+/// # Panics
+///
+/// Panics if `target_features_per_page` is 0.
+pub fn suggest_page_size(sample: &[Feature], target_features_per_page: usize) -> Result<u64> {
+    assert!(
+        target_features_per_page > 0,
+        "target_features_per_page must be positive"
+    );
+    if sample.is_empty() {
+        return Ok(DEFAULT_PAGE_SIZE_GOAL);
+    }
+    let mut total_size = 0;
+    for feature in sample {
+        // Each feature is preceded by its length
+        total_size += serialized_size(&0u64)? + serialized_size(feature)?;
+    }
+    let average_size = total_size as f64 / sample.len() as f64;
+    // A page isn't finished until its size exceeds the goal, so aim between the size of
+    // `target_features_per_page - 1` and `target_features_per_page` features.
+    let goal = (target_features_per_page as f64 - 0.5) * average_size;
+    Ok(goal.round() as u64)
+}
+
+/// Rewrites the file behind `http_reader` to `output`, e.g. to serve an uncompressed copy of a
+/// compressed file, keeping the original's branching factor and coordinate precision.
+///
+/// Features are streamed from [`HttpReader::select_all`] and spooled to a temporary file, as with
+/// [`Writer::add_feature`], so the dataset is never held in memory. However, the output's index
+/// precedes its pages, so nothing is written to `output` until every feature has been read.
+///
+/// [`HttpReader::select_all`]: crate::HttpReader::select_all
+pub async fn transcode<W: Write>(
+    http_reader: &mut crate::HttpReader,
+    output: W,
+    is_compressed: bool,
+) -> Result<W> {
+    let header = http_reader.header().clone();
+    let mut writer = Writer::new(output, is_compressed)?;
+    writer.set_branching_factor(header.branching_factor)?;
+    writer.set_coord_precision_nanos(header.coord_precision_nanos)?;
+    writer.add_features(http_reader.select_all().await?).await?;
+    writer.finish()
+}
 
 #[derive(Debug)]
+#[must_use = "nothing is written until `finish` is called"]
 pub struct Writer<W: Write> {
-    inner: W,
-    feature_tempfile: CountingWriter<BufWriter<File>>,
+    /// `None` once the writer has been finished.
+    inner: Option<W>,
+    feature_buffer: FeatureBuffer,
+    /// How many (serialized) bytes of features to buffer in memory before switching to a tempfile.
+    memory_budget: u64,
     feature_entries: Vec<FeatureEntry>,
     extent: Bounds,
     header: Header,
-    /// How many bytes before rolling over to a new page, note we don't switch to a new page until
-    /// after this limit is hit, so pages will be slightly larger than this size.
-    page_size_goal: u64,
+    /// See [`Writer::set_page_size_target`].
+    page_size_target: PageSizeTarget,
+    /// Only used when the output is compressed.
+    compression_level: i32,
+    /// Store integer properties using the smallest integer variant that fits.
+    downcast_integers: bool,
+    /// If set, the properties of each added feature must match.
+    schema: Option<Schema>,
+    /// See [`Writer::set_validate_geometries`].
+    validate_geometries: bool,
+    /// See [`Writer::set_orient_polygons`].
+    orient_polygons: Option<WindingOrder>,
+    /// See [`Writer::set_simplify_tolerance`].
+    simplify_tolerance: Option<f64>,
+    /// See [`Writer::set_zstd_dictionary`].
+    zstd_dictionary: Option<ZstdDictionary>,
+    /// Serialized features, to train the zstd dictionary from, and the size of each.
+    dictionary_samples: Vec<u8>,
+    dictionary_sample_sizes: Vec<usize>,
+    /// The index in `feature_entries` of each feature added, by the hash of its serialized bytes,
+    /// if duplicate features are skipped.
+    feature_hashes: Option<HashMap<u64, Vec<usize>>>,
+    /// See [`Writer::set_intern_property_keys`].
+    property_key_interner: Option<PropertyKeyInterner>,
+    /// Set if adding a feature failed part way through, leaving the writer corrupt.
+    poisoned: bool,
+    /// Set if adding a feature returned any error, after which the writer may be dropped without
+    /// finishing it.
+    add_failed: bool,
 }
 
 impl<W: Write> Writer<W> {
     pub fn new(inner: W, is_compressed: bool) -> Result<Self> {
+        #[cfg(target_arch = "wasm32")]
+        if is_compressed {
+            return Err(Error::UnsupportedCompression(Compression::Zstd));
+        }
         let header = Header {
-            is_compressed,
-            page_count: 0,
-            feature_count: 0,
+            compression: Compression::from(is_compressed),
+            ..Default::default()
         };
         Ok(Self {
-            inner,
-            feature_tempfile: CountingWriter::new(BufWriter::new(tempfile()?), "feature_tempfile"),
+            inner: Some(inner),
+            feature_buffer: FeatureBuffer::Memory {
+                features: vec![],
+                size: 0,
+            },
+            memory_budget: DEFAULT_MEMORY_BUDGET,
             feature_entries: vec![],
             extent: Bounds::empty(),
             header,
-            page_size_goal: DEFAULT_PAGE_SIZE_GOAL,
+            page_size_target: PageSizeTarget::Decoded(DEFAULT_PAGE_SIZE_GOAL),
+            compression_level: DEFAULT_COMPRESSION_LEVEL,
+            downcast_integers: false,
+            schema: None,
+            validate_geometries: false,
+            orient_polygons: None,
+            simplify_tolerance: None,
+            zstd_dictionary: None,
+            dictionary_samples: vec![],
+            dictionary_sample_sizes: vec![],
+            feature_hashes: None,
+            property_key_interner: Some(PropertyKeyInterner::new()),
+            poisoned: false,
+            add_failed: false,
         })
     }
 
+    /// Like [`Writer::new`], but all features are buffered in memory, however many there are, so
+    /// no temporary files are created. This is useful where there's no writable filesystem, e.g.
+    /// in the browser.
+    ///
+    /// On wasm32, zstd compressed output isn't supported, so `is_compressed` must be false, but
+    /// output can be compressed with [`Compression::Lz4`] or [`Compression::Brotli`], see
+    /// [`Writer::set_compression`].
+    ///
+    /// See [`Writer::set_memory_budget`].
+    pub fn new_in_memory(inner: W, is_compressed: bool) -> Result<Self> {
+        let mut writer = Self::new(inner, is_compressed)?;
+        writer.set_memory_budget(u64::MAX);
+        Ok(writer)
+    }
+
+    /// The goal of the [`Writer::page_size_target`], whether it's decoded or encoded.
     pub fn page_size_goal(&self) -> u64 {
-        self.page_size_goal
+        self.page_size_target.goal()
     }
 
+    /// How many bytes of features to put in each page.
+    ///
+    /// This bounds the decoded, i.e. uncompressed, size of each page, which includes each
+    /// feature's 8 byte length prefix, but not the page's header. A page is finished as soon as
+    /// its decoded size exceeds the goal, so pages are larger than the goal by at most one
+    /// feature, and only the last page can be smaller.
+    ///
+    /// To bound the encoded size of compressed pages instead, see
+    /// [`Writer::set_page_size_target`]. [`Writer::finish_with_stats`] reports both.
     pub fn set_page_size_goal(&mut self, page_size_goal: u64) {
-        self.page_size_goal = page_size_goal;
+        self.page_size_target = PageSizeTarget::Decoded(page_size_goal);
+    }
+
+    pub fn page_size_target(&self) -> PageSizeTarget {
+        self.page_size_target
+    }
+
+    /// Like [`Writer::set_page_size_goal`], but can instead bound the encoded size of each page,
+    /// with [`PageSizeTarget::Encoded`]. Either way, a page is finished as soon as it exceeds the
+    /// goal.
+    ///
+    /// Compressed pages vary a lot in encoded size with a decoded target, depending on how
+    /// compressible their features are, so an encoded target makes the size of each range request
+    /// more predictable when reading over HTTP.
+    ///
+    /// With the `parallel` feature, pages are only compressed in parallel with a decoded target,
+    /// since an encoded target depends on compressing each page in turn.
+    pub fn set_page_size_target(&mut self, page_size_target: PageSizeTarget) {
+        self.page_size_target = page_size_target;
+    }
+
+    pub fn memory_budget(&self) -> u64 {
+        self.memory_budget
+    }
+
+    /// Small outputs are assembled entirely in memory, avoiding any disk I/O. Once the added
+    /// features exceed this many bytes (when serialized), they're moved to a temporary file, as is
+    /// everything after. Defaults to 16MiB, set to 0 to always use temporary files.
+    ///
+    /// The in-memory representation of features is larger than their serialized size, so actual
+    /// memory usage can be several times this.
+    pub fn set_memory_budget(&mut self, memory_budget: u64) {
+        self.memory_budget = memory_budget;
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.header.compression
+    }
+
+    /// How the contents of each page are compressed, which is stored in the header so readers
+    /// can decode it.
+    ///
+    /// [`Writer::new`]'s `is_compressed` sets this to [`Compression::Zstd`] if true, or
+    /// [`Compression::None`] if false. Files compressed with anything but zstd can't be read by
+    /// versions of geomedea which predate this setting.
+    ///
+    /// On wasm32, [`Writer::finish`] returns [`Error::UnsupportedCompression`] for zstd.
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.header.compression = compression;
+    }
+
+    pub fn compression_level(&self) -> i32 {
+        self.compression_level
+    }
+
+    /// Trade write speed for output size - higher levels are slower, but produce smaller files.
+    ///
+    /// This only has an effect on zstd compressed output, and the level does not need to be known
+    /// to read the file.
+    pub fn set_compression_level(&mut self, compression_level: i32) -> Result<()> {
+        let valid_range = zstd_compression_level_range();
+        if !valid_range.contains(&compression_level) {
+            return Err(Error::InvalidCompressionLevel {
+                level: compression_level,
+                min: *valid_range.start(),
+                max: *valid_range.end(),
+            });
+        }
+        self.compression_level = compression_level;
+        Ok(())
+    }
+
+    pub fn zstd_dictionary(&self) -> Option<&ZstdDictionary> {
+        self.zstd_dictionary.as_ref()
+    }
+
+    /// Compress every page with a shared zstd dictionary, which is stored once in the header.
+    ///
+    /// Each page is compressed independently, so that it can be read on its own, which means small
+    /// pages compress poorly without a dictionary. A dictionary of similar content lets even small
+    /// pages compress well, at the cost of storing the dictionary.
+    ///
+    /// A trained dictionary is trained on the first features added, up to 100 times `max_size`
+    /// bytes of them. If there are too few features to train a dictionary, pages are compressed
+    /// without one.
+    ///
+    /// This has no effect unless the output is zstd compressed. Returns [`Error::InvalidDictionary`] if a
+    /// provided dictionary isn't in zstd's format.
+    pub fn set_zstd_dictionary(&mut self, zstd_dictionary: Option<ZstdDictionary>) -> Result<()> {
+        if let Some(ZstdDictionary::Provided(dictionary)) = &zstd_dictionary {
+            if !dictionary.starts_with(&ZSTD_DICTIONARY_MAGIC) {
+                return Err(Error::InvalidDictionary);
+            }
+        }
+        self.zstd_dictionary = zstd_dictionary;
+        Ok(())
+    }
+
+    pub fn branching_factor(&self) -> u16 {
+        self.header.branching_factor
+    }
+
+    /// The number of children of each node in the spatial index.
+    ///
+    /// A larger branching factor means a shallower index, but more nodes must be read at each
+    /// level of the index when searching.
+    pub fn set_branching_factor(&mut self, branching_factor: u16) -> Result<()> {
+        if branching_factor < 2 {
+            return Err(Error::InvalidBranchingFactor(branching_factor));
+        }
+        self.header.branching_factor = branching_factor;
+        Ok(())
+    }
+
+    pub fn coord_precision_nanos(&self) -> i32 {
+        self.header.coord_precision_nanos
+    }
+
+    /// The precision, in nanodegrees, with which coordinates are stored.
+    ///
+    /// A coarser precision produces smaller (compressed) files, at the cost of rounding each
+    /// coordinate to the nearest multiple of the precision. Readers convert coordinates back to
+    /// the in-memory precision of [`COORD_PRECISION_NANOS`].
+    ///
+    /// Returns an error if the full range of longitudes can't be represented as an `i32` with the
+    /// given precision, or if features have already been added, since they were stored with the
+    /// previous precision.
+    pub fn set_coord_precision_nanos(&mut self, coord_precision_nanos: i32) -> Result<()> {
+        if !is_valid_coord_precision(coord_precision_nanos) {
+            return Err(Error::InvalidCoordPrecision(coord_precision_nanos));
+        }
+        if self.header.feature_count > 0 {
+            return Err(Error::CoordPrecisionChangedAfterFeatures);
+        }
+        self.header.coord_precision_nanos = coord_precision_nanos;
+        Ok(())
+    }
+
+    pub fn downcast_integers(&self) -> bool {
+        self.downcast_integers
     }
 
+    /// Store each integer property with the smallest integer variant (of the same signedness)
+    /// that can hold its value, e.g. `PropertyValue::Int64(5)` is stored as
+    /// `PropertyValue::Int8(5)`.
+    ///
+    /// The numeric values are unchanged, but readers will see the smaller variants. This can
+    /// significantly shrink files with many integer properties, like those converted from GeoJSON.
+    pub fn set_downcast_integers(&mut self, downcast_integers: bool) {
+        self.downcast_integers = downcast_integers;
+    }
+
+    pub fn page_checksums(&self) -> bool {
+        self.header.has_page_checksums
+    }
+
+    /// Store a checksum of each page's encoded contents, which readers verify before decoding the
+    /// page, returning [`Error::PageChecksumMismatch`] if the page was corrupted.
+    ///
+    /// This costs 8 bytes per page, and requires readers to buffer each page in full. The header
+    /// and index are not checksummed.
+    pub fn set_page_checksums(&mut self, page_checksums: bool) {
+        self.header.has_page_checksums = page_checksums;
+    }
+
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref()
+    }
+
+    /// Reject any subsequently added feature with a property whose type doesn't match the type
+    /// declared by `schema`, see [`Schema::validate`].
+    pub fn set_schema(&mut self, schema: Option<Schema>) {
+        self.schema = schema;
+    }
+
+    pub fn validate_geometries(&self) -> bool {
+        self.validate_geometries
+    }
+
+    /// Reject any subsequently added feature whose geometry is invalid, returning
+    /// [`Error::InvalidGeometry`], see [`Geometry::validate`].
+    ///
+    /// [`Geometry::validate`]: crate::Geometry::validate
+    pub fn set_validate_geometries(&mut self, validate_geometries: bool) {
+        self.validate_geometries = validate_geometries;
+    }
+
+    pub fn orient_polygons(&self) -> Option<WindingOrder> {
+        self.orient_polygons
+    }
+
+    /// Rewind the rings of each subsequently added feature's polygons, so that exterior rings
+    /// wind in the given order, and interior rings wind the other way, see [`Polygon::orient`].
+    ///
+    /// By default, rings are written exactly as they're added.
+    ///
+    /// [`Polygon::orient`]: crate::Polygon::orient
+    pub fn set_orient_polygons(&mut self, exterior: Option<WindingOrder>) {
+        self.orient_polygons = exterior;
+    }
+
+    pub fn simplify_tolerance(&self) -> Option<f64> {
+        self.simplify_tolerance
+    }
+
+    /// Simplify the geometry of each subsequently added feature, removing vertices within
+    /// `epsilon_degrees` of the simplified line, see [`Geometry::simplify`]. Features are
+    /// simplified before their bounds are computed for the index.
+    ///
+    /// By default, geometries are written exactly as they're added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon_degrees` is negative or NaN.
+    ///
+    /// [`Geometry::simplify`]: crate::Geometry::simplify
+    pub fn set_simplify_tolerance(&mut self, epsilon_degrees: Option<f64>) {
+        if let Some(epsilon_degrees) = epsilon_degrees {
+            assert!(
+                epsilon_degrees >= 0.0,
+                "epsilon_degrees must not be negative, but was {epsilon_degrees}"
+            );
+        }
+        self.simplify_tolerance = epsilon_degrees;
+    }
+
+    pub fn dedup(&self) -> bool {
+        self.feature_hashes.is_some()
+    }
+
+    /// Skip any feature which is identical (in both geometry and properties) to one that was
+    /// already added.
+    ///
+    /// Features are looked up by a 64 bit hash of their serialized bytes, and only skipped if
+    /// those bytes match a feature with the same hash, which may need to be read back from the
+    /// tempfile. This costs roughly 50 bytes of memory per distinct feature until the writer is
+    /// finished, plus the time to hash each feature. Only features added after enabling dedup are
+    /// compared, and skipped features are not assigned an input index by
+    /// [`Writer::finish_with_feature_locations`].
+    pub fn set_dedup(&mut self, dedup: bool) {
+        if !dedup {
+            self.feature_hashes = None;
+        } else if self.feature_hashes.is_none() {
+            self.feature_hashes = Some(HashMap::new());
+        }
+    }
+
+    pub fn intern_property_keys(&self) -> bool {
+        self.property_key_interner.is_some()
+    }
+
+    /// While features are buffered in memory, those with the same property keys share a single
+    /// allocation of each key, rather than each keeping its own copies. For data with a uniform
+    /// schema, this saves many small allocations. Enabled by default.
+    ///
+    /// Every distinct key is kept until the features are moved to a temporary file (see
+    /// [`Writer::set_memory_budget`]), so it may be worth disabling for data where most keys are
+    /// unique. It doesn't change the output.
+    pub fn set_intern_property_keys(&mut self, intern_property_keys: bool) {
+        if !intern_property_keys {
+            self.property_key_interner = None;
+        } else if self.property_key_interner.is_none() {
+            self.property_key_interner = Some(PropertyKeyInterner::new());
+        }
+    }
+
+    /// Each feature is stored within a single page, so it must be smaller than 4GiB once
+    /// serialized, otherwise [`Error::FeatureExceedsPageLimit`] is returned, and the writer can
+    /// still be used. A feature larger than the page size goal is allowed, and finishes its page.
+    ///
+    /// If this method otherwise errors, the writer is left in a corrupt state, and any further
+    /// calls to `add_feature` or `finish` will return [`Error::Poisoned`].
     pub fn add_feature(&mut self, feature: &Feature) -> Result<()> {
-        self.header.feature_count += 1;
+        let result = self.transform_and_add_feature(feature);
+        if result.is_err() {
+            self.add_failed = true;
+        }
+        result
+    }
 
-        let tmp_offset = self.feature_tempfile.total_bytes_written();
-        let bounds = feature.geometry().bounds();
-        self.extent.extend(&bounds);
-        self.feature_entries
-            .push(FeatureEntry { bounds, tmp_offset });
-        serialize_into(&mut self.feature_tempfile, feature)?;
+    fn transform_and_add_feature(&mut self, feature: &Feature) -> Result<()> {
+        if self.poisoned {
+            return Err(Error::Poisoned);
+        }
+        let transformed;
+        let feature = if self.simplify_tolerance.is_some() || self.orient_polygons.is_some() {
+            let mut feature = feature.clone();
+            if let Some(epsilon_degrees) = self.simplify_tolerance {
+                *feature.geometry_mut() = feature.geometry().simplify(epsilon_degrees);
+            }
+            if let Some(exterior) = self.orient_polygons {
+                feature.geometry_mut().orient(exterior);
+            }
+            transformed = feature;
+            &transformed
+        } else {
+            feature
+        };
+        if let Some(schema) = &self.schema {
+            schema.validate(feature.properties())?;
+        }
+        if self.validate_geometries {
+            feature
+                .geometry()
+                .validate()
+                .map_err(Error::InvalidGeometry)?;
+        }
+        // Each feature is preceded by its length
+        let feature_size = serialized_size(&0u64)? + serialized_size(feature)?;
+        if feature_size > MAX_PAGE_SIZE {
+            return Err(Error::FeatureExceedsPageLimit {
+                size: feature_size,
+                limit: MAX_PAGE_SIZE,
+            });
+        }
+        // A partially written feature would corrupt the tempfile, so until we succeed, assume the
+        // worst.
+        self.poisoned = true;
+        if let FeatureBuffer::Memory { size, .. } = &self.feature_buffer {
+            if size + feature_size > self.memory_budget {
+                self.move_features_to_tempfile()?;
+            }
+        }
+        self.try_add_feature(feature, feature_size)?;
+        self.poisoned = false;
+        Ok(())
+    }
 
+    /// Adds each feature from `features` as it arrives, e.g. from the [`FeatureStream`] of an
+    /// [`HttpReader`], without collecting them first.
+    ///
+    /// Only reading the stream is async, features are still buffered and written synchronously.
+    /// Stops at the first error, whether from the stream or from [`Writer::add_feature`].
+    ///
+    /// [`FeatureStream`]: crate::FeatureStream
+    /// [`HttpReader`]: crate::HttpReader
+    pub async fn add_features(
+        &mut self,
+        features: impl Stream<Item = Result<Feature>>,
+    ) -> Result<()> {
+        let mut features = std::pin::pin!(features);
+        while let Some(feature) = features.next().await {
+            let feature = match feature {
+                Ok(feature) => feature,
+                Err(error) => {
+                    self.add_failed = true;
+                    return Err(error);
+                }
+            };
+            self.add_feature(&feature)?;
+        }
         Ok(())
     }
 
-    // TODO: do on drop?
-    pub fn finish(mut self) -> Result<W> {
-        let mut feature_buffer = self
-            .feature_tempfile
-            .into_inner()
-            .into_inner()
-            .map_err(|e| e.into_error())?;
-        feature_buffer.rewind()?;
-        let mut feature_reader = BufReader::new(feature_buffer);
-
-        self.feature_entries.sort_by(|a, b| {
-            // PERF: memoize hilbert on node
-            let ha = hilbert::scaled_hilbert(&a.bounds.center(), &self.extent);
-            let hb = hilbert::scaled_hilbert(&b.bounds.center(), &self.extent);
-            hb.cmp(&ha)
-        });
+    /// Once the in-memory features exceed the memory budget, move them, and all subsequent
+    /// features, to a tempfile.
+    fn move_features_to_tempfile(&mut self) -> Result<()> {
+        debug!(
+            "exceeded memory budget of {} bytes, using a tempfile",
+            self.memory_budget
+        );
+        let mut tempfile = CountingWriter::new(BufWriter::new(tempfile()?), "feature_tempfile");
+        let FeatureBuffer::Memory { features, .. } = &self.feature_buffer else {
+            unreachable!("only in-memory features are moved")
+        };
+        let mut offsets = Vec::with_capacity(features.len());
+        for feature in features {
+            offsets.push(tempfile.total_bytes_written());
+            serialize_into(&mut tempfile, feature)?;
+        }
+        for entry in &mut self.feature_entries {
+            entry.buffer_position = offsets[entry.buffer_position as usize];
+        }
+        self.feature_buffer = FeatureBuffer::Tempfile(tempfile);
+        // Features are no longer kept in memory, so neither are their keys.
+        if let Some(interner) = &mut self.property_key_interner {
+            *interner = PropertyKeyInterner::new();
+        }
+        Ok(())
+    }
 
-        let mut packed_r_tree = PackedRTreeWriter::new(self.feature_entries.len() as u64)?;
-        let (page_headers, page_contents) = {
-            if self.header.is_compressed {
-                let mut page_writer = FeatureWriter::<_, ZstdPageEncoder<_>>::new(
-                    BufWriter::new(tempfile()?),
-                    self.page_size_goal,
-                );
-                page_writer.write_features(
-                    self.feature_entries,
-                    &mut feature_reader,
-                    &mut packed_r_tree,
-                )?;
-                page_writer.finish()?
+    fn try_add_feature(&mut self, feature: &Feature, feature_size: u64) -> Result<()> {
+        let coord_precision_nanos = self.header.coord_precision_nanos;
+        let rewritten_feature;
+        let (feature, bounds) =
+            if coord_precision_nanos == COORD_PRECISION_NANOS && !self.downcast_integers {
+                (feature, feature.geometry().bounds())
             } else {
-                let mut page_writer = FeatureWriter::<_, UncompressedPageEncoder<_>>::new(
-                    BufWriter::new(tempfile()?),
-                    self.page_size_goal,
-                );
-                page_writer.write_features(
-                    self.feature_entries,
-                    &mut feature_reader,
-                    &mut packed_r_tree,
-                )?;
-                page_writer.finish()?
+                let mut geometry = feature.geometry().clone();
+                let bounds = if coord_precision_nanos == COORD_PRECISION_NANOS {
+                    geometry.bounds()
+                } else {
+                    geometry.rescale_to_precision(coord_precision_nanos);
+                    // The index is always in the in-memory precision, so compute the bounds from
+                    // the rounded coordinates readers will see.
+                    let mut rounded = geometry.clone();
+                    rounded.rescale_from_precision(coord_precision_nanos);
+                    rounded.bounds()
+                };
+                let mut properties = feature.properties().clone();
+                if self.downcast_integers {
+                    properties.downcast_integers();
+                }
+                rewritten_feature = Feature::new(geometry, properties);
+                (&rewritten_feature, bounds)
+            };
+
+        let mut serialized = None;
+        if let Some(feature_hashes) = &mut self.feature_hashes {
+            let bytes = bincode::serialize(feature)?;
+            let mut hasher = DefaultHasher::new();
+            bytes.hash(&mut hasher);
+            let entry_indices = feature_hashes.entry(hasher.finish()).or_default();
+            // Different features can have the same hash, so compare the bytes of each.
+            for &entry_idx in entry_indices.iter() {
+                let buffer_position = self.feature_entries[entry_idx].buffer_position;
+                if self.feature_buffer.contains_at(buffer_position, &bytes)? {
+                    debug!("skipping duplicate feature: {feature:?}");
+                    return Ok(());
+                }
+            }
+            entry_indices.push(self.feature_entries.len());
+            serialized = Some(bytes);
+        }
+
+        let buffer_position = match &mut self.feature_buffer {
+            FeatureBuffer::Memory { features, size } => {
+                *size += feature_size;
+                let mut feature = feature.clone();
+                if let Some(interner) = &mut self.property_key_interner {
+                    feature.intern_property_keys(interner);
+                }
+                features.push(feature);
+                features.len() as u64 - 1
+            }
+            FeatureBuffer::Tempfile(tempfile) => {
+                let offset = tempfile.total_bytes_written();
+                match serialized {
+                    Some(bytes) => tempfile.write_all(&bytes)?,
+                    None => serialize_into(tempfile, feature)?,
+                }
+                offset
             }
         };
 
+        if let Some(ZstdDictionary::Train { max_size }) = &self.zstd_dictionary {
+            if self.header.compression == Compression::Zstd
+                && self.dictionary_samples.len() < max_size.saturating_mul(100)
+            {
+                let sample_start = self.dictionary_samples.len();
+                serialize_into(&mut self.dictionary_samples, feature)?;
+                self.dictionary_sample_sizes
+                    .push(self.dictionary_samples.len() - sample_start);
+            }
+        }
+
+        let input_idx = self.header.feature_count;
+        self.header.feature_count += 1;
+        self.extent.extend(&bounds);
+        self.feature_entries.push(FeatureEntry {
+            bounds,
+            buffer_position,
+            input_idx,
+        });
+
+        Ok(())
+    }
+
+    /// Write the header, index, and features to the output.
+    ///
+    /// Nothing is written until this is called. A writer which has features, and is dropped
+    /// without calling `finish`, leaves the output empty, logs an error, and panics in debug
+    /// builds, unless adding a feature failed. Use [`Writer::abandon`] to drop it deliberately.
+    pub fn finish(self) -> Result<W> {
+        let (inner, _feature_locations) = self.finish_with_feature_locations()?;
+        Ok(inner)
+    }
+
+    /// Give up on writing, e.g. because reading the input failed, returning the output, to which
+    /// nothing has been written.
+    pub fn abandon(mut self) -> W {
+        self.inner.take().expect("writer is only finished once")
+    }
+
+    /// Like [`Writer::finish`], but also returns statistics about the pages that were written,
+    /// which is useful for tuning [`Writer::set_page_size_goal`].
+    pub fn finish_with_stats(mut self) -> Result<(W, WriteStats)> {
+        let (inner, _feature_locations, stats) = self.write_output()?;
+        Ok((inner, stats))
+    }
+
+    /// Like [`Writer::finish`], but also returns where each feature ended up in the output.
+    ///
+    /// Features are re-ordered when written, so the returned list contains the input index of each
+    /// feature (in the order it was passed to [`Writer::add_feature`]) along with its location.
+    /// It is sorted by input index.
+    ///
+    /// This is useful for building external indexes, which can then read a feature
+    /// with [`crate::Reader::read_feature_at`].
+    pub fn finish_with_feature_locations(mut self) -> Result<(W, Vec<(u64, FeatureLocation)>)> {
+        let (inner, feature_locations, _stats) = self.write_output()?;
+        Ok((inner, feature_locations))
+    }
+
+    fn write_output(&mut self) -> Result<(W, Vec<(u64, FeatureLocation)>, WriteStats)> {
+        // Taking `inner` marks the writer as finished, even if we fail part way through.
+        let mut inner = self.inner.take().expect("writer is only finished once");
+        if self.poisoned {
+            return Err(Error::Poisoned);
+        }
+
+        // Compute each feature's hilbert value once, rather than on every comparison.
+        let mut hilbert_entries: Vec<(u32, FeatureEntry)> =
+            std::mem::take(&mut self.feature_entries)
+                .into_iter()
+                .map(|entry| {
+                    let hilbert = hilbert::scaled_hilbert(&entry.bounds.center(), &self.extent);
+                    (hilbert, entry)
+                })
+                .collect();
+        // Descending, and stable, so features with the same value remain in input order.
+        hilbert_entries.sort_by_key(|(hilbert, _entry)| Reverse(*hilbert));
+        let feature_entries: Vec<FeatureEntry> = hilbert_entries
+            .into_iter()
+            .map(|(_hilbert, entry)| entry)
+            .collect();
+
+        let feature_buffer = std::mem::replace(
+            &mut self.feature_buffer,
+            FeatureBuffer::Memory {
+                features: vec![],
+                size: 0,
+            },
+        );
+        let mut packed_r_tree =
+            PackedRTreeWriter::new(feature_entries.len() as u64, self.header.branching_factor);
+        // If the features fit in memory, so do the pages.
+        let page_scratch = match feature_buffer {
+            FeatureBuffer::Memory { .. } => Scratch::Memory(Cursor::new(vec![])),
+            FeatureBuffer::Tempfile(_) => Scratch::Tempfile(tempfile()?),
+        };
+        let sorted_features = feature_buffer.into_sorted(feature_entries)?;
+        if self.header.compression == Compression::Zstd {
+            self.header.dictionary = self.build_zstd_dictionary();
+        }
+        let page_scratch = BufWriter::new(page_scratch);
+        let (page_headers, page_contents, mut feature_locations) = match self.header.compression {
+            Compression::None => self.write_pages::<_, UncompressedPageEncoder<_>>(
+                sorted_features,
+                page_scratch,
+                &mut packed_r_tree,
+            )?,
+            Compression::Zstd => {
+                self.write_compressed_pages(sorted_features, page_scratch, &mut packed_r_tree)?
+            }
+            Compression::Lz4 => self.write_pages::<_, Lz4PageEncoder<_>>(
+                sorted_features,
+                page_scratch,
+                &mut packed_r_tree,
+            )?,
+            Compression::Brotli => self.write_pages::<_, BrotliPageEncoder<_>>(
+                sorted_features,
+                page_scratch,
+                &mut packed_r_tree,
+            )?,
+        };
+
         self.header.page_count = page_headers.len() as u64;
+        self.header.extent = self.extent.clone();
 
         // write file header
-        serialize_into(&mut self.inner, &self.header)?;
+        serialize_into(&mut inner, &self.header)?;
 
         // write index
-        packed_r_tree.write(&mut self.inner)?;
+        packed_r_tree.write(&mut inner)?;
 
         // Copy ordered features from tmp location to after the index
         let mut page_contents = page_contents.into_inner().map_err(|r| r.into_error())?;
         page_contents.rewind()?;
-        for (page_idx, page_header) in page_headers.iter().enumerate() {
-            debug!("serializing page #{page_idx} {page_header:?}");
-            serialize_into(&mut self.inner, &page_header)?;
-            std::io::copy(
-                &mut BufReader::new(
-                    (&mut page_contents).take(page_header.encoded_page_length() as u64),
-                ),
-                &mut self.inner,
-            )?;
+        for (page_idx, (page_header, page_bounds)) in page_headers.iter().enumerate() {
+            debug!("serializing page #{page_idx} {page_header:?} {page_bounds:?}");
+            serialize_into(&mut inner, &page_header)?;
+            serialize_into(&mut inner, &page_bounds)?;
+            let mut page_contents =
+                (&mut page_contents).take(page_header.encoded_page_length() as u64);
+            if self.header.has_page_checksums {
+                let mut encoded = Vec::with_capacity(untrusted_capacity(
+                    page_header.encoded_page_length() as u64,
+                ));
+                page_contents.read_to_end(&mut encoded)?;
+                serialize_into(&mut inner, &page_checksum(&encoded))?;
+                inner.write_all(&encoded)?;
+            } else {
+                std::io::copy(&mut BufReader::new(page_contents), &mut inner)?;
+            }
         }
 
-        self.inner.flush()?;
-        Ok(self.inner)
+        inner.flush()?;
+
+        feature_locations.sort_by_key(|(input_idx, _location)| *input_idx);
+        let stats = WriteStats::new(
+            page_headers
+                .iter()
+                .map(|(page_header, _bounds)| page_header),
+        );
+        Ok((inner, feature_locations, stats))
+    }
+}
+
+/// Statistics about the pages written by [`Writer::finish_with_stats`].
+///
+/// A file always has at least one page, even if it has no features.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteStats {
+    page_count: u64,
+    min_features_per_page: u32,
+    mean_features_per_page: f64,
+    max_features_per_page: u32,
+    min_encoded_page_length: u32,
+    mean_encoded_page_length: f64,
+    max_encoded_page_length: u32,
+}
+
+impl WriteStats {
+    fn new<'a>(page_headers: impl ExactSizeIterator<Item = &'a PageHeader>) -> Self {
+        let page_count = page_headers.len() as u64;
+        let mut stats = Self {
+            page_count,
+            min_features_per_page: u32::MAX,
+            mean_features_per_page: 0.0,
+            max_features_per_page: 0,
+            min_encoded_page_length: u32::MAX,
+            mean_encoded_page_length: 0.0,
+            max_encoded_page_length: 0,
+        };
+        let mut total_features = 0u64;
+        let mut total_encoded_length = 0u64;
+        for page_header in page_headers {
+            let feature_count = page_header.feature_count();
+            stats.min_features_per_page = stats.min_features_per_page.min(feature_count);
+            stats.max_features_per_page = stats.max_features_per_page.max(feature_count);
+            total_features += feature_count as u64;
+
+            let encoded_page_length = page_header.encoded_page_length();
+            stats.min_encoded_page_length = stats.min_encoded_page_length.min(encoded_page_length);
+            stats.max_encoded_page_length = stats.max_encoded_page_length.max(encoded_page_length);
+            total_encoded_length += encoded_page_length as u64;
+        }
+        if page_count == 0 {
+            stats.min_features_per_page = 0;
+            stats.min_encoded_page_length = 0;
+        } else {
+            stats.mean_features_per_page = total_features as f64 / page_count as f64;
+            stats.mean_encoded_page_length = total_encoded_length as f64 / page_count as f64;
+        }
+        stats
+    }
+
+    pub fn page_count(&self) -> u64 {
+        self.page_count
+    }
+
+    pub fn min_features_per_page(&self) -> u32 {
+        self.min_features_per_page
+    }
+
+    pub fn mean_features_per_page(&self) -> f64 {
+        self.mean_features_per_page
+    }
+
+    pub fn max_features_per_page(&self) -> u32 {
+        self.max_features_per_page
+    }
+
+    /// The smallest page, in bytes on disk, i.e. after compression if the file is compressed.
+    /// This excludes the page header.
+    pub fn min_encoded_page_length(&self) -> u32 {
+        self.min_encoded_page_length
+    }
+
+    /// The mean page size, in bytes on disk, see [`WriteStats::min_encoded_page_length`].
+    pub fn mean_encoded_page_length(&self) -> f64 {
+        self.mean_encoded_page_length
+    }
+
+    /// The largest page size, in bytes on disk, see [`WriteStats::min_encoded_page_length`].
+    pub fn max_encoded_page_length(&self) -> u32 {
+        self.max_encoded_page_length
+    }
+}
+
+impl<W: Write> Writer<W> {
+    /// The dictionary to compress pages with, if any, see [`Writer::set_zstd_dictionary`].
+    fn build_zstd_dictionary(&mut self) -> Option<Arc<[u8]>> {
+        match self.zstd_dictionary.as_ref()? {
+            ZstdDictionary::Provided(dictionary) => Some(Arc::from(dictionary.as_slice())),
+            ZstdDictionary::Train { max_size } => {
+                let samples = std::mem::take(&mut self.dictionary_samples);
+                let sample_sizes = std::mem::take(&mut self.dictionary_sample_sizes);
+                match train_zstd_dictionary(&samples, &sample_sizes, *max_size) {
+                    Ok(dictionary) => Some(Arc::from(dictionary)),
+                    Err(e) => {
+                        warn!("Unable to train a zstd dictionary from {} features, compressing pages without one: {e}", sample_sizes.len());
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the header and bounds of each page, the page contents, and the input index and
+    /// output location of each feature, in the order they were written.
+    #[cfg(not(any(feature = "parallel", target_arch = "wasm32")))]
+    #[allow(clippy::type_complexity)]
+    fn write_compressed_pages<PW: Write + Seek>(
+        &self,
+        sorted_features: impl IntoIterator<Item = Result<(FeatureEntry, Feature)>>,
+        page_contents: PW,
+        packed_r_tree: &mut PackedRTreeWriter,
+    ) -> Result<(Vec<(PageHeader, Bounds)>, PW, Vec<(u64, FeatureLocation)>)> {
+        self.write_pages::<_, ZstdPageEncoder<_>>(sorted_features, page_contents, packed_r_tree)
+    }
+
+    /// zstd isn't built for wasm32, see [`Error::UnsupportedCompression`].
+    #[cfg(target_arch = "wasm32")]
+    #[allow(clippy::type_complexity)]
+    fn write_compressed_pages<PW: Write + Seek>(
+        &self,
+        _sorted_features: impl IntoIterator<Item = Result<(FeatureEntry, Feature)>>,
+        _page_contents: PW,
+        _packed_r_tree: &mut PackedRTreeWriter,
+    ) -> Result<(Vec<(PageHeader, Bounds)>, PW, Vec<(u64, FeatureLocation)>)> {
+        Err(Error::UnsupportedCompression(Compression::Zstd))
+    }
+
+    /// Like [`Writer::write_compressed_pages`], but each page is encoded with `PE`, on the
+    /// current thread.
+    #[allow(clippy::type_complexity)]
+    fn write_pages<PW: Write + Seek, PE: PageEncoder<PW>>(
+        &self,
+        sorted_features: impl IntoIterator<Item = Result<(FeatureEntry, Feature)>>,
+        page_contents: PW,
+        packed_r_tree: &mut PackedRTreeWriter,
+    ) -> Result<(Vec<(PageHeader, Bounds)>, PW, Vec<(u64, FeatureLocation)>)> {
+        let mut page_writer = FeatureWriter::<_, PE>::new(
+            page_contents,
+            self.page_size_target,
+            self.compression_level,
+            self.header.dictionary.clone(),
+            self.header.has_page_checksums,
+        );
+        let feature_locations = page_writer.write_features(sorted_features, packed_r_tree)?;
+        let (page_headers, page_contents) = page_writer.finish()?;
+        Ok((page_headers, page_contents, feature_locations))
+    }
+
+    /// Like the serial version, but compresses pages on multiple threads, unless the page size
+    /// target is encoded.
+    #[cfg(feature = "parallel")]
+    #[allow(clippy::type_complexity)]
+    fn write_compressed_pages<PW: Write + Seek>(
+        &self,
+        sorted_features: impl IntoIterator<Item = Result<(FeatureEntry, Feature)>>,
+        page_contents: PW,
+        packed_r_tree: &mut PackedRTreeWriter,
+    ) -> Result<(Vec<(PageHeader, Bounds)>, PW, Vec<(u64, FeatureLocation)>)> {
+        let PageSizeTarget::Decoded(page_size_goal) = self.page_size_target else {
+            return self.write_pages::<_, ZstdPageEncoder<_>>(
+                sorted_features,
+                page_contents,
+                packed_r_tree,
+            );
+        };
+        parallel::write_compressed_pages(
+            sorted_features,
+            page_contents,
+            page_size_goal,
+            self.compression_level,
+            self.header.dictionary.clone(),
+            self.header.has_page_checksums,
+            packed_r_tree,
+        )
+    }
+}
+
+impl<W: Write> Drop for Writer<W> {
+    fn drop(&mut self) {
+        if self.inner.is_none()
+            || self.header.feature_count == 0
+            || self.add_failed
+            || std::thread::panicking()
+        {
+            return;
+        }
+        // Finishing here would hide any errors, and block on writing the whole output.
+        error!(
+            "Writer was dropped without calling `finish`, so none of its {} features were written",
+            self.header.feature_count
+        );
+        if cfg!(debug_assertions) {
+            panic!("Writer was dropped without calling `finish` or `abandon`");
+        }
     }
 }
 
 trait PageEncoder<W: Write>: Write + Sized {
-    fn new(inner: W) -> Result<Self>;
+    fn new(inner: W, compression_level: i32, dictionary: Option<&[u8]>) -> Result<Self>;
     fn total_bytes_in(&self) -> u64;
     fn total_bytes_out(&self) -> u64;
     fn finish(self) -> Result<CountingWriter<W>>;
 }
 
+/// The features added to a [`Writer`], in the order they were added.
+#[derive(Debug)]
+enum FeatureBuffer {
+    /// `size` is roughly the total serialized size of `features`.
+    Memory {
+        features: Vec<Feature>,
+        size: u64,
+    },
+    Tempfile(CountingWriter<BufWriter<File>>),
+}
+
+impl FeatureBuffer {
+    /// Whether the feature at `buffer_position` has the serialized `bytes`.
+    fn contains_at(&mut self, buffer_position: u64, bytes: &[u8]) -> Result<bool> {
+        match self {
+            FeatureBuffer::Memory { features, .. } => {
+                Ok(bincode::serialize(&features[buffer_position as usize])? == bytes)
+            }
+            FeatureBuffer::Tempfile(tempfile) => {
+                tempfile.flush()?;
+                let mut file = tempfile.inner().get_ref();
+                file.seek(SeekFrom::Start(buffer_position))?;
+                // A serialized feature is never a prefix of another, so reading as many bytes
+                // can only match the same feature, even if the one in the tempfile is shorter.
+                let mut existing = vec![0; bytes.len()];
+                let read = file.read_exact(&mut existing);
+                // Features are appended, so write at the end again, even if reading failed.
+                file.seek(SeekFrom::End(0))?;
+                match read {
+                    Ok(()) => Ok(existing == bytes),
+                    Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+                    Err(e) => Err(e.into()),
+                }
+            }
+        }
+    }
+
+    /// The buffered features, in the order of `feature_entries`.
+    fn into_sorted(
+        self,
+        feature_entries: Vec<FeatureEntry>,
+    ) -> Result<Box<dyn Iterator<Item = Result<(FeatureEntry, Feature)>>>> {
+        match self {
+            FeatureBuffer::Memory { features, .. } => {
+                let mut features: Vec<Option<Feature>> = features.into_iter().map(Some).collect();
+                Ok(Box::new(feature_entries.into_iter().map(move |entry| {
+                    let feature = features[entry.buffer_position as usize]
+                        .take()
+                        .expect("each feature has one entry");
+                    Ok((entry, feature))
+                })))
+            }
+            FeatureBuffer::Tempfile(tempfile) => {
+                let mut tempfile = tempfile
+                    .into_inner()
+                    .into_inner()
+                    .map_err(|e| e.into_error())?;
+                tempfile.rewind()?;
+                let mut feature_reader = BufReader::new(tempfile);
+                Ok(Box::new(feature_entries.into_iter().map(move |entry| {
+                    feature_reader.seek(SeekFrom::Start(entry.buffer_position))?;
+                    let feature: Feature = deserialize_from(&mut feature_reader)?;
+                    Ok((entry, feature))
+                })))
+            }
+        }
+    }
+}
+
+/// Where pages are assembled before being copied to the output, after the index.
+#[derive(Debug)]
+enum Scratch {
+    Memory(Cursor<Vec<u8>>),
+    Tempfile(File),
+}
+
+impl Read for Scratch {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Scratch::Memory(cursor) => cursor.read(buf),
+            Scratch::Tempfile(file) => file.read(buf),
+        }
+    }
+}
+
+impl Write for Scratch {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Scratch::Memory(cursor) => cursor.write(buf),
+            Scratch::Tempfile(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Scratch::Memory(cursor) => cursor.flush(),
+            Scratch::Tempfile(file) => file.flush(),
+        }
+    }
+}
+
+impl Seek for Scratch {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            Scratch::Memory(cursor) => cursor.seek(pos),
+            Scratch::Tempfile(file) => file.seek(pos),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct FeatureEntry {
     bounds: Bounds,
-    tmp_offset: u64,
+    /// The index of the feature if the features are in memory, otherwise its offset in the
+    /// tempfile.
+    buffer_position: u64,
+    /// The order in which this feature was added to the Writer
+    input_idx: u64,
 }
 
 enum CurrentPage<W: Write, PE: PageEncoder<W>> {
@@ -160,18 +1181,29 @@ struct Page<W: Write, PE: PageEncoder<W>> {
     starting_offset: u64,
     feature_count: u32,
     encoder: PE,
+    /// The encoder's bytes in and out when it was last flushed, for measuring the encoded size.
+    flushed_bytes_in: u64,
+    flushed_bytes_out: u64,
     _marker: PhantomData<W>, // Do I need this?
 }
 
 impl<W: Write + Seek, PE: PageEncoder<W>> Page<W, PE> {
-    pub fn new(page_id: u32, starting_offset: u64, writer: W) -> Result<Self> {
-        let encoder = PE::new(writer)?;
+    pub fn new(
+        page_id: u32,
+        starting_offset: u64,
+        writer: W,
+        compression_level: i32,
+        dictionary: Option<&[u8]>,
+    ) -> Result<Self> {
+        let encoder = PE::new(writer, compression_level, dictionary)?;
         Ok(Self {
             page_id,
             starting_offset,
             bounds: Bounds::empty(),
             feature_count: 0,
             encoder,
+            flushed_bytes_in: 0,
+            flushed_bytes_out: 0,
             _marker: PhantomData,
         })
     }
@@ -180,48 +1212,82 @@ impl<W: Write + Seek, PE: PageEncoder<W>> Page<W, PE> {
         self.bounds.extend(bounds)
     }
 
-    fn add_feature(&mut self, feature: &Feature) -> Result<(u64, FeatureLocation)> {
+    /// `bounds` are the feature's bounds in the in-memory precision, as stored in the index.
+    fn add_feature(&mut self, feature: &Feature, bounds: &Bounds) -> Result<FeatureLocation> {
         let feature_location = FeatureLocation {
             page_starting_offset: self.starting_offset,
             feature_offset: self.encoder.total_bytes_in() as u32,
         };
-        self.extend(&feature.geometry().bounds());
+        self.extend(bounds);
         self.feature_count += 1;
 
         let serialized_size = serialized_size(feature)?;
-        if let Err(e) = self.encoder.write_u64::<LittleEndian>(serialized_size) {
-            todo!("Error while serializing size: {e:?}. Poison state to make it clear the writer is corrupted and not usable from this point");
-        }
-        if let Err(e) = serialize_into(&mut self.encoder, feature) {
-            todo!("Error while serializing: {e:?}. Poison state to make it clear the writer is corrupted and not usable from this point");
-        }
+        self.encoder.write_u64::<LittleEndian>(serialized_size)?;
+        serialize_into(&mut self.encoder, feature)?;
         debug!("wrote {feature_location:?} with {feature:?}");
 
-        let page_size = self.encoder.total_bytes_in();
-        debug!("page size (uncompressed): {page_size}");
+        debug!(
+            "page size (uncompressed): {page_size}",
+            page_size = self.encoder.total_bytes_in()
+        );
+
+        Ok(feature_location)
+    }
 
-        Ok((page_size, feature_location))
+    /// Whether the page has exceeded `page_size_target`, so should be finished.
+    fn is_full(&mut self, page_size_target: PageSizeTarget) -> Result<bool> {
+        match page_size_target {
+            // Includes the length prefix of every feature.
+            PageSizeTarget::Decoded(goal) => Ok(is_page_full(self.encoder.total_bytes_in(), goal)),
+            PageSizeTarget::Encoded(goal) => {
+                // The encoder buffers its output, so `total_bytes_out` is only accurate after a
+                // flush. Encoding doesn't grow the data (beyond a few bytes of framing), so until
+                // the bytes written since the last flush could exceed the goal, the page can't be
+                // full, and we avoid flushing, which costs compression.
+                let max_bytes_out = self.flushed_bytes_out
+                    + (self.encoder.total_bytes_in() - self.flushed_bytes_in);
+                if !is_page_full(max_bytes_out, goal) {
+                    return Ok(false);
+                }
+                self.encoder.flush()?;
+                self.flushed_bytes_in = self.encoder.total_bytes_in();
+                self.flushed_bytes_out = self.encoder.total_bytes_out();
+                Ok(is_page_full(self.flushed_bytes_out, goal))
+            }
+        }
     }
 
-    fn finish(self) -> Result<(PageHeader, CountingWriter<W>)> {
+    fn finish(self) -> Result<(PageHeader, Bounds, CountingWriter<W>)> {
         let decoded_page_length = self.encoder.total_bytes_in() as u32;
         let writer = self.encoder.finish()?;
         let encoded_page_length =
             u32::try_from(writer.total_bytes_written()).expect("page must be less than u32 bytes");
         let header = PageHeader::new(encoded_page_length, decoded_page_length, self.feature_count);
-        Ok((header, writer))
+        Ok((header, self.bounds, writer))
     }
 }
 
 struct FeatureWriter<W: Write, PE: PageEncoder<W>> {
     current_page: Option<CurrentPage<W, PE>>,
-    finished_pages: Vec<PageHeader>,
+    finished_pages: Vec<(PageHeader, Bounds)>,
     next_page_starting_offset: u64,
-    page_size_goal: u64,
+    page_size_target: PageSizeTarget,
+    compression_level: i32,
+    dictionary: Option<Arc<[u8]>>,
+    /// Each page's header is followed by a checksum, which affects where the next page starts.
+    has_page_checksums: bool,
+    /// Set if writing a feature failed part way through, leaving the current page corrupt.
+    poisoned: bool,
 }
 
 impl<W: Write + Seek, PE: PageEncoder<W>> FeatureWriter<W, PE> {
-    fn new(writer: W, page_size_goal: u64) -> Self {
+    fn new(
+        writer: W,
+        page_size_target: PageSizeTarget,
+        compression_level: i32,
+        dictionary: Option<Arc<[u8]>>,
+        has_page_checksums: bool,
+    ) -> Self {
         let current_page = CurrentPage::Unstarted {
             writer,
             next_page_id: 0,
@@ -230,50 +1296,70 @@ impl<W: Write + Seek, PE: PageEncoder<W>> FeatureWriter<W, PE> {
             current_page: Some(current_page),
             next_page_starting_offset: 0,
             finished_pages: vec![],
-            page_size_goal,
+            page_size_target,
+            compression_level,
+            dictionary,
+            has_page_checksums,
+            poisoned: false,
         }
     }
 
-    fn finish(mut self) -> Result<(Vec<PageHeader>, W)> {
+    fn finish(mut self) -> Result<(Vec<(PageHeader, Bounds)>, W)> {
+        if self.poisoned {
+            return Err(Error::Poisoned);
+        }
         let writer = match self
             .current_page
             .take()
             .expect("we always replace current_page")
         {
             CurrentPage::Started { page } => {
-                let (finished_page, writer) = page.finish()?;
-                self.finished_pages.push(finished_page);
+                let (finished_page, page_bounds, writer) = page.finish()?;
+                self.finished_pages.push((finished_page, page_bounds));
                 writer.into_inner()
             }
             CurrentPage::Unstarted { writer, .. } => writer,
         };
 
         if self.finished_pages.is_empty() {
-            self.finished_pages.push(PageHeader::new(0, 0, 0));
+            self.finished_pages
+                .push((PageHeader::new(0, 0, 0), Bounds::empty()));
         }
         Ok((self.finished_pages, writer))
     }
 
-    fn write_features<R: Read + Seek>(
+    /// Returns the input index and output location of each feature, in the order they were written.
+    fn write_features(
         &mut self,
-        feature_entries: impl IntoIterator<Item = FeatureEntry>,
-        mut feature_reader: R,
+        sorted_features: impl IntoIterator<Item = Result<(FeatureEntry, Feature)>>,
         packed_r_tree: &mut PackedRTreeWriter,
-    ) -> Result<()> {
-        for tmp_feature in feature_entries {
-            feature_reader.seek(SeekFrom::Start(tmp_feature.tmp_offset))?;
-            let feature: Feature = deserialize_from(&mut feature_reader)?;
-            let offset = self.add_feature(&feature)?;
-            packed_r_tree.push_leaf(Node::leaf_node(tmp_feature.bounds, offset))?;
+    ) -> Result<Vec<(u64, FeatureLocation)>> {
+        let mut feature_locations = vec![];
+        for sorted_feature in sorted_features {
+            let (entry, feature) = sorted_feature?;
+            let offset = self.add_feature(&feature, &entry.bounds)?;
+            feature_locations.push((entry.input_idx, offset.clone()));
+            packed_r_tree.push_leaf(Node::leaf_node(entry.bounds, offset))?;
         }
-        Ok(())
+        Ok(feature_locations)
     }
 
-    /// If this method errors, this writer may be left in a corrupt state. You must create a new
-    /// writer.
+    /// If this method errors, this writer is left in a corrupt state, and any further calls will
+    /// return [`Error::Poisoned`].
     ///
     /// Returns the location of the feature in the pages
-    fn add_feature(&mut self, feature: &Feature) -> Result<FeatureLocation> {
+    fn add_feature(&mut self, feature: &Feature, bounds: &Bounds) -> Result<FeatureLocation> {
+        if self.poisoned {
+            return Err(Error::Poisoned);
+        }
+        // Until we've successfully replaced `current_page`, any early return leaves us corrupt.
+        self.poisoned = true;
+        let feature_location = self.try_add_feature(feature, bounds)?;
+        self.poisoned = false;
+        Ok(feature_location)
+    }
+
+    fn try_add_feature(&mut self, feature: &Feature, bounds: &Bounds) -> Result<FeatureLocation> {
         let mut page = match self
             .current_page
             .take()
@@ -285,31 +1371,36 @@ impl<W: Write + Seek, PE: PageEncoder<W>> FeatureWriter<W, PE> {
                 next_page_id,
             } => {
                 let starting_offset = self.next_page_starting_offset;
-                Page::new(next_page_id, starting_offset, writer)?
+                Page::new(
+                    next_page_id,
+                    starting_offset,
+                    writer,
+                    self.compression_level,
+                    self.dictionary.as_deref(),
+                )?
             }
         };
 
-        let (page_size, feature_location) = page.add_feature(feature)?;
+        let feature_size = serialized_size(&0u64)? + serialized_size(feature)?;
+        if page.feature_count > 0 && page.encoder.total_bytes_in() + feature_size > MAX_PAGE_SIZE {
+            // Every offset within a page must fit in a u32, so start a new page.
+            let (writer, next_page_id) = self.finish_page(page)?;
+            page = Page::new(
+                next_page_id,
+                self.next_page_starting_offset,
+                writer,
+                self.compression_level,
+                self.dictionary.as_deref(),
+            )?;
+        }
+
+        let feature_location = page.add_feature(feature, bounds)?;
 
         // TODO: move this into CurrentPage?
-        let next_page = if page_size > self.page_size_goal {
-            let page_id = page.page_id;
-            let next_page_id = page.page_id + 1;
-            let (page_header, writer) = page.finish()?;
-            self.finished_pages.push(page_header);
-            // Don't I need to account for the size of the page header here?
-            self.next_page_starting_offset +=
-                writer.total_bytes_written() + PageHeader::serialized_size() as u64;
-            assert_eq!(next_page_id as usize, self.finished_pages.len());
-
-            debug!(
-               "Finished page {page_id} with {bytes_written} bytes written to output. Next page will start at {next_offset}",
-               bytes_written = writer.total_bytes_written(),
-               next_offset = self.next_page_starting_offset
-            );
-
+        let next_page = if page.is_full(self.page_size_target)? {
+            let (writer, next_page_id) = self.finish_page(page)?;
             CurrentPage::Unstarted {
-                writer: writer.into_inner(),
+                writer,
                 next_page_id,
             }
         } else {
@@ -319,19 +1410,75 @@ impl<W: Write + Seek, PE: PageEncoder<W>> FeatureWriter<W, PE> {
         std::mem::swap(&mut self.current_page, &mut Some(next_page));
         Ok(feature_location)
     }
+
+    /// Returns the writer for the next page, and the next page's id.
+    fn finish_page(&mut self, page: Page<W, PE>) -> Result<(W, u32)> {
+        let page_id = page.page_id;
+        let next_page_id = page.page_id + 1;
+        let (page_header, page_bounds, writer) = page.finish()?;
+        self.finished_pages.push((page_header, page_bounds));
+        // The next page starts after this page's header, bounds, and checksum, as well as its
+        // contents.
+        self.next_page_starting_offset += writer.total_bytes_written()
+            + PageHeader::serialized_size(self.has_page_checksums) as u64;
+        assert_eq!(next_page_id as usize, self.finished_pages.len());
+
+        debug!(
+           "Finished page {page_id} with {bytes_written} bytes written to output. Next page will start at {next_offset}",
+           bytes_written = writer.total_bytes_written(),
+           next_offset = self.next_page_starting_offset
+        );
+        Ok((writer.into_inner(), next_page_id))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn zstd_compression_level_range() -> std::ops::RangeInclusive<i32> {
+    zstd::compression_level_range()
+}
+
+/// zstd output isn't supported on wasm32, so any level zstd would accept is allowed.
+#[cfg(target_arch = "wasm32")]
+fn zstd_compression_level_range() -> std::ops::RangeInclusive<i32> {
+    // ZSTD_minCLevel() and ZSTD_maxCLevel()
+    -(1 << 17)..=22
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn train_zstd_dictionary(
+    samples: &[u8],
+    sample_sizes: &[usize],
+    max_size: usize,
+) -> std::io::Result<Vec<u8>> {
+    zstd::dict::from_continuous(samples, sample_sizes, max_size)
+}
+
+/// zstd output isn't supported on wasm32, so there's nothing to train a dictionary for.
+#[cfg(target_arch = "wasm32")]
+fn train_zstd_dictionary(
+    _samples: &[u8],
+    _sample_sizes: &[usize],
+    _max_size: usize,
+) -> std::io::Result<Vec<u8>> {
+    Err(std::io::ErrorKind::Unsupported.into())
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 struct ZstdPageEncoder<W: Write> {
     counting_zstd_encoder: CountingWriter<zstd::Encoder<'static, CountingWriter<W>>>,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl<W: Write> PageEncoder<W> for ZstdPageEncoder<W> {
-    fn new(write: W) -> Result<Self> {
+    fn new(write: W, compression_level: i32, dictionary: Option<&[u8]>) -> Result<Self> {
         let counting_writer = CountingWriter::new(write, "ZstdPageEncoder output");
-        let counting_zstd_encoder = CountingWriter::new(
-            zstd::Encoder::new(counting_writer, 0)?,
-            "ZstdPageEncoder input",
-        );
+        let zstd_encoder = match dictionary {
+            Some(dictionary) => {
+                zstd::Encoder::with_dictionary(counting_writer, compression_level, dictionary)?
+            }
+            None => zstd::Encoder::new(counting_writer, compression_level)?,
+        };
+        let counting_zstd_encoder = CountingWriter::new(zstd_encoder, "ZstdPageEncoder input");
         Ok(Self {
             counting_zstd_encoder,
         })
@@ -353,6 +1500,7 @@ impl<W: Write> PageEncoder<W> for ZstdPageEncoder<W> {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl<W: Write> Write for ZstdPageEncoder<W> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
         Write::write(&mut self.counting_zstd_encoder, buf)
@@ -363,12 +1511,105 @@ impl<W: Write> Write for ZstdPageEncoder<W> {
     }
 }
 
+struct Lz4PageEncoder<W: Write> {
+    counting_lz4_encoder: CountingWriter<lz4_flex::frame::FrameEncoder<CountingWriter<W>>>,
+}
+
+impl<W: Write> PageEncoder<W> for Lz4PageEncoder<W> {
+    fn new(write: W, _compression_level: i32, _dictionary: Option<&[u8]>) -> Result<Self> {
+        let counting_writer = CountingWriter::new(write, "Lz4PageEncoder output");
+        let lz4_encoder = lz4_flex::frame::FrameEncoder::new(counting_writer);
+        let counting_lz4_encoder = CountingWriter::new(lz4_encoder, "Lz4PageEncoder input");
+        Ok(Self {
+            counting_lz4_encoder,
+        })
+    }
+
+    fn total_bytes_in(&self) -> u64 {
+        self.counting_lz4_encoder.total_bytes_written()
+    }
+
+    fn total_bytes_out(&self) -> u64 {
+        self.counting_lz4_encoder
+            .inner()
+            .get_ref()
+            .total_bytes_written()
+    }
+
+    fn finish(self) -> Result<CountingWriter<W>> {
+        Ok(self
+            .counting_lz4_encoder
+            .into_inner()
+            .finish()
+            .map_err(std::io::Error::from)?)
+    }
+}
+
+impl<W: Write> Write for Lz4PageEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Write::write(&mut self.counting_lz4_encoder, buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Write::flush(&mut self.counting_lz4_encoder)
+    }
+}
+
+struct BrotliPageEncoder<W: Write> {
+    counting_brotli_encoder: CountingWriter<brotli::CompressorWriter<CountingWriter<W>>>,
+}
+
+impl<W: Write> PageEncoder<W> for BrotliPageEncoder<W> {
+    fn new(write: W, _compression_level: i32, _dictionary: Option<&[u8]>) -> Result<Self> {
+        let counting_writer = CountingWriter::new(write, "BrotliPageEncoder output");
+        let brotli_encoder = brotli::CompressorWriter::new(
+            counting_writer,
+            4096,
+            BROTLI_QUALITY,
+            BROTLI_LG_WINDOW_SIZE,
+        );
+        let counting_brotli_encoder =
+            CountingWriter::new(brotli_encoder, "BrotliPageEncoder input");
+        Ok(Self {
+            counting_brotli_encoder,
+        })
+    }
+
+    fn total_bytes_in(&self) -> u64 {
+        self.counting_brotli_encoder.total_bytes_written()
+    }
+
+    fn total_bytes_out(&self) -> u64 {
+        self.counting_brotli_encoder
+            .inner()
+            .get_ref()
+            .total_bytes_written()
+    }
+
+    fn finish(mut self) -> Result<CountingWriter<W>> {
+        // `into_inner` finishes the stream, but can't report an error, so first flush everything
+        // written so far, which can.
+        self.counting_brotli_encoder.flush()?;
+        Ok(self.counting_brotli_encoder.into_inner().into_inner())
+    }
+}
+
+impl<W: Write> Write for BrotliPageEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Write::write(&mut self.counting_brotli_encoder, buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Write::flush(&mut self.counting_brotli_encoder)
+    }
+}
+
 struct UncompressedPageEncoder<W: Write> {
     inner: CountingWriter<W>,
 }
 
 impl<W: Write> PageEncoder<W> for UncompressedPageEncoder<W> {
-    fn new(inner: W) -> Result<Self> {
+    fn new(inner: W, _compression_level: i32, _dictionary: Option<&[u8]>) -> Result<Self> {
         Ok(Self {
             inner: CountingWriter::new(inner, "UncompressedPageEncoder"),
         })
@@ -400,11 +1641,11 @@ impl<W: Write> Write for UncompressedPageEncoder<W> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{ensure_logging, wkt};
+    use crate::feature::{Properties, PropertyType, PropertyValue};
+    use crate::{ensure_logging, wkt, Geometry, LineString, LngLat};
 
     mod test_sizes {
         use super::*;
-        use crate::feature::Properties;
 
         #[test]
         fn uncompressed_page_offsets() {
@@ -422,7 +1663,7 @@ mod tests {
             let mut output = vec![];
             {
                 let mut writer = Writer::new(&mut output, is_compressed).unwrap();
-                writer.page_size_goal = 15;
+                writer.set_page_size_goal(15);
                 for point in multipoint.points() {
                     let geometry = point.clone().into();
                     let feature = Feature::new(geometry, Properties::empty());
@@ -432,10 +1673,982 @@ mod tests {
             }
 
             if is_compressed {
-                assert_eq!(337, output.len());
+                assert_eq!(401, output.len());
             } else {
-                assert_eq!(317, output.len());
+                assert_eq!(381, output.len());
+            }
+        }
+    }
+
+    /// Fails all writes after the first `remaining` bytes.
+    struct FailingWriter {
+        inner: std::io::Cursor<Vec<u8>>,
+        remaining: usize,
+    }
+
+    impl FailingWriter {
+        fn new(remaining: usize) -> Self {
+            Self {
+                inner: std::io::Cursor::new(vec![]),
+                remaining,
+            }
+        }
+    }
+
+    impl Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.remaining == 0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "FailingWriter is out of space",
+                ));
+            }
+            let len = buf.len().min(self.remaining);
+            self.remaining -= len;
+            self.inner.write(&buf[..len])
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Seek for FailingWriter {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn failing_page_writer_is_poisoned() {
+        let mut feature_writer = FeatureWriter::<_, UncompressedPageEncoder<_>>::new(
+            FailingWriter::new(4),
+            PageSizeTarget::Decoded(DEFAULT_PAGE_SIZE_GOAL),
+            DEFAULT_COMPRESSION_LEVEL,
+            None,
+            false,
+        );
+        let feature = Feature::new(wkt!(POINT(1 2)).into(), Properties::empty());
+        let bounds = feature.geometry().bounds();
+        assert!(matches!(
+            feature_writer.add_feature(&feature, &bounds),
+            Err(Error::IO(_))
+        ));
+        assert!(matches!(
+            feature_writer.add_feature(&feature, &bounds),
+            Err(Error::Poisoned)
+        ));
+        assert!(matches!(feature_writer.finish(), Err(Error::Poisoned)));
+    }
+
+    #[test]
+    fn failing_output() {
+        let mut writer = Writer::new(FailingWriter::new(20), true).unwrap();
+        let feature = Feature::new(wkt!(POINT(1 2)).into(), Properties::empty());
+        writer.add_feature(&feature).unwrap();
+        assert!(matches!(writer.finish(), Err(Error::IO(_))));
+    }
+
+    #[test]
+    fn downcast_integers() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        writer.set_downcast_integers(true);
+        let values = [0i64, 5, -128, 127, 300, i64::MAX];
+        for value in values {
+            let mut properties = Properties::empty();
+            properties.insert("signed".to_string(), PropertyValue::Int64(value));
+            properties.insert(
+                "unsigned".to_string(),
+                PropertyValue::UInt64(value.unsigned_abs()),
+            );
+            properties.insert(
+                "nested".to_string(),
+                PropertyValue::Vec(vec![PropertyValue::Int32(value as i32)]),
+            );
+            let feature = Feature::new(wkt!(POINT(1 1)).into(), properties);
+            writer.add_feature(&feature).unwrap();
+        }
+        let output = writer.finish().unwrap();
+
+        let reader = crate::Reader::new(output.as_slice()).unwrap();
+        let mut features = reader.select_all().unwrap();
+        let mut signed = vec![];
+        let mut unsigned = vec![];
+        let mut nested = vec![];
+        while let Some(feature) = features.try_next().unwrap() {
+            signed.push(feature.property("signed").unwrap().clone());
+            unsigned.push(feature.property("unsigned").unwrap().clone());
+            nested.push(feature.property("nested").unwrap().clone());
+        }
+        // Features are reordered when written, but each value should have the smallest variant
+        assert!(signed.contains(&PropertyValue::Int8(0)));
+        assert!(signed.contains(&PropertyValue::Int8(5)));
+        assert!(signed.contains(&PropertyValue::Int8(-128)));
+        assert!(signed.contains(&PropertyValue::Int8(127)));
+        assert!(signed.contains(&PropertyValue::Int16(300)));
+        assert!(signed.contains(&PropertyValue::Int64(i64::MAX)));
+        assert!(unsigned.contains(&PropertyValue::UInt8(128)));
+        assert!(unsigned.contains(&PropertyValue::UInt16(300)));
+        assert!(unsigned.contains(&PropertyValue::UInt64(i64::MAX as u64)));
+        assert!(nested.contains(&PropertyValue::Vec(vec![PropertyValue::Int8(5)])));
+    }
+
+    #[test]
+    fn in_memory() {
+        let features: Vec<Feature> = (0..50)
+            .map(|idx| {
+                let mut properties = Properties::empty();
+                properties.insert("name".to_string(), format!("feature-{idx}").into());
+                Feature::new(LngLat::degrees(idx as f64, idx as f64).into(), properties)
+            })
+            .collect();
+        let write = |memory_budget: u64| {
+            let mut writer = Writer::new(vec![], true).unwrap();
+            writer.set_page_size_goal(200);
+            writer.set_memory_budget(memory_budget);
+            for feature in &features {
+                writer.add_feature(feature).unwrap();
+            }
+            let is_in_memory = matches!(writer.feature_buffer, FeatureBuffer::Memory { .. });
+            (writer.finish().unwrap(), is_in_memory)
+        };
+
+        let (in_memory, is_in_memory) = write(DEFAULT_MEMORY_BUDGET);
+        assert!(is_in_memory);
+        let (in_tempfile, is_in_memory) = write(0);
+        assert!(!is_in_memory);
+        assert_eq!(in_memory, in_tempfile);
+
+        // Moved to a tempfile part way through
+        let (moved, is_in_memory) = write(500);
+        assert!(!is_in_memory);
+        assert_eq!(in_memory, moved);
+    }
+
+    #[test]
+    fn new_in_memory() {
+        let mut writer = Writer::new_in_memory(vec![], false).unwrap();
+        let mut properties = Properties::empty();
+        // Larger than the default memory budget
+        let name = "x".repeat(DEFAULT_MEMORY_BUDGET as usize);
+        properties.insert("name".to_string(), name.clone().into());
+        writer
+            .add_feature(&Feature::new(wkt!(POINT(1 1)).into(), properties))
+            .unwrap();
+        assert!(matches!(
+            writer.feature_buffer,
+            FeatureBuffer::Memory { .. }
+        ));
+        let output = writer.finish().unwrap();
+
+        let reader = crate::Reader::new(output.as_slice()).unwrap();
+        let mut features = reader.select_all().unwrap();
+        let feature = features.try_next().unwrap().unwrap();
+        assert_eq!(feature.property("name"), Some(&PropertyValue::String(name)));
+    }
+
+    #[test]
+    fn schema() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        writer.set_schema(Some(
+            Schema::new()
+                .with_column("name", PropertyType::String)
+                .with_column("population", PropertyType::Int32),
+        ));
+
+        let mut properties = Properties::empty();
+        properties.insert("name".to_string(), "Seattle".into());
+        properties.insert("population".to_string(), PropertyValue::Int32(737_015));
+        // Not declared
+        properties.insert("state".to_string(), "WA".into());
+        let matching = Feature::new(wkt!(POINT(1 1)).into(), properties);
+        writer.add_feature(&matching).unwrap();
+
+        let mut properties = Properties::empty();
+        properties.insert("name".to_string(), "Tacoma".into());
+        properties.insert("population".to_string(), "219,346".into());
+        let mismatching = Feature::new(wkt!(POINT(2 2)).into(), properties);
+        let Err(Error::SchemaMismatch {
+            column,
+            expected,
+            found,
+        }) = writer.add_feature(&mismatching)
+        else {
+            panic!("expected a schema mismatch");
+        };
+        assert_eq!(column, "population");
+        assert_eq!(expected, PropertyType::Int32);
+        assert_eq!(found, PropertyType::String);
+
+        // The writer is still usable
+        let output = writer.finish().unwrap();
+        let reader = crate::Reader::new(output.as_slice()).unwrap();
+        assert_eq!(reader.header().feature_count, 1);
+    }
+
+    #[test]
+    fn dedup() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        writer.set_dedup(true);
+        let mut properties = Properties::empty();
+        properties.insert("name".to_string(), "Seattle".into());
+        let feature = Feature::new(wkt!(POINT(1 1)).into(), properties);
+        writer.add_feature(&feature).unwrap();
+        writer.add_feature(&feature).unwrap();
+        // Same geometry, different properties
+        let other = Feature::new(wkt!(POINT(1 1)).into(), Properties::empty());
+        writer.add_feature(&other).unwrap();
+        let output = writer.finish().unwrap();
+
+        let reader = crate::Reader::new(output.as_slice()).unwrap();
+        let mut features = reader.select_all().unwrap();
+        let mut read = vec![];
+        while let Some(feature) = features.try_next().unwrap() {
+            read.push(feature.properties().clone());
+        }
+        assert_eq!(read.len(), 2);
+        assert!(read.contains(feature.properties()));
+        assert!(read.contains(other.properties()));
+    }
+
+    #[test]
+    fn dedup_hash_collision() {
+        for memory_budget in [DEFAULT_MEMORY_BUDGET, 0] {
+            let mut writer = Writer::new(vec![], true).unwrap();
+            writer.set_memory_budget(memory_budget);
+            writer.set_dedup(true);
+            let feature = Feature::new(wkt!(POINT(1 1)).into(), Properties::empty());
+            writer.add_feature(&feature).unwrap();
+
+            // Pretend that a different feature has the same hash as `feature`.
+            let other = Feature::new(wkt!(POINT(2 2)).into(), Properties::empty());
+            let mut hasher = DefaultHasher::new();
+            bincode::serialize(&other).unwrap().hash(&mut hasher);
+            let feature_hashes = writer.feature_hashes.as_mut().unwrap();
+            let colliding = feature_hashes.values().next().unwrap().clone();
+            feature_hashes.insert(hasher.finish(), colliding);
+
+            writer.add_feature(&other).unwrap();
+            writer.add_feature(&other).unwrap();
+            writer.add_feature(&feature).unwrap();
+            let output = writer.finish().unwrap();
+            let reader = crate::Reader::new(output.as_slice()).unwrap();
+            assert_eq!(reader.header().feature_count, 2, "{memory_budget}");
+        }
+    }
+
+    #[test]
+    fn intern_property_keys() {
+        let write = |intern_property_keys: bool| {
+            let mut writer = Writer::new(vec![], true).unwrap();
+            assert!(writer.intern_property_keys());
+            writer.set_intern_property_keys(intern_property_keys);
+            for idx in 0..10 {
+                // Each feature allocates its own keys.
+                let mut properties = Properties::empty();
+                properties.insert("name".to_string(), format!("name-{idx}").into());
+                properties.insert("rank".to_string(), PropertyValue::UInt32(idx));
+                let feature = Feature::new(wkt!(POINT(1 1)).into(), properties);
+                writer.add_feature(&feature).unwrap();
+            }
+
+            let FeatureBuffer::Memory { features, .. } = &writer.feature_buffer else {
+                panic!("expected features to be buffered in memory");
+            };
+            let key_pointers = |feature: &Feature| -> Vec<*const u8> {
+                feature
+                    .properties()
+                    .iter()
+                    .map(|(key, _value)| key.as_ptr())
+                    .collect()
+            };
+            let first = key_pointers(&features[0]);
+            let shares_keys = features[1..]
+                .iter()
+                .all(|feature| key_pointers(feature) == first);
+            assert_eq!(shares_keys, intern_property_keys);
+            writer.finish().unwrap()
+        };
+        // Interning is only an in-memory optimization, it doesn't change the output.
+        assert_eq!(write(true), write(false));
+    }
+
+    #[tokio::test]
+    async fn add_features_from_stream() {
+        let input = crate::test_data::small_pages(10, false);
+        let mut http_reader = crate::HttpReader::test_reader(&input).await.unwrap();
+        let feature_stream = http_reader.select_all().await.unwrap();
+
+        // recompress the remote file
+        let mut writer = Writer::new(vec![], true).unwrap();
+        writer.add_features(feature_stream).await.unwrap();
+        let output = writer.finish().unwrap();
+
+        let reader = crate::Reader::new(output.as_slice()).unwrap();
+        assert_eq!(reader.header().compression, Compression::Zstd);
+        let mut features = reader.select_all().unwrap();
+        let mut names = vec![];
+        while let Some(feature) = features.try_next().unwrap() {
+            names.push(feature.property("name").unwrap().clone());
+        }
+        assert_eq!(names.len(), 10);
+        assert!(names.contains(&PropertyValue::String("prop-0".to_string())));
+        assert!(names.contains(&PropertyValue::String("prop-9".to_string())));
+    }
+
+    #[tokio::test]
+    async fn transcode_to_uncompressed() {
+        let input = crate::test_data::small_pages(20, true);
+        let mut http_reader = crate::HttpReader::test_reader(&input).await.unwrap();
+        let output = transcode(&mut http_reader, vec![], false).await.unwrap();
+
+        let read_all = |bytes: &[u8]| {
+            let reader = crate::Reader::new(bytes).unwrap();
+            let mut features = reader.select_all().unwrap();
+            let mut read = vec![];
+            while let Some(feature) = features.try_next().unwrap() {
+                read.push(feature);
+            }
+            read
+        };
+        let output_reader = crate::Reader::new(output.as_slice()).unwrap();
+        assert_eq!(output_reader.header().compression, Compression::None);
+        let expected = read_all(&input);
+        assert_eq!(expected.len(), 20);
+        assert_eq!(read_all(&output), expected);
+    }
+
+    #[test]
+    fn suggested_page_size() {
+        let features: Vec<Feature> = (0..100)
+            .map(|idx| {
+                let mut properties = Properties::empty();
+                properties.insert("name".to_string(), format!("feature-{idx:03}").into());
+                Feature::new(LngLat::degrees(idx as f64, 0.0).into(), properties)
+            })
+            .collect();
+        let page_size_goal = suggest_page_size(&features[..10], 10).unwrap();
+
+        let mut writer = Writer::new(vec![], true).unwrap();
+        writer.set_page_size_goal(page_size_goal);
+        for feature in &features {
+            writer.add_feature(feature).unwrap();
+        }
+        let output = writer.finish().unwrap();
+        let reader = crate::Reader::new(output.as_slice()).unwrap();
+        assert_eq!(reader.header().page_count, 10);
+
+        assert_eq!(suggest_page_size(&[], 10).unwrap(), DEFAULT_PAGE_SIZE_GOAL);
+    }
+
+    #[test]
+    fn encoded_page_size_target() {
+        let goal = 200;
+        let features: Vec<Feature> = (0..100)
+            .map(|idx| {
+                let mut properties = Properties::empty();
+                // Very compressible
+                properties.insert("name".to_string(), "a".repeat(100).into());
+                Feature::new(LngLat::degrees(idx as f64, 0.0).into(), properties)
+            })
+            .collect();
+        let max_feature_size = features
+            .iter()
+            .map(|feature| serialized_size(&0u64).unwrap() + serialized_size(feature).unwrap())
+            .max()
+            .unwrap();
+        let write = |page_size_target| {
+            let mut writer = Writer::new(vec![], true).unwrap();
+            writer.set_page_size_target(page_size_target);
+            for feature in &features {
+                writer.add_feature(feature).unwrap();
+            }
+            writer.finish_with_feature_locations().unwrap()
+        };
+
+        let (decoded, _locations) = write(PageSizeTarget::Decoded(goal));
+        let (encoded, locations) = write(PageSizeTarget::Encoded(goal));
+        let page_count = |output: &[u8]| crate::Reader::new(output).unwrap().header().page_count;
+        // Each encoded page holds many more features than a decoded one.
+        assert!(page_count(&encoded) > 1);
+        assert!(page_count(&encoded) * 3 < page_count(&decoded));
+
+        let mut page_starts: Vec<u64> = locations
+            .iter()
+            .map(|(_input_idx, location)| location.page_starting_offset())
+            .collect();
+        page_starts.sort();
+        page_starts.dedup();
+        for window in page_starts.windows(2) {
+            let encoded_length = window[1] - window[0] - PageHeader::serialized_size(false) as u64;
+            assert!(encoded_length > goal);
+            assert!(encoded_length < goal + max_feature_size);
+        }
+
+        let mut features = crate::Reader::new(encoded.as_slice())
+            .unwrap()
+            .select_all()
+            .unwrap();
+        let mut count = 0;
+        while features.try_next().unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 100);
+    }
+
+    #[tokio::test]
+    async fn zstd_dictionary() {
+        let features: Vec<Feature> = (0..1000)
+            .map(|idx| {
+                let mut properties = Properties::empty();
+                properties.insert("name".to_string(), format!("feature-{idx}").into());
+                properties.insert(
+                    "kind".to_string(),
+                    ["road", "river", "rail"][idx % 3].into(),
+                );
+                properties.insert("rank".to_string(), PropertyValue::Int64(idx as i64 % 7));
+                Feature::new(
+                    LngLat::degrees(idx as f64 / 10.0, idx as f64 / 20.0).into(),
+                    properties,
+                )
+            })
+            .collect();
+        let write = |zstd_dictionary| {
+            let mut writer = Writer::new(vec![], true).unwrap();
+            writer.set_page_size_goal(200);
+            writer.set_zstd_dictionary(zstd_dictionary).unwrap();
+            for feature in &features {
+                writer.add_feature(feature).unwrap();
+            }
+            writer.finish().unwrap()
+        };
+        let without_dictionary = write(None);
+        let with_dictionary = write(Some(ZstdDictionary::Train { max_size: 1024 }));
+
+        let reader = crate::Reader::new(with_dictionary.as_slice()).unwrap();
+        assert!(reader.header().dictionary.is_some());
+        // Even including the dictionary, small pages compress much better with one.
+        assert!(with_dictionary.len() < without_dictionary.len());
+
+        let read_all = |bytes: &[u8]| {
+            let mut features = crate::Reader::new(bytes).unwrap().select_all().unwrap();
+            let mut read = vec![];
+            while let Some(feature) = features.try_next().unwrap() {
+                read.push(feature);
+            }
+            read
+        };
+        let expected = read_all(&without_dictionary);
+        assert_eq!(expected.len(), 1000);
+        assert_eq!(read_all(&with_dictionary), expected);
+
+        let mut http_reader = crate::HttpReader::test_reader(&with_dictionary)
+            .await
+            .unwrap();
+        let bbox = wkt!(RECT(10 5,20 10));
+        let mut features = http_reader.select_bbox(&bbox).await.unwrap();
+        let mut count = 0;
+        while let Some(feature) = features.next().await.transpose().unwrap() {
+            assert!(bbox.intersects(&feature.geometry().bounds()));
+            count += 1;
+        }
+        assert_eq!(count, 101);
+
+        let mut writer = Writer::new(vec![], true).unwrap();
+        assert!(matches!(
+            writer
+                .set_zstd_dictionary(Some(ZstdDictionary::Provided(b"not a dictionary".to_vec()))),
+            Err(Error::InvalidDictionary)
+        ));
+    }
+
+    #[test]
+    fn validate_geometries() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        writer.set_validate_geometries(true);
+        let unclosed = Feature::new(
+            wkt!(POLYGON((0 0,10 0,10 10,0 10))).into(),
+            Properties::empty(),
+        );
+        assert!(matches!(
+            writer.add_feature(&unclosed),
+            Err(Error::InvalidGeometry(errors)) if errors.len() == 1
+        ));
+        let valid = Feature::new(
+            wkt!(POLYGON((0 0,10 0,10 10,0 10,0 0))).into(),
+            Properties::empty(),
+        );
+        writer.add_feature(&valid).unwrap();
+        let output = writer.finish().unwrap();
+        let reader = crate::Reader::new(output.as_slice()).unwrap();
+        assert_eq!(reader.header().feature_count, 1);
+    }
+
+    #[test]
+    fn orient_polygons() {
+        let clockwise = Feature::new(
+            wkt!(POLYGON((0 0,0 10,10 10,10 0,0 0))).into(),
+            Properties::empty(),
+        );
+        let write = |orient_polygons| {
+            let mut writer = Writer::new(vec![], true).unwrap();
+            writer.set_orient_polygons(orient_polygons);
+            writer.add_feature(&clockwise).unwrap();
+            let output = writer.finish().unwrap();
+            let mut features = crate::Reader::new(output.as_slice())
+                .unwrap()
+                .select_all()
+                .unwrap();
+            features.try_next().unwrap().unwrap()
+        };
+        assert_eq!(write(None), clockwise);
+        assert_eq!(
+            write(Some(WindingOrder::CounterClockwise)).geometry(),
+            &Geometry::from(wkt!(POLYGON((0 0,10 0,10 10,0 10,0 0))))
+        );
+    }
+
+    #[test]
+    fn simplify_tolerance() {
+        let feature = Feature::new(wkt!(LINESTRING(0 0,5 0.1,10 0)).into(), Properties::empty());
+        let write = |simplify_tolerance| {
+            let mut writer = Writer::new(vec![], true).unwrap();
+            writer.set_simplify_tolerance(simplify_tolerance);
+            writer.add_feature(&feature).unwrap();
+            let output = writer.finish().unwrap();
+            let reader = crate::Reader::new(output.as_slice()).unwrap();
+            let extent = reader.info().extent();
+            let mut features = reader.select_all().unwrap();
+            (features.try_next().unwrap().unwrap(), extent)
+        };
+        assert_eq!(write(None).0, feature);
+        let (simplified, extent) = write(Some(0.5));
+        assert_eq!(
+            simplified.geometry(),
+            &Geometry::from(wkt!(LINESTRING(0 0,10 0)))
+        );
+        // The bounds are of the simplified geometry.
+        assert_eq!(extent, Some(wkt!(RECT(0 0, 10 0))));
+    }
+
+    #[tokio::test]
+    async fn compression() {
+        let features: Vec<Feature> = (0..200)
+            .map(|idx| {
+                let mut properties = Properties::empty();
+                properties.insert("name".to_string(), format!("feature-{idx}").into());
+                Feature::new(
+                    LngLat::degrees(idx as f64 / 10.0, idx as f64 / 20.0).into(),
+                    properties,
+                )
+            })
+            .collect();
+        let write = |compression| {
+            let mut writer = Writer::new(vec![], false).unwrap();
+            writer.set_compression(compression);
+            writer.set_page_size_goal(2000);
+            for feature in &features {
+                writer.add_feature(feature).unwrap();
+            }
+            writer.finish().unwrap()
+        };
+        let read_all = |bytes: &[u8]| {
+            let mut features = crate::Reader::new(bytes).unwrap().select_all().unwrap();
+            let mut read = vec![];
+            while let Some(feature) = features.try_next().unwrap() {
+                read.push(feature);
+            }
+            read
+        };
+        let uncompressed = write(Compression::None);
+        let expected = read_all(&uncompressed);
+        assert_eq!(expected.len(), 200);
+
+        let bbox = wkt!(RECT(5 2.5,10 5));
+        for compression in [Compression::Zstd, Compression::Lz4, Compression::Brotli] {
+            let output = write(compression);
+            assert!(output.len() < uncompressed.len(), "{compression:?}");
+
+            let reader = crate::Reader::new(output.as_slice()).unwrap();
+            assert_eq!(reader.header().compression, compression);
+            assert_eq!(read_all(&output), expected, "{compression:?}");
+
+            let mut features = crate::Reader::new(output.as_slice())
+                .unwrap()
+                .select_bbox(&bbox)
+                .unwrap();
+            let mut count = 0;
+            while let Some(feature) = features.try_next().unwrap() {
+                assert!(bbox.intersects(&feature.geometry().bounds()));
+                count += 1;
+            }
+            assert_eq!(count, 51, "{compression:?}");
+
+            let mut http_reader = crate::HttpReader::test_reader(&output).await.unwrap();
+            let mut features = http_reader.select_bbox(&bbox).await.unwrap();
+            let mut count = 0;
+            while let Some(feature) = features.next().await.transpose().unwrap() {
+                assert!(bbox.intersects(&feature.geometry().bounds()));
+                count += 1;
+            }
+            assert_eq!(count, 51, "{compression:?}");
+        }
+
+        // Empty files have an empty page, whatever the compression.
+        let mut writer = Writer::new(vec![], false).unwrap();
+        writer.set_compression(Compression::Brotli);
+        let empty = writer.finish().unwrap();
+        assert!(read_all(&empty).is_empty());
+    }
+
+    #[tokio::test]
+    async fn page_checksums() {
+        let features: Vec<Feature> = (0..200)
+            .map(|idx| {
+                let mut properties = Properties::empty();
+                properties.insert("name".to_string(), format!("feature-{idx}").into());
+                Feature::new(
+                    LngLat::degrees(idx as f64 / 10.0, idx as f64 / 20.0).into(),
+                    properties,
+                )
+            })
+            .collect();
+        let write = |compression, page_checksums| {
+            let mut writer = Writer::new(vec![], false).unwrap();
+            writer.set_compression(compression);
+            writer.set_page_size_goal(2000);
+            assert!(!writer.page_checksums());
+            writer.set_page_checksums(page_checksums);
+            for feature in &features {
+                writer.add_feature(feature).unwrap();
+            }
+            writer.finish().unwrap()
+        };
+        let read_all = |bytes: &[u8]| {
+            let mut features = crate::Reader::new(bytes).unwrap().select_all()?;
+            let mut read = vec![];
+            while let Some(feature) = features.try_next()? {
+                read.push(feature);
+            }
+            Ok::<_, Error>(read)
+        };
+
+        let bbox = wkt!(RECT(5 2.5,10 5));
+        for compression in [
+            Compression::None,
+            Compression::Zstd,
+            Compression::Lz4,
+            Compression::Brotli,
+        ] {
+            let without_checksums = write(compression, false);
+            let mut output = write(compression, true);
+            let reader = crate::Reader::new(output.as_slice()).unwrap();
+            assert!(reader.header().has_page_checksums);
+            assert!(
+                !crate::Reader::new(without_checksums.as_slice())
+                    .unwrap()
+                    .header()
+                    .has_page_checksums
+            );
+            // Each page has an 8 byte checksum.
+            let page_count = reader.header().page_count as usize;
+            assert!(page_count > 1);
+            assert_eq!(output.len(), without_checksums.len() + 8 * page_count);
+            assert_eq!(
+                read_all(&output).unwrap(),
+                read_all(&without_checksums).unwrap(),
+                "{compression:?}"
+            );
+
+            let mut http_reader = crate::HttpReader::test_reader(&output).await.unwrap();
+            let mut features = http_reader.select_bbox(&bbox).await.unwrap();
+            let mut count = 0;
+            while let Some(feature) = features.next().await.transpose().unwrap() {
+                assert!(bbox.intersects(&feature.geometry().bounds()));
+                count += 1;
+            }
+            assert_eq!(count, 51, "{compression:?}");
+
+            // Corrupt the last page
+            *output.last_mut().unwrap() ^= 0xff;
+            assert!(
+                matches!(read_all(&output), Err(Error::PageChecksumMismatch { .. })),
+                "{compression:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn page_size_goal_bounds_decoded_pages() {
+        let page_size_goal = 300;
+        let write = |is_compressed| {
+            let mut writer = Writer::new(vec![], is_compressed).unwrap();
+            writer.set_page_size_goal(page_size_goal);
+            for idx in 0..50 {
+                let mut properties = Properties::empty();
+                properties.insert("name".to_string(), "x".repeat(idx * 7 % 120).into());
+                writer
+                    .add_feature(&Feature::new(
+                        LngLat::degrees(idx as f64, 0.0).into(),
+                        properties,
+                    ))
+                    .unwrap();
+            }
+            writer.finish_with_feature_locations().unwrap()
+        };
+
+        let (uncompressed, uncompressed_locations) = write(false);
+        let (compressed, compressed_locations) = write(true);
+        for (_input_idx, location) in uncompressed_locations.iter().chain(&compressed_locations) {
+            // A feature only starts a page at or below the goal, since the page would otherwise
+            // have been finished.
+            assert!(location.feature_offset() as u64 <= page_size_goal);
+        }
+
+        // Pages are split the same way whether or not they're compressed.
+        let page_count = |output: &[u8]| crate::Reader::new(output).unwrap().header().page_count;
+        assert!(page_count(&uncompressed) > 1);
+        assert_eq!(page_count(&uncompressed), page_count(&compressed));
+
+        // Every page but the last is larger than the goal.
+        let mut page_starts: Vec<u64> = uncompressed_locations
+            .iter()
+            .map(|(_input_idx, location)| location.page_starting_offset())
+            .collect();
+        page_starts.sort();
+        page_starts.dedup();
+        for window in page_starts.windows(2) {
+            let page_length = window[1] - window[0] - PageHeader::serialized_size(false) as u64;
+            assert!(page_length > page_size_goal);
+        }
+    }
+
+    #[test]
+    fn write_stats() {
+        let mut writer = Writer::new(vec![], false).unwrap();
+        writer.set_page_size_goal(100);
+        for idx in 0..25 {
+            let mut properties = Properties::empty();
+            properties.insert("name".to_string(), format!("feature-{idx:03}").into());
+            writer
+                .add_feature(&Feature::new(
+                    LngLat::degrees(idx as f64, 0.0).into(),
+                    properties,
+                ))
+                .unwrap();
+        }
+        let (output, stats) = writer.finish_with_stats().unwrap();
+
+        let reader = crate::Reader::new(output.as_slice()).unwrap();
+        assert_eq!(stats.page_count(), reader.header().page_count);
+        assert!(stats.page_count() > 1);
+        assert_eq!(
+            stats.mean_features_per_page() * stats.page_count() as f64,
+            25.0
+        );
+        assert!(stats.min_features_per_page() >= 1);
+        assert!(stats.min_features_per_page() <= stats.max_features_per_page());
+        assert!(stats.max_encoded_page_length() > 100);
+        assert!(stats.mean_encoded_page_length() <= stats.max_encoded_page_length() as f64);
+
+        // An empty file still has a single, empty, page.
+        let (_output, stats) = Writer::new(vec![], false)
+            .unwrap()
+            .finish_with_stats()
+            .unwrap();
+        assert_eq!(stats.page_count(), 1);
+        assert_eq!(stats.max_features_per_page(), 0);
+        assert_eq!(stats.max_encoded_page_length(), 0);
+    }
+
+    #[test]
+    #[cfg_attr(
+        debug_assertions,
+        should_panic(expected = "dropped without calling `finish` or `abandon`")
+    )]
+    fn drop_without_finish() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        writer
+            .add_feature(&Feature::new(wkt!(POINT(1 2)).into(), Properties::empty()))
+            .unwrap();
+        drop(writer);
+    }
+
+    #[test]
+    fn abandon() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        for feature_idx in 0..3 {
+            let geometry = LngLat::degrees(feature_idx as f64, feature_idx as f64).into();
+            writer
+                .add_feature(&Feature::new(geometry, Properties::empty()))
+                .unwrap();
+        }
+        assert!(writer.abandon().is_empty());
+    }
+
+    #[test]
+    fn drop_after_failed_add() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        writer.set_validate_geometries(true);
+        writer
+            .add_feature(&Feature::new(wkt!(POINT(1 2)).into(), Properties::empty()))
+            .unwrap();
+        let unclosed = Feature::new(
+            wkt!(POLYGON((0 0,10 0,10 10,0 10))).into(),
+            Properties::empty(),
+        );
+        writer.add_feature(&unclosed).unwrap_err();
+        // Dropping is expected once adding a feature fails, e.g. when returning the error.
+        drop(writer);
+    }
+
+    #[test]
+    fn uncompressed_feature_locations() {
+        feature_locations(false)
+    }
+
+    #[test]
+    fn compressed_feature_locations() {
+        feature_locations(true)
+    }
+
+    fn feature_locations(is_compressed: bool) {
+        let mut writer = Writer::new(vec![], is_compressed).unwrap();
+        // Use small page size to make sure we're testing multiple pages
+        writer.set_page_size_goal(100);
+        for feature_idx in 0..10 {
+            let geometry = LngLat::degrees(feature_idx as f64, feature_idx as f64).into();
+            let mut properties = Properties::empty();
+            properties.insert("name".to_string(), format!("prop-{feature_idx}").into());
+            writer
+                .add_feature(&Feature::new(geometry, properties))
+                .unwrap();
+        }
+        let (output, feature_locations) = writer.finish_with_feature_locations().unwrap();
+        assert_eq!(feature_locations.len(), 10);
+
+        for (expected_idx, (input_idx, location)) in feature_locations.into_iter().enumerate() {
+            assert_eq!(expected_idx as u64, input_idx);
+            let reader = crate::Reader::new(output.as_slice()).unwrap();
+            let feature = reader.read_feature_at(location).unwrap().unwrap();
+            assert_eq!(
+                feature.property("name").unwrap(),
+                &PropertyValue::String(format!("prop-{input_idx}"))
+            );
+        }
+    }
+
+    #[test]
+    fn feature_larger_than_page_size_goal() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        writer.set_page_size_goal(100);
+        let small = |name: &str| {
+            let mut properties = Properties::empty();
+            properties.insert("name".to_string(), name.into());
+            Feature::new(wkt!(POINT(1 1)).into(), properties)
+        };
+        let huge = {
+            let points = (0..1000)
+                .map(|i| LngLat::degrees(i as f64 / 10.0, 1.0))
+                .collect();
+            let mut properties = Properties::empty();
+            properties.insert("name".to_string(), "huge".into());
+            Feature::new(LineString::new(points).into(), properties)
+        };
+        assert!(serialized_size(&huge).unwrap() > 100);
+
+        writer.add_feature(&small("before")).unwrap();
+        writer.add_feature(&huge).unwrap();
+        writer.add_feature(&small("after")).unwrap();
+        let (output, feature_locations) = writer.finish_with_feature_locations().unwrap();
+
+        let names = ["before", "huge", "after"];
+        for (input_idx, location) in feature_locations {
+            let reader = crate::Reader::new(output.as_slice()).unwrap();
+            let feature = reader.read_feature_at(location).unwrap().unwrap();
+            assert_eq!(
+                feature.property("name").unwrap(),
+                &PropertyValue::from(names[input_idx as usize])
+            );
+        }
+
+        let reader = crate::Reader::new(output.as_slice()).unwrap();
+        let mut features = reader.select_bbox(&wkt!(RECT(50 0,60 2))).unwrap();
+        assert_eq!(
+            features.try_next().unwrap().unwrap().geometry(),
+            huge.geometry()
+        );
+        assert!(features.try_next().unwrap().is_none());
+    }
+
+    #[test]
+    fn compression_level() {
+        let write = |compression_level: i32| {
+            let mut output = vec![];
+            let mut writer = Writer::new(&mut output, true).unwrap();
+            writer.set_compression_level(compression_level).unwrap();
+            for feature_idx in 0..100 {
+                let geometry = wkt!(POINT(1 2)).into();
+                let mut properties = Properties::empty();
+                properties.insert("name".to_string(), format!("prop-{feature_idx}").into());
+                writer
+                    .add_feature(&Feature::new(geometry, properties))
+                    .unwrap();
             }
+            writer.finish().unwrap();
+            output
+        };
+
+        let fast = write(1);
+        let small = write(19);
+        assert!(small.len() <= fast.len());
+
+        let reader = crate::Reader::new(small.as_slice()).unwrap();
+        let mut features = reader.select_all().unwrap();
+        let mut count = 0;
+        while features.try_next().unwrap().is_some() {
+            count += 1;
         }
+        assert_eq!(count, 100);
+    }
+
+    #[test]
+    fn invalid_compression_level() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        let too_high = *zstd::compression_level_range().end() + 1;
+        assert!(matches!(
+            writer.set_compression_level(too_high),
+            Err(Error::InvalidCompressionLevel { .. })
+        ));
+        assert_eq!(writer.compression_level(), DEFAULT_COMPRESSION_LEVEL);
+    }
+
+    #[test]
+    fn invalid_coord_precision() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        // 180° would be 2_571_428_571 units, which doesn't fit in an i32
+        assert!(matches!(
+            writer.set_coord_precision_nanos(70),
+            Err(Error::InvalidCoordPrecision(70))
+        ));
+        assert!(matches!(
+            writer.set_coord_precision_nanos(0),
+            Err(Error::InvalidCoordPrecision(0))
+        ));
+        assert_eq!(writer.coord_precision_nanos(), COORD_PRECISION_NANOS);
+    }
+
+    #[test]
+    fn coord_precision_after_features() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        writer
+            .add_feature(&Feature::new(wkt!(POINT(1 2)).into(), Properties::empty()))
+            .unwrap();
+        assert!(matches!(
+            writer.set_coord_precision_nanos(1000),
+            Err(Error::CoordPrecisionChangedAfterFeatures)
+        ));
+        assert_eq!(writer.coord_precision_nanos(), COORD_PRECISION_NANOS);
+        writer.finish().unwrap();
     }
 }
@@ -0,0 +1,224 @@
+//! Compresses pages on multiple threads, see [`write_compressed_pages`].
+
+use super::{is_page_full, FeatureEntry, MAX_PAGE_SIZE};
+use crate::bounds::Bounds;
+use crate::packed_r_tree::{Node, PackedRTreeWriter};
+use crate::{serialize_into, serialized_size, Error, Feature, FeatureLocation, PageHeader, Result};
+use byteorder::{LittleEndian, WriteBytesExt};
+use rayon::prelude::*;
+use std::io::Write;
+use std::sync::Arc;
+
+/// The contents of a page, before compression.
+struct UnencodedPage {
+    bytes: Vec<u8>,
+    bounds: Bounds,
+    /// The input index, bounds, and offset within the page of each feature.
+    features: Vec<(u64, Bounds, u32)>,
+}
+
+impl UnencodedPage {
+    fn new() -> Self {
+        Self {
+            bytes: vec![],
+            bounds: Bounds::empty(),
+            features: vec![],
+        }
+    }
+}
+
+/// Like writing `sorted_features` with a `FeatureWriter` using a `ZstdPageEncoder`, producing
+/// identical output, but pages are compressed in parallel.
+///
+/// Pages are split exactly as they are by `FeatureWriter`, since page boundaries only depend on
+/// the uncompressed size. A few pages per thread are buffered at a time, and then each batch is
+/// compressed in parallel and written in order, after which the compressed size of each page, and
+/// so the starting offset of the next, is known.
+///
+/// Returns the header and bounds of each page, along with the input index and output location of
+/// each feature, in the order they were written.
+#[allow(clippy::type_complexity)]
+pub(super) fn write_compressed_pages<W: Write>(
+    sorted_features: impl IntoIterator<Item = Result<(FeatureEntry, Feature)>>,
+    mut output: W,
+    page_size_goal: u64,
+    compression_level: i32,
+    dictionary: Option<Arc<[u8]>>,
+    has_page_checksums: bool,
+    packed_r_tree: &mut PackedRTreeWriter,
+) -> Result<(Vec<(PageHeader, Bounds)>, W, Vec<(u64, FeatureLocation)>)> {
+    let batch_size = rayon::current_num_threads() * 2;
+    let mut pages = PageSink {
+        finished_pages: vec![],
+        feature_locations: vec![],
+        next_page_starting_offset: 0,
+        compression_level,
+        dictionary,
+        has_page_checksums,
+    };
+
+    let mut batch = vec![];
+    let mut page = UnencodedPage::new();
+    for sorted_feature in sorted_features {
+        let (entry, feature) = sorted_feature?;
+        let feature_size = serialized_size(&0u64)? + serialized_size(&feature)?;
+        if !page.features.is_empty() && page.bytes.len() as u64 + feature_size > MAX_PAGE_SIZE {
+            // Every offset within a page must fit in a u32, so start a new page.
+            batch.push(std::mem::replace(&mut page, UnencodedPage::new()));
+        }
+
+        let feature_offset = page.bytes.len() as u32;
+        page.bounds.extend(&entry.bounds);
+        page.bytes
+            .write_u64::<LittleEndian>(serialized_size(&feature)?)?;
+        serialize_into(&mut page.bytes, &feature)?;
+        page.features
+            .push((entry.input_idx, entry.bounds, feature_offset));
+
+        if is_page_full(page.bytes.len() as u64, page_size_goal) {
+            batch.push(std::mem::replace(&mut page, UnencodedPage::new()));
+        }
+        if batch.len() >= batch_size {
+            pages.write_batch(std::mem::take(&mut batch), &mut output, packed_r_tree)?;
+        }
+    }
+    if !page.features.is_empty() {
+        batch.push(page);
+    }
+    pages.write_batch(batch, &mut output, packed_r_tree)?;
+
+    if pages.finished_pages.is_empty() {
+        pages
+            .finished_pages
+            .push((PageHeader::new(0, 0, 0), Bounds::empty()));
+    }
+    Ok((pages.finished_pages, output, pages.feature_locations))
+}
+
+struct PageSink {
+    finished_pages: Vec<(PageHeader, Bounds)>,
+    feature_locations: Vec<(u64, FeatureLocation)>,
+    next_page_starting_offset: u64,
+    compression_level: i32,
+    dictionary: Option<Arc<[u8]>>,
+    has_page_checksums: bool,
+}
+
+impl PageSink {
+    fn write_batch<W: Write>(
+        &mut self,
+        batch: Vec<UnencodedPage>,
+        output: &mut W,
+        packed_r_tree: &mut PackedRTreeWriter,
+    ) -> Result<()> {
+        let compression_level = self.compression_level;
+        let dictionary = self.dictionary.as_deref();
+        let compressed_pages = batch
+            .into_par_iter()
+            .map(|page| {
+                // The same as `ZstdPageEncoder`, so the output is identical.
+                let mut encoder = match dictionary {
+                    Some(dictionary) => {
+                        zstd::Encoder::with_dictionary(vec![], compression_level, dictionary)?
+                    }
+                    None => zstd::Encoder::new(vec![], compression_level)?,
+                };
+                encoder.write_all(&page.bytes)?;
+                let compressed = encoder.finish()?;
+                Ok::<_, Error>((page, compressed))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (page, compressed) in compressed_pages {
+            let page_starting_offset = self.next_page_starting_offset;
+            for (input_idx, bounds, feature_offset) in page.features.iter() {
+                let location = FeatureLocation {
+                    page_starting_offset,
+                    feature_offset: *feature_offset,
+                };
+                self.feature_locations.push((*input_idx, location.clone()));
+                packed_r_tree.push_leaf(Node::leaf_node(bounds.clone(), location))?;
+            }
+            output.write_all(&compressed)?;
+
+            let encoded_page_length =
+                u32::try_from(compressed.len()).expect("page must be less than u32 bytes");
+            let page_header = PageHeader::new(
+                encoded_page_length,
+                page.bytes.len() as u32,
+                page.features.len() as u32,
+            );
+            self.finished_pages.push((page_header, page.bounds));
+            self.next_page_starting_offset += compressed.len() as u64
+                + PageHeader::serialized_size(self.has_page_checksums) as u64;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{FeatureWriter, PageSizeTarget, ZstdPageEncoder, DEFAULT_COMPRESSION_LEVEL};
+    use super::*;
+    use crate::geometry::Bounded;
+    use crate::{LngLat, Properties};
+    use std::io::Cursor;
+
+    #[test]
+    fn same_as_serial() {
+        let features: Vec<Feature> = (0..200)
+            .map(|idx| {
+                let mut properties = Properties::empty();
+                properties.insert("name".to_string(), format!("feature-{idx}").into());
+                Feature::new(LngLat::degrees(idx as f64, 0.0).into(), properties)
+            })
+            .collect();
+        let sorted_features = || {
+            features.iter().enumerate().map(|(input_idx, feature)| {
+                let entry = FeatureEntry {
+                    bounds: feature.geometry().bounds(),
+                    buffer_position: 0,
+                    input_idx: input_idx as u64,
+                };
+                Ok((entry, feature.clone()))
+            })
+        };
+        let page_size_goal = 300;
+
+        let mut serial_tree = PackedRTreeWriter::new(200, 16);
+        let mut serial_writer = FeatureWriter::<_, ZstdPageEncoder<_>>::new(
+            Cursor::new(vec![]),
+            PageSizeTarget::Decoded(page_size_goal),
+            DEFAULT_COMPRESSION_LEVEL,
+            None,
+            false,
+        );
+        let serial_locations = serial_writer
+            .write_features(sorted_features(), &mut serial_tree)
+            .unwrap();
+        let (serial_pages, serial_output) = serial_writer.finish().unwrap();
+
+        let mut parallel_tree = PackedRTreeWriter::new(200, 16);
+        let (parallel_pages, parallel_output, parallel_locations) = write_compressed_pages(
+            sorted_features(),
+            vec![],
+            page_size_goal,
+            DEFAULT_COMPRESSION_LEVEL,
+            None,
+            false,
+            &mut parallel_tree,
+        )
+        .unwrap();
+
+        assert!(serial_pages.len() > 10);
+        assert_eq!(format!("{parallel_pages:?}"), format!("{serial_pages:?}"));
+        assert_eq!(parallel_output, serial_output.into_inner());
+        assert_eq!(parallel_locations, serial_locations);
+
+        let mut serial_index = vec![];
+        serial_tree.write(&mut serial_index).unwrap();
+        let mut parallel_index = vec![];
+        parallel_tree.write(&mut parallel_index).unwrap();
+        assert_eq!(parallel_index, serial_index);
+    }
+}
@@ -0,0 +1,270 @@
+use geomedea_geozero::geomedea::geojson::{feature_to_geojson, GeoJsonWriter};
+use geomedea_geozero::geomedea::{Feature, Reader, Writer};
+use geomedea_geozero::{restore_foreign_members, stash_foreign_members};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+type Error = Box<dyn std::error::Error>;
+type Result<T> = std::result::Result<T, Error>;
+
+const USAGE: &str = "Usage:
+    geomedea convert <input.geojson> <output.geomedea> [--compressed] [--page-size <bytes>] [--compression-level <level>]
+    geomedea convert <input.geomedea> <output.geojson>";
+
+struct ConvertOptions {
+    is_compressed: bool,
+    page_size: Option<u64>,
+    compression_level: Option<i32>,
+}
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("convert") => {}
+        Some(other) => return Err(format!("Unknown command: {other}\n{USAGE}").into()),
+        None => return Err(USAGE.into()),
+    }
+    let (Some(input_path), Some(output_path)) = (args.next(), args.next()) else {
+        return Err(format!("Missing input or output path\n{USAGE}").into());
+    };
+
+    let mut options = ConvertOptions {
+        is_compressed: false,
+        page_size: None,
+        compression_level: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {arg}\n{USAGE}"))
+        };
+        match arg.as_str() {
+            "--compressed" => options.is_compressed = true,
+            "--page-size" => options.page_size = Some(value()?.parse()?),
+            "--compression-level" => options.compression_level = Some(value()?.parse()?),
+            other => return Err(format!("Unknown argument: {other}\n{USAGE}").into()),
+        }
+    }
+    if options.compression_level.is_some() && !options.is_compressed {
+        return Err("--compression-level requires --compressed".into());
+    }
+
+    match (is_geomedea(&input_path), is_geomedea(&output_path)) {
+        (false, true) => geojson_to_geomedea(
+            BufReader::new(File::open(&input_path)?),
+            BufWriter::new(File::create(&output_path)?),
+            &options,
+        ),
+        (true, false) => geomedea_to_geojson(
+            BufReader::new(File::open(&input_path)?),
+            BufWriter::new(File::create(&output_path)?),
+        ),
+        _ => Err(format!(
+            "Expected to convert between a .geojson and a .geomedea file, but got {input_path} and {output_path}"
+        )
+        .into()),
+    }
+}
+
+fn is_geomedea(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext == "geomedea")
+}
+
+fn geojson_to_geomedea(
+    input: impl Read,
+    output: impl Write,
+    options: &ConvertOptions,
+) -> Result<()> {
+    let mut writer = Writer::new(output, options.is_compressed)?;
+    if let Some(page_size) = options.page_size {
+        writer.set_page_size_goal(page_size);
+    }
+    if let Some(compression_level) = options.compression_level {
+        writer.set_compression_level(compression_level)?;
+    }
+
+    // Each feature is added as soon as it's parsed, so the whole input is never in memory.
+    let mut deserializer = serde_json::Deserializer::from_reader(input);
+    let visitor = GeoJsonVisitor {
+        add_feature: |mut feature: Value| -> Result<()> {
+            // Features' `id`, `bbox` and other foreign members are kept as a property.
+            stash_foreign_members(&mut feature);
+            writer.add_feature(&Feature::from_geojson_value(feature)?)?;
+            Ok(())
+        },
+    };
+    let parsed = deserializer
+        .deserialize_map(visitor)
+        .and_then(|()| deserializer.end());
+    if let Err(error) = parsed {
+        writer.abandon();
+        return Err(error.into());
+    }
+    writer.finish()?.flush()?;
+    Ok(())
+}
+
+fn geomedea_to_geojson(input: impl Read, output: impl Write) -> Result<()> {
+    let mut features = Reader::new(input)?.select_all()?;
+    let mut writer = GeoJsonWriter::new(output)?;
+    while let Some(feature) = features.try_next()? {
+        // Move any stashed foreign members back out of the properties.
+        let mut geojson = feature_to_geojson(&feature);
+        restore_foreign_members(&mut geojson)?;
+        writer.write_geojson_feature(&geojson)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Visits the top level object of a GeoJSON document, passing each feature of a
+/// FeatureCollection to `add_feature` as it's parsed, or the object itself if it's a Feature.
+struct GeoJsonVisitor<F> {
+    add_feature: F,
+}
+
+impl<'de, F: FnMut(Value) -> Result<()>> Visitor<'de> for GeoJsonVisitor<F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a GeoJSON FeatureCollection or Feature")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> std::result::Result<(), A::Error> {
+        // Every member other than `features`, which is only kept in case this is a Feature.
+        let mut members = Map::new();
+        let mut has_features = false;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "features" {
+                map.next_value_seed(FeaturesSeed(&mut self.add_feature))?;
+                has_features = true;
+            } else {
+                members.insert(key, map.next_value()?);
+            }
+        }
+        match members.get("type").and_then(Value::as_str) {
+            Some("FeatureCollection") if has_features => Ok(()),
+            Some("Feature") if !has_features => {
+                (self.add_feature)(Value::Object(members)).map_err(de::Error::custom)
+            }
+            _ => Err(de::Error::custom(
+                "expected a FeatureCollection with `features`, or a Feature",
+            )),
+        }
+    }
+}
+
+/// Passes each element of a FeatureCollection's `features` to the wrapped function.
+struct FeaturesSeed<'a, F>(&'a mut F);
+
+impl<'de, F: FnMut(Value) -> Result<()>> DeserializeSeed<'de> for FeaturesSeed<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(Value) -> Result<()>> Visitor<'de> for FeaturesSeed<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of GeoJSON Features")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(feature) = seq.next_element::<Value>()? {
+            (self.0)(feature).map_err(de::Error::custom)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn convert(input: &Value, is_compressed: bool) -> Value {
+        let options = ConvertOptions {
+            is_compressed,
+            page_size: Some(200),
+            compression_level: None,
+        };
+        let mut geomedea = vec![];
+        geojson_to_geomedea(input.to_string().as_bytes(), &mut geomedea, &options).unwrap();
+        let mut geojson = vec![];
+        geomedea_to_geojson(geomedea.as_slice(), &mut geojson).unwrap();
+        let mut output: Value = serde_json::from_slice(&geojson).unwrap();
+        // Features are written in the order of the index, rather than the order they were added.
+        let features = output["features"].as_array_mut().unwrap();
+        features.sort_by_key(|feature| feature["geometry"]["coordinates"].to_string());
+        output
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut features: Vec<Value> = (0..50)
+            .map(|idx| {
+                json!({
+                    "type": "Feature",
+                    "id": idx,
+                    "bbox": [idx as f64, 1.0, idx as f64, 1.0],
+                    "geometry": { "type": "Point", "coordinates": [idx as f64, 1.0] },
+                    "properties": { "name": format!("feature-{idx}"), "tags": { "rank": idx } },
+                })
+            })
+            .chain([json!({
+                "type": "Feature",
+                "title": "no properties",
+                "geometry": { "type": "LineString", "coordinates": [[1.0, 2.0], [3.0, 4.0]] },
+                "properties": null,
+            })])
+            .collect();
+        features.sort_by_key(|feature| feature["geometry"]["coordinates"].to_string());
+        let input = json!({
+            "features": features,
+            "type": "FeatureCollection",
+        });
+        for is_compressed in [false, true] {
+            assert_eq!(convert(&input, is_compressed), input);
+        }
+    }
+
+    #[test]
+    fn single_feature() {
+        let feature = json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [1.0, 2.0] },
+            "properties": { "name": "Seattle" },
+        });
+        let expected = json!({ "type": "FeatureCollection", "features": [feature.clone()] });
+        assert_eq!(convert(&feature, true), expected);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let options = ConvertOptions {
+            is_compressed: true,
+            page_size: None,
+            compression_level: None,
+        };
+        let feature = r#"{"type":"Feature","geometry":{"type":"Point","coordinates":[1,2]}}"#;
+        let truncated = format!(r#"{{"type":"FeatureCollection","features":[{feature},{feature}"#);
+        let mut output = vec![];
+        assert!(geojson_to_geomedea(truncated.as_bytes(), &mut output, &options).is_err());
+        assert!(output.is_empty());
+
+        let geometry = r#"{"type":"Point","coordinates":[1,2]}"#;
+        assert!(geojson_to_geomedea(geometry.as_bytes(), &mut output, &options).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Converting CSV, with a WKT geometry column, to geomedea.
+
+use crate::GeomedeaWriter;
+use geozero::csv::CsvReader;
+use geozero::error::Result as GeozeroResult;
+use geozero::GeozeroDatasource;
+use std::io::{Read, Write};
+
+/// Convert the CSV `input` to geomedea, written to `output`.
+///
+/// Each row's geometry is parsed from the WKT in its `geometry_column`, and every other column
+/// becomes a property. CSV values are all strings, so any which are numbers are stored as
+/// `Int64` or `Float64` properties, see [`GeomedeaWriter::set_parse_numeric_strings`]. Empty
+/// values are stored as empty strings.
+///
+/// ```no_run
+/// # use std::fs::File;
+/// # use std::io::{BufReader, BufWriter};
+/// // name,population,geometry
+/// // Seattle,737015,POINT(-122.33 47.61)
+/// let input = BufReader::new(File::open("cities.csv").unwrap());
+/// let output = BufWriter::new(File::create("cities.geomedea").unwrap());
+/// geomedea_geozero::csv_to_geomedea(input, "geometry", output, true).unwrap();
+/// ```
+pub fn csv_to_geomedea<R: Read, W: Write>(
+    input: R,
+    geometry_column: &str,
+    output: W,
+    is_compressed: bool,
+) -> GeozeroResult<()> {
+    let mut csv = CsvReader::new(geometry_column, input);
+    let mut writer = GeomedeaWriter::new(output, is_compressed)?;
+    writer.set_parse_numeric_strings(true);
+    if let Err(error) = csv.process(&mut writer) {
+        writer.abandon();
+        return Err(error);
+    }
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geomedea::geojson::feature_to_geojson;
+    use geomedea::Reader;
+    use serde_json::json;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    #[test]
+    fn cities() {
+        let input = BufReader::new(File::open("../test_fixtures/cities.csv").unwrap());
+        let mut output = vec![];
+        csv_to_geomedea(input, "geometry", &mut output, true).unwrap();
+
+        let mut features = Reader::new(output.as_slice())
+            .unwrap()
+            .select_all()
+            .unwrap();
+        let mut geojson = vec![];
+        while let Some(feature) = features.try_next().unwrap() {
+            geojson.push(feature_to_geojson(&feature));
+        }
+        geojson.sort_by_key(|feature| feature["properties"]["name"].as_str().unwrap().to_string());
+
+        assert_eq!(
+            geojson,
+            vec![
+                json!({
+                    "type": "Feature",
+                    "geometry": { "type": "Point", "coordinates": [-116.2, 43.61] },
+                    "properties": {
+                        "name": "Boise",
+                        "population": 235684,
+                        "area_km2": "",
+                        "zip": 83702,
+                    },
+                }),
+                json!({
+                    "type": "Feature",
+                    "geometry": { "type": "Point", "coordinates": [-73.21, 44.48] },
+                    "properties": {
+                        "name": "Burlington",
+                        "population": 44743,
+                        "area_km2": 40.1,
+                        "zip": "05401",
+                    },
+                }),
+                json!({
+                    "type": "Feature",
+                    "geometry": { "type": "Point", "coordinates": [-122.68, 45.52] },
+                    "properties": {
+                        "name": "Portland, OR",
+                        "population": 652503,
+                        "area_km2": 375.5,
+                        "zip": 97201,
+                    },
+                }),
+                json!({
+                    "type": "Feature",
+                    "geometry": { "type": "Point", "coordinates": [-122.33, 47.61] },
+                    "properties": {
+                        "name": "Seattle",
+                        "population": 737015,
+                        "area_km2": 369.2,
+                        "zip": 98101,
+                    },
+                }),
+            ]
+        );
+    }
+}
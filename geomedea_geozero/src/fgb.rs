@@ -0,0 +1,125 @@
+//! Converting between FlatGeobuf and geomedea.
+//!
+//! FlatGeobuf declares a typed column schema up front, whereas geomedea stores each feature's
+//! properties with the feature. So converting from FlatGeobuf is lossless, but when converting to
+//! FlatGeobuf, the columns are inferred from the first feature's properties. Any later property
+//! which isn't one of those columns, or whose type differs, can't be written. Writing the geomedea
+//! file with a [`Schema`](geomedea::Schema) ensures every feature agrees.
+
+use crate::GeomedeaReader;
+use ::flatgeobuf::{FgbReader, FgbWriter, GeometryType};
+use geozero::error::{GeozeroError, Result as GeozeroResult};
+use geozero::GeozeroDatasource;
+use std::fmt::Display;
+use std::io::{Read, Seek, Write};
+
+/// Convert the FlatGeobuf `input` to geomedea, written to `output`.
+///
+/// ```no_run
+/// # use std::fs::File;
+/// # use std::io::{BufReader, BufWriter};
+/// let input = BufReader::new(File::open("countries.fgb").unwrap());
+/// let output = BufWriter::new(File::create("countries.geomedea").unwrap());
+/// geomedea_geozero::fgb_to_geomedea(input, output, true).unwrap();
+/// ```
+#[cfg(feature = "writer")]
+pub fn fgb_to_geomedea<R: Read + Seek, W: Write>(
+    input: R,
+    output: W,
+    is_compressed: bool,
+) -> GeozeroResult<()> {
+    let mut features = FgbReader::open(input)
+        .map_err(fgb_error)?
+        .select_all()
+        .map_err(fgb_error)?;
+    let mut writer = crate::GeomedeaWriter::new(output, is_compressed)?;
+    if let Err(error) = features.process_features(&mut writer) {
+        writer.abandon();
+        return Err(fgb_error(error));
+    }
+    writer.finish()
+}
+
+/// Convert the geomedea `input` to FlatGeobuf, written to `output`, with the dataset `name`.
+///
+/// See the [module docs](self) for how properties are mapped to FlatGeobuf columns.
+pub fn geomedea_to_fgb<R: Read, W: Write>(
+    input: R,
+    mut output: W,
+    name: &str,
+) -> GeozeroResult<()> {
+    let mut fgb = FgbWriter::create(name, GeometryType::Unknown).map_err(fgb_error)?;
+    GeomedeaReader::new(input)
+        .map_err(geomedea_error)?
+        .select_all()
+        .map_err(geomedea_error)?
+        .process(&mut fgb)?;
+    fgb.write(&mut output).map_err(fgb_error)?;
+    Ok(())
+}
+
+fn fgb_error(error: impl Display) -> GeozeroError {
+    GeozeroError::Dataset(error.to_string())
+}
+
+fn geomedea_error(error: geomedea::Error) -> GeozeroError {
+    GeozeroError::Dataset(error.to_string())
+}
+
+#[cfg(feature = "writer")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geomedea::{Feature, Geometry, LngLat, Properties, PropertyValue, Reader, Writer};
+    use std::io::Cursor;
+
+    fn read_all(geomedea: &[u8]) -> Vec<Feature> {
+        let mut features = Reader::new(geomedea).unwrap().select_all().unwrap();
+        let mut all = vec![];
+        while let Some(feature) = features.try_next().unwrap() {
+            all.push(feature);
+        }
+        // FlatGeobuf orders features by its own index, so compare them by name.
+        all.sort_by_key(|feature| feature.properties().get_str("name").unwrap().to_string());
+        all
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut writer = Writer::new(vec![], true).unwrap();
+        for idx in 0..20 {
+            let geometry = if idx % 2 == 0 {
+                Geometry::from(LngLat::degrees(idx as f64, -(idx as f64)))
+            } else {
+                geomedea::LineString::new(vec![
+                    LngLat::degrees(idx as f64, 0.0),
+                    LngLat::degrees(idx as f64, 10.0),
+                ])
+                .into()
+            };
+            let mut properties = Properties::empty();
+            properties.insert("name".to_string(), format!("feature-{idx:02}").into());
+            properties.insert("rank".to_string(), PropertyValue::Int64(idx));
+            properties.insert(
+                "score".to_string(),
+                PropertyValue::Float64(idx as f64 / 2.0),
+            );
+            properties.insert("even".to_string(), PropertyValue::Bool(idx % 2 == 0));
+            writer
+                .add_feature(&Feature::new(geometry, properties))
+                .unwrap();
+        }
+        let geomedea = writer.finish().unwrap();
+
+        let mut fgb = vec![];
+        geomedea_to_fgb(geomedea.as_slice(), &mut fgb, "roundtrip").unwrap();
+        assert_eq!(&fgb[..3], b"fgb");
+
+        let mut roundtripped = vec![];
+        fgb_to_geomedea(Cursor::new(fgb), &mut roundtripped, true).unwrap();
+
+        let expected = read_all(&geomedea);
+        assert_eq!(expected.len(), 20);
+        assert_eq!(read_all(&roundtripped), expected);
+    }
+}
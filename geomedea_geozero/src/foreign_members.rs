@@ -0,0 +1,233 @@
+//! GeoJSON features can have a `bbox`, an `id`, and arbitrary "foreign members" in addition to
+//! their geometry and properties. geomedea only stores geometry and properties, so these would
+//! otherwise be lost when converting GeoJSON to geomedea and back.
+//!
+//! To preserve them, [`stash_foreign_members`] before converting GeoJSON to geomedea, and
+//! [`restore_foreign_members`] after converting back to GeoJSON.
+//!
+//! Only feature level members are preserved. Members of a FeatureCollection (other than its
+//! `features`) or of a geometry have nowhere to be stored in a geomedea file, so they're out of
+//! scope: [`stash_foreign_members`] leaves them in place, and it's up to the caller to keep them
+//! if they're needed.
+//!
+//! A feature whose `properties` are `null` (or missing) only has its stashed members as properties,
+//! so it's restored with `"properties": null`. Features without foreign members aren't changed,
+//! so how their `null` properties round trip is up to the conversion itself.
+
+use serde_json::{Map, Value};
+
+/// The property in which [`stash_foreign_members`] stores a feature's foreign members, encoded as
+/// a JSON string.
+pub const FOREIGN_MEMBERS_PROPERTY: &str = "__geojson_foreign";
+
+/// The members of a feature which geomedea stores natively.
+const FEATURE_MEMBERS: [&str; 3] = ["type", "geometry", "properties"];
+
+/// Move the foreign members (including `bbox` and `id`) of each feature in `geojson` into its
+/// [`FOREIGN_MEMBERS_PROPERTY`] property.
+///
+/// `geojson` may be a FeatureCollection or a single Feature. Anything else is left as is.
+pub fn stash_foreign_members(geojson: &mut Value) {
+    for_each_feature(geojson, &mut |feature| {
+        let foreign_keys: Vec<String> = feature
+            .keys()
+            .filter(|key| !FEATURE_MEMBERS.contains(&key.as_str()))
+            .cloned()
+            .collect();
+        if foreign_keys.is_empty() {
+            return;
+        }
+
+        let mut foreign_members = Map::new();
+        for key in foreign_keys {
+            let value = feature.remove(&key).expect("key was just found");
+            foreign_members.insert(key, value);
+        }
+        // Restoring this replaces the (otherwise empty) properties which hold the stash.
+        if matches!(feature.get("properties"), None | Some(Value::Null)) {
+            foreign_members.insert("properties".to_string(), Value::Null);
+        }
+        let encoded = Value::Object(foreign_members).to_string();
+
+        let properties = feature
+            .entry("properties")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if properties.is_null() {
+            *properties = Value::Object(Map::new());
+        }
+        if let Value::Object(properties) = properties {
+            properties.insert(FOREIGN_MEMBERS_PROPERTY.to_string(), Value::String(encoded));
+        } else {
+            log::warn!("unable to stash foreign members of feature with invalid properties");
+        }
+    })
+}
+
+/// The inverse of [`stash_foreign_members`]: move the contents of each feature's
+/// [`FOREIGN_MEMBERS_PROPERTY`] property back to the feature.
+///
+/// Returns an error if a stashed property isn't valid JSON.
+pub fn restore_foreign_members(geojson: &mut Value) -> serde_json::Result<()> {
+    let mut result = Ok(());
+    for_each_feature(geojson, &mut |feature| {
+        if result.is_err() {
+            return;
+        }
+        let Some(Value::Object(properties)) = feature.get_mut("properties") else {
+            return;
+        };
+        let Some(Value::String(encoded)) = properties.remove(FOREIGN_MEMBERS_PROPERTY) else {
+            return;
+        };
+        match serde_json::from_str::<Map<String, Value>>(&encoded) {
+            Ok(foreign_members) => feature.extend(foreign_members),
+            Err(err) => result = Err(err),
+        }
+    });
+    result
+}
+
+fn for_each_feature(geojson: &mut Value, f: &mut impl FnMut(&mut Map<String, Value>)) {
+    let Value::Object(object) = geojson else {
+        return;
+    };
+    match object.get("type").and_then(Value::as_str) {
+        Some("Feature") => f(object),
+        Some("FeatureCollection") => {
+            let Some(Value::Array(features)) = object.get_mut("features") else {
+                return;
+            };
+            for feature in features.iter_mut().filter_map(Value::as_object_mut) {
+                f(feature)
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(feature = "writer")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GeomedeaReader, GeomedeaWriter};
+    use geozero::geojson::{GeoJsonReader, GeoJsonWriter};
+    use geozero::GeozeroDatasource;
+    use serde_json::json;
+
+    #[test]
+    fn round_trip() {
+        let mut input = json!({
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "id": "feature-1",
+                "bbox": [1.0, 2.0, 1.0, 2.0],
+                "title": "Example",
+                "geometry": { "type": "Point", "coordinates": [1.0, 2.0] },
+                "properties": { "name": "a" }
+            }]
+        });
+        stash_foreign_members(&mut input);
+        let input = input.to_string();
+
+        let mut output = vec![];
+        let mut writer = GeomedeaWriter::new(&mut output, false).unwrap();
+        GeoJsonReader(input.as_bytes())
+            .process(&mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let reader = GeomedeaReader::new(output.as_slice()).unwrap();
+        let mut features = reader.select_all().unwrap();
+        let mut geojson_output = vec![];
+        features
+            .process(&mut GeoJsonWriter::new(&mut geojson_output))
+            .unwrap();
+
+        let mut actual: Value = serde_json::from_slice(&geojson_output).unwrap();
+        restore_foreign_members(&mut actual).unwrap();
+        let feature = &actual["features"][0];
+        assert_eq!(feature["id"], json!("feature-1"));
+        assert_eq!(feature["bbox"], json!([1.0, 2.0, 1.0, 2.0]));
+        assert_eq!(feature["title"], json!("Example"));
+        assert_eq!(feature["properties"], json!({ "name": "a" }));
+    }
+
+    #[test]
+    fn without_properties() {
+        let mut feature = json!({
+            "type": "Feature",
+            "bbox": [1.0, 2.0, 1.0, 2.0],
+            "geometry": { "type": "Point", "coordinates": [1.0, 2.0] },
+            "properties": null
+        });
+        let original = feature.clone();
+        stash_foreign_members(&mut feature);
+        assert!(feature.get("bbox").is_none());
+        assert!(feature["properties"][FOREIGN_MEMBERS_PROPERTY].is_string());
+
+        restore_foreign_members(&mut feature).unwrap();
+        assert_eq!(feature["bbox"], original["bbox"]);
+        assert_eq!(feature, original);
+    }
+
+    #[test]
+    fn null_properties_round_trip() {
+        let mut input = json!({
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "id": 7,
+                "geometry": { "type": "Point", "coordinates": [1.0, 2.0] },
+                "properties": null
+            }]
+        });
+        let original = input.clone();
+        stash_foreign_members(&mut input);
+        let input = input.to_string();
+
+        let mut output = vec![];
+        let mut writer = GeomedeaWriter::new(&mut output, false).unwrap();
+        GeoJsonReader(input.as_bytes())
+            .process(&mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let reader = GeomedeaReader::new(output.as_slice()).unwrap();
+        let mut geojson_output = vec![];
+        reader
+            .select_all()
+            .unwrap()
+            .process(&mut GeoJsonWriter::new(&mut geojson_output))
+            .unwrap();
+
+        let mut actual: Value = serde_json::from_slice(&geojson_output).unwrap();
+        restore_foreign_members(&mut actual).unwrap();
+        assert_eq!(actual["features"][0]["id"], json!(7));
+        assert_eq!(actual["features"][0]["properties"], Value::Null);
+        assert_eq!(
+            actual["features"][0]["properties"],
+            original["features"][0]["properties"]
+        );
+    }
+
+    #[test]
+    fn collection_members_are_left_in_place() {
+        let mut input = json!({
+            "type": "FeatureCollection",
+            "name": "cities",
+            "crs": { "type": "name", "properties": { "name": "EPSG:4326" } },
+            "features": [{
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [1.0, 2.0] },
+                "properties": { "name": "a" }
+            }]
+        });
+        let original = input.clone();
+        stash_foreign_members(&mut input);
+        // Neither the collection's members nor the feature without any are changed.
+        assert_eq!(input, original);
+        restore_foreign_members(&mut input).unwrap();
+        assert_eq!(input, original);
+    }
+}
@@ -48,6 +48,8 @@ impl<R: Read> GeozeroDatasource for GeozeroFeatureIter<'_, R> {
 mod processing {
     use super::*;
     use crate::geomedea_to_geozero_column_value;
+    use geomedea::PropertyValue;
+    use geozero::ColumnValue;
 
     pub(crate) fn process_feature<P: FeatureProcessor>(
         processor: &mut P,
@@ -61,7 +63,16 @@ mod processing {
         if !feature.properties().is_empty() {
             processor.properties_begin()?;
             for (property_idx, (key, value)) in feature.properties().iter().enumerate() {
-                processor.property(property_idx, key, &geomedea_to_geozero_column_value(value))?;
+                let json;
+                let column_value = match value {
+                    // Nested values keep their structure as JSON, e.g. when writing GeoJSON.
+                    PropertyValue::Vec(_) | PropertyValue::Map(_) => {
+                        json = serde_json::Value::from(value).to_string();
+                        ColumnValue::Json(&json)
+                    }
+                    value => geomedea_to_geozero_column_value(value)?,
+                };
+                processor.property(property_idx, key, &column_value)?;
             }
             processor.properties_end()?;
         }
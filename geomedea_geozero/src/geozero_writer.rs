@@ -9,10 +9,13 @@ use std::io::Write;
 
 /// Write geomedea from a geozero data source - e.g. converting a different format to geomedea.
 #[derive(Debug)]
+#[must_use = "nothing is written until `finish` is called"]
 pub struct GeozeroWriter<W: Write> {
     inner: Writer<W>,
     current_feature: Option<FeatureBuilder>,
     is_feature_processor: bool,
+    strict_properties: bool,
+    parse_numeric_strings: bool,
 }
 
 #[derive(Debug)]
@@ -84,6 +87,8 @@ impl<W: Write> GeozeroWriter<W> {
             inner: writer,
             current_feature: Some(feature_builder),
             is_feature_processor: false,
+            strict_properties: false,
+            parse_numeric_strings: false,
         })
     }
 
@@ -91,6 +96,41 @@ impl<W: Write> GeozeroWriter<W> {
         self.inner.set_page_size_goal(bytes);
     }
 
+    /// See [`Writer::set_compression_level`].
+    pub fn set_compression_level(&mut self, compression_level: i32) -> GeozeroResult<()> {
+        self.inner
+            .set_compression_level(compression_level)
+            .map_err(|e| GeozeroError::Dataset(e.to_string()))
+    }
+
+    /// See [`Writer::set_intern_property_keys`].
+    pub fn set_intern_property_keys(&mut self, intern_property_keys: bool) {
+        self.inner.set_intern_property_keys(intern_property_keys);
+    }
+
+    /// Return an error for any property which can't be stored losslessly, rather than coercing
+    /// it, e.g. `Json` and `DateTime` values are otherwise stored as strings.
+    ///
+    /// Disabled by default.
+    pub fn set_strict_properties(&mut self, strict_properties: bool) {
+        self.strict_properties = strict_properties;
+    }
+
+    /// Store string values which are numbers, e.g. `"42"` or `"-1.5"`, as `Int64` or `Float64`
+    /// properties. This is useful for sources like CSV, where every value is a string.
+    ///
+    /// Strings which wouldn't survive being parsed, like `"007"`, remain strings.
+    ///
+    /// Disabled by default.
+    pub fn set_parse_numeric_strings(&mut self, parse_numeric_strings: bool) {
+        self.parse_numeric_strings = parse_numeric_strings;
+    }
+
+    /// See [`Writer::abandon`], e.g. after processing the input failed.
+    pub fn abandon(self) -> W {
+        self.inner.abandon()
+    }
+
     pub fn finish(mut self) -> GeozeroResult<()> {
         if self.is_feature_processor {
             assert!(
@@ -135,11 +175,55 @@ impl<W: Write> PropertyProcessor for GeozeroWriter<W> {
         let Some(current_feature) = &mut self.current_feature else {
             return Err(no_feature_started("property"));
         };
-        current_feature.set_property(name, value)?;
+        if self.strict_properties {
+            let coerced_type = match value {
+                geozero::ColumnValue::Json(_) => Some("Json"),
+                geozero::ColumnValue::DateTime(_) => Some("DateTime"),
+                _ => None,
+            };
+            if let Some(coerced_type) = coerced_type {
+                return Err(GeozeroError::Property(format!(
+                    "column `{name}` has a {coerced_type} value, which can't be stored losslessly"
+                )));
+            }
+        }
+        let parsed = match value {
+            geozero::ColumnValue::String(string) if self.parse_numeric_strings => {
+                parse_numeric_string(string)
+            }
+            _ => None,
+        };
+        current_feature.set_property(name, parsed.as_ref().unwrap_or(value))?;
         Ok(false)
     }
 }
 
+/// See [`GeozeroWriter::set_parse_numeric_strings`].
+fn parse_numeric_string(string: &str) -> Option<geozero::ColumnValue<'static>> {
+    let digits = string.strip_prefix('-').unwrap_or(string);
+    let first_digit = digits.chars().next()?;
+    // Excludes e.g. "inf", "NaN", "+1", and "1_000".
+    if !(first_digit.is_ascii_digit() || first_digit == '.')
+        || !digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '-' | '+'))
+    {
+        return None;
+    }
+    // Leading zeros would be lost, e.g. in zip codes.
+    if digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.") {
+        return None;
+    }
+    if let Ok(integer) = string.parse::<i64>() {
+        return Some(geozero::ColumnValue::Long(integer));
+    }
+    string
+        .parse::<f64>()
+        .ok()
+        .filter(|float| float.is_finite())
+        .map(geozero::ColumnValue::Double)
+}
+
 impl<W: Write> FeatureProcessor for GeozeroWriter<W> {
     fn dataset_begin(&mut self, _name: Option<&str>) -> GeozeroResult<()> {
         // remove default configuration used only for non-feature GeometryProcessor
@@ -647,12 +731,64 @@ mod tests {
         assert_relative_eq!(lnglat.lat_degrees(), -89.99999981438727, epsilon = 1e-7);
 
         if is_compressed {
-            assert_eq!(output.len(), 62578);
+            assert_eq!(output.len(), 62634);
         } else {
-            assert_eq!(output.len(), 150044);
+            assert_eq!(output.len(), 150100);
         }
     }
 
+    #[test]
+    fn intern_property_keys() {
+        let write = |intern_property_keys: bool| {
+            let input = BufReader::new(File::open("../test_fixtures/places.json").unwrap());
+            let mut geojson = geozero::geojson::GeoJsonReader(input);
+
+            let mut output = vec![];
+            let mut writer = GeozeroWriter::new(&mut output, true).unwrap();
+            writer.set_intern_property_keys(intern_property_keys);
+            geojson.process(&mut writer).unwrap();
+            writer.finish().unwrap();
+            output
+        };
+        // Interning is only an in-memory optimization, it doesn't change the output.
+        assert_eq!(write(true), write(false));
+    }
+
+    #[test]
+    fn strict_properties() {
+        let input = r#"{
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [1.0, 2.0] },
+                "properties": { "name": "a", "tags": { "kind": "cafe" } }
+            }]
+        }"#;
+        let write = |strict_properties: bool| {
+            let mut output = vec![];
+            let mut writer = GeozeroWriter::new(&mut output, false).unwrap();
+            writer.set_strict_properties(strict_properties);
+            geozero::geojson::GeoJson(input).process(&mut writer)?;
+            writer.finish()?;
+            Ok::<_, GeozeroError>(output)
+        };
+
+        let Err(GeozeroError::Property(message)) = write(true) else {
+            panic!("expected strict mode to reject the Json column");
+        };
+        assert!(message.contains("`tags`"), "{message}");
+
+        let output = write(false).unwrap();
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let feature = reader.select_all().unwrap().try_next().unwrap().unwrap();
+        assert_eq!(
+            feature.property("tags"),
+            Some(&geomedea::PropertyValue::String(
+                r#"{"kind":"cafe"}"#.to_string()
+            ))
+        );
+    }
+
     #[test]
     fn select_all_from_larger_json_uncompressed() {
         select_all_from_larger_json(false)
@@ -683,9 +819,9 @@ mod tests {
         assert_relative_eq!(lnglat.lat_degrees(), -89.99999981438727, epsilon = 1e-7);
 
         if is_compressed {
-            assert_eq!(output.len(), 64928);
+            assert_eq!(output.len(), 65176);
         } else {
-            assert_eq!(output.len(), 150188);
+            assert_eq!(output.len(), 150436);
         }
     }
 
@@ -724,9 +860,9 @@ mod tests {
             name
         );
         if is_compressed {
-            assert_eq!(output.len(), 80937);
+            assert_eq!(output.len(), 81057);
         } else {
-            assert_eq!(output.len(), 105123);
+            assert_eq!(output.len(), 105243);
         }
     }
 
@@ -831,7 +967,7 @@ mod tests {
         input.process_geom(&mut geomedea_writer).unwrap();
         // should this finish be in process_geom?
         geomedea_writer.finish().unwrap();
-        assert_eq!(output.len(), 497);
+        assert_eq!(output.len(), 537);
     }
 
     #[test]
@@ -858,6 +994,39 @@ mod tests {
         assert_eq!(tests_ran, 13);
     }
 
+    #[test]
+    fn parse_numeric_strings() {
+        use geozero::ColumnValue;
+        assert_eq!(parse_numeric_string("42"), Some(ColumnValue::Long(42)));
+        assert_eq!(parse_numeric_string("-7"), Some(ColumnValue::Long(-7)));
+        assert_eq!(parse_numeric_string("0"), Some(ColumnValue::Long(0)));
+        assert_eq!(
+            parse_numeric_string("-1.5"),
+            Some(ColumnValue::Double(-1.5))
+        );
+        assert_eq!(
+            parse_numeric_string("0.25"),
+            Some(ColumnValue::Double(0.25))
+        );
+        assert_eq!(
+            parse_numeric_string("1e3"),
+            Some(ColumnValue::Double(1000.0))
+        );
+        for string in [
+            "",
+            "-",
+            "007",
+            "NaN",
+            "inf",
+            "+1",
+            "1_000",
+            "1.2.3",
+            "12 Main St",
+        ] {
+            assert_eq!(parse_numeric_string(string), None, "{string:?}");
+        }
+    }
+
     #[test]
     fn focused_round_trip() {
         // test_round_trip("../test_fixtures/canonical-geojson/good-point.geojson");
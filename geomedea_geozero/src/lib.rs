@@ -1,7 +1,22 @@
+#[cfg(feature = "csv")]
+pub mod csv;
+#[cfg(feature = "flatgeobuf")]
+pub mod fgb;
+mod foreign_members;
 mod geozero_reader;
 #[cfg(feature = "writer")]
 mod geozero_writer;
 
+#[cfg(feature = "csv")]
+pub use csv::csv_to_geomedea;
+#[cfg(all(feature = "flatgeobuf", feature = "writer"))]
+pub use fgb::fgb_to_geomedea;
+#[cfg(feature = "flatgeobuf")]
+pub use fgb::geomedea_to_fgb;
+pub use foreign_members::{
+    restore_foreign_members, stash_foreign_members, FOREIGN_MEMBERS_PROPERTY,
+};
+
 pub use geozero_reader::process_features as process_geomedea;
 pub use geozero_reader::GeozeroReader as GeomedeaReader;
 
@@ -11,10 +26,12 @@ pub use geozero_writer::GeozeroWriter as GeomedeaWriter;
 pub use geomedea;
 pub use geozero;
 
+/// Returns an error for `Vec` and `Map` values, which have no `ColumnValue` equivalent.
+/// [`GeomedeaReader`] instead passes them on as `ColumnValue::Json`.
 pub fn geomedea_to_geozero_column_value(
     property_value: &geomedea::PropertyValue,
-) -> geozero::ColumnValue {
-    match property_value {
+) -> geozero::error::Result<geozero::ColumnValue> {
+    Ok(match property_value {
         geomedea::PropertyValue::Bool(value) => geozero::ColumnValue::Bool(*value),
         geomedea::PropertyValue::Int8(value) => geozero::ColumnValue::Byte(*value),
         geomedea::PropertyValue::UInt8(value) => geozero::ColumnValue::UByte(*value),
@@ -28,9 +45,12 @@ pub fn geomedea_to_geozero_column_value(
         geomedea::PropertyValue::Float64(value) => geozero::ColumnValue::Double(*value),
         geomedea::PropertyValue::Bytes(value) => geozero::ColumnValue::Binary(value),
         geomedea::PropertyValue::String(value) => geozero::ColumnValue::String(value),
-        geomedea::PropertyValue::Vec(_value) => todo!("handle unsupported"),
-        geomedea::PropertyValue::Map(_value) => todo!("handle unsupported"),
-    }
+        geomedea::PropertyValue::Vec(_) | geomedea::PropertyValue::Map(_) => {
+            return Err(geozero::error::GeozeroError::Property(format!(
+                "{property_value:?} has no equivalent geozero column value"
+            )))
+        }
+    })
 }
 
 pub fn geozero_to_geomedea_property_value(
@@ -103,4 +123,32 @@ mod tests {
         let input: serde_json::Value = serde_json::from_str(&input).unwrap();
         assert_eq!(input, round_trip);
     }
+    #[test]
+    fn nested_properties_to_json() {
+        use geomedea::{Feature, LngLat, Properties, PropertyValue, Writer};
+
+        let mut nested = Properties::empty();
+        nested.insert("a".to_string(), PropertyValue::Int64(-1));
+        let mut properties = Properties::empty();
+        properties.insert(
+            "tags".to_string(),
+            PropertyValue::Vec(vec!["city".into(), PropertyValue::Bool(true)]),
+        );
+        properties.insert("nested".to_string(), PropertyValue::Map(nested));
+        assert!(geomedea_to_geozero_column_value(properties.get("tags").unwrap()).is_err());
+
+        let mut writer = Writer::new(vec![], true).unwrap();
+        writer
+            .add_feature(&Feature::new(LngLat::degrees(1.0, 2.0).into(), properties))
+            .unwrap();
+        let output = writer.finish().unwrap();
+
+        let reader = GeomedeaReader::new(&*output).unwrap();
+        let round_trip = reader.select_all().unwrap().to_json().unwrap();
+        let round_trip: serde_json::Value = serde_json::from_str(&round_trip).unwrap();
+        assert_eq!(
+            round_trip["features"][0]["properties"],
+            json!({ "tags": ["city", true], "nested": { "a": -1 } })
+        );
+    }
 }